@@ -0,0 +1,391 @@
+//! Inspect and operate on persisted Forge runs.
+//!
+//! Usage: `forge [--root DIR] [--logs DIR] [--checkpoints DIR] [--sessions DIR]
+//! [--attachments DIR] [--json] <command> [args]`
+//!
+//! Every store defaults to `--root` (itself defaulting to `$FORGE_ROOT` or the
+//! current directory). Commands:
+//!
+//! - `runs` — list runs with event and checkpoint counts
+//! - `checkpoints <run>` — list a run's checkpoints
+//! - `log <run> [--from-seq N] [--raw]` — pretty-print (or dump) the event log
+//! - `tail <run> [--from-seq N] [--interval-ms N] [--raw]` — follow the event log
+//! - `transcript <session>` — render a session transcript
+//! - `diff <run> <from> <to>` — diff two checkpoints
+//! - `verify <run>` — check log hash chain/seal and checkpoint files
+//! - `export <run> <out.json> [--redact] [--sensitive-tool NAME]... [--redact-path PATH]...
+//!   [--redact-pattern REGEX]...` — write a run bundle for bug reports; any
+//!   redaction flag redacts with the default patterns plus the given rules
+//! - `query [--run ID]... [--variant NAME]... [--tool NAME] [--call-id ID]
+//!   [--since-ms MS] [--until-ms MS] [--from-seq N] [--to-seq N] [--limit N]
+//!   [--summary]` — print matching log records as JSON lines, or aggregate
+//!   counts and tool durations with `--summary`
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::Duration;
+
+use forge::runtime::event::{EventRecord, EventSink};
+use forge::runtime::inspect::{ChangeKind, RunInspector};
+use forge::runtime::output::TerminalEventSink;
+use forge::runtime::query::{EventQuery, QuerySummary};
+use forge::runtime::redact::RedactionPolicy;
+
+const VALUE_FLAGS: &[&str] = &[
+    "--from-seq",
+    "--interval-ms",
+    "--sensitive-tool",
+    "--redact-path",
+    "--redact-pattern",
+];
+
+const USAGE: &str = "usage: forge [--root DIR] [--logs DIR] [--checkpoints DIR] [--sessions DIR] \
+[--attachments DIR] [--json] <runs|checkpoints|log|tail|transcript|diff|verify|export|query> [args]";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(args) {
+        Ok(code) => code,
+        Err(message) => {
+            eprintln!("forge: {}", message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+struct Options {
+    inspector: RunInspector,
+    json: bool,
+    command: String,
+    args: Vec<String>,
+}
+
+fn parse_options(args: Vec<String>) -> Result<Options, String> {
+    let mut root = std::env::var_os("FORGE_ROOT")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("."));
+    let mut roots: [Option<PathBuf>; 4] = Default::default();
+    let mut json = false;
+    let mut iter = args.into_iter();
+    let command = loop {
+        let Some(arg) = iter.next() else {
+            return Err(USAGE.to_string());
+        };
+        let slot = match arg.as_str() {
+            "--json" => {
+                json = true;
+                continue;
+            }
+            "--root" => None,
+            "--logs" => Some(0),
+            "--checkpoints" => Some(1),
+            "--sessions" => Some(2),
+            "--attachments" => Some(3),
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            flag if flag.starts_with("--") => return Err(format!("unknown flag {}", flag)),
+            _ => break arg,
+        };
+        let value = iter
+            .next()
+            .map(PathBuf::from)
+            .ok_or_else(|| format!("missing value for {}", arg))?;
+        match slot {
+            Some(index) => roots[index] = Some(value),
+            None => root = value,
+        }
+    };
+
+    let [logs, checkpoints, sessions, attachments] = roots;
+    let or_root = |path: Option<PathBuf>| path.unwrap_or_else(|| root.clone());
+    let inspector = RunInspector::new(&root)
+        .with_log_root(or_root(logs))
+        .with_checkpoint_root(or_root(checkpoints))
+        .with_session_root(or_root(sessions))
+        .with_attachment_root(or_root(attachments));
+    Ok(Options {
+        inspector,
+        json,
+        command,
+        args: iter.collect(),
+    })
+}
+
+fn run(args: Vec<String>) -> Result<ExitCode, String> {
+    let Options {
+        inspector,
+        json,
+        command,
+        args,
+    } = parse_options(args)?;
+    let positional = |index: usize, name: &str| {
+        let mut values = Vec::new();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if VALUE_FLAGS.contains(&arg.as_str()) {
+                iter.next();
+            } else if !arg.starts_with("--") {
+                values.push(arg.clone());
+            }
+        }
+        values
+            .into_iter()
+            .nth(index)
+            .ok_or_else(|| format!("{} requires <{}>", command, name))
+    };
+
+    match command.as_str() {
+        "runs" => {
+            let runs = inspector.list_runs().map_err(io_error)?;
+            if json {
+                return print_json(&runs);
+            }
+            for run in runs {
+                println!(
+                    "{}\t{} events\t{} checkpoints",
+                    run.run_id, run.event_count, run.checkpoint_count
+                );
+            }
+        }
+        "checkpoints" => {
+            let run_id = positional(0, "run")?;
+            let checkpoints = inspector.list_checkpoints(&run_id).map_err(io_error)?;
+            if json {
+                return print_json(&checkpoints);
+            }
+            for checkpoint in checkpoints {
+                println!(
+                    "{}\t{}\tnext={}\titerations={}\tinterrupts={}",
+                    checkpoint.checkpoint_id,
+                    checkpoint.created_at,
+                    checkpoint.next_node,
+                    checkpoint.iterations,
+                    checkpoint.pending_interrupts
+                );
+            }
+        }
+        "log" | "tail" => {
+            let run_id = positional(0, "run")?;
+            let from_seq = flag_value(&args, "--from-seq")?.unwrap_or(0);
+            let raw = json || args.iter().any(|arg| arg == "--raw");
+            let printer = RecordPrinter::new(raw);
+            let logs = inspector.logs();
+            let mut next_seq = from_seq;
+            loop {
+                let records = logs.tail_from(&run_id, next_seq).map_err(io_error)?;
+                for record in records {
+                    next_seq = record.meta.seq + 1;
+                    printer.print(record)?;
+                }
+                if command == "log" {
+                    break;
+                }
+                let interval = flag_value(&args, "--interval-ms")?.unwrap_or(500);
+                std::thread::sleep(Duration::from_millis(interval));
+            }
+            printer.finish()?;
+        }
+        "transcript" => {
+            let session_id = positional(0, "session")?;
+            let transcript = inspector.render_transcript(&session_id).map_err(io_error)?;
+            print!("{}", transcript);
+        }
+        "diff" => {
+            let run_id = positional(0, "run")?;
+            let from = positional(1, "from")?;
+            let to = positional(2, "to")?;
+            let diff = inspector
+                .diff_checkpoints(&run_id, &from, &to)
+                .map_err(io_error)?;
+            if json {
+                return print_json(&diff);
+            }
+            println!("--- {}\n+++ {}", diff.from, diff.to);
+            if diff.next_node.0 != diff.next_node.1 {
+                println!("next_node: {} -> {}", diff.next_node.0, diff.next_node.1);
+            }
+            if diff.iterations.0 != diff.iterations.1 {
+                println!("iterations: {} -> {}", diff.iterations.0, diff.iterations.1);
+            }
+            for change in diff.state {
+                let show = |value: &Option<serde_json::Value>| {
+                    value
+                        .as_ref()
+                        .map(|value| value.to_string())
+                        .unwrap_or_default()
+                };
+                match change.kind {
+                    ChangeKind::Added => println!("+ {} {}", change.path, show(&change.after)),
+                    ChangeKind::Removed => println!("- {} {}", change.path, show(&change.before)),
+                    ChangeKind::Changed => println!(
+                        "~ {} {} -> {}",
+                        change.path,
+                        show(&change.before),
+                        show(&change.after)
+                    ),
+                }
+            }
+        }
+        "verify" => {
+            let run_id = positional(0, "run")?;
+            let report = inspector.verify(&run_id).map_err(io_error)?;
+            if json {
+                print_json(&report)?;
+            } else {
+                let chain = match &report.chain {
+                    Some(chain) if chain.sealed => "hash chain + seal",
+                    Some(_) => "hash chain",
+                    None => "seq order only (log is not hash-chained)",
+                };
+                println!(
+                    "{}: {} records, checked {}",
+                    run_id, report.record_count, chain
+                );
+                let first_broken = report
+                    .chain
+                    .as_ref()
+                    .and_then(|chain| chain.first_broken.as_ref())
+                    .or(report.sequence_break.as_ref());
+                if let Some(broken) = first_broken {
+                    println!("broken at seq {}: {}", broken.seq, broken.reason);
+                }
+                for error in &report.checkpoint_errors {
+                    println!("checkpoint {}", error);
+                }
+                println!("{}", if report.is_valid() { "OK" } else { "FAILED" });
+            }
+            if !report.is_valid() {
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+        "export" => {
+            let run_id = positional(0, "run")?;
+            let out = positional(1, "out.json")?;
+            let inspector = match redaction_policy(&args)? {
+                Some(policy) => inspector.with_redaction(Arc::new(policy)),
+                None => inspector,
+            };
+            let bundle = inspector.write_bundle(&run_id, &out).map_err(io_error)?;
+            eprintln!(
+                "wrote {}: {} events, {} checkpoints, {} sessions, {} attachments",
+                out,
+                bundle.events.len(),
+                bundle.checkpoints.len(),
+                bundle.sessions.len(),
+                bundle.attachments.len()
+            );
+        }
+        "query" => {
+            let (query, rest) = EventQuery::parse_args(&args).map_err(|err| err.to_string())?;
+            let summary = rest.iter().any(|arg| arg == "--summary");
+            if let Some(arg) = rest.iter().find(|arg| arg.as_str() != "--summary") {
+                return Err(format!("unexpected argument {}", arg));
+            }
+            let matches = query.execute(inspector.logs()).map_err(io_error)?;
+            if summary {
+                return print_json(&QuerySummary::from_matches(&matches));
+            }
+            for item in matches {
+                println!("{}", to_json_line(&item)?);
+            }
+        }
+        other => return Err(format!("unknown command {}\n{}", other, USAGE)),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Prints records as JSON lines or through the terminal renderer.
+struct RecordPrinter {
+    terminal: Option<TerminalEventSink<std::io::Stdout>>,
+}
+
+impl RecordPrinter {
+    fn new(raw: bool) -> Self {
+        Self {
+            terminal: (!raw).then(TerminalEventSink::stdout),
+        }
+    }
+
+    fn print(&self, record: EventRecord) -> Result<(), String> {
+        match &self.terminal {
+            Some(sink) => sink.emit(record.event).map_err(|err| err.to_string()),
+            None => {
+                println!("{}", to_json_line(&record)?);
+                Ok(())
+            }
+        }
+    }
+
+    fn finish(&self) -> Result<(), String> {
+        match &self.terminal {
+            Some(sink) => sink.finish().map_err(|err| err.to_string()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Redaction policy for `export`, if any redaction flag was given.
+fn redaction_policy(args: &[String]) -> Result<Option<RedactionPolicy>, String> {
+    let tools = flag_values(args, "--sensitive-tool")?;
+    let paths = flag_values(args, "--redact-path")?;
+    let patterns = flag_values(args, "--redact-pattern")?;
+    let requested = args.iter().any(|arg| arg == "--redact");
+    if !requested && tools.is_empty() && paths.is_empty() && patterns.is_empty() {
+        return Ok(None);
+    }
+    let mut policy = RedactionPolicy::new().with_default_patterns();
+    for tool in tools {
+        policy = policy.with_sensitive_tool(tool);
+    }
+    for path in paths {
+        policy = policy.with_json_path(path);
+    }
+    for pattern in patterns {
+        policy = policy
+            .with_pattern(&pattern)
+            .map_err(|err| err.to_string())?;
+    }
+    Ok(Some(policy))
+}
+
+/// Every value given for a repeatable flag.
+fn flag_values(args: &[String], flag: &str) -> Result<Vec<String>, String> {
+    let mut values = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == flag {
+            let value = iter
+                .next()
+                .ok_or_else(|| format!("missing value for {}", flag))?;
+            values.push(value.clone());
+        }
+    }
+    Ok(values)
+}
+
+fn flag_value(args: &[String], flag: &str) -> Result<Option<u64>, String> {
+    let Some(index) = args.iter().position(|arg| arg == flag) else {
+        return Ok(None);
+    };
+    let value = args
+        .get(index + 1)
+        .ok_or_else(|| format!("missing value for {}", flag))?;
+    value
+        .parse()
+        .map(Some)
+        .map_err(|_| format!("{} expects a number, got {:?}", flag, value))
+}
+
+fn print_json<T: serde::Serialize>(value: &T) -> Result<ExitCode, String> {
+    let json = serde_json::to_string_pretty(value).map_err(|err| err.to_string())?;
+    println!("{}", json);
+    Ok(ExitCode::SUCCESS)
+}
+
+fn to_json_line<T: serde::Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string(value).map_err(|err| err.to_string())
+}
+
+fn io_error(err: std::io::Error) -> String {
+    err.to_string()
+}
@@ -0,0 +1,744 @@
+//! Prebuilt model-driven agent loop on top of [`LoopNode`].
+//!
+//! [`AgentNode`] runs the ReAct-style loop every tool-using agent needs:
+//! call the model with the conversation and the registry's tools, execute
+//! the tool calls it requests through [`LoopContext::run_tools`], append the
+//! results and repeat until the model answers without tool calls, a stop
+//! condition fires, or `max_steps` is reached.
+//!
+//! Messages are recorded into the graph state through [`AgentState`]. When a
+//! tool needs permission the node returns `GraphError::Interrupted` tagged
+//! with its own name, carrying every pending request of the turn; resuming
+//! the graph with a permission reply (`"once"`, `"always"`, `"reject"`, or
+//! an object mapping permissions to replies) runs the pending tool calls
+//! instead of asking the model again. The pending turn (recorded messages,
+//! tool calls still to run, finished results and step count) is saved in the
+//! interrupt's `resume_state`, so it survives in the checkpoint and a resume
+//! in a fresh process continues it too.
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::runtime::cancel::CancellationToken;
+use crate::runtime::component::{ChatModel, ChatRequest, ChatResponse};
+use crate::runtime::error::{GraphError, GraphResult, Interrupt, ResumeCommand};
+use crate::runtime::event::Event;
+use crate::runtime::message::{Message, MessageRole, Part};
+use crate::runtime::node::NodeSpec;
+use crate::runtime::permission::{
+    PermissionBatch, PermissionPolicy, PermissionRequest, PermissionSession,
+};
+use crate::runtime::pricing::COST_METADATA_KEY;
+use crate::runtime::r#loop::{LoopContext, LoopNode, DEFAULT_TOOL_CONCURRENCY};
+use crate::runtime::state::GraphState;
+use crate::runtime::tool::{AttachmentPolicy, ToolCall, ToolOutput, ToolRegistry};
+
+const DEFAULT_MAX_STEPS: usize = 10;
+
+type StopCondition = Arc<dyn Fn(&ChatResponse) -> bool + Send + Sync>;
+
+/// Graph state that carries the conversation an [`AgentNode`] drives.
+///
+/// Resume values reach the node through [`GraphState::get`] under
+/// `resume:<node name>`, and the saved turn under `interrupt:<node name>`,
+/// so states used with interrupts should store both `serde_json::Value`s as
+/// the executor's pause/resume tests do. Without the saved turn a resume
+/// asks the model again.
+pub trait AgentState: GraphState {
+    fn messages(&self) -> &[Message];
+
+    fn messages_mut(&mut self) -> &mut Vec<Message>;
+}
+
+/// Model-driven tool loop; build it, then convert with
+/// [`into_loop_node`](Self::into_loop_node) or [`into_node`](Self::into_node).
+#[derive(Clone)]
+pub struct AgentNode {
+    name: String,
+    model: Arc<dyn ChatModel>,
+    tools: Arc<ToolRegistry>,
+    system_prompt: Option<String>,
+    max_steps: usize,
+    stop_conditions: Vec<StopCondition>,
+    streaming: bool,
+    session_id: Option<String>,
+    gate: Arc<PermissionSession>,
+    attachment_policy: AttachmentPolicy,
+    cancel: CancellationToken,
+    tool_concurrency: usize,
+}
+
+impl AgentNode {
+    pub fn new(
+        name: impl Into<String>,
+        model: Arc<dyn ChatModel>,
+        tools: Arc<ToolRegistry>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            model,
+            tools,
+            system_prompt: None,
+            max_steps: DEFAULT_MAX_STEPS,
+            stop_conditions: Vec::new(),
+            streaming: true,
+            session_id: None,
+            gate: Arc::new(PermissionSession::new(PermissionPolicy::default())),
+            attachment_policy: AttachmentPolicy::default(),
+            cancel: CancellationToken::new(),
+            tool_concurrency: DEFAULT_TOOL_CONCURRENCY,
+        }
+    }
+
+    /// Sent as the first message of every request; not recorded in state.
+    pub fn with_system_prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.system_prompt = Some(prompt.into());
+        self
+    }
+
+    /// Fail once the model has been called this many times in one run
+    /// without finishing.
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps.max(1);
+        self
+    }
+
+    /// Stop after a response for which `condition` holds, once its tool
+    /// calls (if any) have run.
+    pub fn with_stop_condition(
+        mut self,
+        condition: impl Fn(&ChatResponse) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.stop_conditions.push(Arc::new(condition));
+        self
+    }
+
+    /// Stop after the model calls `tool`, e.g. a `final_answer` tool.
+    pub fn with_stop_on_tool(self, tool: impl Into<String>) -> Self {
+        let tool = tool.into();
+        self.with_stop_condition(move |response| {
+            response.tool_calls().iter().any(|call| call.tool == tool)
+        })
+    }
+
+    /// Use `ChatModel::stream` (the default) or `generate`.
+    pub fn with_streaming(mut self, streaming: bool) -> Self {
+        self.streaming = streaming;
+        self
+    }
+
+    /// Session id for requests and step events; defaults to the node name.
+    pub fn with_session_id(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
+        self
+    }
+
+    pub fn with_gate(mut self, gate: Arc<PermissionSession>) -> Self {
+        self.gate = gate;
+        self
+    }
+
+    pub fn with_attachment_policy(mut self, policy: AttachmentPolicy) -> Self {
+        self.attachment_policy = policy;
+        self
+    }
+
+    pub fn with_cancel_token(mut self, token: CancellationToken) -> Self {
+        self.cancel = token;
+        self
+    }
+
+    /// Run at most `limit` of a response's tool calls at once.
+    pub fn with_tool_concurrency(mut self, limit: usize) -> Self {
+        self.tool_concurrency = limit.max(1);
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn into_loop_node<S: AgentState>(self) -> LoopNode<S> {
+        let name = self.name.clone();
+        let tools = Arc::clone(&self.tools);
+        let gate = Arc::clone(&self.gate);
+        let policy = self.attachment_policy.clone();
+        let cancel = self.cancel.clone();
+        let tool_concurrency = self.tool_concurrency;
+        let runner = Arc::new(AgentRunner { config: self });
+        LoopNode::with_tools_and_gate_and_policy(name, tools, gate, policy, move |state, ctx| {
+            let runner = Arc::clone(&runner);
+            async move { runner.run(state, ctx).await }
+        })
+        .with_cancel_token(cancel)
+        .with_tool_concurrency(tool_concurrency)
+    }
+
+    pub fn into_node<S: AgentState>(self) -> NodeSpec<S> {
+        self.into_loop_node().into_node()
+    }
+}
+
+/// A turn interrupted by a permission request or a tool, saved in the
+/// interrupt so resuming the node continues it, even in a new process.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Suspended {
+    /// [`conversation_mark`] of the messages the node was entered with.
+    entered: String,
+    /// Messages recorded since the node was entered.
+    recorded: Vec<Message>,
+    pending: PendingTools,
+    step: usize,
+    permissions: Vec<String>,
+}
+
+/// Tool calls from the latest response that have not run yet.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct PendingTools {
+    calls: Vec<ToolCall>,
+    /// Result part per call, filled in as calls finish so an interrupted
+    /// batch only re-runs the calls that did not.
+    done: Vec<Option<Part>>,
+    stop: bool,
+}
+
+impl PendingTools {
+    fn new(calls: Vec<ToolCall>, stop: bool) -> Self {
+        let done = vec![None; calls.len()];
+        Self { calls, done, stop }
+    }
+}
+
+struct AgentRunner {
+    config: AgentNode,
+}
+
+impl AgentRunner {
+    async fn run<S: AgentState>(&self, mut state: S, ctx: LoopContext) -> GraphResult<S> {
+        let config = &self.config;
+        let mark = conversation_mark(state.messages());
+        let entered = state.messages().len();
+        let mut step = 0;
+        let mut pending = None;
+        if let Some(suspended) = take_suspended(&mut state, &config.name, &mark) {
+            state.messages_mut().extend(suspended.recorded);
+            step = suspended.step;
+            pending = Some(suspended.pending);
+            if let Some(value) = resume_value(&state, &config.name) {
+                ctx.resume_permissions(&suspended.permissions, &ResumeCommand::new(value))?;
+            }
+        }
+
+        loop {
+            let mut tools = match pending.take() {
+                Some(tools) => tools,
+                None => {
+                    if step >= config.max_steps {
+                        return Err(agent_error(
+                            &config.name,
+                            format!(
+                                "reached max steps ({}) without a final answer",
+                                config.max_steps
+                            ),
+                        ));
+                    }
+                    step += 1;
+                    let response = self.call_model(state.messages(), &ctx).await?;
+                    let calls = response.tool_calls();
+                    let stop = config
+                        .stop_conditions
+                        .iter()
+                        .any(|condition| condition(&response));
+                    state.messages_mut().push(response.message);
+                    if calls.is_empty() {
+                        return Ok(state);
+                    }
+                    PendingTools::new(calls, stop)
+                }
+            };
+
+            let waiting: Vec<usize> = (0..tools.calls.len())
+                .filter(|index| tools.done[*index].is_none())
+                .collect();
+            let calls = waiting
+                .iter()
+                .map(|index| tools.calls[*index].clone())
+                .collect();
+            let batch = ctx.run_tools_partial(calls).await;
+            let interrupts = match batch {
+                Ok(batch) => {
+                    for (index, output) in waiting.into_iter().zip(batch.results) {
+                        let call = &tools.calls[index];
+                        tools.done[index] = output.map(|output| result_part(call, output));
+                    }
+                    batch.interrupts
+                }
+                Err(GraphError::Interrupted(interrupts)) => interrupts,
+                Err(err) => return Err(err),
+            };
+            if !interrupts.is_empty() {
+                let permissions = interrupts
+                    .iter()
+                    .flat_map(|interrupt| interrupt_permissions(&interrupt.value))
+                    .collect();
+                let suspended = Suspended {
+                    entered: mark,
+                    recorded: state.messages()[entered..].to_vec(),
+                    pending: tools,
+                    step,
+                    permissions,
+                };
+                // The executor hands back the first saved state it finds
+                // for the node, so one copy of the turn is enough.
+                return Err(GraphError::Interrupted(
+                    interrupts
+                        .into_iter()
+                        .enumerate()
+                        .map(|(index, interrupt)| {
+                            let tagged = Interrupt::with_id(
+                                interrupt.value,
+                                config.name.clone(),
+                                interrupt.id,
+                            );
+                            if index == 0 {
+                                tagged.with_resume_state(&suspended)
+                            } else {
+                                tagged
+                            }
+                        })
+                        .collect(),
+                ));
+            }
+            let mut results = Message::new(MessageRole::Tool);
+            results.parts.extend(tools.done.into_iter().flatten());
+            state.messages_mut().push(results);
+            if tools.stop {
+                return Ok(state);
+            }
+        }
+    }
+
+    async fn call_model(
+        &self,
+        conversation: &[Message],
+        ctx: &LoopContext,
+    ) -> GraphResult<ChatResponse> {
+        let config = &self.config;
+        let session_id = config.session_id.as_deref().unwrap_or(&config.name);
+        let mut messages = Vec::with_capacity(conversation.len() + 1);
+        if let Some(prompt) = &config.system_prompt {
+            let mut system = Message::new(MessageRole::System);
+            system.parts.push(Part::TextFinal {
+                text: prompt.clone(),
+            });
+            messages.push(system);
+        }
+        messages.extend(conversation.iter().cloned());
+        let request = ChatRequest::new(session_id, uuid::Uuid::new_v4().to_string(), messages)
+            .with_registry_tools(&config.tools)
+            .with_cancellation_token(ctx.cancellation_token().clone());
+
+        ctx.emit(Event::StepStart {
+            session_id: session_id.to_string(),
+        })?;
+        let response = if config.streaming {
+            config.model.stream(request, ctx.sink()).await?
+        } else {
+            config.model.generate(request).await?
+        };
+        ctx.emit(Event::StepFinish {
+            session_id: session_id.to_string(),
+            tokens: response.usage.clone().unwrap_or_default(),
+            cost: response
+                .metadata
+                .get(COST_METADATA_KEY)
+                .and_then(|cost| cost.as_f64())
+                .unwrap_or(0.0),
+            model: Some(
+                response
+                    .model
+                    .clone()
+                    .unwrap_or_else(|| config.model.model_id().to_string()),
+            ),
+        })?;
+        Ok(response)
+    }
+}
+
+fn result_part(call: &ToolCall, output: GraphResult<ToolOutput>) -> Part {
+    match output {
+        Ok(output) => Part::ToolResult {
+            tool: call.tool.clone(),
+            call_id: call.call_id.clone(),
+            output,
+        },
+        Err(err) => Part::ToolError {
+            tool: call.tool.clone(),
+            call_id: call.call_id.clone(),
+            error: err.to_string(),
+        },
+    }
+}
+
+/// Identifies the conversation a node was entered with, so a saved turn is
+/// only restored into the run it was taken from.
+fn conversation_mark(messages: &[Message]) -> String {
+    format!(
+        "{}:{}",
+        messages.len(),
+        messages
+            .last()
+            .map(|message| message.id.as_str())
+            .unwrap_or("")
+    )
+}
+
+/// Take the turn saved by an earlier interrupt of `node`, clearing it from
+/// the state so a later entry of the node starts fresh.
+fn take_suspended<S: GraphState>(state: &mut S, node: &str, mark: &str) -> Option<Suspended> {
+    let key = format!("interrupt:{}", node);
+    let saved = state
+        .get(&key)
+        .and_then(|value| value.downcast_ref::<serde_json::Value>())
+        .filter(|value| !value.is_null())
+        .cloned()?;
+    state.set(&key, Box::new(serde_json::Value::Null));
+    serde_json::from_value::<Suspended>(saved)
+        .ok()
+        .filter(|suspended| suspended.entered == mark)
+}
+
+/// Permissions named by a batch or single-request interrupt value.
+fn interrupt_permissions(value: &serde_json::Value) -> Vec<String> {
+    if let Ok(batch) = serde_json::from_value::<PermissionBatch>(value.clone()) {
+        return batch.permissions();
+    }
+    serde_json::from_value::<PermissionRequest>(value.clone())
+        .map(|request| vec![request.permission])
+        .unwrap_or_default()
+}
+
+fn resume_value<S: GraphState>(state: &S, node: &str) -> Option<serde_json::Value> {
+    state
+        .get(&format!("resume:{}", node))
+        .and_then(|value| value.downcast_ref::<serde_json::Value>())
+        .cloned()
+}
+
+fn agent_error(node: &str, message: impl Into<String>) -> GraphError {
+    GraphError::ExecutionError {
+        node: format!("agent:{}", node),
+        message: message.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AgentNode, AgentState};
+    use crate::runtime::constants::{END, START};
+    use crate::runtime::error::{GraphError, GraphResult, ResumeCommand};
+    use crate::runtime::event::{Event, EventSink};
+    use crate::runtime::executor::ExecutionResult;
+    use crate::runtime::graph::StateGraph;
+    use crate::runtime::message::{Message, MessageRole, Part};
+    use crate::runtime::permission::{
+        PermissionDecision, PermissionPolicy, PermissionRule, PermissionSession,
+    };
+    use crate::runtime::pricing::{ModelPrice, PricedChatModel, PricingRegistry};
+    use crate::runtime::provider::scripted::{ScriptedChatModel, ScriptedTurn};
+    use crate::runtime::state::GraphState;
+    use crate::runtime::tool::{ToolDefinition, ToolOutput, ToolRegistry};
+    use futures::executor::block_on;
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+    use std::any::Any;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default, Serialize, Deserialize)]
+    struct ChatState {
+        messages: Vec<Message>,
+        resume: Option<serde_json::Value>,
+        interrupt: Option<serde_json::Value>,
+    }
+
+    impl GraphState for ChatState {
+        fn get(&self, key: &str) -> Option<&dyn Any> {
+            match key {
+                "resume:agent" => self.resume.as_ref().map(|value| value as &dyn Any),
+                "interrupt:agent" => self.interrupt.as_ref().map(|value| value as &dyn Any),
+                _ => None,
+            }
+        }
+
+        fn set(&mut self, key: &str, value: Box<dyn Any + Send + Sync>) {
+            let Ok(value) = value.downcast::<serde_json::Value>() else {
+                return;
+            };
+            match key {
+                "resume:agent" => self.resume = Some(*value),
+                "interrupt:agent" => self.interrupt = Some(*value),
+                _ => {}
+            }
+        }
+    }
+
+    impl AgentState for ChatState {
+        fn messages(&self) -> &[Message] {
+            &self.messages
+        }
+
+        fn messages_mut(&mut self) -> &mut Vec<Message> {
+            &mut self.messages
+        }
+    }
+
+    #[derive(Default)]
+    struct CaptureSink {
+        events: Mutex<Vec<Event>>,
+    }
+
+    impl EventSink for CaptureSink {
+        fn emit(&self, event: Event) -> GraphResult<()> {
+            self.events.lock().unwrap().push(event);
+            Ok(())
+        }
+    }
+
+    fn user_state(text: &str) -> ChatState {
+        let mut message = Message::new(MessageRole::User);
+        message.parts.push(Part::TextFinal {
+            text: text.to_string(),
+        });
+        ChatState {
+            messages: vec![message],
+            ..ChatState::default()
+        }
+    }
+
+    fn weather_tools(runs: Arc<AtomicUsize>) -> Arc<ToolRegistry> {
+        let mut registry = ToolRegistry::new();
+        registry.register_with_definition(
+            ToolDefinition::new("weather", "Current weather for a city"),
+            Arc::new(move |call, _ctx| {
+                runs.fetch_add(1, Ordering::SeqCst);
+                Box::pin(async move {
+                    let city = call.input["city"].as_str().unwrap_or_default().to_string();
+                    Ok(ToolOutput::text(format!("{}: sunny", city)))
+                })
+            }),
+        );
+        Arc::new(registry)
+    }
+
+    #[test]
+    fn agent_steps_carry_the_cost_priced_by_the_model() {
+        let model = ScriptedChatModel::new("small")
+            .with_turn(ScriptedTurn::text("Hi.").with_usage(1_000, 1_000));
+        let pricing = PricingRegistry::new().with_price("small", ModelPrice::new(1.0, 3.0));
+        let node = AgentNode::new(
+            "agent",
+            Arc::new(PricedChatModel::new(Arc::new(model), Arc::new(pricing))),
+            Arc::new(ToolRegistry::new()),
+        )
+        .into_loop_node::<ChatState>();
+        let sink = Arc::new(CaptureSink::default());
+
+        block_on(node.run(user_state("Hello"), sink.clone())).expect("agent finishes");
+
+        let events = sink.events.lock().unwrap();
+        let costs = events
+            .iter()
+            .filter_map(|event| match event {
+                Event::StepFinish { cost, .. } => Some(*cost),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(costs.len(), 1);
+        assert!((costs[0] - 0.004).abs() < 1e-12, "{costs:?}");
+    }
+
+    #[test]
+    fn agent_runs_tools_until_final_answer_and_records_messages() {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let model = ScriptedChatModel::new("scripted")
+            .with_turn(ScriptedTurn::tool_call(
+                "weather",
+                "call-1",
+                json!({"city": "Oslo"}),
+            ))
+            .with_turn(ScriptedTurn::streamed(["It is ", "sunny."]));
+        let node = AgentNode::new(
+            "agent",
+            Arc::new(model.clone()),
+            weather_tools(runs.clone()),
+        )
+        .with_system_prompt("You answer weather questions.")
+        .into_loop_node::<ChatState>();
+        let sink = Arc::new(CaptureSink::default());
+
+        let state = block_on(node.run(user_state("Weather in Oslo?"), sink.clone()))
+            .expect("agent finishes");
+        let roles = state
+            .messages
+            .iter()
+            .map(|message| message.role.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            roles,
+            vec![
+                MessageRole::User,
+                MessageRole::Assistant,
+                MessageRole::Tool,
+                MessageRole::Assistant
+            ]
+        );
+        match &state.messages[2].parts[0] {
+            Part::ToolResult {
+                call_id, output, ..
+            } => {
+                assert_eq!(call_id, "call-1");
+                assert_eq!(output.content, json!("Oslo: sunny"));
+            }
+            other => panic!("unexpected part {:?}", other),
+        }
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+
+        let requests = model.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].messages[0].role, MessageRole::System);
+        assert_eq!(requests[0].tools[0].name, "weather");
+        assert_eq!(requests[1].messages.len(), 4);
+
+        let events = sink.events.lock().unwrap();
+        let steps = events
+            .iter()
+            .filter(|event| matches!(event, Event::StepStart { .. }))
+            .count();
+        assert_eq!(steps, 2);
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, Event::TextDelta { delta, .. } if delta == "sunny.")));
+    }
+
+    #[test]
+    fn agent_honours_stop_conditions_and_max_steps() {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let looping = ScriptedChatModel::new("scripted").with_turn(
+            ScriptedTurn::tool_call("weather", "call-n", json!({"city": "Rome"})).repeating(),
+        );
+        let node = AgentNode::new(
+            "agent",
+            Arc::new(looping.clone()),
+            weather_tools(runs.clone()),
+        )
+        .with_max_steps(3)
+        .with_streaming(false)
+        .into_loop_node::<ChatState>();
+        let sink: Arc<dyn EventSink> = Arc::new(CaptureSink::default());
+        match block_on(node.run(user_state("loop"), Arc::clone(&sink))) {
+            Err(GraphError::ExecutionError { node, message }) => {
+                assert_eq!(node, "agent:agent");
+                assert!(message.contains("max steps (3)"));
+            }
+            other => panic!("unexpected result {:?}", other.map(|state| state.messages)),
+        }
+        assert_eq!(looping.call_count(), 3);
+
+        let node = AgentNode::new("agent", Arc::new(looping.clone()), weather_tools(runs))
+            .with_stop_on_tool("weather")
+            .into_loop_node::<ChatState>();
+        let state = block_on(node.run(user_state("once"), sink)).expect("stops");
+        assert_eq!(state.messages.len(), 3);
+        assert_eq!(state.messages[2].role, MessageRole::Tool);
+        assert_eq!(looping.call_count(), 4);
+    }
+
+    #[test]
+    fn agent_resumes_pending_tool_call_after_permission_interrupt() {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let model = ScriptedChatModel::new("scripted")
+            .with_turn(ScriptedTurn::tool_call(
+                "weather",
+                "call-1",
+                json!({"city": "Lima"}),
+            ))
+            .with_turn(ScriptedTurn::text("Sunny in Lima."));
+        let gate = Arc::new(PermissionSession::new(PermissionPolicy::new(vec![
+            PermissionRule::new(PermissionDecision::Ask, vec!["tool:weather".to_string()]),
+        ])));
+        let node = AgentNode::new(
+            "agent",
+            Arc::new(model.clone()),
+            weather_tools(runs.clone()),
+        )
+        .with_gate(gate)
+        .into_node::<ChatState>();
+
+        let mut graph = StateGraph::<ChatState>::new();
+        graph.add_node_spec(node);
+        graph.add_edge(START, "agent");
+        graph.add_edge("agent", END);
+        let compiled = graph.compile().expect("compile");
+
+        let first =
+            block_on(compiled.invoke_resumable(user_state("Weather in Lima?"))).expect("runs");
+        let checkpoint = match first {
+            ExecutionResult::Interrupted {
+                checkpoint,
+                interrupts,
+            } => {
+                assert_eq!(interrupts.len(), 1);
+                assert_eq!(interrupts[0].node, "agent");
+                assert_eq!(
+                    interrupts[0].value["requests"][0]["permission"],
+                    "tool:weather"
+                );
+                checkpoint
+            }
+            ExecutionResult::Complete(_) => panic!("expected a permission interrupt"),
+        };
+        assert_eq!(runs.load(Ordering::SeqCst), 0);
+        assert_eq!(model.call_count(), 1);
+
+        // Resume in a "new process": a fresh node and a checkpoint that went
+        // through JSON, with a model that only knows the final answer.
+        let checkpoint = serde_json::from_value(serde_json::to_value(&checkpoint).unwrap())
+            .expect("checkpoint round-trips");
+        let model =
+            ScriptedChatModel::new("scripted").with_turn(ScriptedTurn::text("Sunny in Lima."));
+        let gate = Arc::new(PermissionSession::new(PermissionPolicy::new(vec![
+            PermissionRule::new(PermissionDecision::Ask, vec!["tool:weather".to_string()]),
+        ])));
+        let node = AgentNode::new(
+            "agent",
+            Arc::new(model.clone()),
+            weather_tools(runs.clone()),
+        )
+        .with_gate(gate)
+        .into_node::<ChatState>();
+        let mut graph = StateGraph::<ChatState>::new();
+        graph.add_node_spec(node);
+        graph.add_edge(START, "agent");
+        graph.add_edge("agent", END);
+        let compiled = graph.compile().expect("compile");
+
+        let resumed =
+            block_on(compiled.resume(checkpoint, ResumeCommand::new("once"))).expect("resumes");
+        let state = match resumed {
+            ExecutionResult::Complete(state) => state,
+            ExecutionResult::Interrupted { .. } => panic!("still interrupted"),
+        };
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert_eq!(model.call_count(), 1);
+        assert_eq!(state.interrupt, Some(serde_json::Value::Null));
+        assert_eq!(state.messages.len(), 4);
+        assert_eq!(
+            state.messages[3].parts,
+            vec![Part::TextFinal {
+                text: "Sunny in Lima.".to_string()
+            }]
+        );
+    }
+}
@@ -0,0 +1,225 @@
+//! Tamper-evident audit logs built from hash-chained event records.
+//!
+//! Each linked record stores the SHA-256 digest of the previous record in
+//! `EventMeta::prev_hash`. Editing, deleting, or reordering a record breaks
+//! the chain at the following record; an optional [`AuditSeal`] pins the
+//! digest of the final record so truncation and tail edits are caught too.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::runtime::event::EventRecord;
+
+const AUDIT_SEAL_VERSION: u32 = 1;
+
+/// SHA-256 hex digest of a record's canonical JSON encoding.
+pub fn record_digest(record: &EventRecord) -> String {
+    let encoded = serde_json::to_vec(record).expect("event record serializes");
+    let digest = Sha256::digest(&encoded);
+    digest.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Links records into a hash chain as they are persisted.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HashChain {
+    head: Option<String>,
+}
+
+impl HashChain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Continue an existing chain whose last record has digest `head`.
+    pub fn resume(head: Option<String>) -> Self {
+        Self { head }
+    }
+
+    /// Digest of the most recently linked record.
+    pub fn head(&self) -> Option<&str> {
+        self.head.as_deref()
+    }
+
+    /// Stamp `prev_hash` on the record and advance the chain head.
+    pub fn link(&mut self, mut record: EventRecord) -> EventRecord {
+        record.meta.prev_hash = self.head.clone();
+        self.head = Some(record_digest(&record));
+        record
+    }
+
+    pub fn link_all(&mut self, records: Vec<EventRecord>) -> Vec<EventRecord> {
+        records
+            .into_iter()
+            .map(|record| self.link(record))
+            .collect()
+    }
+}
+
+/// Final digest pinning the end of a hash-chained log.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AuditSeal {
+    pub version: u32,
+    pub record_count: usize,
+    pub last_seq: Option<u64>,
+    pub digest: Option<String>,
+    pub sealed_at_ms: u64,
+}
+
+impl AuditSeal {
+    pub fn for_records(records: &[EventRecord]) -> Self {
+        Self {
+            version: AUDIT_SEAL_VERSION,
+            record_count: records.len(),
+            last_seq: records.last().map(|record| record.meta.seq),
+            digest: records.last().map(record_digest),
+            sealed_at_ms: chrono::Utc::now().timestamp_millis().max(0) as u64,
+        }
+    }
+}
+
+/// First point at which a chain fails verification.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ChainBreak {
+    /// Position of the offending record in the log.
+    pub index: usize,
+    /// Seq of the offending record (or of the last record for seal mismatches).
+    pub seq: u64,
+    pub reason: String,
+}
+
+/// Result of verifying a hash-chained log.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ChainReport {
+    pub record_count: usize,
+    pub head: Option<String>,
+    pub sealed: bool,
+    pub first_broken: Option<ChainBreak>,
+}
+
+impl ChainReport {
+    pub fn is_valid(&self) -> bool {
+        self.first_broken.is_none()
+    }
+}
+
+/// Verify record links (and the seal, when given) in log order.
+pub fn verify_chain(records: &[EventRecord], seal: Option<&AuditSeal>) -> ChainReport {
+    let mut report = ChainReport {
+        record_count: records.len(),
+        head: None,
+        sealed: seal.is_some(),
+        first_broken: None,
+    };
+    let mut previous_seq: Option<u64> = None;
+    for (index, record) in records.iter().enumerate() {
+        let broken = |reason: String| ChainBreak {
+            index,
+            seq: record.meta.seq,
+            reason,
+        };
+        if record.meta.prev_hash != report.head {
+            report.first_broken = Some(broken(match (&record.meta.prev_hash, &report.head) {
+                (None, _) => "record is not hash-chained".to_string(),
+                (Some(_), None) => "first record references a missing predecessor".to_string(),
+                (Some(_), Some(_)) => "prev_hash does not match preceding record".to_string(),
+            }));
+            return report;
+        }
+        if let Some(previous) = previous_seq {
+            if record.meta.seq <= previous {
+                report.first_broken = Some(broken(format!(
+                    "seq {} does not follow {}",
+                    record.meta.seq, previous
+                )));
+                return report;
+            }
+        }
+        previous_seq = Some(record.meta.seq);
+        report.head = Some(record_digest(record));
+    }
+
+    if let Some(seal) = seal {
+        let last_seq = records.last().map(|record| record.meta.seq).unwrap_or(0);
+        let reason = if seal.record_count != records.len() {
+            Some(format!(
+                "seal covers {} records but log has {}",
+                seal.record_count,
+                records.len()
+            ))
+        } else if seal.digest != report.head {
+            Some("final record does not match seal digest".to_string())
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            report.first_broken = Some(ChainBreak {
+                index: records.len().saturating_sub(1),
+                seq: last_seq,
+                reason,
+            });
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{verify_chain, AuditSeal, HashChain};
+    use crate::runtime::event::{Event, EventRecord, EventSequencer};
+
+    fn chained(count: usize) -> Vec<EventRecord> {
+        let sequencer = EventSequencer::new();
+        let mut chain = HashChain::new();
+        (0..count)
+            .map(|index| {
+                chain.link(sequencer.record(Event::StepStart {
+                    session_id: format!("s{}", index),
+                }))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn intact_chain_verifies() {
+        let records = chained(3);
+        assert!(records[0].meta.prev_hash.is_none());
+        assert!(records[1].meta.prev_hash.is_some());
+        let seal = AuditSeal::for_records(&records);
+        let report = verify_chain(&records, Some(&seal));
+        assert!(report.is_valid());
+        assert_eq!(report.head, seal.digest);
+    }
+
+    #[test]
+    fn modification_breaks_following_record() {
+        let mut records = chained(3);
+        records[1].event = Event::StepStart {
+            session_id: "tampered".to_string(),
+        };
+        let broken = verify_chain(&records, None).first_broken.expect("broken");
+        assert_eq!(broken.seq, records[2].meta.seq);
+    }
+
+    #[test]
+    fn deletion_and_reordering_are_detected() {
+        let mut deleted = chained(3);
+        let removed = deleted.remove(1);
+        let broken = verify_chain(&deleted, None).first_broken.expect("broken");
+        assert_eq!(broken.seq, removed.meta.seq + 1);
+
+        let mut reordered = chained(3);
+        reordered.swap(1, 2);
+        let broken = verify_chain(&reordered, None).first_broken.expect("broken");
+        assert_eq!(broken.index, 1);
+    }
+
+    #[test]
+    fn seal_detects_truncated_tail() {
+        let records = chained(3);
+        let seal = AuditSeal::for_records(&records);
+        let report = verify_chain(&records[..2], Some(&seal));
+        let broken = report.first_broken.expect("broken");
+        assert_eq!(broken.seq, records[1].meta.seq);
+        assert!(broken.reason.contains("seal"));
+    }
+}
@@ -1,13 +1,26 @@
-//! Cancellation primitives for runtime execution.
+//! Cancellation primitives and timers for runtime execution.
+//!
+//! [`Delay`], [`sleep`] and [`timeout`] are runtime-agnostic: a single
+//! shared timer thread wakes waiting tasks, so a pending wait never blocks
+//! the executor thread that polls it.
 
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, Mutex};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+
+use crate::runtime::error::{GraphError, GraphResult};
 
 /// Cooperative cancellation token for long-running tasks.
 #[derive(Clone, Debug, Default)]
 pub struct CancellationToken {
     cancelled: Arc<AtomicBool>,
     reason: Arc<Mutex<Option<String>>>,
+    wakers: Arc<Mutex<BTreeMap<u64, Waker>>>,
+    next_waker: Arc<AtomicU64>,
 }
 
 impl CancellationToken {
@@ -17,8 +30,14 @@ impl CancellationToken {
 
     pub fn cancel(&self, reason: impl Into<String>) {
         self.cancelled.store(true, Ordering::SeqCst);
-        let mut guard = self.reason.lock().unwrap();
-        *guard = Some(reason.into());
+        {
+            let mut guard = self.reason.lock().unwrap();
+            *guard = Some(reason.into());
+        }
+        let wakers = std::mem::take(&mut *self.wakers.lock().unwrap());
+        for waker in wakers.into_values() {
+            waker.wake();
+        }
     }
 
     pub fn is_cancelled(&self) -> bool {
@@ -32,4 +51,275 @@ impl CancellationToken {
     pub fn abort_reason(&self) -> String {
         self.reason().unwrap_or_else(|| "cancelled".to_string())
     }
+
+    /// Wake `waker` when the token is cancelled.
+    ///
+    /// Pass the key from a previous call to replace that waker; remove it
+    /// with [`remove_waker`](Self::remove_waker) once the task finishes.
+    pub fn register_waker(&self, key: Option<u64>, waker: &Waker) -> u64 {
+        let key = key.unwrap_or_else(|| self.next_waker.fetch_add(1, Ordering::SeqCst));
+        self.wakers.lock().unwrap().insert(key, waker.clone());
+        if self.is_cancelled() {
+            waker.wake_by_ref();
+        }
+        key
+    }
+
+    pub fn remove_waker(&self, key: u64) {
+        self.wakers.lock().unwrap().remove(&key);
+    }
+}
+
+/// Tokens are equal when they share the same underlying cancellation flag.
+impl PartialEq for CancellationToken {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.cancelled, &other.cancelled)
+    }
+}
+
+/// Run `future`, resolving to `GraphError::Aborted` as soon as `token` is
+/// cancelled instead of waiting for the future to finish.
+pub fn cancellable<'a, T, F>(token: Option<&'a CancellationToken>, future: F) -> Cancellable<'a, F>
+where
+    F: Future<Output = GraphResult<T>>,
+{
+    Cancellable {
+        token,
+        key: None,
+        future: Box::pin(future),
+    }
+}
+
+/// Future returned by [`cancellable`].
+pub struct Cancellable<'a, F> {
+    token: Option<&'a CancellationToken>,
+    key: Option<u64>,
+    future: Pin<Box<F>>,
+}
+
+impl<T, F> Future for Cancellable<'_, F>
+where
+    F: Future<Output = GraphResult<T>>,
+{
+    type Output = GraphResult<T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Some(token) = self.token {
+            if token.is_cancelled() {
+                return Poll::Ready(Err(GraphError::Aborted {
+                    reason: token.abort_reason(),
+                }));
+            }
+            self.key = Some(token.register_waker(self.key, cx.waker()));
+        }
+        self.future.as_mut().poll(cx)
+    }
+}
+
+impl<F> Drop for Cancellable<'_, F> {
+    fn drop(&mut self) {
+        if let (Some(token), Some(key)) = (self.token, self.key) {
+            token.remove_waker(key);
+        }
+    }
+}
+
+type TimerKey = (Instant, u64);
+
+/// Deadlines waited on by pending [`Delay`]s, served by one timer thread.
+struct TimerQueue {
+    entries: Mutex<BTreeMap<TimerKey, Waker>>,
+    changed: Condvar,
+    next_id: AtomicU64,
+}
+
+/// The shared timer queue, or `None` if its thread could not be started.
+fn timer_queue() -> Option<&'static TimerQueue> {
+    static QUEUE: OnceLock<Option<&'static TimerQueue>> = OnceLock::new();
+    *QUEUE.get_or_init(|| {
+        let queue: &'static TimerQueue = Box::leak(Box::new(TimerQueue {
+            entries: Mutex::new(BTreeMap::new()),
+            changed: Condvar::new(),
+            next_id: AtomicU64::new(0),
+        }));
+        std::thread::Builder::new()
+            .name("forge-timer".to_string())
+            .spawn(move || run_timer(queue))
+            .ok()
+            .map(|_| queue)
+    })
+}
+
+fn run_timer(queue: &TimerQueue) {
+    let mut entries = queue.entries.lock().unwrap();
+    loop {
+        let now = Instant::now();
+        while let Some(entry) = entries.first_entry() {
+            if entry.key().0 > now {
+                break;
+            }
+            entry.remove().wake();
+        }
+        entries = match entries.keys().next() {
+            Some((deadline, _)) => {
+                let wait = deadline.saturating_duration_since(now);
+                queue.changed.wait_timeout(entries, wait).unwrap().0
+            }
+            None => queue.changed.wait(entries).unwrap(),
+        };
+    }
+}
+
+/// Future that completes once `duration` has elapsed.
+#[derive(Debug)]
+pub struct Delay {
+    deadline: Instant,
+    key: Option<TimerKey>,
+}
+
+impl Delay {
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            deadline: Instant::now() + duration,
+            key: None,
+        }
+    }
+
+    fn unregister(&mut self) {
+        if let (Some(key), Some(queue)) = (self.key.take(), timer_queue()) {
+            queue.entries.lock().unwrap().remove(&key);
+        }
+    }
+}
+
+impl Future for Delay {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if Instant::now() >= self.deadline {
+            self.unregister();
+            return Poll::Ready(());
+        }
+        let Some(queue) = timer_queue() else {
+            // No timer thread: degrade to re-polling until the deadline.
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        };
+        let deadline = self.deadline;
+        let key = *self
+            .key
+            .get_or_insert_with(|| (deadline, queue.next_id.fetch_add(1, Ordering::SeqCst)));
+        let mut entries = queue.entries.lock().unwrap();
+        let earliest = entries.keys().next().map_or(true, |first| key <= *first);
+        entries.insert(key, cx.waker().clone());
+        if earliest {
+            queue.changed.notify_one();
+        }
+        Poll::Pending
+    }
+}
+
+impl Drop for Delay {
+    fn drop(&mut self) {
+        self.unregister();
+    }
+}
+
+/// Wait for `duration`, resolving to `GraphError::Aborted` as soon as
+/// `token` is cancelled.
+pub fn sleep(
+    duration: Duration,
+    token: Option<&CancellationToken>,
+) -> impl Future<Output = GraphResult<()>> + Send + '_ {
+    cancellable(token, async move {
+        Delay::new(duration).await;
+        Ok(())
+    })
+}
+
+/// Run `future`, resolving to `None` if it has not finished within
+/// `duration`. The future is dropped on timeout.
+pub fn timeout<F: Future>(duration: Duration, future: F) -> Timeout<F> {
+    Timeout {
+        future: Box::pin(future),
+        delay: Delay::new(duration),
+    }
+}
+
+/// Future returned by [`timeout`].
+pub struct Timeout<F> {
+    future: Pin<Box<F>>,
+    delay: Delay,
+}
+
+impl<F: Future> Future for Timeout<F> {
+    type Output = Option<F::Output>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Poll::Ready(output) = self.future.as_mut().poll(cx) {
+            return Poll::Ready(Some(output));
+        }
+        match Pin::new(&mut self.delay).poll(cx) {
+            Poll::Ready(()) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cancellable, sleep, timeout, CancellationToken};
+    use crate::runtime::error::GraphError;
+    use futures::executor::block_on;
+
+    #[test]
+    fn cancellable_aborts_pending_future_when_token_fires() {
+        let token = CancellationToken::new();
+        let canceller = token.clone();
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            canceller.cancel("user stop");
+        });
+
+        let result = block_on(cancellable(
+            Some(&token),
+            futures::future::pending::<Result<(), GraphError>>(),
+        ));
+        handle.join().unwrap();
+        match result {
+            Err(GraphError::Aborted { reason }) => assert_eq!(reason, "user stop"),
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert!(token.wakers.lock().unwrap().is_empty());
+
+        let done = block_on(cancellable(None, async { Ok::<_, GraphError>(7) }));
+        assert_eq!(done.expect("completes"), 7);
+    }
+
+    #[test]
+    fn sleep_wakes_on_deadline_or_cancel() {
+        let started = std::time::Instant::now();
+        block_on(sleep(std::time::Duration::from_millis(20), None)).expect("sleep");
+        assert!(started.elapsed() >= std::time::Duration::from_millis(20));
+
+        let token = CancellationToken::new();
+        let canceller = token.clone();
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            canceller.cancel("stop");
+        });
+        let started = std::time::Instant::now();
+        let result = block_on(sleep(std::time::Duration::from_secs(30), Some(&token)));
+        handle.join().unwrap();
+        assert!(matches!(result, Err(GraphError::Aborted { .. })));
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+
+        let timed_out = block_on(timeout(
+            std::time::Duration::from_millis(10),
+            futures::future::pending::<()>(),
+        ));
+        assert!(timed_out.is_none());
+        let finished = block_on(timeout(std::time::Duration::from_secs(5), async { 3 }));
+        assert_eq!(finished, Some(3));
+    }
 }
@@ -1,5 +1,8 @@
 //! Compaction policy and result types.
 
+use crate::runtime::component::ChatRequest;
+use crate::runtime::tokens::TokenEstimator;
+
 /// Compaction trigger policy.
 #[derive(Clone, Debug, PartialEq, Default)]
 pub struct CompactionPolicy {
@@ -82,6 +85,19 @@ impl CompactionPolicy {
         message_trigger || token_trigger
     }
 
+    /// Decide before sending: compare the estimated prompt size of
+    /// `request` with the token threshold.
+    pub fn should_compact_request(
+        &self,
+        request: &ChatRequest,
+        estimator: &dyn TokenEstimator,
+    ) -> bool {
+        self.should_compact_with_usage(
+            request.messages.len(),
+            Some(estimator.count_request(request)),
+        )
+    }
+
     pub fn requires_token_usage(&self) -> bool {
         self.enabled
             && (self.max_tokens.is_some()
@@ -173,6 +189,25 @@ mod tests {
         assert!(policy.should_compact_with_usage(0, Some(95)));
     }
 
+    #[test]
+    fn compaction_policy_checks_estimated_request_size() {
+        use crate::runtime::component::ChatRequest;
+        use crate::runtime::message::{Message, MessageRole, Part};
+        use crate::runtime::tokens::HeuristicEstimator;
+
+        let mut message = Message::new(MessageRole::User);
+        message.parts.push(Part::TextFinal {
+            text: "x".repeat(400),
+        });
+        let request = ChatRequest::new("s", "m", vec![message]);
+        let estimator = HeuristicEstimator::default();
+        assert!(
+            CompactionPolicy::token_ratio(100, 0.95).should_compact_request(&request, &estimator)
+        );
+        assert!(!CompactionPolicy::token_ratio(1_000, 0.95)
+            .should_compact_request(&request, &estimator));
+    }
+
     #[test]
     fn compaction_result_holds_summary() {
         let result = CompactionResult::new("summary", 10);
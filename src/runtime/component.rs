@@ -6,6 +6,7 @@ use std::sync::Arc;
 
 use serde::{Deserialize, Serialize};
 
+use crate::runtime::cancel::CancellationToken;
 use crate::runtime::error::{GraphError, GraphResult};
 use crate::runtime::event::{Event, EventSink, TokenUsage};
 use crate::runtime::message::{Message, MessageRole, Part};
@@ -20,8 +21,68 @@ pub struct ChatRequest {
     pub messages: Vec<Message>,
     pub temperature: Option<f32>,
     pub max_output_tokens: Option<u32>,
+    /// Tools the model may call; providers map these to native tool schemas.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<ToolDefinition>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<ToolChoice>,
+    /// Ask for JSON matching a schema; see `runtime::structured`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_schema: Option<ResponseSchema>,
     #[serde(default)]
     pub metadata: serde_json::Map<String, serde_json::Value>,
+    /// Lets callers abort an in-flight request; never serialized.
+    #[serde(skip)]
+    pub cancellation: Option<CancellationToken>,
+}
+
+/// How the model should pick among `ChatRequest::tools`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ToolChoice {
+    /// The model decides whether to call a tool.
+    Auto,
+    /// The model must not call tools.
+    None,
+    /// The model must call at least one tool.
+    Required,
+    /// The model must call the named tool.
+    Tool(String),
+}
+
+/// JSON Schema the response must conform to.
+///
+/// Providers with a native JSON-schema mode (OpenAI `response_format`) use
+/// it; others receive the schema as a system instruction.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ResponseSchema {
+    pub name: String,
+    pub schema: serde_json::Value,
+    /// Request provider-side strict enforcement where supported.
+    #[serde(default)]
+    pub strict: bool,
+}
+
+impl ResponseSchema {
+    pub fn new(name: impl Into<String>, schema: serde_json::Value) -> Self {
+        Self {
+            name: name.into(),
+            schema,
+            strict: false,
+        }
+    }
+
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Instruction text for providers without a native schema mode.
+    pub fn instruction(&self) -> String {
+        format!(
+            "Respond with only a JSON value (no prose, no code fences) that conforms to this JSON Schema named `{}`:\n{}",
+            self.name, self.schema
+        )
+    }
 }
 
 impl ChatRequest {
@@ -36,7 +97,11 @@ impl ChatRequest {
             messages,
             temperature: None,
             max_output_tokens: None,
+            tools: Vec::new(),
+            tool_choice: None,
+            response_schema: None,
             metadata: serde_json::Map::new(),
+            cancellation: None,
         }
     }
 
@@ -50,10 +115,52 @@ impl ChatRequest {
         self
     }
 
+    pub fn with_tool(mut self, tool: ToolDefinition) -> Self {
+        self.tools.push(tool);
+        self
+    }
+
+    pub fn with_tools(mut self, tools: impl IntoIterator<Item = ToolDefinition>) -> Self {
+        self.tools.extend(tools);
+        self
+    }
+
+    /// Offer every tool registered in `registry`, sorted by name.
+    pub fn with_registry_tools(self, registry: &ToolRegistry) -> Self {
+        let mut tools = registry.definitions();
+        tools.sort_by(|a, b| a.name.cmp(&b.name));
+        self.with_tools(tools)
+    }
+
+    pub fn with_tool_choice(mut self, tool_choice: ToolChoice) -> Self {
+        self.tool_choice = Some(tool_choice);
+        self
+    }
+
+    pub fn with_response_schema(mut self, schema: ResponseSchema) -> Self {
+        self.response_schema = Some(schema);
+        self
+    }
+
     pub fn with_metadata(mut self, key: impl Into<String>, value: serde_json::Value) -> Self {
         self.metadata.insert(key.into(), value);
         self
     }
+
+    pub fn with_cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Returns `GraphError::Aborted` once the request's token is cancelled.
+    pub fn check_cancelled(&self) -> GraphResult<()> {
+        match &self.cancellation {
+            Some(token) if token.is_cancelled() => Err(GraphError::Aborted {
+                reason: token.abort_reason(),
+            }),
+            _ => Ok(()),
+        }
+    }
 }
 
 /// Response payload from chat model generation.
@@ -113,6 +220,22 @@ impl ChatResponse {
             Some(text)
         }
     }
+
+    /// Tool calls requested by the model, in response order.
+    pub fn tool_calls(&self) -> Vec<ToolCall> {
+        self.message
+            .parts
+            .iter()
+            .filter_map(|part| match part {
+                Part::ToolCall {
+                    tool,
+                    call_id,
+                    input,
+                } => Some(ToolCall::new(tool.clone(), call_id.clone(), input.clone())),
+                _ => None,
+            })
+            .collect()
+    }
 }
 
 /// Standard interface for chat-capable models.
@@ -141,6 +264,10 @@ pub trait ChatModel: Send + Sync {
 }
 
 /// Deterministic mock chat model useful for local tests.
+///
+/// Replies with the same text to every request; use
+/// [`ScriptedChatModel`](crate::runtime::provider::scripted::ScriptedChatModel)
+/// for multi-turn scripts.
 #[derive(Clone, Debug)]
 pub struct MockChatModel {
     model_id: String,
@@ -15,6 +15,9 @@ pub struct Interrupt {
     pub id: String,
     /// 中断发生的节点
     pub node: String,
+    /// 节点继续执行所需的私有数据；恢复时以 `interrupt:<node>` 写回状态
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resume_state: Option<serde_json::Value>,
 }
 
 impl Interrupt {
@@ -38,6 +41,7 @@ impl Interrupt {
             value: serde_json::to_value(value).unwrap_or(serde_json::Value::Null),
             id,
             node: node.into(),
+            resume_state: None,
         }
     }
 
@@ -47,8 +51,15 @@ impl Interrupt {
             value: serde_json::to_value(value).unwrap_or(serde_json::Value::Null),
             id: id.into(),
             node: node.into(),
+            resume_state: None,
         }
     }
+
+    /// 附加节点恢复时需要的私有数据
+    pub fn with_resume_state(mut self, state: impl Serialize) -> Self {
+        self.resume_state = serde_json::to_value(state).ok();
+        self
+    }
 }
 
 /// 恢复命令，用于继续被中断的执行
@@ -128,6 +139,15 @@ pub enum GraphError {
     PermissionDenied { permission: String, message: String },
     /// Checkpoint persistence error
     CheckpointError { run_id: String, message: String },
+    /// Model provider request failed (HTTP status or transport error)
+    ProviderError {
+        provider: String,
+        /// HTTP status, `None` for transport failures (timeouts, resets)
+        status: Option<u16>,
+        /// Server-requested delay from `Retry-After`/`retry-after-ms`
+        retry_after_ms: Option<u64>,
+        message: String,
+    },
     /// Generic error
     Other(String),
 }
@@ -169,6 +189,11 @@ impl fmt::Display for GraphError {
             Self::CheckpointError { run_id, message } => {
                 write!(f, "Checkpoint error for run '{}': {}", run_id, message)
             }
+            Self::ProviderError {
+                provider, message, ..
+            } => {
+                write!(f, "Provider '{}' error: {}", provider, message)
+            }
             Self::Other(msg) => write!(f, "{}", msg),
         }
     }
@@ -57,11 +57,27 @@ pub enum ToolUpdate {
 }
 
 /// Event metadata for protocol-level fields.
-#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+///
+/// Correlation fields (`run_id`, `node`, `step`, `parent_span_id`, `namespace`)
+/// are optional and omitted from the wire format when unset.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct EventMeta {
     pub event_id: String,
     pub timestamp_ms: u64,
     pub seq: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_span_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    /// SHA-256 digest of the previous record in a hash-chained log.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
 }
 
 impl EventMeta {
@@ -70,10 +86,94 @@ impl EventMeta {
             event_id: uuid::Uuid::new_v4().to_string(),
             timestamp_ms: now_ms(),
             seq,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_run_id(mut self, run_id: impl Into<String>) -> Self {
+        self.run_id = Some(run_id.into());
+        self
+    }
+
+    pub fn with_node(mut self, node: impl Into<String>) -> Self {
+        self.node = Some(node.into());
+        self
+    }
+
+    pub fn with_step(mut self, step: u64) -> Self {
+        self.step = Some(step);
+        self
+    }
+
+    pub fn with_parent_span_id(mut self, parent_span_id: impl Into<String>) -> Self {
+        self.parent_span_id = Some(parent_span_id.into());
+        self
+    }
+
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    /// Copy correlation fields from a context, keeping values already set.
+    pub fn apply_context(&mut self, context: &EventContext) {
+        if self.run_id.is_none() {
+            self.run_id = context.run_id.clone();
+        }
+        if self.node.is_none() {
+            self.node = context.node.clone();
+        }
+        if self.step.is_none() {
+            self.step = context.step;
+        }
+        if self.parent_span_id.is_none() {
+            self.parent_span_id = context.parent_span_id.clone();
+        }
+        if self.namespace.is_none() {
+            self.namespace = context.namespace.clone();
         }
     }
 }
 
+/// Correlation context stamped onto event metadata by recording sinks.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct EventContext {
+    pub run_id: Option<String>,
+    pub node: Option<String>,
+    pub step: Option<u64>,
+    pub parent_span_id: Option<String>,
+    pub namespace: Option<String>,
+}
+
+impl EventContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn for_run(run_id: impl Into<String>) -> Self {
+        Self {
+            run_id: Some(run_id.into()),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    pub fn with_parent_span_id(mut self, parent_span_id: impl Into<String>) -> Self {
+        self.parent_span_id = Some(parent_span_id.into());
+        self
+    }
+
+    /// Update the node/step position for subsequent events.
+    pub fn enter_node(&mut self, node: impl Into<String>, step: u64) {
+        self.node = Some(node.into());
+        self.step = Some(step);
+    }
+}
+
 /// Event record with protocol metadata.
 #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct EventRecord {
@@ -106,6 +206,11 @@ pub fn sort_records_by_meta(records: &mut [EventRecord]) {
     records.sort_by(EventRecord::cmp_meta);
 }
 
+/// Serde helper: omit a seq that was never set.
+pub(crate) fn is_zero_seq(seq: &u64) -> bool {
+    *seq == 0
+}
+
 pub fn max_record_seq(records: &[EventRecord]) -> Option<u64> {
     records.iter().map(|record| record.meta.seq).max()
 }
@@ -127,10 +232,21 @@ impl EventSequencer {
         }
     }
 
+    /// The seq of the last record handed out (the start seq if none yet).
+    pub fn last_seq(&self) -> u64 {
+        self.next_seq.load(AtomicOrdering::Relaxed)
+    }
+
     pub fn record(&self, event: Event) -> EventRecord {
         let seq = self.next_seq.fetch_add(1, AtomicOrdering::Relaxed) + 1;
         EventRecord::new(event, seq)
     }
+
+    pub fn record_with_context(&self, event: Event, context: &EventContext) -> EventRecord {
+        let mut record = self.record(event);
+        record.meta.apply_context(context);
+        record
+    }
 }
 
 fn now_ms() -> u64 {
@@ -226,6 +342,9 @@ pub enum Event {
         session_id: String,
         tokens: TokenUsage,
         cost: f64,
+        /// Model that served the step; used to price it.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        model: Option<String>,
     },
     PermissionAsked {
         permission: String,
@@ -264,16 +383,83 @@ pub enum Event {
         to: SessionPhase,
         reason: String,
     },
+    ModelRetry {
+        model: String,
+        /// 1-based attempt that failed
+        attempt: u32,
+        delay_ms: u64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        status: Option<u16>,
+        error: String,
+    },
+}
+
+impl Event {
+    /// Variant name as it appears in serialized events (e.g. `"ToolStart"`).
+    pub fn variant_name(&self) -> &'static str {
+        match self {
+            Event::RunStarted { .. } => "RunStarted",
+            Event::RunPaused { .. } => "RunPaused",
+            Event::RunResumed { .. } => "RunResumed",
+            Event::RunCompleted { .. } => "RunCompleted",
+            Event::RunFailed { .. } => "RunFailed",
+            Event::RunAborted { .. } => "RunAborted",
+            Event::TextDelta { .. } => "TextDelta",
+            Event::TextFinal { .. } => "TextFinal",
+            Event::Attachment { .. } => "Attachment",
+            Event::Error { .. } => "Error",
+            Event::ToolStart { .. } => "ToolStart",
+            Event::ToolUpdate { .. } => "ToolUpdate",
+            Event::ToolResult { .. } => "ToolResult",
+            Event::ToolAttachment { .. } => "ToolAttachment",
+            Event::ToolError { .. } => "ToolError",
+            Event::ToolStatus { .. } => "ToolStatus",
+            Event::StepStart { .. } => "StepStart",
+            Event::StepFinish { .. } => "StepFinish",
+            Event::PermissionAsked { .. } => "PermissionAsked",
+            Event::PermissionReplied { .. } => "PermissionReplied",
+            Event::SessionCompacted { .. } => "SessionCompacted",
+            Event::SessionCompactionRequested { .. } => "SessionCompactionRequested",
+            Event::SessionPhaseChanged { .. } => "SessionPhaseChanged",
+            Event::SessionPhaseTransitionRejected { .. } => "SessionPhaseTransitionRejected",
+            Event::ModelRetry { .. } => "ModelRetry",
+        }
+    }
+
+    /// Tool name and call id for tool lifecycle events.
+    pub fn tool_call(&self) -> Option<(&str, &str)> {
+        match self {
+            Event::ToolStart { tool, call_id, .. }
+            | Event::ToolUpdate { tool, call_id, .. }
+            | Event::ToolResult { tool, call_id, .. }
+            | Event::ToolAttachment { tool, call_id, .. }
+            | Event::ToolError { tool, call_id, .. }
+            | Event::ToolStatus { tool, call_id, .. } => Some((tool.as_str(), call_id.as_str())),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::{
-        max_record_seq, sort_records_by_meta, Event, EventMeta, EventRecord, EventSequencer,
-        TokenUsage,
+        max_record_seq, sort_records_by_meta, Event, EventContext, EventMeta, EventRecord,
+        EventSequencer, TokenUsage,
     };
     use crate::runtime::tool::ToolState;
 
+    #[test]
+    fn event_variant_name_matches_serialized_tag() {
+        let event = Event::ToolError {
+            tool: "grep".to_string(),
+            call_id: "call-1".to_string(),
+            error: "boom".to_string(),
+        };
+        let json = serde_json::to_value(&event).expect("serialize");
+        assert!(json.get(event.variant_name()).is_some());
+        assert_eq!(event.tool_call(), Some(("grep", "call-1")));
+    }
+
     #[test]
     fn tool_status_event_can_be_emitted() {
         let event = Event::ToolStatus {
@@ -322,6 +508,7 @@ mod tests {
             session_id: "s1".to_string(),
             tokens: TokenUsage::default(),
             cost: 0.0,
+            model: None,
         });
 
         assert!(!first.meta.event_id.is_empty());
@@ -352,6 +539,7 @@ mod tests {
             event_id: "e1".to_string(),
             timestamp_ms: 42,
             seq: 7,
+            ..Default::default()
         };
         let record = EventRecord::with_meta(
             Event::StepStart {
@@ -364,6 +552,46 @@ mod tests {
         assert!(matches!(record.event, Event::StepStart { .. }));
     }
 
+    #[test]
+    fn event_meta_omits_unset_correlation_fields() {
+        let meta = EventMeta {
+            event_id: "e1".to_string(),
+            timestamp_ms: 42,
+            seq: 7,
+            ..Default::default()
+        };
+
+        let json = serde_json::to_value(&meta).expect("serialize");
+        assert_eq!(
+            json,
+            serde_json::json!({"event_id": "e1", "timestamp_ms": 42, "seq": 7})
+        );
+    }
+
+    #[test]
+    fn event_sequencer_applies_context_without_overriding_meta() {
+        let sequencer = EventSequencer::new();
+        let mut context = EventContext::for_run("run-1").with_namespace("outer/inner");
+        context.enter_node("planner", 3);
+
+        let record = sequencer.record_with_context(
+            Event::StepStart {
+                session_id: "s1".to_string(),
+            },
+            &context,
+        );
+        assert_eq!(record.meta.run_id.as_deref(), Some("run-1"));
+        assert_eq!(record.meta.node.as_deref(), Some("planner"));
+        assert_eq!(record.meta.step, Some(3));
+        assert_eq!(record.meta.namespace.as_deref(), Some("outer/inner"));
+        assert_eq!(record.meta.parent_span_id, None);
+
+        let mut meta = EventMeta::new(1).with_node("explicit");
+        meta.apply_context(&context);
+        assert_eq!(meta.node.as_deref(), Some("explicit"));
+        assert_eq!(meta.run_id.as_deref(), Some("run-1"));
+    }
+
     #[test]
     fn event_record_cmp_orders_by_seq_then_timestamp_then_id() {
         let base = Event::StepStart {
@@ -375,6 +603,7 @@ mod tests {
                 event_id: "a".to_string(),
                 timestamp_ms: 10,
                 seq: 1,
+                ..Default::default()
             },
         );
         let record_b = EventRecord::with_meta(
@@ -383,6 +612,7 @@ mod tests {
                 event_id: "b".to_string(),
                 timestamp_ms: 20,
                 seq: 1,
+                ..Default::default()
             },
         );
         let record_c = EventRecord::with_meta(
@@ -391,6 +621,7 @@ mod tests {
                 event_id: "c".to_string(),
                 timestamp_ms: 5,
                 seq: 2,
+                ..Default::default()
             },
         );
 
@@ -410,6 +641,7 @@ mod tests {
                     event_id: "b".to_string(),
                     timestamp_ms: 10,
                     seq: 1,
+                    ..Default::default()
                 },
             ),
             EventRecord::with_meta(
@@ -418,6 +650,7 @@ mod tests {
                     event_id: "a".to_string(),
                     timestamp_ms: 10,
                     seq: 1,
+                    ..Default::default()
                 },
             ),
             EventRecord::with_meta(
@@ -426,6 +659,7 @@ mod tests {
                     event_id: "c".to_string(),
                     timestamp_ms: 5,
                     seq: 0,
+                    ..Default::default()
                 },
             ),
         ];
@@ -457,6 +691,7 @@ mod tests {
                     event_id: "a".to_string(),
                     timestamp_ms: 1,
                     seq: 5,
+                    ..Default::default()
                 },
             ),
             EventRecord::with_meta(
@@ -465,6 +700,7 @@ mod tests {
                     event_id: "b".to_string(),
                     timestamp_ms: 2,
                     seq: 9,
+                    ..Default::default()
                 },
             ),
         ];
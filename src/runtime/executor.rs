@@ -19,20 +19,23 @@ use crate::runtime::compaction::{
 use crate::runtime::constants::{END, MAX_ITERATIONS, START};
 use crate::runtime::error::{GraphError, GraphResult, Interrupt, ResumeCommand};
 use crate::runtime::event::{
-    Event, EventRecord, EventRecordSink, EventSequencer, EventSink, TokenUsage,
+    Event, EventContext, EventRecord, EventRecordSink, EventSequencer, EventSink, TokenUsage,
 };
 use crate::runtime::graph::{evaluate_branch, Edge, StateGraph};
 use crate::runtime::message::{Message, MessageRole, Part};
 use crate::runtime::metrics::{MetricsCollector, RunMetrics, RunMetricsBuilder};
 use crate::runtime::node::{Node, NodeSpec};
 use crate::runtime::permission::{PermissionDecision, PermissionGate, PermissionRequest};
+use crate::runtime::pricing::{Budget, CostLedger, PricingRegistry};
 use crate::runtime::prune::{prune_tool_events, PrunePolicy};
+use crate::runtime::redact::{RedactingEventRecordSink, RedactionPolicy};
 use crate::runtime::session::{CheckpointRecord, CheckpointStore, SessionSnapshot};
 use crate::runtime::state::GraphState;
+use crate::runtime::tokens::TokenEstimator;
 use crate::runtime::tool::{
     AttachmentPolicy, AttachmentStore, ToolCall, ToolContext, ToolOutput, ToolRegistry,
 };
-use crate::runtime::trace::{ExecutionTrace, TraceEvent};
+use crate::runtime::trace::{ExecutionTrace, SpanKind, SpanRecorder, TraceEvent};
 
 /// Durability mode for checkpoint persistence.
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -84,6 +87,22 @@ pub struct ExecutionConfig {
     pub checkpoint_store: Option<Arc<CheckpointStore>>,
     /// Persistence durability mode when checkpoint_store is configured.
     pub checkpoint_durability: CheckpointDurability,
+    /// Optional namespace stamped onto event records (e.g. subgraph path).
+    pub event_namespace: Option<String>,
+    /// Optional parent span id stamped onto event records.
+    pub parent_span_id: Option<String>,
+    /// Optional pricing used to fill in `StepFinish` costs.
+    pub pricing: Option<Arc<PricingRegistry>>,
+    /// Optional token/cost budget enforced for each invocation.
+    pub budget: Option<Budget>,
+    /// Optional shared ledger accumulating costs across invocations.
+    pub cost_ledger: Option<Arc<std::sync::Mutex<CostLedger>>>,
+    /// Optional redaction applied to records before they reach the record sink.
+    pub redaction: Option<Arc<RedactionPolicy>>,
+    /// Optional estimator for token-based compaction before usage is reported.
+    pub token_estimator: Option<Arc<dyn TokenEstimator>>,
+    /// Optional run id; each invocation generates a fresh one when unset.
+    pub run_id: Option<String>,
 }
 
 impl ExecutionConfig {
@@ -108,6 +127,14 @@ impl ExecutionConfig {
             session_snapshot: None,
             checkpoint_store: None,
             checkpoint_durability: CheckpointDurability::Sync,
+            event_namespace: None,
+            parent_span_id: None,
+            pricing: None,
+            budget: None,
+            cost_ledger: None,
+            redaction: None,
+            token_estimator: None,
+            run_id: None,
         }
     }
 
@@ -133,6 +160,14 @@ impl ExecutionConfig {
             session_snapshot: None,
             checkpoint_store: None,
             checkpoint_durability: CheckpointDurability::Sync,
+            event_namespace: None,
+            parent_span_id: None,
+            pricing: None,
+            budget: None,
+            cost_ledger: None,
+            redaction: None,
+            token_estimator: None,
+            run_id: None,
         }
     }
 
@@ -237,6 +272,83 @@ impl ExecutionConfig {
         self
     }
 
+    /// Set the namespace stamped onto event records (e.g. `parent/child` for subgraphs).
+    pub fn with_event_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.event_namespace = Some(namespace.into());
+        self
+    }
+
+    /// Set the parent span id stamped onto event records and root spans.
+    /// `OtlpExporter` only accepts 16-hex-digit span ids.
+    pub fn with_parent_span_id(mut self, parent_span_id: impl Into<String>) -> Self {
+        self.parent_span_id = Some(parent_span_id.into());
+        self
+    }
+
+    /// Price `StepFinish` events that report zero cost by their `model`,
+    /// falling back to the registry's default model.
+    ///
+    /// With pricing, a budget, or a ledger configured, every run path
+    /// (`invoke`, `invoke_resumable`, `resume`, `stream_events`) runs nodes
+    /// with an event sink so their steps are counted.
+    pub fn with_pricing(mut self, pricing: Arc<PricingRegistry>) -> Self {
+        self.pricing = Some(pricing);
+        self
+    }
+
+    /// Abort with `GraphError::Aborted` once one invocation exceeds the
+    /// budget. Each `invoke*`/`resume`/`stream_events` call starts from zero;
+    /// a shared [`with_cost_ledger`](Self::with_cost_ledger) does not count
+    /// against it.
+    pub fn with_budget(mut self, budget: Budget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Accumulate token and cost totals of every invocation into a
+    /// caller-owned ledger. `RunMetrics` from `invoke_with_metrics` carry the
+    /// invocation's own totals.
+    pub fn with_cost_ledger(mut self, ledger: Arc<std::sync::Mutex<CostLedger>>) -> Self {
+        self.cost_ledger = Some(ledger);
+        self
+    }
+
+    /// Redact records sent to the event record sink; in-memory history keeps real values.
+    pub fn with_redaction(mut self, policy: Arc<RedactionPolicy>) -> Self {
+        self.redaction = Some(policy);
+        self
+    }
+
+    /// Estimate the session transcript's token count for token-based
+    /// compaction, so the threshold is checked even before (or beyond) the
+    /// latest provider-reported usage.
+    pub fn with_token_estimator(mut self, estimator: Arc<dyn TokenEstimator>) -> Self {
+        self.token_estimator = Some(estimator);
+        self
+    }
+
+    /// Run under a caller-chosen id, so event records, checkpoints, and run
+    /// logs can be looked up before the invocation returns.
+    pub fn with_run_id(mut self, run_id: impl Into<String>) -> Self {
+        self.run_id = Some(run_id.into());
+        self
+    }
+
+    /// The configured run id, or a fresh one.
+    fn new_run_id(&self) -> String {
+        self.run_id
+            .clone()
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string())
+    }
+
+    /// Build the base correlation context for a run.
+    pub fn event_context(&self, run_id: impl Into<String>) -> EventContext {
+        let mut context = EventContext::for_run(run_id);
+        context.namespace = self.event_namespace.clone();
+        context.parent_span_id = self.parent_span_id.clone();
+        context
+    }
+
     /// Seed session snapshot with structured messages.
     pub fn with_snapshot_messages<I>(mut self, session_id: impl Into<String>, messages: I) -> Self
     where
@@ -282,10 +394,14 @@ pub struct Checkpoint<S> {
     /// Resume values (from user input)
     #[serde(default)]
     pub resume_values: HashMap<String, serde_json::Value>,
+    /// Seq of the run's last event record, continued on resume
+    #[serde(default, skip_serializing_if = "crate::runtime::event::is_zero_seq")]
+    pub event_seq: u64,
 }
 
 /// Execution result - may complete or be interrupted
 #[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
 pub enum ExecutionResult<S> {
     /// Execution completed successfully
     Complete(S),
@@ -296,6 +412,15 @@ pub enum ExecutionResult<S> {
     },
 }
 
+/// Result of [`CompiledGraph::stream_events_with_run_id`].
+#[derive(Debug)]
+pub struct StreamEventsResult<S> {
+    /// Run id stamped on the run's event records
+    pub run_id: String,
+    /// Final state
+    pub state: S,
+}
+
 /// Result of execution with metrics
 #[derive(Debug)]
 pub struct ExecutionResultWithMetrics<S> {
@@ -342,28 +467,9 @@ impl ToolExecutor {
         }
         let permission = format!("tool:{}", call.tool);
         match self.gate.decide(&permission) {
-            PermissionDecision::Allow => {
-                let mut context = ToolContext::new(
-                    Arc::clone(&self.sink),
-                    Arc::clone(&self.gate),
-                    self.attachment_policy.clone(),
-                    call.tool.clone(),
-                    call.call_id.clone(),
-                )
-                .with_cancellation_token(self.cancel.clone());
-                if let Some(store) = &self.attachment_store {
-                    context = context.with_attachment_store(Arc::clone(store));
-                }
-                self.tools.run_with_events(call, context).await
-            }
+            PermissionDecision::Allow => self.run_allowed(call).await,
             PermissionDecision::Ask => {
-                let mut metadata = serde_json::Map::new();
-                metadata.insert("tool".to_string(), serde_json::json!(call.tool));
-                metadata.insert("call_id".to_string(), serde_json::json!(call.call_id));
-                metadata.insert("input".to_string(), call.input.clone());
-                let request = PermissionRequest::new(permission.clone(), vec![permission.clone()])
-                    .with_metadata(metadata)
-                    .with_always(vec![permission.clone()]);
+                let request = tool_permission_request(&call);
                 self.sink.emit(request.to_event())?;
                 Err(GraphError::Interrupted(vec![Interrupt::new(
                     request,
@@ -376,6 +482,39 @@ impl ToolExecutor {
             }),
         }
     }
+
+    /// Run a call whose permission has already been granted.
+    pub(crate) async fn run_allowed(&self, call: ToolCall) -> GraphResult<ToolOutput> {
+        if self.cancel.is_cancelled() {
+            return Err(GraphError::Aborted {
+                reason: self.cancel.abort_reason(),
+            });
+        }
+        let mut context = ToolContext::new(
+            Arc::clone(&self.sink),
+            Arc::clone(&self.gate),
+            self.attachment_policy.clone(),
+            call.tool.clone(),
+            call.call_id.clone(),
+        )
+        .with_cancellation_token(self.cancel.clone());
+        if let Some(store) = &self.attachment_store {
+            context = context.with_attachment_store(Arc::clone(store));
+        }
+        self.tools.run_with_events(call, context).await
+    }
+}
+
+/// The permission request raised when `call` needs approval.
+pub(crate) fn tool_permission_request(call: &ToolCall) -> PermissionRequest {
+    let permission = format!("tool:{}", call.tool);
+    let mut metadata = serde_json::Map::new();
+    metadata.insert("tool".to_string(), serde_json::json!(call.tool));
+    metadata.insert("call_id".to_string(), serde_json::json!(call.call_id));
+    metadata.insert("input".to_string(), call.input.clone());
+    PermissionRequest::new(permission.clone(), vec![permission.clone()])
+        .with_metadata(metadata)
+        .with_always(vec![permission])
 }
 
 /// A compiled graph ready for execution
@@ -445,13 +584,17 @@ impl<S: GraphState> CompiledGraph<S> {
         &self,
         initial_state: S,
     ) -> GraphResult<ExecutionResultWithMetrics<S>> {
-        let run_id = uuid::Uuid::new_v4().to_string();
+        let run_id = self.config.new_run_id();
         let mut metrics_builder = if self.config.collect_metrics {
             Some(RunMetricsBuilder::new(&run_id, &self.config.config_id))
         } else {
             None
         };
 
+        let costs = CostTracker::for_config(&self.config);
+        let recorder = EventRecorder::for_config(&self.config, 0);
+        let run_context = self.config.event_context(run_id.clone());
+
         let mut state = initial_state;
         let mut current_node = self.get_next_node(START, &state)?;
         let mut iterations = 0;
@@ -487,7 +630,12 @@ impl<S: GraphState> CompiledGraph<S> {
                 .get(&current_node)
                 .ok_or_else(|| GraphError::NodeNotFound(current_node.clone()))?;
 
-            match node.execute(state).await {
+            let mut context = run_context.clone();
+            context.enter_node(current_node.clone(), iterations as u64);
+            match self
+                .execute_node(node, state, recorder.as_ref(), costs.as_ref(), context)
+                .await
+            {
                 Ok(new_state) => {
                     state = new_state;
                     // Record metrics (tokens would come from state if available)
@@ -513,7 +661,10 @@ impl<S: GraphState> CompiledGraph<S> {
 
         // Finalize metrics
         let metrics = metrics_builder.map(|mb| {
-            let m = mb.build(true);
+            let mut m = mb.build(true);
+            if let Some(costs) = &costs {
+                costs.ledger().apply_to_metrics(&mut m);
+            }
             // Add to collector if present
             if let Some(ref collector) = self.metrics_collector {
                 collector.add_run(m.clone());
@@ -574,6 +725,18 @@ impl<S: GraphState> CompiledGraph<S> {
         initial_state: S,
         sink: std::sync::Arc<dyn EventSink>,
     ) -> GraphResult<S> {
+        self.stream_events_with_run_id(initial_state, sink)
+            .await
+            .map(|run| run.state)
+    }
+
+    /// Like [`stream_events`](Self::stream_events), also returning the run id
+    /// stamped on the event records (`ExecutionConfig::run_id` when set).
+    pub async fn stream_events_with_run_id(
+        &self,
+        initial_state: S,
+        sink: std::sync::Arc<dyn EventSink>,
+    ) -> GraphResult<StreamEventsResult<S>> {
         let mut state = initial_state;
         let mut current_node = self.get_next_node(START, &state)?;
         let mut iterations = 0;
@@ -586,13 +749,48 @@ impl<S: GraphState> CompiledGraph<S> {
             .event_history
             .clone()
             .unwrap_or_else(|| Arc::new(std::sync::Mutex::new(Vec::new())));
-        let record_sink = self.config.event_record_sink.clone();
+        let recorder = use_history.then(|| {
+            Arc::new(EventRecorder::new(
+                &self.config,
+                Some(Arc::clone(&history)),
+                0,
+            ))
+        });
         let trace = self.config.trace.clone();
         let snapshot = self.config.session_snapshot.clone();
-        let sink: Arc<dyn EventSink> = if use_history {
-            Arc::new(RecordingSink::new(sink, Arc::clone(&history), record_sink))
-        } else {
-            sink
+        let run_id = self.config.new_run_id();
+        let run_context = self.config.event_context(run_id.clone());
+        let spans = trace.as_ref().map(|trace| {
+            Arc::new(std::sync::Mutex::new(
+                SpanRecorder::new(Arc::clone(trace))
+                    .with_parent_span_id(self.config.parent_span_id.clone()),
+            ))
+        });
+        // Closes spans left open on every way out, early returns included.
+        let _finish_spans = FinishSpans(spans.clone());
+        let costs = CostTracker::for_config(&self.config);
+        // Every node run gets its own sink stack stamped with that node's
+        // context.
+        let node_sink = |context: EventContext| -> Arc<dyn EventSink> {
+            let sink: Arc<dyn EventSink> = match &recorder {
+                Some(recorder) => Arc::new(RecordingSink {
+                    inner: Arc::clone(&sink),
+                    recorder: Arc::clone(recorder),
+                    context,
+                }),
+                None => Arc::clone(&sink),
+            };
+            let sink: Arc<dyn EventSink> = match &spans {
+                Some(spans) => Arc::new(SpanSink {
+                    inner: sink,
+                    spans: Arc::clone(spans),
+                }),
+                None => sink,
+            };
+            match &costs {
+                Some(costs) => costs.wrap(sink),
+                None => sink,
+            }
         };
 
         while current_node != END && iterations < self.config.max_iterations {
@@ -609,12 +807,28 @@ impl<S: GraphState> CompiledGraph<S> {
                 .get(&current_node)
                 .ok_or_else(|| GraphError::NodeNotFound(current_node.clone()))?;
 
+            if let Some(spans) = &spans {
+                spans.lock().unwrap().enter_node(current_node.clone());
+            }
+            let mut context = run_context.clone();
+            context.enter_node(current_node.clone(), iterations as u64);
+            let sink = node_sink(context);
             if let Some(trace) = &trace {
                 trace.lock().unwrap().record_event(TraceEvent::NodeStart {
                     node: current_node.clone(),
                 });
             }
-            state = node.execute_stream(state, sink.clone()).await?;
+            let result = node.execute_stream(state, sink.clone()).await;
+            if let Some(spans) = &spans {
+                spans
+                    .lock()
+                    .unwrap()
+                    .exit_node(result.as_ref().err().map(|err| err.to_string()).as_deref());
+            }
+            state = result?;
+            if let Some(costs) = &costs {
+                costs.check()?;
+            }
             if let Some(snapshot) = &snapshot {
                 let mut message = Message::new(MessageRole::System);
                 message.parts.push(Part::TextFinal {
@@ -638,12 +852,25 @@ impl<S: GraphState> CompiledGraph<S> {
 
             let session_id = resolve_session_id(&state);
             let message_count = resolve_message_count(&snapshot, &history);
-            let token_usage = if self.config.compaction_policy.requires_token_usage() {
-                resolve_latest_token_usage(&history, &session_id)
-            } else {
-                None
+            let (token_usage, estimated_tokens) =
+                if self.config.compaction_policy.requires_token_usage() {
+                    (
+                        resolve_latest_token_usage(&history, &session_id),
+                        self.config
+                            .token_estimator
+                            .as_deref()
+                            .and_then(|estimator| estimate_snapshot_tokens(&snapshot, estimator)),
+                    )
+                } else {
+                    (None, None)
+                };
+            let token_total = match (
+                token_usage.as_ref().map(token_usage_total),
+                estimated_tokens,
+            ) {
+                (Some(reported), Some(estimated)) => Some(reported.max(estimated)),
+                (reported, estimated) => reported.or(estimated),
             };
-            let token_total = token_usage.as_ref().map(token_usage_total);
 
             if self
                 .config
@@ -652,7 +879,18 @@ impl<S: GraphState> CompiledGraph<S> {
             {
                 let messages = collect_compaction_messages(&snapshot);
                 let context = CompactionContext::new(messages);
-                if let Some(summary) = self.config.compaction_hook.before_compaction(&context) {
+                if let Some(spans) = &spans {
+                    let mut spans = spans.lock().unwrap();
+                    spans.open("compaction", SpanKind::Compaction, "compaction");
+                    spans.annotate("compaction", "message_count", message_count.into());
+                }
+                let summary = self.config.compaction_hook.before_compaction(&context);
+                if let Some(spans) = &spans {
+                    let mut spans = spans.lock().unwrap();
+                    spans.annotate("compaction", "compacted", summary.is_some().into());
+                    spans.close("compaction");
+                }
+                if let Some(summary) = summary {
                     let result = CompactionResult::new(summary, 0);
                     self.config.compaction_hook.after_compaction(&result);
                     if let Some(trace) = &trace {
@@ -673,7 +911,10 @@ impl<S: GraphState> CompiledGraph<S> {
                     sink.emit(crate::runtime::event::Event::SessionCompactionRequested {
                         session_id,
                         message_count,
-                        tokens: token_usage.unwrap_or_default(),
+                        tokens: token_usage.unwrap_or_else(|| TokenUsage {
+                            input: estimated_tokens.unwrap_or(0),
+                            ..TokenUsage::default()
+                        }),
                         context_window: self.config.compaction_policy.context_window,
                         threshold_ratio: self.config.compaction_policy.token_ratio,
                     })?;
@@ -695,7 +936,7 @@ impl<S: GraphState> CompiledGraph<S> {
             return Err(GraphError::MaxIterationsExceeded);
         }
 
-        Ok(state)
+        Ok(StreamEventsResult { run_id, state })
     }
 
     /// Get the next node to execute
@@ -730,6 +971,38 @@ impl<S: GraphState> CompiledGraph<S> {
         }
     }
 
+    /// Run a node outside `stream_events`. With event recording or cost
+    /// tracking configured the node gets a sink, so its events are recorded
+    /// under `context` and its `StepFinish` events are priced and counted;
+    /// the budget is checked once it returns.
+    async fn execute_node(
+        &self,
+        node: &NodeSpec<S>,
+        state: S,
+        recorder: Option<&Arc<EventRecorder>>,
+        costs: Option<&Arc<CostTracker>>,
+        context: EventContext,
+    ) -> GraphResult<S> {
+        let sink: Arc<dyn EventSink> = match recorder {
+            Some(recorder) => Arc::new(RecordingSink {
+                inner: Arc::new(crate::runtime::event::NoopEventSink),
+                recorder: Arc::clone(recorder),
+                context,
+            }),
+            None if costs.is_some() => Arc::new(crate::runtime::event::NoopEventSink),
+            None => return node.execute(state).await,
+        };
+        let sink = match costs {
+            Some(costs) => costs.wrap(sink),
+            None => sink,
+        };
+        let state = node.execute_stream(state, sink).await?;
+        if let Some(costs) = costs {
+            costs.check()?;
+        }
+        Ok(state)
+    }
+
     /// Get all node names
     pub fn get_nodes(&self) -> Vec<&str> {
         self.nodes.keys().map(|s| s.as_str()).collect()
@@ -745,14 +1018,19 @@ impl<S: GraphState> CompiledGraph<S> {
     where
         S: Serialize,
     {
-        let run_id = uuid::Uuid::new_v4().to_string();
-        self.emit_run_event(Event::RunStarted {
-            run_id: run_id.clone(),
-            status: crate::runtime::session_state::RunStatus::Running,
-        })?;
+        let run_id = self.config.new_run_id();
+        let recorder = EventRecorder::for_config(&self.config, 0);
+        self.emit_run_event(
+            recorder.as_ref(),
+            Event::RunStarted {
+                run_id: run_id.clone(),
+                status: crate::runtime::session_state::RunStatus::Running,
+            },
+        )?;
         let result = self
             .run_with_checkpoint(
                 run_id.clone(),
+                recorder.as_ref(),
                 initial_state,
                 START.to_string(),
                 0,
@@ -761,28 +1039,33 @@ impl<S: GraphState> CompiledGraph<S> {
             .await;
         match &result {
             Ok(ExecutionResult::Complete(_)) => {
-                self.emit_run_event(Event::RunCompleted {
-                    run_id,
-                    status: crate::runtime::session_state::RunStatus::Completed,
-                })?;
-            }
-            Ok(ExecutionResult::Interrupted { checkpoint, .. }) => {
-                self.emit_run_event(Event::RunPaused {
-                    run_id,
-                    checkpoint_id: checkpoint.checkpoint_id.clone(),
-                })?;
+                self.emit_run_event(
+                    recorder.as_ref(),
+                    Event::RunCompleted {
+                        run_id,
+                        status: crate::runtime::session_state::RunStatus::Completed,
+                    },
+                )?;
             }
+            // `run_with_checkpoint` emits `RunPaused` itself.
+            Ok(ExecutionResult::Interrupted { .. }) => {}
             Err(GraphError::Aborted { reason }) => {
-                self.emit_run_event(Event::RunAborted {
-                    run_id,
-                    reason: reason.clone(),
-                })?;
+                self.emit_run_event(
+                    recorder.as_ref(),
+                    Event::RunAborted {
+                        run_id,
+                        reason: reason.clone(),
+                    },
+                )?;
             }
             Err(err) => {
-                self.emit_run_event(Event::RunFailed {
-                    run_id,
-                    error: err.to_string(),
-                })?;
+                self.emit_run_event(
+                    recorder.as_ref(),
+                    Event::RunFailed {
+                        run_id,
+                        error: err.to_string(),
+                    },
+                )?;
             }
         }
         result
@@ -887,6 +1170,7 @@ impl<S: GraphState> CompiledGraph<S> {
     async fn run_with_checkpoint(
         &self,
         run_id: String,
+        recorder: Option<&Arc<EventRecorder>>,
         initial_state: S,
         start_node: String,
         start_iterations: usize,
@@ -903,6 +1187,8 @@ impl<S: GraphState> CompiledGraph<S> {
         };
         let mut iterations = start_iterations;
         let mut deferred_checkpoint: Option<Checkpoint<S>> = None;
+        let costs = CostTracker::for_config(&self.config);
+        let run_context = self.config.event_context(run_id.clone());
 
         while current_node != END && iterations < self.config.max_iterations {
             iterations += 1;
@@ -922,6 +1208,7 @@ impl<S: GraphState> CompiledGraph<S> {
                 current_node = self.get_next_node(&current_node, &state)?;
                 let checkpoint = self.build_checkpoint(
                     &run_id,
+                    recorder,
                     &state,
                     &current_node,
                     Vec::new(),
@@ -944,7 +1231,12 @@ impl<S: GraphState> CompiledGraph<S> {
                 .get(&current_node)
                 .ok_or_else(|| GraphError::NodeNotFound(current_node.clone()))?;
 
-            match node.execute(state.clone()).await {
+            let mut context = run_context.clone();
+            context.enter_node(current_node.clone(), iterations as u64);
+            match self
+                .execute_node(node, state.clone(), recorder, costs.as_ref(), context)
+                .await
+            {
                 Ok(new_state) => {
                     state = new_state;
                 }
@@ -954,14 +1246,27 @@ impl<S: GraphState> CompiledGraph<S> {
                     }
                     // Node still interrupted after this execution attempt.
                     // Return a fresh checkpoint so callers can provide another resume value.
-                    let checkpoint = self.build_checkpoint(
+                    let mut checkpoint = self.build_checkpoint(
                         &run_id,
+                        recorder,
                         &state,
                         &current_node,
                         interrupts.clone(),
                         iterations,
                         &resume_values,
                     );
+                    // Record the pause before persisting, so a resume numbers
+                    // its records after it.
+                    self.emit_run_event(
+                        recorder,
+                        Event::RunPaused {
+                            run_id: run_id.clone(),
+                            checkpoint_id: checkpoint.checkpoint_id.clone(),
+                        },
+                    )?;
+                    if let Some(recorder) = recorder {
+                        checkpoint.event_seq = recorder.last_seq();
+                    }
                     self.maybe_persist_checkpoint(&checkpoint, &mut deferred_checkpoint)?;
                     self.flush_deferred_checkpoint(&mut deferred_checkpoint)?;
                     return Ok(ExecutionResult::Interrupted {
@@ -979,6 +1284,7 @@ impl<S: GraphState> CompiledGraph<S> {
             current_node = self.get_next_node(&current_node, &state)?;
             let checkpoint = self.build_checkpoint(
                 &run_id,
+                recorder,
                 &state,
                 &current_node,
                 Vec::new(),
@@ -993,8 +1299,15 @@ impl<S: GraphState> CompiledGraph<S> {
             return Err(GraphError::MaxIterationsExceeded);
         }
 
-        let checkpoint =
-            self.build_checkpoint(&run_id, &state, END, Vec::new(), iterations, &resume_values);
+        let checkpoint = self.build_checkpoint(
+            &run_id,
+            recorder,
+            &state,
+            END,
+            Vec::new(),
+            iterations,
+            &resume_values,
+        );
         self.maybe_persist_checkpoint(&checkpoint, &mut deferred_checkpoint)?;
         self.flush_deferred_checkpoint(&mut deferred_checkpoint)?;
 
@@ -1003,21 +1316,36 @@ impl<S: GraphState> CompiledGraph<S> {
 
     async fn resume_from_checkpoint(
         &self,
-        checkpoint: Checkpoint<S>,
+        mut checkpoint: Checkpoint<S>,
         command: Option<ResumeCommand>,
     ) -> GraphResult<ExecutionResult<S>>
     where
         S: Serialize,
     {
         let resume_values = self.apply_resume_command(&checkpoint, command)?;
+        // Hand the interrupted node back whatever it saved to continue.
+        if let Some(saved) = checkpoint
+            .pending_interrupts
+            .iter()
+            .filter(|interrupt| interrupt.node == checkpoint.next_node)
+            .find_map(|interrupt| interrupt.resume_state.clone())
+        {
+            let key = format!("interrupt:{}", checkpoint.next_node);
+            checkpoint.state.set(&key, Box::new(saved));
+        }
         let run_id = checkpoint.run_id.clone();
-        self.emit_run_event(Event::RunResumed {
-            run_id: run_id.clone(),
-            checkpoint_id: checkpoint.checkpoint_id.clone(),
-        })?;
+        let recorder = EventRecorder::for_config(&self.config, checkpoint.event_seq);
+        self.emit_run_event(
+            recorder.as_ref(),
+            Event::RunResumed {
+                run_id: run_id.clone(),
+                checkpoint_id: checkpoint.checkpoint_id.clone(),
+            },
+        )?;
         let result = self
             .run_with_checkpoint(
                 run_id.clone(),
+                recorder.as_ref(),
                 checkpoint.state,
                 checkpoint.next_node,
                 checkpoint.iterations,
@@ -1026,28 +1354,33 @@ impl<S: GraphState> CompiledGraph<S> {
             .await;
         match &result {
             Ok(ExecutionResult::Complete(_)) => {
-                self.emit_run_event(Event::RunCompleted {
-                    run_id,
-                    status: crate::runtime::session_state::RunStatus::Completed,
-                })?;
-            }
-            Ok(ExecutionResult::Interrupted { checkpoint, .. }) => {
-                self.emit_run_event(Event::RunPaused {
-                    run_id,
-                    checkpoint_id: checkpoint.checkpoint_id.clone(),
-                })?;
+                self.emit_run_event(
+                    recorder.as_ref(),
+                    Event::RunCompleted {
+                        run_id,
+                        status: crate::runtime::session_state::RunStatus::Completed,
+                    },
+                )?;
             }
+            // `run_with_checkpoint` emits `RunPaused` itself.
+            Ok(ExecutionResult::Interrupted { .. }) => {}
             Err(GraphError::Aborted { reason }) => {
-                self.emit_run_event(Event::RunAborted {
-                    run_id,
-                    reason: reason.clone(),
-                })?;
+                self.emit_run_event(
+                    recorder.as_ref(),
+                    Event::RunAborted {
+                        run_id,
+                        reason: reason.clone(),
+                    },
+                )?;
             }
             Err(err) => {
-                self.emit_run_event(Event::RunFailed {
-                    run_id,
-                    error: err.to_string(),
-                })?;
+                self.emit_run_event(
+                    recorder.as_ref(),
+                    Event::RunFailed {
+                        run_id,
+                        error: err.to_string(),
+                    },
+                )?;
             }
         }
         result
@@ -1138,9 +1471,11 @@ impl<S: GraphState> CompiledGraph<S> {
         Ok(resume_values)
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn build_checkpoint(
         &self,
         run_id: &str,
+        recorder: Option<&Arc<EventRecorder>>,
         state: &S,
         next_node: impl Into<String>,
         pending_interrupts: Vec<Interrupt>,
@@ -1156,6 +1491,7 @@ impl<S: GraphState> CompiledGraph<S> {
             pending_interrupts,
             iterations,
             resume_values: resume_values.clone(),
+            event_seq: recorder.map_or(0, |recorder| recorder.last_seq()),
         }
     }
 
@@ -1298,7 +1634,15 @@ impl<S: GraphState> CompiledGraph<S> {
         }
     }
 
-    fn emit_run_event(&self, event: Event) -> GraphResult<()> {
+    /// Emit a run lifecycle event, recording it under the run's context.
+    fn emit_run_event(
+        &self,
+        recorder: Option<&Arc<EventRecorder>>,
+        event: Event,
+    ) -> GraphResult<()> {
+        if let (Some(recorder), Some(run_id)) = (recorder, event_run_id(&event)) {
+            recorder.record(event.clone(), &self.config.event_context(run_id))?;
+        }
         if let Some(sink) = &self.config.run_event_sink {
             sink.emit(event)?;
         }
@@ -1306,13 +1650,85 @@ impl<S: GraphState> CompiledGraph<S> {
     }
 }
 
-struct RecordingSink {
-    inner: Arc<dyn EventSink>,
-    history: Arc<std::sync::Mutex<Vec<EventRecord>>>,
+/// Run id carried by a run lifecycle event.
+fn event_run_id(event: &Event) -> Option<&str> {
+    match event {
+        Event::RunStarted { run_id, .. }
+        | Event::RunPaused { run_id, .. }
+        | Event::RunResumed { run_id, .. }
+        | Event::RunCompleted { run_id, .. }
+        | Event::RunFailed { run_id, .. }
+        | Event::RunAborted { run_id, .. } => Some(run_id),
+        _ => None,
+    }
+}
+
+/// Numbers, stamps, and stores the event records of one invocation.
+struct EventRecorder {
     sequencer: EventSequencer,
+    history: Option<Arc<std::sync::Mutex<Vec<EventRecord>>>>,
     record_sink: Option<Arc<dyn EventRecordSink>>,
 }
 
+impl EventRecorder {
+    /// Recorder for the configured history and record sink, or `None` when
+    /// neither is set. Seqs continue after `last_seq`.
+    fn for_config(config: &ExecutionConfig, last_seq: u64) -> Option<Arc<Self>> {
+        if config.event_history.is_none() && config.event_record_sink.is_none() {
+            return None;
+        }
+        Some(Arc::new(Self::new(
+            config,
+            config.event_history.clone(),
+            last_seq,
+        )))
+    }
+
+    fn new(
+        config: &ExecutionConfig,
+        history: Option<Arc<std::sync::Mutex<Vec<EventRecord>>>>,
+        last_seq: u64,
+    ) -> Self {
+        let record_sink = match (&config.event_record_sink, &config.redaction) {
+            (Some(sink), Some(policy)) => Some(Arc::new(RedactingEventRecordSink::new(
+                Arc::clone(sink),
+                Arc::clone(policy),
+            )) as Arc<dyn EventRecordSink>),
+            (sink, _) => sink.clone(),
+        };
+        Self {
+            sequencer: EventSequencer::with_start_seq(last_seq),
+            history,
+            record_sink,
+        }
+    }
+
+    fn record(&self, event: Event, context: &EventContext) -> GraphResult<()> {
+        let record = self.sequencer.record_with_context(event, context);
+        if let Some(history) = &self.history {
+            history.lock().unwrap().push(record.clone());
+        }
+        match &self.record_sink {
+            Some(record_sink) => record_sink.emit_record(record),
+            None => Ok(()),
+        }
+    }
+
+    fn last_seq(&self) -> u64 {
+        self.sequencer.last_seq()
+    }
+}
+
+/// Records events under the context of the node that emitted them.
+///
+/// Each node run gets its own sink, so events from tasks a node spawned are
+/// never stamped with the node that runs after it.
+struct RecordingSink {
+    inner: Arc<dyn EventSink>,
+    recorder: Arc<EventRecorder>,
+    context: EventContext,
+}
+
 fn resolve_message_count(
     snapshot: &Option<Arc<std::sync::Mutex<SessionSnapshot>>>,
     history: &Arc<std::sync::Mutex<Vec<EventRecord>>>,
@@ -1347,6 +1763,20 @@ fn token_usage_total(tokens: &TokenUsage) -> u64 {
     tokens.input + tokens.output + tokens.reasoning + tokens.cache_read + tokens.cache_write
 }
 
+fn estimate_snapshot_tokens(
+    snapshot: &Option<Arc<std::sync::Mutex<SessionSnapshot>>>,
+    estimator: &dyn TokenEstimator,
+) -> Option<u64> {
+    let snapshot = snapshot.as_ref()?.lock().unwrap();
+    Some(
+        snapshot
+            .messages
+            .iter()
+            .map(|message| estimator.count_text(&message.content) + estimator.message_overhead())
+            .sum(),
+    )
+}
+
 fn collect_compaction_messages(
     snapshot: &Option<Arc<std::sync::Mutex<SessionSnapshot>>>,
 ) -> Vec<String> {
@@ -1364,32 +1794,136 @@ fn collect_compaction_messages(
         .unwrap_or_default()
 }
 
-impl RecordingSink {
-    fn new(
-        inner: Arc<dyn EventSink>,
-        history: Arc<std::sync::Mutex<Vec<EventRecord>>>,
-        record_sink: Option<Arc<dyn EventRecordSink>>,
-    ) -> Self {
-        Self {
-            inner,
-            history,
-            sequencer: EventSequencer::new(),
-            record_sink,
-        }
+impl EventSink for RecordingSink {
+    fn emit(&self, event: Event) -> GraphResult<()> {
+        self.recorder.record(event.clone(), &self.context)?;
+        self.inner.emit(event)
     }
 }
 
-impl EventSink for RecordingSink {
+/// Derives tool/model/permission spans from events flowing to the sink.
+struct SpanSink {
+    inner: Arc<dyn EventSink>,
+    spans: Arc<std::sync::Mutex<SpanRecorder>>,
+}
+
+impl EventSink for SpanSink {
     fn emit(&self, event: Event) -> GraphResult<()> {
-        let record = self.sequencer.record(event.clone());
-        self.history.lock().unwrap().push(record.clone());
-        if let Some(record_sink) = &self.record_sink {
-            record_sink.emit_record(record.clone())?;
-        }
+        self.spans.lock().unwrap().observe(&event);
         self.inner.emit(event)
     }
 }
 
+/// Finishes a [`SpanRecorder`] when dropped, so a run that ends early still
+/// records its open spans as incomplete.
+struct FinishSpans(Option<Arc<std::sync::Mutex<SpanRecorder>>>);
+
+impl Drop for FinishSpans {
+    fn drop(&mut self) {
+        if let Some(spans) = &self.0 {
+            if let Ok(mut spans) = spans.lock() {
+                spans.finish();
+            }
+        }
+    }
+}
+
+/// Cost accounting for one invocation.
+///
+/// Prices `StepFinish` events by the model that served them, keeps the
+/// invocation's own totals for budget checks and metrics, and adds each step
+/// to the caller's shared ledger, if any.
+struct CostTracker {
+    pricing: Option<Arc<PricingRegistry>>,
+    budget: Option<Budget>,
+    run: std::sync::Mutex<CostLedger>,
+    shared: Option<Arc<std::sync::Mutex<CostLedger>>>,
+}
+
+impl CostTracker {
+    fn for_config(config: &ExecutionConfig) -> Option<Arc<Self>> {
+        if config.pricing.is_none() && config.budget.is_none() && config.cost_ledger.is_none() {
+            return None;
+        }
+        Some(Arc::new(Self {
+            pricing: config.pricing.clone(),
+            budget: config.budget.clone(),
+            run: std::sync::Mutex::new(CostLedger::new()),
+            shared: config.cost_ledger.clone(),
+        }))
+    }
+
+    fn price(&self, model: Option<&str>, tokens: &TokenUsage) -> Option<f64> {
+        let pricing = self.pricing.as_ref()?;
+        match model {
+            Some(model) => pricing
+                .cost(model, tokens)
+                .or_else(|| pricing.default_cost(tokens)),
+            None => pricing.default_cost(tokens),
+        }
+    }
+
+    fn record(&self, session_id: &str, tokens: &TokenUsage, cost: f64) {
+        self.run.lock().unwrap().record(session_id, tokens, cost);
+        if let Some(shared) = &self.shared {
+            shared.lock().unwrap().record(session_id, tokens, cost);
+        }
+    }
+
+    /// Totals for this invocation only.
+    fn ledger(&self) -> CostLedger {
+        self.run.lock().unwrap().clone()
+    }
+
+    fn check(&self) -> GraphResult<()> {
+        match &self.budget {
+            Some(budget) => budget.check(&self.run.lock().unwrap()),
+            None => Ok(()),
+        }
+    }
+
+    /// Sink for nodes run outside `stream_events`, which only feeds the ledger.
+    fn wrap(self: &Arc<Self>, inner: Arc<dyn EventSink>) -> Arc<dyn EventSink> {
+        Arc::new(CostSink {
+            inner,
+            costs: Arc::clone(self),
+        })
+    }
+}
+
+/// Fills in `StepFinish` costs, accumulates them, and enforces the budget.
+struct CostSink {
+    inner: Arc<dyn EventSink>,
+    costs: Arc<CostTracker>,
+}
+
+impl EventSink for CostSink {
+    fn emit(&self, event: Event) -> GraphResult<()> {
+        let Event::StepFinish {
+            session_id,
+            tokens,
+            cost,
+            model,
+        } = event
+        else {
+            return self.inner.emit(event);
+        };
+        let cost = if cost == 0.0 {
+            self.costs.price(model.as_deref(), &tokens).unwrap_or(cost)
+        } else {
+            cost
+        };
+        self.costs.record(&session_id, &tokens, cost);
+        self.inner.emit(Event::StepFinish {
+            session_id,
+            tokens,
+            cost,
+            model,
+        })?;
+        self.costs.check()
+    }
+}
+
 // Need to implement Clone for CompiledGraph to support ablation studies
 impl<S: GraphState> Clone for CompiledGraph<S> {
     fn clone(&self) -> Self {
@@ -1635,6 +2169,59 @@ mod tests {
         assert_eq!(*hook_calls.lock().unwrap(), 2);
     }
 
+    #[test]
+    fn stream_events_compacts_on_estimated_tokens_without_usage() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink: Arc<dyn EventSink> = Arc::new(CaptureSink {
+            events: events.clone(),
+        });
+        let snapshot = Arc::new(Mutex::new(SessionSnapshot::new("s1")));
+        let mut long = Message::new(MessageRole::User);
+        long.parts.push(Part::TextFinal {
+            text: "x".repeat(800),
+        });
+        snapshot.lock().unwrap().push_message(&long);
+
+        let mut graph = StateGraph::<StreamState>::new();
+        graph.add_stream_node("streamer", |state, _sink| async move { Ok(state) });
+        graph.add_edge(START, "streamer");
+        graph.add_edge("streamer", END);
+
+        let config = ExecutionConfig::new()
+            .with_compaction_policy(CompactionPolicy::token_ratio(200, 0.9))
+            .with_session_snapshot(Arc::clone(&snapshot));
+        let compiled = graph
+            .compile()
+            .expect("compile")
+            .with_config(config.clone());
+        let _ =
+            block_on(compiled.stream_events(StreamState::default(), sink.clone())).expect("run");
+        assert!(!events
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|event| matches!(event, Event::SessionCompactionRequested { .. })));
+
+        let mut graph = StateGraph::<StreamState>::new();
+        graph.add_stream_node("streamer", |state, _sink| async move { Ok(state) });
+        graph.add_edge(START, "streamer");
+        graph.add_edge("streamer", END);
+        let compiled = graph
+            .compile()
+            .expect("compile")
+            .with_config(config.with_token_estimator(Arc::new(
+                crate::runtime::tokens::HeuristicEstimator::default(),
+            )));
+        let _ = block_on(compiled.stream_events(StreamState::default(), sink)).expect("run");
+        let captured = events.lock().unwrap();
+        let tokens = captured.iter().find_map(|event| match event {
+            Event::SessionCompactionRequested { tokens, .. } => Some(tokens.input),
+            _ => None,
+        });
+        // 800 chars / 4 + message overhead, plus whatever the run appended.
+        assert!(tokens.is_some_and(|tokens| tokens >= 204));
+    }
+
     #[test]
     fn stream_events_emits_event_records_with_metadata() {
         let records = Arc::new(Mutex::new(Vec::new()));
@@ -1680,6 +2267,171 @@ mod tests {
         }
     }
 
+    #[test]
+    fn stream_events_redacts_records_but_keeps_history() {
+        let records = Arc::new(Mutex::new(Vec::new()));
+        let record_sink: Arc<dyn EventRecordSink> = Arc::new(CaptureRecordSink {
+            records: records.clone(),
+        });
+        let history = Arc::new(Mutex::new(Vec::new()));
+        let sink: Arc<dyn EventSink> = Arc::new(CaptureSink {
+            events: Arc::new(Mutex::new(Vec::new())),
+        });
+
+        let mut graph = StateGraph::<StreamState>::new();
+        graph.add_stream_node("node", |state, sink| async move {
+            sink.emit(Event::ToolStart {
+                tool: "vault".to_string(),
+                call_id: "c1".to_string(),
+                input: serde_json::json!({"secret": "s3cr3t"}),
+            })?;
+            Ok(state)
+        });
+        graph.add_edge(START, "node");
+        graph.add_edge("node", END);
+
+        let policy = RedactionPolicy::new().with_sensitive_tool("vault");
+        let compiled = graph.compile().expect("compile").with_config(
+            ExecutionConfig::new()
+                .with_event_record_sink(record_sink)
+                .with_event_history(Arc::clone(&history))
+                .with_redaction(Arc::new(policy)),
+        );
+
+        let _ = block_on(compiled.stream_events(StreamState::default(), sink)).expect("run");
+
+        let captured = records.lock().unwrap();
+        assert!(matches!(
+            &captured[0].event,
+            Event::ToolStart { input, .. } if input == &serde_json::json!("[REDACTED]")
+        ));
+        let history = history.lock().unwrap();
+        assert!(matches!(
+            &history[0].event,
+            Event::ToolStart { input, .. } if input["secret"] == "s3cr3t"
+        ));
+    }
+
+    #[test]
+    fn stream_events_stamps_correlation_fields_on_records() {
+        let records = Arc::new(Mutex::new(Vec::new()));
+        let record_sink: Arc<dyn EventRecordSink> = Arc::new(CaptureRecordSink {
+            records: records.clone(),
+        });
+        let sink: Arc<dyn EventSink> = Arc::new(CaptureSink {
+            events: Arc::new(Mutex::new(Vec::new())),
+        });
+
+        let mut graph = StateGraph::<StreamState>::new();
+        graph.add_stream_node("first", |state, sink| async move {
+            sink.emit(Event::ToolStart {
+                tool: "grep".to_string(),
+                call_id: "1".to_string(),
+                input: serde_json::json!({}),
+            })?;
+            Ok(state)
+        });
+        graph.add_stream_node("second", |state, sink| async move {
+            sink.emit(Event::TextDelta {
+                session_id: "s1".to_string(),
+                message_id: "m1".to_string(),
+                delta: "hello".to_string(),
+            })?;
+            Ok(state)
+        });
+        graph.add_edge(START, "first");
+        graph.add_edge("first", "second");
+        graph.add_edge("second", END);
+
+        let compiled = graph.compile().expect("compile").with_config(
+            ExecutionConfig::new()
+                .with_event_record_sink(record_sink)
+                .with_event_namespace("parent/child")
+                .with_parent_span_id("00000000000000a1"),
+        );
+
+        let _ = block_on(compiled.stream_events(StreamState::default(), sink)).expect("run");
+
+        let captured = records.lock().unwrap();
+        assert_eq!(captured.len(), 2);
+        let run_id = captured[0].meta.run_id.clone().expect("run id");
+        assert!(captured
+            .iter()
+            .all(|record| record.meta.run_id.as_deref() == Some(run_id.as_str())));
+        assert_eq!(captured[0].meta.node.as_deref(), Some("first"));
+        assert_eq!(captured[0].meta.step, Some(1));
+        assert_eq!(captured[1].meta.node.as_deref(), Some("second"));
+        assert_eq!(captured[1].meta.step, Some(2));
+        assert!(captured.iter().all(|record| {
+            record.meta.namespace.as_deref() == Some("parent/child")
+                && record.meta.parent_span_id.as_deref() == Some("00000000000000a1")
+        }));
+    }
+
+    #[test]
+    fn stream_events_with_run_id_stamps_each_node_with_its_own_context() {
+        let records = Arc::new(Mutex::new(Vec::new()));
+        let record_sink: Arc<dyn EventRecordSink> = Arc::new(CaptureRecordSink {
+            records: records.clone(),
+        });
+        let sink: Arc<dyn EventSink> = Arc::new(CaptureSink {
+            events: Arc::new(Mutex::new(Vec::new())),
+        });
+        // The first node hands its sink to work that outlives it.
+        let stashed: Arc<Mutex<Option<Arc<dyn EventSink>>>> = Arc::new(Mutex::new(None));
+
+        let mut graph = StateGraph::<StreamState>::new();
+        let stash = Arc::clone(&stashed);
+        graph.add_stream_node("first", move |state, sink| {
+            let stash = Arc::clone(&stash);
+            async move {
+                *stash.lock().unwrap() = Some(sink);
+                Ok(state)
+            }
+        });
+        let stash = Arc::clone(&stashed);
+        graph.add_stream_node("second", move |state, sink| {
+            let stash = Arc::clone(&stash);
+            async move {
+                let background = stash.lock().unwrap().take().expect("stashed sink");
+                background.emit(Event::TextDelta {
+                    session_id: "s1".to_string(),
+                    message_id: "m1".to_string(),
+                    delta: "late".to_string(),
+                })?;
+                sink.emit(Event::TextDelta {
+                    session_id: "s1".to_string(),
+                    message_id: "m2".to_string(),
+                    delta: "own".to_string(),
+                })?;
+                Ok(state)
+            }
+        });
+        graph.add_edge(START, "first");
+        graph.add_edge("first", "second");
+        graph.add_edge("second", END);
+
+        let compiled = graph.compile().expect("compile").with_config(
+            ExecutionConfig::new()
+                .with_event_record_sink(record_sink)
+                .with_run_id("run-7"),
+        );
+
+        let run = block_on(compiled.stream_events_with_run_id(StreamState::default(), sink))
+            .expect("run");
+
+        assert_eq!(run.run_id, "run-7");
+        let captured = records.lock().unwrap();
+        assert_eq!(captured.len(), 2);
+        assert!(captured
+            .iter()
+            .all(|record| record.meta.run_id.as_deref() == Some("run-7")));
+        assert_eq!(captured[0].meta.node.as_deref(), Some("first"));
+        assert_eq!(captured[0].meta.step, Some(1));
+        assert_eq!(captured[1].meta.node.as_deref(), Some("second"));
+        assert_eq!(captured[1].meta.step, Some(2));
+    }
+
     #[test]
     fn prune_ordering_executes_before_compaction() {
         let history = Arc::new(Mutex::new(Vec::new()));
@@ -1826,6 +2578,210 @@ mod tests {
             .any(|event| matches!(event, TraceEvent::NodeFinish { .. })));
     }
 
+    #[test]
+    fn stream_events_prices_steps_and_enforces_budget() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink: Arc<dyn EventSink> = Arc::new(CaptureSink {
+            events: Arc::clone(&events),
+        });
+        let ledger = Arc::new(Mutex::new(CostLedger::new()));
+
+        let mut graph = StateGraph::<StreamState>::new();
+        graph.add_stream_node("node", |state, sink| async move {
+            sink.emit(Event::StepFinish {
+                session_id: "s1".to_string(),
+                tokens: TokenUsage {
+                    input: 1_000,
+                    output: 1_000,
+                    ..Default::default()
+                },
+                cost: 0.0,
+                model: None,
+            })?;
+            Ok(state)
+        });
+        graph.add_edge(START, "node");
+        graph.add_edge("node", "node");
+
+        let pricing = PricingRegistry::new()
+            .with_price("m", crate::runtime::pricing::ModelPrice::new(1.0, 3.0))
+            .with_default_model("m");
+        let compiled = graph.compile().expect("compile").with_config(
+            ExecutionConfig::new()
+                .with_pricing(Arc::new(pricing))
+                .with_budget(Budget::new().with_max_cost_usd(0.01))
+                .with_cost_ledger(Arc::clone(&ledger)),
+        );
+
+        let result = block_on(compiled.stream_events(StreamState::default(), sink));
+        assert!(matches!(result, Err(GraphError::Aborted { .. })));
+
+        let ledger = ledger.lock().unwrap();
+        assert_eq!(ledger.total_tokens, 6_000);
+        assert!((ledger.total_cost_usd - 0.012).abs() < 1e-12);
+        let events = events.lock().unwrap();
+        assert!(events.iter().all(|event| matches!(
+            event,
+            Event::StepFinish { cost, .. } if (*cost - 0.004).abs() < 1e-12
+        )));
+    }
+
+    #[test]
+    fn invoke_prices_steps_by_model_and_budgets_each_run() {
+        let mut graph = StateGraph::<StreamState>::new();
+        graph.add_stream_node("node", |state, sink| async move {
+            sink.emit(Event::StepFinish {
+                session_id: "s1".to_string(),
+                tokens: TokenUsage {
+                    input: 1_000,
+                    output: 1_000,
+                    ..Default::default()
+                },
+                cost: 0.0,
+                model: Some("big-2025".to_string()),
+            })?;
+            Ok(state)
+        });
+        graph.add_edge(START, "node");
+        graph.add_edge("node", END);
+
+        let pricing = Arc::new(
+            PricingRegistry::new()
+                .with_price("small", crate::runtime::pricing::ModelPrice::new(1.0, 3.0))
+                .with_price("big", crate::runtime::pricing::ModelPrice::new(10.0, 30.0))
+                .with_default_model("small"),
+        );
+        let ledger = Arc::new(Mutex::new(CostLedger::new()));
+        let compiled = graph.compile().expect("compile").with_config(
+            ExecutionConfig::new()
+                .with_metrics()
+                .with_pricing(Arc::clone(&pricing))
+                .with_budget(Budget::new().with_max_cost_usd(0.05))
+                .with_cost_ledger(Arc::clone(&ledger)),
+        );
+
+        // Each run costs $0.04 at the "big" price; the budget applies per run.
+        for _ in 0..2 {
+            let result =
+                block_on(compiled.invoke_with_metrics(StreamState::default())).expect("run");
+            let metrics = result.metrics.expect("metrics");
+            assert!((metrics.total_cost_usd - 0.04).abs() < 1e-12);
+            assert!((metrics.session_costs["s1"] - 0.04).abs() < 1e-12);
+        }
+        assert!((ledger.lock().unwrap().total_cost_usd - 0.08).abs() < 1e-12);
+
+        let strict = compiled.clone().with_config(
+            ExecutionConfig::new()
+                .with_pricing(pricing)
+                .with_budget(Budget::new().with_max_cost_usd(0.03)),
+        );
+        assert!(matches!(
+            block_on(strict.invoke(StreamState::default())),
+            Err(GraphError::Aborted { .. })
+        ));
+    }
+
+    #[test]
+    fn stream_events_records_node_and_tool_spans() {
+        let trace = Arc::new(Mutex::new(ExecutionTrace::new()));
+        let sink: Arc<dyn EventSink> = Arc::new(CaptureSink {
+            events: Arc::new(Mutex::new(Vec::new())),
+        });
+
+        let mut graph = StateGraph::<StreamState>::new();
+        graph.add_stream_node("node", |state, sink| async move {
+            sink.emit(Event::ToolStart {
+                tool: "grep".to_string(),
+                call_id: "c1".to_string(),
+                input: serde_json::json!({}),
+            })?;
+            sink.emit(Event::ToolError {
+                tool: "grep".to_string(),
+                call_id: "c1".to_string(),
+                error: "boom".to_string(),
+            })?;
+            Ok(state)
+        });
+        graph.add_edge(START, "node");
+        graph.add_edge("node", END);
+
+        let compiled = graph.compile().expect("compile").with_config(
+            ExecutionConfig::new()
+                .with_trace(Arc::clone(&trace))
+                .with_parent_span_id("00000000000000a1"),
+        );
+
+        let _ = block_on(compiled.stream_events(StreamState::default(), sink)).expect("run");
+
+        let trace = trace.lock().unwrap();
+        assert_eq!(trace.spans.len(), 2);
+        let tool = &trace.spans[0];
+        let node = &trace.spans[1];
+        assert_eq!(tool.kind, SpanKind::Tool);
+        assert_eq!(
+            tool.attributes.get("error"),
+            Some(&serde_json::json!("boom"))
+        );
+        assert_eq!(node.kind, SpanKind::Node);
+        assert_eq!(node.node, "node");
+        assert_eq!(node.parent_span_id.as_deref(), Some("00000000000000a1"));
+        assert_eq!(tool.parent_span_id, node.span_id);
+    }
+
+    #[test]
+    fn stream_events_finishes_spans_when_budget_aborts() {
+        let trace = Arc::new(Mutex::new(ExecutionTrace::new()));
+        let sink: Arc<dyn EventSink> = Arc::new(CaptureSink {
+            events: Arc::new(Mutex::new(Vec::new())),
+        });
+
+        let mut graph = StateGraph::<StreamState>::new();
+        graph.add_stream_node("node", |state, sink| async move {
+            sink.emit(Event::PermissionAsked {
+                permission: "tool:bash".to_string(),
+                patterns: Vec::new(),
+                metadata: Default::default(),
+                always: Vec::new(),
+            })?;
+            sink.emit(Event::StepFinish {
+                session_id: "s1".to_string(),
+                tokens: TokenUsage {
+                    input: 1_000,
+                    ..Default::default()
+                },
+                cost: 0.0,
+                model: None,
+            })?;
+            Ok(state)
+        });
+        graph.add_edge(START, "node");
+        graph.add_edge("node", END);
+
+        let pricing = PricingRegistry::new()
+            .with_price("m", crate::runtime::pricing::ModelPrice::new(1.0, 3.0))
+            .with_default_model("m");
+        let compiled = graph.compile().expect("compile").with_config(
+            ExecutionConfig::new()
+                .with_trace(Arc::clone(&trace))
+                .with_pricing(Arc::new(pricing))
+                .with_budget(Budget::new().with_max_cost_usd(0.0001)),
+        );
+
+        let result = block_on(compiled.stream_events(StreamState::default(), sink));
+        assert!(matches!(result, Err(GraphError::Aborted { .. })));
+
+        let trace = trace.lock().unwrap();
+        let permission = trace
+            .spans
+            .iter()
+            .find(|span| span.kind == SpanKind::Permission)
+            .expect("permission span recorded");
+        assert_eq!(
+            permission.attributes.get("incomplete"),
+            Some(&serde_json::json!(true))
+        );
+    }
+
     #[test]
     fn stream_events_updates_session_snapshot() {
         let trace = Arc::new(Mutex::new(ExecutionTrace::new()));
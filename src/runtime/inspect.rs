@@ -0,0 +1,842 @@
+//! Offline inspection of persisted runs.
+//!
+//! [`RunInspector`] reads the on-disk stores (`RunLogStore`,
+//! `CheckpointStore`, `SessionStore`, `FileAttachmentStore`) to list runs,
+//! render transcripts, diff checkpoints, verify log integrity, and export
+//! self-contained [`RunBundle`]s for bug reports. The `forge` binary is a
+//! thin command-line front end over it.
+
+use std::collections::{BTreeSet, HashMap};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::runtime::audit::{AuditSeal, ChainBreak, ChainReport, HashChain};
+use crate::runtime::event::EventRecord;
+use crate::runtime::redact::RedactionPolicy;
+use crate::runtime::session::{
+    AttachmentRecord, AttachmentResolver, CheckpointRecord, CheckpointStore, RunLogStore,
+    SessionSnapshot, SessionStore,
+};
+
+pub const RUN_BUNDLE_VERSION: u32 = 1;
+
+const ATTACHMENT_SCHEME: &str = "attachment://";
+
+/// One row of [`RunInspector::list_runs`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RunSummary {
+    pub run_id: String,
+    pub event_count: usize,
+    pub checkpoint_count: usize,
+    pub first_timestamp_ms: Option<u64>,
+    pub last_timestamp_ms: Option<u64>,
+}
+
+/// One row of [`RunInspector::list_checkpoints`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CheckpointSummary {
+    pub checkpoint_id: String,
+    pub created_at: String,
+    pub next_node: String,
+    pub iterations: usize,
+    pub pending_interrupts: usize,
+}
+
+/// Kind of change between two JSON values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// A single difference at a JSON pointer path.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ValueChange {
+    pub path: String,
+    pub kind: ChangeKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub before: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after: Option<serde_json::Value>,
+}
+
+/// Structural diff of two JSON values, keyed by JSON pointer paths.
+///
+/// Objects are compared key by key and arrays index by index; any other
+/// mismatch is reported as a single `Changed` entry.
+pub fn diff_values(before: &serde_json::Value, after: &serde_json::Value) -> Vec<ValueChange> {
+    let mut changes = Vec::new();
+    diff_into("", before, after, &mut changes);
+    changes
+}
+
+fn diff_into(
+    path: &str,
+    before: &serde_json::Value,
+    after: &serde_json::Value,
+    changes: &mut Vec<ValueChange>,
+) {
+    use serde_json::Value;
+
+    match (before, after) {
+        (Value::Object(left), Value::Object(right)) => {
+            let keys: BTreeSet<&String> = left.keys().chain(right.keys()).collect();
+            for key in keys {
+                let child = format!("{}/{}", path, escape_pointer(key));
+                match (left.get(key), right.get(key)) {
+                    (Some(old), Some(new)) => diff_into(&child, old, new, changes),
+                    (Some(old), None) => changes.push(removed(child, old)),
+                    (None, Some(new)) => changes.push(added(child, new)),
+                    (None, None) => {}
+                }
+            }
+        }
+        (Value::Array(left), Value::Array(right)) => {
+            for index in 0..left.len().max(right.len()) {
+                let child = format!("{}/{}", path, index);
+                match (left.get(index), right.get(index)) {
+                    (Some(old), Some(new)) => diff_into(&child, old, new, changes),
+                    (Some(old), None) => changes.push(removed(child, old)),
+                    (None, Some(new)) => changes.push(added(child, new)),
+                    (None, None) => {}
+                }
+            }
+        }
+        (old, new) if old != new => changes.push(ValueChange {
+            path: path.to_string(),
+            kind: ChangeKind::Changed,
+            before: Some(old.clone()),
+            after: Some(new.clone()),
+        }),
+        _ => {}
+    }
+}
+
+fn added(path: String, value: &serde_json::Value) -> ValueChange {
+    ValueChange {
+        path,
+        kind: ChangeKind::Added,
+        before: None,
+        after: Some(value.clone()),
+    }
+}
+
+fn removed(path: String, value: &serde_json::Value) -> ValueChange {
+    ValueChange {
+        path,
+        kind: ChangeKind::Removed,
+        before: Some(value.clone()),
+        after: None,
+    }
+}
+
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Differences between two checkpoints of the same run.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CheckpointDiff {
+    pub run_id: String,
+    pub from: String,
+    pub to: String,
+    pub next_node: (String, String),
+    pub iterations: (usize, usize),
+    pub pending_interrupts: (usize, usize),
+    pub state: Vec<ValueChange>,
+}
+
+/// Integrity check of a run's log and checkpoints.
+///
+/// Hash-chained logs are verified with [`crate::runtime::audit::verify_chain`];
+/// unchained logs only get a seq-ordering check.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    pub run_id: String,
+    pub record_count: usize,
+    pub chained: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain: Option<ChainReport>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence_break: Option<ChainBreak>,
+    pub checkpoints_checked: usize,
+    pub checkpoint_errors: Vec<String>,
+}
+
+impl IntegrityReport {
+    pub fn is_valid(&self) -> bool {
+        self.chain.as_ref().map_or(true, ChainReport::is_valid)
+            && self.sequence_break.is_none()
+            && self.checkpoint_errors.is_empty()
+    }
+}
+
+/// Everything persisted about a run, in one JSON document.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RunBundle {
+    pub version: u32,
+    pub run_id: String,
+    pub exported_at: String,
+    pub events: Vec<EventRecord>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seal: Option<AuditSeal>,
+    pub checkpoints: Vec<CheckpointRecord>,
+    pub sessions: Vec<SessionSnapshot>,
+    pub attachments: Vec<AttachmentRecord>,
+    /// Contents were redacted on export; a chained log is re-linked and
+    /// re-sealed over the redacted records, so it no longer matches the
+    /// original log's digests.
+    #[serde(default)]
+    pub redacted: bool,
+}
+
+/// Read-only view over the file-backed stores of a Forge deployment.
+pub struct RunInspector {
+    logs: RunLogStore,
+    checkpoints: CheckpointStore,
+    sessions: SessionStore,
+    attachments: AttachmentResolver,
+    redaction: Option<Arc<RedactionPolicy>>,
+}
+
+impl RunInspector {
+    /// Use `root` for every store.
+    pub fn new(root: impl AsRef<Path>) -> Self {
+        let root = root.as_ref();
+        Self {
+            logs: RunLogStore::new(root),
+            checkpoints: CheckpointStore::new(root),
+            sessions: SessionStore::new(root),
+            attachments: AttachmentResolver::new(root),
+            redaction: None,
+        }
+    }
+
+    pub fn with_log_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.logs = RunLogStore::new(root);
+        self
+    }
+
+    pub fn with_checkpoint_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.checkpoints = CheckpointStore::new(root);
+        self
+    }
+
+    pub fn with_session_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.sessions = SessionStore::new(root);
+        self
+    }
+
+    pub fn with_attachment_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.attachments = AttachmentResolver::new(root);
+        self
+    }
+
+    /// Redact events, snapshots, checkpoint state, and attachments in
+    /// exported bundles. A hash-chained log is re-linked (and re-sealed, if
+    /// it was sealed) over the redacted records so the bundle still verifies.
+    pub fn with_redaction(mut self, policy: Arc<RedactionPolicy>) -> Self {
+        self.redaction = Some(policy);
+        self
+    }
+
+    pub fn logs(&self) -> &RunLogStore {
+        &self.logs
+    }
+
+    pub fn checkpoints(&self) -> &CheckpointStore {
+        &self.checkpoints
+    }
+
+    pub fn sessions(&self) -> &SessionStore {
+        &self.sessions
+    }
+
+    /// Runs with a log or at least one checkpoint, sorted by id.
+    pub fn list_runs(&self) -> std::io::Result<Vec<RunSummary>> {
+        let mut run_ids: BTreeSet<String> = self.logs.list_runs()?.into_iter().collect();
+        run_ids.extend(self.checkpoints.list_runs()?);
+        let mut runs = Vec::new();
+        for run_id in run_ids {
+            let stats = self.logs.stats(&run_id)?;
+            runs.push(RunSummary {
+                event_count: stats.record_count,
+                checkpoint_count: self.checkpoints.list(&run_id)?.len(),
+                first_timestamp_ms: stats.first_timestamp_ms,
+                last_timestamp_ms: stats.last_timestamp_ms,
+                run_id,
+            });
+        }
+        Ok(runs)
+    }
+
+    /// Checkpoints of a run, oldest first.
+    pub fn list_checkpoints(&self, run_id: &str) -> std::io::Result<Vec<CheckpointSummary>> {
+        let mut checkpoints = Vec::new();
+        for checkpoint_id in self.checkpoints.list(run_id)? {
+            let record = self.checkpoints.load(run_id, &checkpoint_id)?;
+            checkpoints.push(CheckpointSummary {
+                checkpoint_id,
+                created_at: record.created_at,
+                next_node: record.next_node,
+                iterations: record.iterations,
+                pending_interrupts: record.pending_interrupts.len(),
+            });
+        }
+        checkpoints.sort_by(|a, b| {
+            a.created_at
+                .cmp(&b.created_at)
+                .then_with(|| a.checkpoint_id.cmp(&b.checkpoint_id))
+        });
+        Ok(checkpoints)
+    }
+
+    pub fn diff_checkpoints(
+        &self,
+        run_id: &str,
+        from: &str,
+        to: &str,
+    ) -> std::io::Result<CheckpointDiff> {
+        let before = self.checkpoints.load(run_id, from)?;
+        let after = self.checkpoints.load(run_id, to)?;
+        Ok(CheckpointDiff {
+            run_id: run_id.to_string(),
+            from: from.to_string(),
+            to: to.to_string(),
+            next_node: (before.next_node, after.next_node),
+            iterations: (before.iterations, after.iterations),
+            pending_interrupts: (
+                before.pending_interrupts.len(),
+                after.pending_interrupts.len(),
+            ),
+            state: diff_values(&before.state, &after.state),
+        })
+    }
+
+    /// Plain-text transcript of a persisted session.
+    pub fn render_transcript(&self, session_id: &str) -> std::io::Result<String> {
+        let snapshot = self.sessions.load(session_id)?;
+        let mut out = format!("# session {}\n", snapshot.session_id);
+        for compaction in &snapshot.compactions {
+            out.push_str(&format!("\n[compacted]\n{}\n", compaction.summary));
+        }
+        for message in &snapshot.messages {
+            out.push_str(&format!("\n[{}]\n{}\n", message.role, message.content));
+        }
+        Ok(out)
+    }
+
+    pub fn verify(&self, run_id: &str) -> std::io::Result<IntegrityReport> {
+        let records = self.logs.load(run_id)?;
+        let seal = self.logs.load_seal(run_id)?;
+        let chained =
+            seal.is_some() || records.iter().any(|record| record.meta.prev_hash.is_some());
+        let (chain, sequence_break) = if chained {
+            (Some(self.logs.verify(run_id)?), None)
+        } else {
+            (None, sequence_break(&records))
+        };
+
+        let mut checkpoints_checked = 0;
+        let mut checkpoint_errors = Vec::new();
+        for checkpoint_id in self.checkpoints.list(run_id)? {
+            checkpoints_checked += 1;
+            match self.checkpoints.load(run_id, &checkpoint_id) {
+                Ok(record) if record.run_id != run_id => checkpoint_errors.push(format!(
+                    "{}: belongs to run {}",
+                    checkpoint_id, record.run_id
+                )),
+                Ok(_) => {}
+                Err(err) => checkpoint_errors.push(format!("{}: {}", checkpoint_id, err)),
+            }
+        }
+
+        Ok(IntegrityReport {
+            run_id: run_id.to_string(),
+            record_count: records.len(),
+            chained,
+            chain,
+            sequence_break,
+            checkpoints_checked,
+            checkpoint_errors,
+        })
+    }
+
+    /// Collect a run's events, seal, checkpoints, and the sessions and
+    /// attachments its events reference.
+    pub fn export_bundle(&self, run_id: &str) -> std::io::Result<RunBundle> {
+        let mut events = self.logs.load(run_id)?;
+        let mut seal = self.logs.load_seal(run_id)?;
+        let mut checkpoints = Vec::new();
+        for checkpoint_id in self.checkpoints.list(run_id)? {
+            checkpoints.push(self.checkpoints.load(run_id, &checkpoint_id)?);
+        }
+        if events.is_empty() && checkpoints.is_empty() {
+            return Err(std::io::Error::new(
+                ErrorKind::NotFound,
+                format!("run {} has no log or checkpoints", run_id),
+            ));
+        }
+
+        let mut session_ids = BTreeSet::new();
+        let mut references = BTreeSet::new();
+        // Tool that produced each referenced attachment, for redaction.
+        let mut producers = HashMap::new();
+        for record in &events {
+            let value = serde_json::to_value(&record.event)
+                .map_err(|err| std::io::Error::new(ErrorKind::InvalidData, err))?;
+            let mut found = BTreeSet::new();
+            collect_references(&value, &mut session_ids, &mut found);
+            if let Some((tool, _)) = record.event.tool_call() {
+                for reference in &found {
+                    producers
+                        .entry(reference.clone())
+                        .or_insert_with(|| tool.to_string());
+                }
+            }
+            references.extend(found);
+        }
+        for checkpoint in &checkpoints {
+            collect_references(&checkpoint.state, &mut session_ids, &mut references);
+        }
+
+        let mut sessions = Vec::new();
+        for session_id in &session_ids {
+            match self.sessions.load(session_id) {
+                Ok(snapshot) => sessions.push(snapshot),
+                Err(err) if err.kind() == ErrorKind::NotFound => {}
+                Err(err) => return Err(err),
+            }
+        }
+        let mut attachments = Vec::new();
+        for reference in &references {
+            match self.attachments.resolve_reference(reference) {
+                Ok(mut record) => {
+                    if let Some(policy) = &self.redaction {
+                        let tool = producers.get(reference).map(String::as_str);
+                        record.attachment = policy.redact_attachment(&record.attachment, tool);
+                    }
+                    attachments.push(record)
+                }
+                Err(err) if err.kind() == ErrorKind::NotFound => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        if let Some(policy) = &self.redaction {
+            let chained =
+                seal.is_some() || events.iter().any(|record| record.meta.prev_hash.is_some());
+            events = events
+                .iter()
+                .map(|record| policy.redact_record(record))
+                .collect();
+            if chained {
+                events = HashChain::new().link_all(events);
+            }
+            seal = seal.map(|_| AuditSeal::for_records(&events));
+            for checkpoint in &mut checkpoints {
+                policy.redact_stored(&mut checkpoint.state);
+                for interrupt in &mut checkpoint.pending_interrupts {
+                    policy.redact_stored(&mut interrupt.value);
+                    if let Some(state) = &mut interrupt.resume_state {
+                        policy.redact_stored(state);
+                    }
+                }
+                checkpoint
+                    .resume_values
+                    .values_mut()
+                    .for_each(|value| policy.redact_stored(value));
+            }
+            sessions = sessions
+                .iter()
+                .map(|snapshot| policy.redact_snapshot(snapshot))
+                .collect();
+        }
+
+        Ok(RunBundle {
+            version: RUN_BUNDLE_VERSION,
+            run_id: run_id.to_string(),
+            exported_at: chrono::Utc::now().to_rfc3339(),
+            events,
+            seal,
+            checkpoints,
+            sessions,
+            attachments,
+            redacted: self.redaction.is_some(),
+        })
+    }
+
+    /// Export a bundle as pretty-printed JSON at `path`.
+    pub fn write_bundle(&self, run_id: &str, path: impl AsRef<Path>) -> std::io::Result<RunBundle> {
+        let bundle = self.export_bundle(run_id)?;
+        let data = serde_json::to_string_pretty(&bundle)
+            .map_err(|err| std::io::Error::new(ErrorKind::InvalidData, err))?;
+        std::fs::write(path, data)?;
+        Ok(bundle)
+    }
+}
+
+fn sequence_break(records: &[EventRecord]) -> Option<ChainBreak> {
+    records.windows(2).enumerate().find_map(|(index, pair)| {
+        (pair[1].meta.seq <= pair[0].meta.seq).then(|| ChainBreak {
+            index: index + 1,
+            seq: pair[1].meta.seq,
+            reason: format!(
+                "seq {} does not follow {}",
+                pair[1].meta.seq, pair[0].meta.seq
+            ),
+        })
+    })
+}
+
+fn collect_references(
+    value: &serde_json::Value,
+    session_ids: &mut BTreeSet<String>,
+    references: &mut BTreeSet<String>,
+) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, child) in map {
+                if let (Some(session_id), true) = (child.as_str(), key == "session_id") {
+                    session_ids.insert(session_id.to_string());
+                }
+                collect_references(child, session_ids, references);
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                collect_references(item, session_ids, references);
+            }
+        }
+        serde_json::Value::String(text) => {
+            // References may be embedded in prose ("see attachment://abc").
+            for (start, _) in text.match_indices(ATTACHMENT_SCHEME) {
+                let reference = text[start..]
+                    .split(|c: char| c.is_whitespace() || "\"'()<>[],".contains(c))
+                    .next()
+                    .unwrap_or_default();
+                if AttachmentResolver::parse_reference(reference).is_some() {
+                    references.insert(reference.to_string());
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff_values, ChangeKind, RunInspector};
+    use crate::runtime::audit::verify_chain;
+    use crate::runtime::event::{Event, EventMeta, EventRecord};
+    use crate::runtime::message::{Message, MessageRole, Part};
+    use crate::runtime::redact::RedactionPolicy;
+    use crate::runtime::session::{
+        AttachmentRecord, CheckpointRecord, CheckpointStore, FileAttachmentStore, RunLogStore,
+        SessionSnapshot, SessionStore,
+    };
+    use crate::runtime::tool::{AttachmentPayload, ToolAttachment, ToolOutput};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    fn record(seq: u64, event: Event) -> EventRecord {
+        EventRecord::with_meta(
+            event,
+            EventMeta {
+                event_id: format!("e{}", seq),
+                timestamp_ms: seq * 10,
+                seq,
+                ..Default::default()
+            },
+        )
+    }
+
+    fn checkpoint(run_id: &str, id: &str, state: serde_json::Value, at: &str) -> CheckpointRecord {
+        let mut record =
+            CheckpointRecord::new(run_id, id, state, "next", 1, Vec::new(), HashMap::new());
+        record.created_at = at.to_string();
+        record
+    }
+
+    fn seeded_root() -> std::path::PathBuf {
+        let root = std::env::temp_dir().join(format!("forge-inspect-{}", uuid::Uuid::new_v4()));
+        let logs = RunLogStore::new(&root).with_hash_chain();
+        logs.append(
+            "run-1",
+            &record(
+                1,
+                Event::StepStart {
+                    session_id: "s1".to_string(),
+                },
+            ),
+        )
+        .expect("append");
+        logs.append(
+            "run-1",
+            &record(
+                2,
+                Event::TextFinal {
+                    session_id: "s1".to_string(),
+                    message_id: "m1".to_string(),
+                    text: "see attachment://a1".to_string(),
+                },
+            ),
+        )
+        .expect("append");
+
+        let checkpoints = CheckpointStore::new(&root);
+        checkpoints
+            .save(&checkpoint(
+                "run-1",
+                "cp-a",
+                serde_json::json!({"count": 1, "items": ["x"]}),
+                "2026-01-01T00:00:00Z",
+            ))
+            .expect("save");
+        checkpoints
+            .save(&checkpoint(
+                "run-1",
+                "cp-b",
+                serde_json::json!({"count": 2, "items": ["x", "y"], "done": true}),
+                "2026-01-01T00:00:01Z",
+            ))
+            .expect("save");
+
+        let mut snapshot = SessionSnapshot::new("s1");
+        let mut message = Message::new(MessageRole::User);
+        message.parts.push(Part::TextFinal {
+            text: "hello".to_string(),
+        });
+        snapshot.push_message(&message);
+        SessionStore::new(&root).save(&snapshot).expect("save");
+
+        FileAttachmentStore::new(&root)
+            .save(&AttachmentRecord::new(
+                "a1",
+                ToolAttachment {
+                    name: "log.txt".to_string(),
+                    mime_type: "text/plain".to_string(),
+                    size: None,
+                    payload: AttachmentPayload::Inline {
+                        data: serde_json::json!("data"),
+                    },
+                },
+            ))
+            .expect("save");
+        root
+    }
+
+    #[test]
+    fn diff_values_reports_json_pointer_paths() {
+        let changes = diff_values(
+            &serde_json::json!({"a": 1, "b": {"c": [1, 2]}, "gone": true}),
+            &serde_json::json!({"a": 2, "b": {"c": [1]}, "new/key": null}),
+        );
+        let paths: Vec<_> = changes
+            .iter()
+            .map(|change| (change.path.as_str(), change.kind))
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                ("/a", ChangeKind::Changed),
+                ("/b/c/1", ChangeKind::Removed),
+                ("/gone", ChangeKind::Removed),
+                ("/new~1key", ChangeKind::Added),
+            ]
+        );
+    }
+
+    #[test]
+    fn inspector_lists_runs_and_diffs_checkpoints() {
+        let root = seeded_root();
+        let inspector = RunInspector::new(&root);
+
+        let runs = inspector.list_runs().expect("runs");
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].event_count, 2);
+        assert_eq!(runs[0].checkpoint_count, 2);
+
+        let checkpoints = inspector.list_checkpoints("run-1").expect("checkpoints");
+        assert_eq!(checkpoints[0].checkpoint_id, "cp-a");
+
+        let diff = inspector
+            .diff_checkpoints("run-1", "cp-a", "cp-b")
+            .expect("diff");
+        assert_eq!(diff.state.len(), 3);
+        assert!(diff
+            .state
+            .iter()
+            .any(|change| change.path == "/done" && change.kind == ChangeKind::Added));
+
+        let transcript = inspector.render_transcript("s1").expect("transcript");
+        assert!(transcript.contains("[user]\nhello"));
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn inspector_verifies_and_exports_bundle() {
+        let root = seeded_root();
+        let inspector = RunInspector::new(&root);
+
+        let report = inspector.verify("run-1").expect("verify");
+        assert!(report.chained);
+        assert!(report.is_valid());
+        assert_eq!(report.checkpoints_checked, 2);
+
+        let path = root.join("bundle.json");
+        let bundle = inspector.write_bundle("run-1", &path).expect("bundle");
+        assert_eq!(bundle.events.len(), 2);
+        assert_eq!(bundle.checkpoints.len(), 2);
+        assert_eq!(bundle.sessions[0].session_id, "s1");
+        assert_eq!(bundle.attachments[0].attachment_id, "a1");
+        assert!(path.exists());
+
+        let log = root.join("run-1").join("events.jsonl");
+        let tampered = std::fs::read_to_string(&log)
+            .expect("read")
+            .replacen("\"s1\"", "\"s2\"", 1);
+        std::fs::write(&log, tampered).expect("write");
+        assert!(!inspector.verify("run-1").expect("verify").is_valid());
+        assert!(inspector.export_bundle("missing").is_err());
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn inspector_redacted_export_reseals_and_redacts_attachments() {
+        let root = seeded_root();
+        let logs = RunLogStore::new(&root).with_hash_chain();
+        logs.append(
+            "run-1",
+            &record(
+                3,
+                Event::ToolResult {
+                    tool: "vault".to_string(),
+                    call_id: "c1".to_string(),
+                    output: ToolOutput::text("stored as attachment://a2"),
+                },
+            ),
+        )
+        .expect("append");
+        logs.append(
+            "run-1",
+            &record(
+                4,
+                Event::TextFinal {
+                    session_id: "s1".to_string(),
+                    message_id: "m2".to_string(),
+                    text: "contacts in attachment://a3".to_string(),
+                },
+            ),
+        )
+        .expect("append");
+        logs.seal("run-1").expect("seal");
+        let attachments = FileAttachmentStore::new(&root);
+        for (id, data) in [("a2", "hunter2"), ("a3", "mail bob@example.com")] {
+            attachments
+                .save(&AttachmentRecord::new(
+                    id,
+                    ToolAttachment {
+                        name: format!("{}.txt", id),
+                        mime_type: "text/plain".to_string(),
+                        size: None,
+                        payload: AttachmentPayload::Inline {
+                            data: serde_json::json!(data),
+                        },
+                    },
+                ))
+                .expect("save");
+        }
+
+        let policy = RedactionPolicy::new()
+            .with_default_patterns()
+            .with_sensitive_tool("vault");
+        let inspector = RunInspector::new(&root).with_redaction(Arc::new(policy));
+        assert_eq!(inspector.list_runs().expect("runs")[0].event_count, 4);
+
+        let bundle = inspector.export_bundle("run-1").expect("bundle");
+        assert!(bundle.redacted);
+        let report = verify_chain(&bundle.events, bundle.seal.as_ref());
+        assert!(report.sealed);
+        assert!(report.is_valid());
+        let payload = |id: &str| {
+            let record = bundle
+                .attachments
+                .iter()
+                .find(|record| record.attachment_id == id)
+                .expect("attachment exported");
+            match &record.attachment.payload {
+                AttachmentPayload::Inline { data } => data.clone(),
+                other => panic!("unexpected payload {:?}", other),
+            }
+        };
+        assert_eq!(payload("a2"), serde_json::json!("[REDACTED]"));
+        assert_eq!(payload("a3"), serde_json::json!("mail [REDACTED]"));
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn inspector_redacted_export_redacts_checkpoint_interrupts_and_resume_values() {
+        let root = seeded_root();
+        let call = serde_json::json!({
+            "tool": "vault",
+            "call_id": "c1",
+            "input": {"key": "hunter2"},
+        });
+        let request = serde_json::json!({
+            "permission": "tool:vault",
+            "patterns": ["tool:vault"],
+            "metadata": call,
+        });
+        let mut record = checkpoint(
+            "run-1",
+            "cp-c",
+            serde_json::json!({
+                "messages": [{"ToolCall": call}],
+                "config": {"api_key": "k-123"},
+            }),
+            "2026-01-01T00:00:02Z",
+        );
+        record.pending_interrupts.push(
+            crate::runtime::error::Interrupt::new(request, "permission:tool:vault")
+                .with_resume_state(serde_json::json!({"pending": {"calls": [call]}})),
+        );
+        record.resume_values.insert(
+            "agent".to_string(),
+            serde_json::json!({"note": "mail ops@example.com"}),
+        );
+        CheckpointStore::new(&root).save(&record).expect("save");
+
+        let policy = RedactionPolicy::new()
+            .with_default_patterns()
+            .with_sensitive_tool("vault")
+            .with_json_path("config.api_key");
+        let inspector = RunInspector::new(&root).with_redaction(Arc::new(policy));
+        let bundle = inspector.export_bundle("run-1").expect("bundle");
+
+        let exported = bundle
+            .checkpoints
+            .iter()
+            .find(|checkpoint| checkpoint.checkpoint_id == "cp-c")
+            .expect("checkpoint exported");
+        assert_eq!(
+            exported.state["messages"][0]["ToolCall"]["input"],
+            "[REDACTED]"
+        );
+        assert_eq!(exported.state["config"]["api_key"], "[REDACTED]");
+        let interrupt = &exported.pending_interrupts[0];
+        assert_eq!(interrupt.value["metadata"]["input"], "[REDACTED]");
+        assert_eq!(interrupt.value["metadata"]["call_id"], "c1");
+        assert_eq!(
+            interrupt.resume_state.as_ref().unwrap()["pending"]["calls"][0]["input"],
+            "[REDACTED]"
+        );
+        assert_eq!(exported.resume_values["agent"]["note"], "mail [REDACTED]");
+        assert!(!serde_json::to_string(&bundle).unwrap().contains("hunter2"));
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
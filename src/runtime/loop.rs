@@ -1,16 +1,26 @@
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
 
 use crate::runtime::cancel::CancellationToken;
-use crate::runtime::error::{GraphResult, ResumeCommand};
+use crate::runtime::error::{GraphError, GraphResult, Interrupt, ResumeCommand};
 use crate::runtime::event::{Event, EventSink};
+use crate::runtime::executor::{tool_permission_request, ToolExecutor};
 use crate::runtime::message::MessageRole;
-use crate::runtime::node::NodeSpec;
-use crate::runtime::permission::{PermissionGate, PermissionPolicy, PermissionSession};
+use crate::runtime::node::{BoxFuture, NodeSpec};
+use crate::runtime::permission::{
+    PermissionBatch, PermissionDecision, PermissionGate, PermissionPolicy, PermissionSession,
+};
 use crate::runtime::session::FileAttachmentStore;
 use crate::runtime::session_state::SessionState;
 use crate::runtime::state::GraphState;
 use crate::runtime::tool::{AttachmentPolicy, AttachmentStore, ToolCall, ToolOutput, ToolRegistry};
 
+/// Calls [`LoopContext::run_tools`] keeps in flight unless configured.
+pub const DEFAULT_TOOL_CONCURRENCY: usize = 8;
+
 /// LoopContext bundles tool registry + event sink for loop handlers.
 #[derive(Clone)]
 pub struct LoopContext {
@@ -20,6 +30,7 @@ pub struct LoopContext {
     attachment_policy: AttachmentPolicy,
     attachment_store: Option<Arc<dyn AttachmentStore>>,
     cancel: CancellationToken,
+    tool_concurrency: usize,
 }
 
 impl LoopContext {
@@ -53,6 +64,7 @@ impl LoopContext {
             attachment_policy,
             attachment_store: Some(default_attachment_store()),
             cancel: CancellationToken::new(),
+            tool_concurrency: DEFAULT_TOOL_CONCURRENCY,
         }
     }
 
@@ -65,6 +77,20 @@ impl LoopContext {
         self
     }
 
+    /// Limit how many calls [`run_tools`](Self::run_tools) runs at once.
+    pub fn with_tool_concurrency(mut self, limit: usize) -> Self {
+        self.tool_concurrency = limit.max(1);
+        self
+    }
+
+    pub fn sink(&self) -> Arc<dyn EventSink> {
+        Arc::clone(&self.sink)
+    }
+
+    pub fn cancellation_token(&self) -> &CancellationToken {
+        &self.cancel
+    }
+
     pub fn reply_permission(
         &self,
         permission: impl Into<String>,
@@ -91,17 +117,213 @@ impl LoopContext {
         Ok(Some(reply))
     }
 
+    /// Reply to every permission in `permissions` from one resume command,
+    /// e.g. after a [`run_tools`](Self::run_tools) interrupt.
+    pub fn resume_permissions(
+        &self,
+        permissions: &[String],
+        command: &ResumeCommand,
+    ) -> GraphResult<Vec<crate::runtime::event::PermissionReply>> {
+        let mut replies = Vec::new();
+        for (permission, reply) in self.gate.apply_resume_all(permissions, command) {
+            self.emit(Event::PermissionReplied {
+                permission,
+                reply: reply.clone(),
+            })?;
+            replies.push(reply);
+        }
+        Ok(replies)
+    }
+
     pub async fn run_tool(&self, call: ToolCall) -> GraphResult<ToolOutput> {
+        self.executor().run(call).await
+    }
+
+    /// Run several calls concurrently, at most `tool_concurrency` at a time,
+    /// returning one result per call in input order.
+    ///
+    /// Permissions are decided for the whole batch before anything runs. If
+    /// any call needs approval, nothing runs and a single interrupt carrying
+    /// a [`PermissionBatch`] with every pending request is returned. Denied
+    /// and failing calls yield `Err` entries without stopping the others;
+    /// cancellation aborts the batch. Each call's lifecycle events are
+    /// emitted in order, interleaved with the other calls' events.
+    ///
+    /// If a call raises an interrupt, the results of the calls that finished
+    /// are dropped with it; use [`run_tools_partial`](Self::run_tools_partial)
+    /// to keep them so a resume only re-runs the interrupted calls.
+    pub async fn run_tools(
+        &self,
+        calls: Vec<ToolCall>,
+    ) -> GraphResult<Vec<GraphResult<ToolOutput>>> {
+        let batch = self.run_tools_partial(calls).await?;
+        if !batch.interrupts.is_empty() {
+            return Err(GraphError::Interrupted(batch.interrupts));
+        }
+        Ok(batch.results.into_iter().flatten().collect())
+    }
+
+    /// Like [`run_tools`](Self::run_tools), but interrupts raised by the
+    /// calls come back next to the results of the calls that finished.
+    ///
+    /// Permission asks and cancellation are still returned as errors, since
+    /// no call ran (or all were abandoned) in those cases.
+    pub async fn run_tools_partial(&self, calls: Vec<ToolCall>) -> GraphResult<ToolBatch> {
+        if self.cancel.is_cancelled() {
+            return Err(GraphError::Aborted {
+                reason: self.cancel.abort_reason(),
+            });
+        }
+        // Decide each permission once so a single "once" reply covers every
+        // call of that tool in the batch; grants are only used up if the
+        // batch runs.
+        let mut permissions: Vec<String> = Vec::new();
+        for call in &calls {
+            let permission = format!("tool:{}", call.tool);
+            if !permissions.contains(&permission) {
+                permissions.push(permission);
+            }
+        }
+        let decisions: HashMap<String, PermissionDecision> = permissions
+            .iter()
+            .cloned()
+            .zip(self.gate.decide_batch(&permissions))
+            .collect();
+        let asks: Vec<_> = calls
+            .iter()
+            .filter(|call| decisions[&format!("tool:{}", call.tool)] == PermissionDecision::Ask)
+            .map(tool_permission_request)
+            .collect();
+        if !asks.is_empty() {
+            for request in &asks {
+                self.emit(request.to_event())?;
+            }
+            return Err(GraphError::Interrupted(vec![Interrupt::new(
+                PermissionBatch { requests: asks },
+                "permission:batch",
+            )]));
+        }
+
+        let executor = self.executor();
+        let futures = calls
+            .into_iter()
+            .map(|call| {
+                let permission = format!("tool:{}", call.tool);
+                let future: BoxFuture<'_, GraphResult<ToolOutput>> = match decisions[&permission] {
+                    PermissionDecision::Deny => Box::pin(async move {
+                        Err(GraphError::PermissionDenied {
+                            permission,
+                            message: "permission denied".to_string(),
+                        })
+                    }),
+                    _ => Box::pin(executor.run_allowed(call)),
+                };
+                future
+            })
+            .collect();
+        let outputs = JoinLimited::new(futures, self.tool_concurrency).await;
+
+        let mut batch = ToolBatch {
+            results: Vec::with_capacity(outputs.len()),
+            interrupts: Vec::new(),
+        };
+        for output in outputs {
+            match output {
+                Err(err @ GraphError::Aborted { .. }) => return Err(err),
+                Err(GraphError::Interrupted(raised)) => {
+                    batch.interrupts.extend(raised);
+                    batch.results.push(None);
+                }
+                output => batch.results.push(Some(output)),
+            }
+        }
+        Ok(batch)
+    }
+
+    fn executor(&self) -> ToolExecutor {
         let gate: Arc<dyn PermissionGate> = self.gate.clone();
-        let executor = crate::runtime::executor::ToolExecutor::new(
+        ToolExecutor::new(
             Arc::clone(&self.tools),
             gate,
             Arc::clone(&self.sink),
             self.attachment_policy.clone(),
             self.attachment_store.clone(),
             self.cancel.clone(),
-        );
-        executor.run(call).await
+        )
+    }
+}
+
+/// Outcome of [`LoopContext::run_tools_partial`].
+#[derive(Debug)]
+pub struct ToolBatch {
+    /// One entry per call in input order; `None` for calls that raised an
+    /// interrupt and should run again on resume.
+    pub results: Vec<Option<GraphResult<ToolOutput>>>,
+    /// Interrupts raised by the calls that did not finish.
+    pub interrupts: Vec<Interrupt>,
+}
+
+/// Polls up to `limit` futures at a time, collecting outputs in input order.
+struct JoinLimited<'a, T> {
+    queued: VecDeque<(usize, BoxFuture<'a, T>)>,
+    running: Vec<(usize, BoxFuture<'a, T>)>,
+    outputs: Vec<Option<T>>,
+    limit: usize,
+}
+
+impl<'a, T> JoinLimited<'a, T> {
+    fn new(futures: Vec<BoxFuture<'a, T>>, limit: usize) -> Self {
+        let outputs = futures.iter().map(|_| None).collect();
+        Self {
+            queued: futures.into_iter().enumerate().collect(),
+            running: Vec::new(),
+            outputs,
+            limit: limit.max(1),
+        }
+    }
+}
+
+// Outputs are only moved, never pinned; the futures are boxed.
+impl<T> Unpin for JoinLimited<'_, T> {}
+
+impl<T> Future for JoinLimited<'_, T> {
+    type Output = Vec<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Vec<T>> {
+        let this = self.get_mut();
+        loop {
+            while this.running.len() < this.limit {
+                match this.queued.pop_front() {
+                    Some(entry) => this.running.push(entry),
+                    None => break,
+                }
+            }
+            let mut finished = false;
+            let mut index = 0;
+            while index < this.running.len() {
+                match this.running[index].1.as_mut().poll(cx) {
+                    Poll::Ready(output) => {
+                        let (slot, _) = this.running.remove(index);
+                        this.outputs[slot] = Some(output);
+                        finished = true;
+                    }
+                    Poll::Pending => index += 1,
+                }
+            }
+            if this.running.is_empty() && this.queued.is_empty() {
+                let outputs = std::mem::take(&mut this.outputs);
+                return Poll::Ready(
+                    outputs
+                        .into_iter()
+                        .map(|output| output.expect("every future completed"))
+                        .collect(),
+                );
+            }
+            // Start queued calls in the slots that just freed up.
+            if !finished || this.queued.is_empty() {
+                return Poll::Pending;
+            }
+        }
     }
 }
 
@@ -115,6 +337,7 @@ pub struct LoopNode<S: GraphState> {
     gate: Arc<PermissionSession>,
     attachment_policy: AttachmentPolicy,
     cancel: CancellationToken,
+    tool_concurrency: usize,
     handler: Arc<
         dyn Fn(S, LoopContext) -> crate::runtime::node::BoxFuture<'static, GraphResult<S>>
             + Send
@@ -144,6 +367,12 @@ impl<S: GraphState> LoopNode<S> {
         self
     }
 
+    /// Limit for [`LoopContext::run_tools`] in handler contexts.
+    pub fn with_tool_concurrency(mut self, limit: usize) -> Self {
+        self.tool_concurrency = limit.max(1);
+        self
+    }
+
     pub fn with_tools<F, Fut>(name: impl Into<String>, tools: Arc<ToolRegistry>, handler: F) -> Self
     where
         F: Fn(S, LoopContext) -> Fut + Send + Sync + 'static,
@@ -195,6 +424,7 @@ impl<S: GraphState> LoopNode<S> {
             gate,
             attachment_policy,
             cancel: CancellationToken::new(),
+            tool_concurrency: DEFAULT_TOOL_CONCURRENCY,
             handler: Arc::new(move |state, ctx| Box::pin(handler(state, ctx))),
         }
     }
@@ -214,7 +444,8 @@ impl<S: GraphState> LoopNode<S> {
             Arc::clone(&self.gate),
             self.attachment_policy.clone(),
         )
-        .with_cancellation_token(self.cancel.clone());
+        .with_cancellation_token(self.cancel.clone())
+        .with_tool_concurrency(self.tool_concurrency);
         (self.handler)(state, ctx)
     }
 
@@ -251,6 +482,7 @@ impl<S: GraphState> LoopNode<S> {
         let gate = Arc::clone(&self.gate);
         let attachment_policy = self.attachment_policy.clone();
         let cancel = self.cancel.clone();
+        let tool_concurrency = self.tool_concurrency;
         NodeSpec::new_stream(self.name, move |state, sink| {
             let ctx = LoopContext::new_with_gate_and_policy(
                 sink,
@@ -258,7 +490,8 @@ impl<S: GraphState> LoopNode<S> {
                 Arc::clone(&gate),
                 attachment_policy.clone(),
             )
-            .with_cancellation_token(cancel.clone());
+            .with_cancellation_token(cancel.clone())
+            .with_tool_concurrency(tool_concurrency);
             handler(state, ctx)
         })
     }
@@ -302,8 +535,8 @@ impl EventSink for SessionStateSink {
 mod tests {
     use super::*;
     use crate::runtime::error::GraphError;
-    use crate::runtime::event::{Event, EventSink};
-    use crate::runtime::permission::PermissionRequest;
+    use crate::runtime::event::{Event, EventSink, PermissionReply};
+    use crate::runtime::permission::{PermissionBatch, PermissionRequest};
     use crate::runtime::permission::{
         PermissionDecision, PermissionPolicy, PermissionRule, PermissionSession,
     };
@@ -526,6 +759,240 @@ mod tests {
             .any(|event| matches!(event, Event::PermissionReplied { .. })));
     }
 
+    /// Returns `Pending` once so other futures get polled in between.
+    struct YieldOnce(bool);
+
+    impl std::future::Future for YieldOnce {
+        type Output = ();
+
+        fn poll(
+            mut self: std::pin::Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<()> {
+            if self.0 {
+                return std::task::Poll::Ready(());
+            }
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            std::task::Poll::Pending
+        }
+    }
+
+    fn batch_registry(in_flight: Arc<Mutex<(usize, usize)>>) -> Arc<ToolRegistry> {
+        let mut registry = ToolRegistry::new();
+        for name in ["a", "b", "c"] {
+            let in_flight = Arc::clone(&in_flight);
+            registry.register(
+                name,
+                Arc::new(move |call, _ctx| {
+                    let in_flight = Arc::clone(&in_flight);
+                    Box::pin(async move {
+                        {
+                            let mut guard = in_flight.lock().unwrap();
+                            guard.0 += 1;
+                            guard.1 = guard.1.max(guard.0);
+                        }
+                        YieldOnce(false).await;
+                        YieldOnce(false).await;
+                        in_flight.lock().unwrap().0 -= 1;
+                        if call.input["interrupt"] == serde_json::json!(true) {
+                            return Err(GraphError::Interrupted(vec![Interrupt::new(
+                                "confirm",
+                                format!("tool:{}", call.tool),
+                            )]));
+                        }
+                        if call.input["fail"] == serde_json::json!(true) {
+                            return Err(GraphError::ExecutionError {
+                                node: format!("tool:{}", call.tool),
+                                message: "boom".to_string(),
+                            });
+                        }
+                        Ok(ToolOutput::text(call.call_id))
+                    })
+                }),
+            );
+        }
+        Arc::new(registry)
+    }
+
+    #[test]
+    fn run_tools_limits_concurrency_and_collects_partial_failures() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink: Arc<dyn EventSink> = Arc::new(CaptureSink {
+            events: events.clone(),
+        });
+        let in_flight = Arc::new(Mutex::new((0, 0)));
+        let ctx =
+            LoopContext::new(sink, batch_registry(Arc::clone(&in_flight))).with_tool_concurrency(2);
+        let calls = vec![
+            ToolCall::new("a", "c1", serde_json::json!({})),
+            ToolCall::new("b", "c2", serde_json::json!({"fail": true})),
+            ToolCall::new("c", "c3", serde_json::json!({})),
+            ToolCall::new("a", "c4", serde_json::json!({})),
+        ];
+
+        let results = block_on(ctx.run_tools(calls.clone())).expect("batch runs");
+        assert_eq!(results.len(), 4);
+        assert_eq!(
+            results[0].as_ref().unwrap().content,
+            serde_json::json!("c1")
+        );
+        assert!(results[1]
+            .as_ref()
+            .unwrap_err()
+            .to_string()
+            .contains("boom"));
+        assert_eq!(
+            results[3].as_ref().unwrap().content,
+            serde_json::json!("c4")
+        );
+        assert_eq!(in_flight.lock().unwrap().1, 2);
+
+        let captured = events.lock().unwrap();
+        for call in &calls {
+            let lifecycle = captured
+                .iter()
+                .filter(|event| event.tool_call().map(|(_, id)| id) == Some(call.call_id.as_str()))
+                .map(Event::variant_name)
+                .filter(|kind| *kind != "ToolStatus")
+                .collect::<Vec<_>>();
+            assert_eq!(lifecycle.first(), Some(&"ToolStart"), "{}", call.call_id);
+            assert!(matches!(
+                lifecycle.last(),
+                Some(&"ToolResult") | Some(&"ToolError")
+            ));
+        }
+        drop(captured);
+
+        let in_flight = Arc::new(Mutex::new((0, 0)));
+        let sink: Arc<dyn EventSink> = Arc::new(CaptureSink {
+            events: Arc::new(Mutex::new(Vec::new())),
+        });
+        let ctx = LoopContext::new(sink, batch_registry(Arc::clone(&in_flight)));
+        block_on(ctx.run_tools(calls)).expect("batch runs");
+        assert_eq!(in_flight.lock().unwrap().1, 4);
+    }
+
+    #[test]
+    fn run_tools_aggregates_permission_asks_into_one_interrupt() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink: Arc<dyn EventSink> = Arc::new(CaptureSink {
+            events: events.clone(),
+        });
+        let gate = Arc::new(PermissionSession::new(PermissionPolicy::new(vec![
+            PermissionRule::new(
+                PermissionDecision::Ask,
+                vec!["tool:a".to_string(), "tool:b".to_string()],
+            ),
+            PermissionRule::new(PermissionDecision::Deny, vec!["tool:c".to_string()]),
+        ])));
+        let in_flight = Arc::new(Mutex::new((0, 0)));
+        let ctx = LoopContext::new_with_gate(sink, batch_registry(in_flight), gate);
+        let calls = vec![
+            ToolCall::new("a", "c1", serde_json::json!({})),
+            ToolCall::new("b", "c2", serde_json::json!({})),
+            ToolCall::new("a", "c3", serde_json::json!({})),
+            ToolCall::new("c", "c4", serde_json::json!({})),
+        ];
+
+        let batch = match block_on(ctx.run_tools(calls.clone())) {
+            Err(GraphError::Interrupted(interrupts)) => {
+                assert_eq!(interrupts.len(), 1);
+                serde_json::from_value::<PermissionBatch>(interrupts[0].value.clone())
+                    .expect("permission batch")
+            }
+            other => panic!("expected interrupted, got {:?}", other.map(|r| r.len())),
+        };
+        assert_eq!(batch.requests.len(), 3);
+        assert_eq!(batch.permissions(), vec!["tool:a", "tool:b"]);
+        {
+            let captured = events.lock().unwrap();
+            assert_eq!(
+                captured
+                    .iter()
+                    .filter(|event| matches!(event, Event::PermissionAsked { .. }))
+                    .count(),
+                3
+            );
+            assert!(!captured
+                .iter()
+                .any(|event| matches!(event, Event::ToolStart { .. })));
+        }
+
+        let replies = ctx
+            .resume_permissions(&batch.permissions(), &ResumeCommand::new("once"))
+            .expect("replies");
+        assert_eq!(replies.len(), 2);
+        let results = block_on(ctx.run_tools(calls)).expect("batch runs after approval");
+        assert!(results[..3].iter().all(|result| result.is_ok()));
+        assert!(matches!(
+            results[3],
+            Err(GraphError::PermissionDenied { .. })
+        ));
+    }
+
+    #[test]
+    fn run_tools_partial_keeps_results_of_calls_that_finished() {
+        let sink: Arc<dyn EventSink> = Arc::new(CaptureSink {
+            events: Arc::new(Mutex::new(Vec::new())),
+        });
+        let in_flight = Arc::new(Mutex::new((0, 0)));
+        let ctx = LoopContext::new(sink, batch_registry(in_flight));
+        let calls = vec![
+            ToolCall::new("a", "c1", serde_json::json!({})),
+            ToolCall::new("b", "c2", serde_json::json!({"interrupt": true})),
+            ToolCall::new("c", "c3", serde_json::json!({})),
+        ];
+
+        let batch = block_on(ctx.run_tools_partial(calls.clone())).expect("batch runs");
+        assert_eq!(batch.interrupts.len(), 1);
+        assert_eq!(batch.interrupts[0].node, "tool:b");
+        assert_eq!(
+            batch.results[0].as_ref().unwrap().as_ref().unwrap().content,
+            serde_json::json!("c1")
+        );
+        assert!(batch.results[1].is_none());
+        assert!(batch.results[2].as_ref().unwrap().is_ok());
+
+        assert!(matches!(
+            block_on(ctx.run_tools(calls)),
+            Err(GraphError::Interrupted(_))
+        ));
+    }
+
+    #[test]
+    fn run_tools_ask_keeps_once_grants_of_other_tools() {
+        let sink: Arc<dyn EventSink> = Arc::new(CaptureSink {
+            events: Arc::new(Mutex::new(Vec::new())),
+        });
+        let gate = Arc::new(PermissionSession::new(PermissionPolicy::new(vec![
+            PermissionRule::new(
+                PermissionDecision::Ask,
+                vec!["tool:a".to_string(), "tool:b".to_string()],
+            ),
+        ])));
+        let in_flight = Arc::new(Mutex::new((0, 0)));
+        let ctx = LoopContext::new_with_gate(sink, batch_registry(in_flight), Arc::clone(&gate));
+        gate.apply_reply("tool:a", PermissionReply::Once);
+        let calls = vec![
+            ToolCall::new("a", "c1", serde_json::json!({})),
+            ToolCall::new("b", "c2", serde_json::json!({})),
+        ];
+
+        assert!(matches!(
+            block_on(ctx.run_tools(calls.clone())),
+            Err(GraphError::Interrupted(_))
+        ));
+        // A reply arriving while the batch waits is not overwritten.
+        gate.apply_reply("tool:b", PermissionReply::Once);
+        let results = block_on(ctx.run_tools(calls.clone())).expect("both granted");
+        assert!(results.iter().all(|result| result.is_ok()));
+        assert!(matches!(
+            block_on(ctx.run_tools(calls)),
+            Err(GraphError::Interrupted(_))
+        ));
+    }
+
     #[test]
     fn loop_node_updates_session_state_from_events() {
         let events = Arc::new(Mutex::new(Vec::new()));
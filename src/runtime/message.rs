@@ -2,8 +2,10 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::runtime::error::{GraphError, GraphResult};
 use crate::runtime::event::{Event, TokenUsage};
-use crate::runtime::tool::{ToolAttachment, ToolOutput};
+use crate::runtime::session::AttachmentResolver;
+use crate::runtime::tool::{AttachmentPayload, ToolAttachment, ToolOutput};
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum MessageRole {
@@ -99,12 +101,237 @@ pub enum Part {
     TokenUsage {
         usage: TokenUsage,
     },
+    Image {
+        source: MediaSource,
+        mime_type: String,
+        /// Provider resolution hint (OpenAI `low`/`high`/`auto`).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        detail: Option<String>,
+    },
+    Audio {
+        source: MediaSource,
+        mime_type: String,
+    },
+    File {
+        source: MediaSource,
+        mime_type: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+    },
     Error {
         message: String,
     },
 }
 
+/// Where the content of an image, audio or file part comes from.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum MediaSource {
+    /// Base64-encoded bytes.
+    Base64 {
+        data: String,
+    },
+    Url {
+        url: String,
+    },
+    /// An `attachment://` reference resolved through `AttachmentResolver`.
+    Attachment {
+        reference: String,
+    },
+}
+
+/// A media source with attachment references resolved.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResolvedMedia {
+    /// Always `Base64` or `Url`.
+    pub source: MediaSource,
+    /// MIME type recorded with the attachment, if any.
+    pub mime_type: Option<String>,
+}
+
+impl MediaSource {
+    pub fn bytes(bytes: &[u8]) -> Self {
+        MediaSource::Base64 {
+            data: encode_base64(bytes),
+        }
+    }
+
+    pub fn url(url: impl Into<String>) -> Self {
+        MediaSource::Url { url: url.into() }
+    }
+
+    pub fn attachment(reference: impl Into<String>) -> Self {
+        MediaSource::Attachment {
+            reference: reference.into(),
+        }
+    }
+
+    /// `data:` URL for base64 sources, the URL or reference otherwise.
+    pub fn to_url(&self, mime_type: &str) -> String {
+        match self {
+            MediaSource::Base64 { data } => format!("data:{};base64,{}", mime_type, data),
+            MediaSource::Url { url } => url.clone(),
+            MediaSource::Attachment { reference } => reference.clone(),
+        }
+    }
+
+    /// Resolve attachment references into inline data or a URL.
+    ///
+    /// Inline attachment data may be a base64 string, a `data:` or
+    /// `http(s)` URL, or an object with a `base64` or `url` field.
+    pub fn resolve(&self, resolver: Option<&AttachmentResolver>) -> GraphResult<ResolvedMedia> {
+        let reference = match self {
+            MediaSource::Attachment { reference } => reference,
+            other => {
+                return Ok(ResolvedMedia {
+                    source: other.clone(),
+                    mime_type: None,
+                })
+            }
+        };
+        let resolver = resolver.ok_or_else(|| {
+            media_error(format!(
+                "{} needs an attachment resolver to be sent to a model",
+                reference
+            ))
+        })?;
+        let record = resolver
+            .resolve_reference(reference)
+            .map_err(|err| media_error(format!("resolve {} failed: {}", reference, err)))?;
+        let source = match &record.attachment.payload {
+            AttachmentPayload::Inline { data } => media_from_value(data),
+            AttachmentPayload::Reference { reference } => {
+                media_from_value(&serde_json::Value::String(reference.clone()))
+            }
+        }
+        .ok_or_else(|| {
+            media_error(format!(
+                "attachment {} does not hold base64 data or a URL",
+                reference
+            ))
+        })?;
+        Ok(ResolvedMedia {
+            source,
+            mime_type: Some(record.attachment.mime_type),
+        })
+    }
+}
+
+fn media_from_value(value: &serde_json::Value) -> Option<MediaSource> {
+    match value {
+        serde_json::Value::String(text) => {
+            if let Some(rest) = text.strip_prefix("data:") {
+                let (_, data) = rest.split_once(";base64,")?;
+                Some(MediaSource::Base64 {
+                    data: data.to_string(),
+                })
+            } else if text.starts_with("http://") || text.starts_with("https://") {
+                Some(MediaSource::url(text.clone()))
+            } else if !text.is_empty() && !text.contains("://") {
+                Some(MediaSource::Base64 { data: text.clone() })
+            } else {
+                None
+            }
+        }
+        serde_json::Value::Object(fields) => {
+            if let Some(data) = fields.get("base64").and_then(|v| v.as_str()) {
+                Some(MediaSource::Base64 {
+                    data: data.to_string(),
+                })
+            } else {
+                fields
+                    .get("url")
+                    .and_then(|v| v.as_str())
+                    .map(MediaSource::url)
+            }
+        }
+        _ => None,
+    }
+}
+
+fn media_error(message: String) -> GraphError {
+    GraphError::ExecutionError {
+        node: "message:media".to_string(),
+        message,
+    }
+}
+
+fn encode_base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * index) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
 impl Part {
+    pub fn image(source: MediaSource, mime_type: impl Into<String>) -> Self {
+        Part::Image {
+            source,
+            mime_type: mime_type.into(),
+            detail: None,
+        }
+    }
+
+    pub fn audio(source: MediaSource, mime_type: impl Into<String>) -> Self {
+        Part::Audio {
+            source,
+            mime_type: mime_type.into(),
+        }
+    }
+
+    pub fn file(source: MediaSource, mime_type: impl Into<String>, name: Option<String>) -> Self {
+        Part::File {
+            source,
+            mime_type: mime_type.into(),
+            name,
+        }
+    }
+
+    /// Whether this is an image, audio or file part.
+    pub fn is_media(&self) -> bool {
+        matches!(
+            self,
+            Part::Image { .. } | Part::Audio { .. } | Part::File { .. }
+        )
+    }
+
+    /// Copy of the part with any attachment reference resolved.
+    pub fn resolve_media(&self, resolver: Option<&AttachmentResolver>) -> GraphResult<Part> {
+        let mut part = self.clone();
+        if let Part::Image {
+            source, mime_type, ..
+        }
+        | Part::Audio { source, mime_type }
+        | Part::File {
+            source, mime_type, ..
+        } = &mut part
+        {
+            if matches!(source, MediaSource::Attachment { .. }) {
+                let resolved = source.resolve(resolver)?;
+                *source = resolved.source;
+                if mime_type.is_empty() {
+                    if let Some(resolved_mime) = resolved.mime_type {
+                        *mime_type = resolved_mime;
+                    }
+                }
+            }
+        }
+        Ok(part)
+    }
+
     pub fn from_event(event: &Event) -> Option<Self> {
         match event {
             Event::TextDelta { delta, .. } => Some(Part::TextDelta {
@@ -170,9 +397,10 @@ impl Part {
 
 #[cfg(test)]
 mod tests {
-    use super::{Message, MessageRole, Part};
+    use super::{encode_base64, MediaSource, Message, MessageRole, Part};
     use crate::runtime::event::{Event, TokenUsage};
-    use crate::runtime::tool::{ToolAttachment, ToolOutput};
+    use crate::runtime::session::{AttachmentResolver, FileAttachmentStore};
+    use crate::runtime::tool::{AttachmentStore, ToolAttachment, ToolOutput};
 
     #[test]
     fn message_new_initializes_empty_parts_and_metadata() {
@@ -322,6 +550,7 @@ mod tests {
                 cache_write: 5,
             },
             cost: 0.01,
+            model: None,
         };
 
         assert_eq!(
@@ -365,4 +594,53 @@ mod tests {
     fn message_role_from_str_rejects_unknown_roles() {
         assert_eq!(MessageRole::parse("unknown"), None);
     }
+
+    #[test]
+    fn encode_base64_pads_partial_chunks() {
+        assert_eq!(encode_base64(b""), "");
+        assert_eq!(encode_base64(b"f"), "Zg==");
+        assert_eq!(encode_base64(b"fo"), "Zm8=");
+        assert_eq!(encode_base64(b"foo"), "Zm9v");
+        assert_eq!(encode_base64(&[0xff, 0xfe, 0x00, 0x10]), "//4AEA==");
+    }
+
+    #[test]
+    fn media_parts_resolve_attachment_references() {
+        let temp = std::env::temp_dir().join(format!("forge-media-{}", uuid::Uuid::new_v4()));
+        let store = FileAttachmentStore::new(temp.clone());
+        let reference = store
+            .store(&ToolAttachment::inline(
+                "chart.png",
+                "image/png",
+                serde_json::json!("iVBORw0KGgo="),
+            ))
+            .expect("store");
+        let resolver = AttachmentResolver::new(temp.clone());
+
+        let part = Part::image(MediaSource::attachment(&reference), "");
+        let resolved = part.resolve_media(Some(&resolver)).expect("resolve");
+        assert_eq!(
+            resolved,
+            Part::image(
+                MediaSource::Base64 {
+                    data: "iVBORw0KGgo=".to_string()
+                },
+                "image/png"
+            )
+        );
+        assert!(part.resolve_media(None).is_err());
+
+        let url = Part::file(
+            MediaSource::url("https://example.com/a.pdf"),
+            "application/pdf",
+            None,
+        );
+        assert_eq!(url.resolve_media(None).expect("unchanged"), url);
+        let json = serde_json::to_value(&url).expect("serialize");
+        assert_eq!(
+            json["File"]["source"]["Url"]["url"],
+            "https://example.com/a.pdf"
+        );
+        std::fs::remove_dir_all(temp).ok();
+    }
 }
@@ -81,6 +81,12 @@ pub struct RunMetrics {
     pub error: Option<String>,
     /// Timestamp when run started
     pub started_at: String,
+    /// Total model cost in USD across all sessions
+    #[serde(default)]
+    pub total_cost_usd: f64,
+    /// Model cost in USD per session id
+    #[serde(default)]
+    pub session_costs: HashMap<String, f64>,
 }
 
 impl RunMetrics {
@@ -97,6 +103,8 @@ impl RunMetrics {
             execution_path: Vec::new(),
             error: None,
             started_at: chrono::Utc::now().to_rfc3339(),
+            total_cost_usd: 0.0,
+            session_costs: HashMap::new(),
         }
     }
 
@@ -66,6 +66,8 @@
 //! ```
 
 // Core modules
+pub mod agent;
+pub mod audit;
 pub mod branch;
 pub mod cancel;
 pub mod channel;
@@ -76,17 +78,26 @@ pub mod error;
 pub mod event;
 pub mod executor;
 pub mod graph;
+pub mod inspect;
 pub mod r#loop;
 pub mod message;
 pub mod node;
+pub mod otel;
 pub mod output;
 pub mod permission;
 pub mod platform;
+pub mod pricing;
+pub mod prompt;
 pub mod provider;
 pub mod prune;
+pub mod query;
+pub mod redact;
+pub mod schema;
 pub mod session;
 pub mod session_state;
 pub mod state;
+pub mod structured;
+pub mod tokens;
 pub mod tool;
 pub mod toolkit;
 pub mod trace;
@@ -99,37 +110,77 @@ pub mod metrics;
 /// Prelude - commonly used types
 pub mod prelude {
     // Core types
-    pub use crate::runtime::cancel::CancellationToken;
+    pub use crate::runtime::agent::{AgentNode, AgentState};
+    pub use crate::runtime::cancel::{cancellable, CancellationToken};
     pub use crate::runtime::constants::END;
     pub use crate::runtime::error::GraphError;
 
+    pub use crate::runtime::audit::{verify_chain, AuditSeal, ChainBreak, ChainReport, HashChain};
     pub use crate::runtime::builtin_tool_registry;
     pub use crate::runtime::compaction::{CompactionPolicy, CompactionResult};
     pub use crate::runtime::component::{
         register_retriever_tool, ChatModel, ChatRequest, ChatResponse, EmbeddingModel,
-        HashEmbeddingModel, InMemoryRetriever, MockChatModel, RetrievedDocument, Retriever,
+        HashEmbeddingModel, InMemoryRetriever, MockChatModel, ResponseSchema, RetrievedDocument,
+        Retriever, ToolChoice,
     };
     pub use crate::runtime::event::{
-        Event, EventMeta, EventRecord, EventRecordSink, EventSequencer, EventSink,
+        Event, EventContext, EventMeta, EventRecord, EventRecordSink, EventSequencer, EventSink,
         NoopEventRecordSink, NoopEventSink, PermissionReply, TokenUsage, ToolUpdate,
     };
     pub use crate::runtime::executor::{CheckpointDurability, CompiledGraph};
     pub use crate::runtime::graph::StateGraph;
-    pub use crate::runtime::message::{Message, MessageRole, Part};
+    pub use crate::runtime::inspect::{
+        diff_values, ChangeKind, CheckpointDiff, CheckpointSummary, IntegrityReport, RunBundle,
+        RunInspector, RunSummary, ValueChange,
+    };
+    pub use crate::runtime::message::{MediaSource, Message, MessageRole, Part, ResolvedMedia};
+    pub use crate::runtime::otel::{OtlpExporter, OtlpExporterConfig, OtlpTarget};
     pub use crate::runtime::output::{
         JsonLineEventRecordSink, JsonLineEventSink, SseEventRecordSink, SseEventSink,
+        TerminalEventSink,
     };
     pub use crate::runtime::permission::{
-        InMemoryPermissionStore, PermissionDecision, PermissionGate, PermissionPolicy,
-        PermissionRequest, PermissionRule, PermissionSession, PermissionSnapshot, PermissionStore,
+        InMemoryPermissionStore, PermissionBatch, PermissionDecision, PermissionGate,
+        PermissionPolicy, PermissionRequest, PermissionRule, PermissionSession, PermissionSnapshot,
+        PermissionStore,
     };
     pub use crate::runtime::platform::{
         stream_cli_jsonl_events, stream_cli_jsonl_records, stream_sse_events, stream_sse_records,
         stream_to_writer, PlatformOutputFormat, PlatformStreamMode,
     };
+    pub use crate::runtime::pricing::{
+        Budget, CostLedger, ModelPrice, PricedChatModel, PricingRegistry,
+    };
+    pub use crate::runtime::prompt::{
+        Escape, MessageTemplate, PromptLibrary, PromptTemplate, PROMPT_ID_METADATA_KEY,
+        PROMPT_VERSION_METADATA_KEY,
+    };
+    pub use crate::runtime::provider::anthropic::{AnthropicChatModel, AnthropicChatModelConfig};
+    pub use crate::runtime::provider::cassette::{
+        Cassette, CassetteChatModel, CassetteMode, Interaction, RecordedError,
+    };
     pub use crate::runtime::provider::openai::{OpenAiChatModel, OpenAiChatModelConfig};
+    pub use crate::runtime::provider::openai_embeddings::{
+        EmbeddingResponse, OpenAiEmbeddingModel, OpenAiEmbeddingModelConfig,
+    };
+    pub use crate::runtime::provider::retry::{
+        RateLimiter, RetryPolicy, RetryingChatModel, RetryingEmbeddingModel,
+    };
+    pub use crate::runtime::provider::router::{
+        FallbackChatModel, LoadBalanceStrategy, LoadBalancedChatModel, RouterChatModel,
+        SERVED_BY_METADATA_KEY,
+    };
+    pub use crate::runtime::provider::scripted::{ScriptedChatModel, ScriptedTurn};
+    pub use crate::runtime::provider::transport::{
+        BlockingPoolTransport, HttpRequest, HttpResponse, HttpTransport,
+    };
     pub use crate::runtime::prune::{PrunePolicy, PruneResult};
+    pub use crate::runtime::query::{DurationStats, EventQuery, QueryMatch, QuerySummary};
     pub use crate::runtime::r#loop::{LoopContext, LoopNode};
+    pub use crate::runtime::redact::{
+        RedactingEventRecordSink, RedactingEventSink, RedactionPolicy,
+    };
+    pub use crate::runtime::schema::SchemaViolation;
     pub use crate::runtime::session::{
         AttachmentResolver, CheckpointRecord, CheckpointStore, SessionMessage, SessionSnapshot,
         SessionSnapshotIo,
@@ -138,11 +189,18 @@ pub mod prelude {
         RunMetadata, RunStatus, SessionPhase, SessionRouting, SessionState, ToolCallRecord,
         ToolCallStatus,
     };
+    pub use crate::runtime::structured::{StructuredOutput, StructuredResponse};
+    pub use crate::runtime::tokens::{
+        BpeEstimator, FittedRequest, HeuristicEstimator, TokenEstimator,
+    };
     pub use crate::runtime::tool::{
         ToolCall, ToolDefinition, ToolMetadata, ToolOutput, ToolRegistry, ToolRunner,
         ToolSchemaRegistry, ToolState,
     };
-    pub use crate::runtime::trace::{ExecutionTrace, TraceEvent, TraceReplay, TraceSpan};
+    pub use crate::runtime::trace::{
+        ChromeTraceExport, ExecutionTrace, SpanKind, SpanRecorder, TraceEvent, TraceReplay,
+        TraceSpan,
+    };
 
     // Metrics and evaluation
 }
@@ -0,0 +1,674 @@
+//! OpenTelemetry (OTLP/JSON) export for execution traces.
+//!
+//! Converts the spans recorded in [`ExecutionTrace`] into OTLP-shaped
+//! `resourceSpans` and `resourceMetrics` payloads following the GenAI
+//! semantic conventions, and writes them to a file or POSTs them to a
+//! collector's OTLP/HTTP JSON endpoint. Collector exports go through an
+//! [`HttpTransport`], so awaiting one never blocks the executor thread.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::runtime::error::{GraphError, GraphResult};
+use crate::runtime::provider::transport::{default_transport, HttpRequest, HttpTransport};
+use crate::runtime::trace::{ExecutionTrace, SpanKind, TraceSpan};
+
+const SCOPE_NAME: &str = "forge";
+const SPAN_KIND_INTERNAL: u64 = 1;
+const SPAN_KIND_CLIENT: u64 = 3;
+const STATUS_CODE_ERROR: u64 = 2;
+const AGGREGATION_TEMPORALITY_CUMULATIVE: u64 = 2;
+
+/// Where OTLP payloads are delivered.
+#[derive(Clone, Debug, PartialEq)]
+pub enum OtlpTarget {
+    /// Write JSON files: `<dir>/traces.json` and `<dir>/metrics.json`.
+    Directory(PathBuf),
+    /// POST to an OTLP/HTTP collector base URL (e.g. `http://localhost:4318`).
+    Collector(String),
+}
+
+/// OTLP exporter configuration.
+#[derive(Clone, Debug)]
+pub struct OtlpExporterConfig {
+    pub target: OtlpTarget,
+    pub service_name: String,
+    pub trace_id: Option<String>,
+    pub timeout_ms: u64,
+    pub transport: Option<Arc<dyn HttpTransport>>,
+}
+
+impl OtlpExporterConfig {
+    pub fn new(target: OtlpTarget) -> Self {
+        Self {
+            target,
+            service_name: "forge".to_string(),
+            trace_id: None,
+            timeout_ms: 10_000,
+            transport: None,
+        }
+    }
+
+    pub fn with_service_name(mut self, service_name: impl Into<String>) -> Self {
+        self.service_name = service_name.into();
+        self
+    }
+
+    /// Use a fixed 32-hex-digit trace id instead of generating one per export.
+    pub fn with_trace_id(mut self, trace_id: impl Into<String>) -> Self {
+        self.trace_id = Some(trace_id.into());
+        self
+    }
+
+    pub fn with_timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.timeout_ms = timeout_ms;
+        self
+    }
+
+    pub fn with_transport(mut self, transport: Arc<dyn HttpTransport>) -> Self {
+        self.transport = Some(transport);
+        self
+    }
+}
+
+/// Exports [`ExecutionTrace`] spans as OTLP/JSON traces and metrics.
+#[derive(Clone, Debug)]
+pub struct OtlpExporter {
+    config: OtlpExporterConfig,
+    transport: Arc<dyn HttpTransport>,
+}
+
+impl OtlpExporter {
+    pub fn new(config: OtlpExporterConfig) -> Self {
+        let transport = config.transport.clone().unwrap_or_else(default_transport);
+        Self { config, transport }
+    }
+
+    pub fn config(&self) -> &OtlpExporterConfig {
+        &self.config
+    }
+
+    /// Build the OTLP `ExportTraceServiceRequest` JSON body.
+    pub fn traces_payload(&self, trace: &ExecutionTrace) -> serde_json::Value {
+        let trace_id = self
+            .config
+            .trace_id
+            .clone()
+            .unwrap_or_else(|| uuid::Uuid::new_v4().simple().to_string());
+        let spans: Vec<serde_json::Value> = trace
+            .spans
+            .iter()
+            .map(|span| otlp_span(span, &trace_id))
+            .collect();
+        serde_json::json!({
+            "resourceSpans": [{
+                "resource": self.resource(),
+                "scopeSpans": [{
+                    "scope": { "name": SCOPE_NAME },
+                    "spans": spans,
+                }],
+            }],
+        })
+    }
+
+    /// Build the OTLP `ExportMetricsServiceRequest` JSON body.
+    ///
+    /// Emits `gen_ai.client.token.usage` (sum by `gen_ai.token.type`) and
+    /// `gen_ai.client.operation.duration` (histogram by operation, seconds).
+    pub fn metrics_payload(&self, trace: &ExecutionTrace) -> serde_json::Value {
+        let time = trace.spans.iter().map(TraceSpan::end_ms).max().unwrap_or(0);
+        let start = trace
+            .spans
+            .iter()
+            .map(|span| span.start_ms)
+            .min()
+            .unwrap_or(0);
+
+        let mut token_totals: Vec<(&str, u64)> = vec![
+            ("input", 0),
+            ("output", 0),
+            ("reasoning", 0),
+            ("cache_read", 0),
+            ("cache_write", 0),
+        ];
+        let mut durations: Vec<(&'static str, u64, f64)> = Vec::new();
+        for span in &trace.spans {
+            if let Some(tokens) = span.attributes.get("tokens") {
+                for (kind, total) in token_totals.iter_mut() {
+                    *total += tokens.get(*kind).and_then(|v| v.as_u64()).unwrap_or(0);
+                }
+            }
+            let operation = operation_name(span.kind);
+            let seconds = span.duration_ms as f64 / 1000.0;
+            match durations.iter_mut().find(|(op, _, _)| *op == operation) {
+                Some((_, count, sum)) => {
+                    *count += 1;
+                    *sum += seconds;
+                }
+                None => durations.push((operation, 1, seconds)),
+            }
+        }
+
+        let token_points: Vec<serde_json::Value> = token_totals
+            .iter()
+            .map(|(kind, total)| {
+                serde_json::json!({
+                    "attributes": [attribute("gen_ai.token.type", &serde_json::json!(kind))],
+                    "startTimeUnixNano": unix_nanos(start),
+                    "timeUnixNano": unix_nanos(time),
+                    "asInt": total.to_string(),
+                })
+            })
+            .collect();
+        let duration_points: Vec<serde_json::Value> = durations
+            .iter()
+            .map(|(operation, count, sum)| {
+                serde_json::json!({
+                    "attributes": [attribute("gen_ai.operation.name", &serde_json::json!(operation))],
+                    "startTimeUnixNano": unix_nanos(start),
+                    "timeUnixNano": unix_nanos(time),
+                    "count": count.to_string(),
+                    "sum": sum,
+                    "bucketCounts": [count.to_string()],
+                    "explicitBounds": [],
+                })
+            })
+            .collect();
+
+        serde_json::json!({
+            "resourceMetrics": [{
+                "resource": self.resource(),
+                "scopeMetrics": [{
+                    "scope": { "name": SCOPE_NAME },
+                    "metrics": [
+                        {
+                            "name": "gen_ai.client.token.usage",
+                            "unit": "{token}",
+                            "sum": {
+                                "dataPoints": token_points,
+                                "aggregationTemporality": AGGREGATION_TEMPORALITY_CUMULATIVE,
+                                "isMonotonic": true,
+                            },
+                        },
+                        {
+                            "name": "gen_ai.client.operation.duration",
+                            "unit": "s",
+                            "histogram": {
+                                "dataPoints": duration_points,
+                                "aggregationTemporality": AGGREGATION_TEMPORALITY_CUMULATIVE,
+                            },
+                        },
+                    ],
+                }],
+            }],
+        })
+    }
+
+    /// Export traces and metrics to the configured target.
+    ///
+    /// Fails without writing anything if the configured trace id or any
+    /// span or parent span id is not a valid OTLP id.
+    pub async fn export(&self, trace: &ExecutionTrace) -> GraphResult<()> {
+        self.validate_ids(trace)?;
+        let traces = self.traces_payload(trace);
+        let metrics = self.metrics_payload(trace);
+        match &self.config.target {
+            OtlpTarget::Directory(dir) => {
+                std::fs::create_dir_all(dir)
+                    .map_err(|err| otlp_error(format!("create dir failed: {}", err)))?;
+                write_json(&dir.join("traces.json"), &traces)?;
+                write_json(&dir.join("metrics.json"), &metrics)
+            }
+            OtlpTarget::Collector(endpoint) => {
+                let base = endpoint.trim_end_matches('/');
+                self.post(format!("{}/v1/traces", base), traces).await?;
+                self.post(format!("{}/v1/metrics", base), metrics).await
+            }
+        }
+    }
+
+    fn validate_ids(&self, trace: &ExecutionTrace) -> GraphResult<()> {
+        if let Some(trace_id) = &self.config.trace_id {
+            if !is_trace_id(trace_id) {
+                return Err(otlp_error(format!(
+                    "trace id must be 32 lowercase hex digits, got {:?}",
+                    trace_id
+                )));
+            }
+        }
+        for span in &trace.spans {
+            for id in [&span.span_id, &span.parent_span_id].into_iter().flatten() {
+                if !is_span_id(id) {
+                    return Err(otlp_error(format!(
+                        "span id must be 16 lowercase hex digits, got {:?} on node {}",
+                        id, span.node
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn post(&self, url: String, payload: serde_json::Value) -> GraphResult<()> {
+        let request = HttpRequest::new(url, payload)
+            .with_header("Content-Type", "application/json")
+            .with_timeout_ms(self.config.timeout_ms)
+            .with_read_timeout_ms(self.config.timeout_ms);
+        let response = self
+            .transport
+            .post(request)
+            .await
+            .map_err(|err| otlp_error(format!("collector request failed: {}", err)))?;
+        if (200..300).contains(&response.status) {
+            return Ok(());
+        }
+        let status = response.status;
+        let body = response.text().await.unwrap_or_default();
+        Err(otlp_error(format!(
+            "collector rejected export with status {}: {}",
+            status, body
+        )))
+    }
+
+    fn resource(&self) -> serde_json::Value {
+        serde_json::json!({
+            "attributes": [
+                attribute("service.name", &serde_json::json!(self.config.service_name)),
+                attribute("telemetry.sdk.name", &serde_json::json!(SCOPE_NAME)),
+            ],
+        })
+    }
+}
+
+fn otlp_span(span: &TraceSpan, trace_id: &str) -> serde_json::Value {
+    let span_id = span
+        .span_id
+        .clone()
+        .unwrap_or_else(|| uuid::Uuid::new_v4().simple().to_string()[..16].to_string());
+    let mut attributes = vec![
+        attribute("forge.span.kind", &serde_json::json!(span.kind.as_str())),
+        attribute("forge.node", &serde_json::json!(span.node)),
+    ];
+    let name = match span.kind {
+        SpanKind::Model => {
+            attributes.push(attribute(
+                "gen_ai.operation.name",
+                &serde_json::json!("chat"),
+            ));
+            if let Some(model) = span.attributes.get("model") {
+                attributes.push(attribute("gen_ai.request.model", model));
+            }
+            if let Some(tokens) = span.attributes.get("tokens") {
+                for (field, key) in [
+                    ("input", "gen_ai.usage.input_tokens"),
+                    ("output", "gen_ai.usage.output_tokens"),
+                    ("reasoning", "gen_ai.usage.reasoning_tokens"),
+                    ("cache_read", "gen_ai.usage.cache_read_tokens"),
+                    ("cache_write", "gen_ai.usage.cache_write_tokens"),
+                ] {
+                    if let Some(value) = tokens.get(field) {
+                        attributes.push(attribute(key, value));
+                    }
+                }
+            }
+            match span.attributes.get("model").and_then(|m| m.as_str()) {
+                Some(model) => format!("chat {}", model),
+                None => "chat".to_string(),
+            }
+        }
+        SpanKind::Tool => {
+            attributes.push(attribute(
+                "gen_ai.operation.name",
+                &serde_json::json!("execute_tool"),
+            ));
+            attributes.push(attribute("gen_ai.tool.name", &serde_json::json!(span.name)));
+            if let Some(call_id) = span.attributes.get("call_id") {
+                attributes.push(attribute("gen_ai.tool.call.id", call_id));
+            }
+            format!("execute_tool {}", span.name)
+        }
+        _ => span.label().to_string(),
+    };
+    for (key, value) in &span.attributes {
+        if matches!(key.as_str(), "tokens" | "call_id" | "model" | "error") {
+            continue;
+        }
+        attributes.push(attribute(&format!("forge.{}", key), value));
+    }
+
+    let mut otlp = serde_json::json!({
+        "traceId": trace_id,
+        "spanId": span_id,
+        "name": name,
+        "kind": if span.kind == SpanKind::Model { SPAN_KIND_CLIENT } else { SPAN_KIND_INTERNAL },
+        "startTimeUnixNano": unix_nanos(span.start_ms),
+        "endTimeUnixNano": unix_nanos(span.end_ms()),
+        "attributes": attributes,
+        "status": {},
+    });
+    if let Some(parent) = &span.parent_span_id {
+        otlp["parentSpanId"] = serde_json::json!(parent);
+    }
+    if let Some(error) = span.attributes.get("error") {
+        otlp["status"] = serde_json::json!({
+            "code": STATUS_CODE_ERROR,
+            "message": error.as_str().map(str::to_string).unwrap_or_else(|| error.to_string()),
+        });
+    }
+    otlp
+}
+
+fn operation_name(kind: SpanKind) -> &'static str {
+    match kind {
+        SpanKind::Model => "chat",
+        SpanKind::Tool => "execute_tool",
+        SpanKind::Node => "node",
+        SpanKind::Permission => "permission",
+        SpanKind::Compaction => "compaction",
+    }
+}
+
+/// OTLP/JSON `KeyValue` with a typed `AnyValue`.
+fn attribute(key: &str, value: &serde_json::Value) -> serde_json::Value {
+    serde_json::json!({ "key": key, "value": any_value(value) })
+}
+
+fn any_value(value: &serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Bool(b) => serde_json::json!({ "boolValue": b }),
+        serde_json::Value::Number(n) if n.is_i64() || n.is_u64() => {
+            serde_json::json!({ "intValue": n.to_string() })
+        }
+        serde_json::Value::Number(n) => serde_json::json!({ "doubleValue": n.as_f64() }),
+        serde_json::Value::String(s) => serde_json::json!({ "stringValue": s }),
+        other => serde_json::json!({ "stringValue": other.to_string() }),
+    }
+}
+
+fn unix_nanos(ms: u64) -> String {
+    (ms as u128 * 1_000_000).to_string()
+}
+
+/// Whether `id` is a valid OTLP span id: 16 lowercase hex digits, not all zero.
+pub fn is_span_id(id: &str) -> bool {
+    is_hex_id(id, 16)
+}
+
+/// Whether `id` is a valid OTLP trace id: 32 lowercase hex digits, not all zero.
+pub fn is_trace_id(id: &str) -> bool {
+    is_hex_id(id, 32)
+}
+
+fn is_hex_id(id: &str, len: usize) -> bool {
+    id.len() == len
+        && id.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+        && id.bytes().any(|b| b != b'0')
+}
+
+fn write_json(path: &std::path::Path, value: &serde_json::Value) -> GraphResult<()> {
+    let data = serde_json::to_string(value)
+        .map_err(|err| otlp_error(format!("serialize failed: {}", err)))?;
+    std::fs::write(path, data).map_err(|err| otlp_error(format!("write failed: {}", err)))
+}
+
+fn otlp_error(message: impl Into<String>) -> GraphError {
+    GraphError::ExecutionError {
+        node: "exporter:otlp".to_string(),
+        message: message.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OtlpExporter, OtlpExporterConfig, OtlpTarget};
+    use crate::runtime::error::GraphResult;
+    use crate::runtime::node::BoxFuture;
+    use crate::runtime::provider::transport::{HttpBody, HttpRequest, HttpResponse, HttpTransport};
+    use crate::runtime::trace::{ExecutionTrace, SpanKind, TraceSpan};
+    use futures::executor::block_on;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    /// Answers every request with a fixed status and records the URLs.
+    #[derive(Debug)]
+    struct FixedTransport {
+        status: u16,
+        urls: Mutex<Vec<String>>,
+    }
+
+    impl HttpTransport for FixedTransport {
+        fn post(&self, request: HttpRequest) -> BoxFuture<'static, GraphResult<HttpResponse>> {
+            self.urls.lock().unwrap().push(request.url);
+            let status = self.status;
+            Box::pin(async move {
+                Ok(HttpResponse::new(
+                    status,
+                    Vec::new(),
+                    HttpBody::from_text("overloaded"),
+                ))
+            })
+        }
+    }
+
+    fn sample_trace() -> ExecutionTrace {
+        let mut trace = ExecutionTrace::new();
+        trace.record_span(
+            TraceSpan::new("agent", 1_000, 40)
+                .with_kind(SpanKind::Model)
+                .with_name("model")
+                .with_span_id("00000000000000b1")
+                .with_parent_span_id("00000000000000a1")
+                .with_attribute("model", serde_json::json!("gpt-4o"))
+                .with_attribute(
+                    "tokens",
+                    serde_json::json!({"input": 10, "output": 5, "reasoning": 0, "cache_read": 2, "cache_write": 0}),
+                ),
+        );
+        trace.record_span(
+            TraceSpan::new("agent", 1_010, 5)
+                .with_kind(SpanKind::Tool)
+                .with_name("grep")
+                .with_span_id("00000000000000c1")
+                .with_attribute("call_id", serde_json::json!("call-1"))
+                .with_attribute("error", serde_json::json!("boom")),
+        );
+        trace.record_span(TraceSpan::new("agent", 1_000, 50).with_span_id("00000000000000a1"));
+        trace
+    }
+
+    fn find_attr<'a>(span: &'a serde_json::Value, key: &str) -> Option<&'a serde_json::Value> {
+        span["attributes"]
+            .as_array()?
+            .iter()
+            .find(|attr| attr["key"] == key)
+            .map(|attr| &attr["value"])
+    }
+
+    #[test]
+    fn traces_payload_follows_genai_conventions() {
+        let exporter = OtlpExporter::new(
+            OtlpExporterConfig::new(OtlpTarget::Directory("unused".into()))
+                .with_trace_id("0123456789abcdef0123456789abcdef"),
+        );
+        let payload = exporter.traces_payload(&sample_trace());
+        let spans = payload["resourceSpans"][0]["scopeSpans"][0]["spans"]
+            .as_array()
+            .expect("spans");
+        assert_eq!(spans.len(), 3);
+
+        let model = &spans[0];
+        assert_eq!(model["name"], "chat gpt-4o");
+        assert_eq!(model["traceId"], "0123456789abcdef0123456789abcdef");
+        assert_eq!(model["parentSpanId"], "00000000000000a1");
+        assert_eq!(model["startTimeUnixNano"], "1000000000");
+        assert_eq!(model["endTimeUnixNano"], "1040000000");
+        assert_eq!(
+            find_attr(model, "gen_ai.usage.input_tokens"),
+            Some(&serde_json::json!({"intValue": "10"}))
+        );
+        assert_eq!(
+            find_attr(model, "gen_ai.request.model"),
+            Some(&serde_json::json!({"stringValue": "gpt-4o"}))
+        );
+
+        let tool = &spans[1];
+        assert_eq!(tool["name"], "execute_tool grep");
+        assert_eq!(
+            find_attr(tool, "gen_ai.tool.call.id"),
+            Some(&serde_json::json!({"stringValue": "call-1"}))
+        );
+        assert_eq!(tool["status"]["code"], 2);
+        assert_eq!(tool["status"]["message"], "boom");
+
+        assert_eq!(spans[2]["name"], "agent");
+        assert!(spans[2].get("parentSpanId").is_none());
+        assert_eq!(
+            payload["resourceSpans"][0]["resource"]["attributes"][0]["value"]["stringValue"],
+            "forge"
+        );
+    }
+
+    #[test]
+    fn metrics_payload_sums_token_usage() {
+        let exporter = OtlpExporter::new(OtlpExporterConfig::new(OtlpTarget::Directory(
+            "unused".into(),
+        )));
+        let payload = exporter.metrics_payload(&sample_trace());
+        let metrics = payload["resourceMetrics"][0]["scopeMetrics"][0]["metrics"]
+            .as_array()
+            .expect("metrics");
+        let usage = &metrics[0];
+        assert_eq!(usage["name"], "gen_ai.client.token.usage");
+        let points = usage["sum"]["dataPoints"].as_array().expect("points");
+        let input = points
+            .iter()
+            .find(|point| point["attributes"][0]["value"]["stringValue"] == "input")
+            .expect("input point");
+        assert_eq!(input["asInt"], "10");
+
+        let duration = &metrics[1];
+        let points = duration["histogram"]["dataPoints"]
+            .as_array()
+            .expect("points");
+        assert_eq!(points.len(), 3);
+    }
+
+    #[test]
+    fn export_writes_files_to_directory() {
+        let dir = std::env::temp_dir().join(format!("forge-otlp-{}", uuid::Uuid::new_v4()));
+        let exporter =
+            OtlpExporter::new(OtlpExporterConfig::new(OtlpTarget::Directory(dir.clone())));
+        block_on(exporter.export(&sample_trace())).expect("export");
+
+        let traces: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(dir.join("traces.json")).unwrap())
+                .unwrap();
+        assert!(traces["resourceSpans"].is_array());
+        assert!(dir.join("metrics.json").exists());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn export_posts_to_collector() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let addr = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let mut paths = Vec::new();
+            for _ in 0..2 {
+                let (mut stream, _) = listener.accept().expect("accept");
+                let mut buf = vec![0u8; 64 * 1024];
+                let mut request = String::new();
+                loop {
+                    let n = stream.read(&mut buf).expect("read");
+                    request.push_str(&String::from_utf8_lossy(&buf[..n]));
+                    if let Some(header_end) = request.find("\r\n\r\n") {
+                        let length = request[..header_end]
+                            .lines()
+                            .find_map(|line| {
+                                let lower = line.to_ascii_lowercase();
+                                lower
+                                    .strip_prefix("content-length:")
+                                    .map(|v| v.trim().parse::<usize>().unwrap())
+                            })
+                            .unwrap_or(0);
+                        if request.len() >= header_end + 4 + length {
+                            break;
+                        }
+                    }
+                    if n == 0 {
+                        break;
+                    }
+                }
+                paths.push(request.lines().next().unwrap_or_default().to_string());
+                stream
+                    .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n{}")
+                    .unwrap();
+            }
+            paths
+        });
+
+        let exporter = OtlpExporter::new(OtlpExporterConfig::new(OtlpTarget::Collector(format!(
+            "http://{}",
+            addr
+        ))));
+        block_on(exporter.export(&sample_trace())).expect("export");
+
+        let paths = server.join().unwrap();
+        assert_eq!(paths[0], "POST /v1/traces HTTP/1.1");
+        assert_eq!(paths[1], "POST /v1/metrics HTTP/1.1");
+    }
+
+    #[test]
+    fn export_goes_through_the_configured_transport() {
+        let transport = Arc::new(FixedTransport {
+            status: 503,
+            urls: Mutex::new(Vec::new()),
+        });
+        let exporter = OtlpExporter::new(
+            OtlpExporterConfig::new(OtlpTarget::Collector("http://collector:4318/".into()))
+                .with_transport(transport.clone()),
+        );
+
+        let err = block_on(exporter.export(&sample_trace())).expect_err("rejected");
+
+        assert!(err.to_string().contains("status 503: overloaded"), "{err}");
+        assert_eq!(
+            *transport.urls.lock().unwrap(),
+            vec!["http://collector:4318/v1/traces".to_string()]
+        );
+    }
+
+    #[test]
+    fn export_rejects_malformed_trace_ids() {
+        let dir = std::env::temp_dir().join(format!("forge-otlp-{}", uuid::Uuid::new_v4()));
+        let config = OtlpExporterConfig::new(OtlpTarget::Directory(dir.clone()));
+        for id in [
+            "0123456789abcdef",
+            "0123456789ABCDEF0123456789ABCDEF",
+            "00000000000000000000000000000000",
+        ] {
+            let exporter = OtlpExporter::new(config.clone().with_trace_id(id));
+            let err = block_on(exporter.export(&sample_trace())).expect_err(id);
+            assert!(err.to_string().contains("trace id"), "{err}");
+        }
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn export_rejects_malformed_span_ids() {
+        let dir = std::env::temp_dir().join(format!("forge-otlp-{}", uuid::Uuid::new_v4()));
+        let exporter =
+            OtlpExporter::new(OtlpExporterConfig::new(OtlpTarget::Directory(dir.clone())));
+        let mut trace = sample_trace();
+        trace.record_span(TraceSpan::new("child", 1_020, 5).with_parent_span_id("outer-span"));
+
+        let err = block_on(exporter.export(&trace)).expect_err("malformed parent");
+
+        assert!(
+            err.to_string().contains("\"outer-span\" on node child"),
+            "{err}"
+        );
+        assert!(!dir.exists());
+    }
+}
@@ -4,7 +4,9 @@ use std::io::Write;
 use std::sync::Mutex;
 
 use crate::runtime::error::{GraphError, GraphResult};
-use crate::runtime::event::{Event, EventRecord, EventRecordSink, EventSink};
+use crate::runtime::event::{
+    Event, EventRecord, EventRecordSink, EventSink, PermissionReply, TokenUsage, ToolUpdate,
+};
 
 /// JSON Lines output for Event stream (CLI-friendly).
 pub struct JsonLineEventSink<W: Write + Send> {
@@ -146,11 +148,364 @@ impl<W: Write + Send> EventRecordSink for SseEventRecordSink<W> {
     }
 }
 
+/// Human-readable terminal output for Event stream (local development).
+///
+/// Streams `TextDelta` inline, prints one-line tool call summaries,
+/// highlights permission prompts and phase transitions, and accumulates
+/// token/cost totals for [`TerminalEventSink::finish`]. Colour is off unless
+/// enabled with `with_color` or detected by [`TerminalEventSink::stdout`].
+pub struct TerminalEventSink<W: Write + Send> {
+    state: Mutex<TerminalState<W>>,
+    color: bool,
+    summary_width: usize,
+    expand: bool,
+}
+
+struct TerminalState<W> {
+    writer: W,
+    streaming: bool,
+    steps: u64,
+    tool_calls: u64,
+    tool_errors: u64,
+    tokens: TokenUsage,
+    cost: f64,
+    finished: bool,
+}
+
+const ANSI_RESET: &str = "\x1b[0m";
+const ANSI_BOLD: &str = "\x1b[1m";
+const ANSI_DIM: &str = "\x1b[2m";
+const ANSI_RED: &str = "\x1b[31m";
+const ANSI_GREEN: &str = "\x1b[32m";
+const ANSI_YELLOW: &str = "\x1b[33m";
+const ANSI_CYAN: &str = "\x1b[36m";
+
+impl TerminalEventSink<std::io::Stdout> {
+    /// Terminal sink on stdout; colour is enabled only for a TTY without
+    /// `NO_COLOR` set.
+    pub fn stdout() -> Self {
+        use std::io::IsTerminal;
+
+        let stdout = std::io::stdout();
+        let color = stdout.is_terminal() && std::env::var_os("NO_COLOR").is_none();
+        Self::new(stdout).with_color(color)
+    }
+}
+
+impl<W: Write + Send> TerminalEventSink<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            state: Mutex::new(TerminalState {
+                writer,
+                streaming: false,
+                steps: 0,
+                tool_calls: 0,
+                tool_errors: 0,
+                tokens: TokenUsage::default(),
+                cost: 0.0,
+                finished: false,
+            }),
+            color: false,
+            summary_width: 80,
+            expand: false,
+        }
+    }
+
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Maximum characters shown for collapsed tool input/output summaries.
+    pub fn with_summary_width(mut self, width: usize) -> Self {
+        self.summary_width = width;
+        self
+    }
+
+    /// Print full pretty-printed tool input/output instead of summaries.
+    pub fn with_expanded_tools(mut self, expand: bool) -> Self {
+        self.expand = expand;
+        self
+    }
+
+    /// Print the token/cost summary. Also runs on `RunCompleted`,
+    /// `RunFailed`, and `RunAborted`; later calls are no-ops.
+    pub fn finish(&self) -> GraphResult<()> {
+        let mut state = self.state.lock().expect("poisoned writer");
+        if state.finished {
+            return Ok(());
+        }
+        state.finished = true;
+        let mut out = String::new();
+        if state.streaming {
+            out.push('\n');
+            state.streaming = false;
+        }
+        let tokens = &state.tokens;
+        out.push_str(&format!(
+            "{} {} steps, {} tool calls ({} failed), tokens in {} / out {}",
+            self.paint(ANSI_BOLD, "── summary:"),
+            state.steps,
+            state.tool_calls,
+            state.tool_errors,
+            tokens.input,
+            tokens.output,
+        ));
+        if tokens.reasoning > 0 {
+            out.push_str(&format!(" / reasoning {}", tokens.reasoning));
+        }
+        if tokens.cache_read > 0 || tokens.cache_write > 0 {
+            out.push_str(&format!(
+                " / cache r{} w{}",
+                tokens.cache_read, tokens.cache_write
+            ));
+        }
+        out.push_str(&format!(", cost ${:.4}\n", state.cost));
+        write_terminal(&mut state.writer, &out)
+    }
+
+    pub fn into_inner(self) -> W {
+        self.state.into_inner().expect("poisoned writer").writer
+    }
+
+    fn paint(&self, style: &str, text: &str) -> String {
+        if self.color {
+            format!("{style}{text}{ANSI_RESET}")
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn summarize(&self, value: &serde_json::Value) -> String {
+        if self.expand {
+            let pretty = serde_json::to_string_pretty(value).unwrap_or_default();
+            return format!("\n{}", indent(&pretty));
+        }
+        let text = match value {
+            serde_json::Value::String(text) => text.clone(),
+            other => other.to_string(),
+        };
+        let line = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        let collapsed = if line.chars().count() > self.summary_width {
+            let cut: String = line.chars().take(self.summary_width).collect();
+            format!("{cut}…")
+        } else {
+            line
+        };
+        format!(" {}", self.paint(ANSI_DIM, &collapsed))
+    }
+
+    fn render(&self, event: &Event) -> Option<String> {
+        let line = match event {
+            Event::RunStarted { run_id, .. } => self.paint(ANSI_BOLD, &format!("▶ run {run_id}")),
+            Event::RunPaused {
+                run_id,
+                checkpoint_id,
+            } => self.paint(
+                ANSI_YELLOW,
+                &format!("⏸ run {run_id} paused at {checkpoint_id}"),
+            ),
+            Event::RunResumed { run_id, .. } => {
+                self.paint(ANSI_BOLD, &format!("▶ run {run_id} resumed"))
+            }
+            Event::RunCompleted { run_id, .. } => {
+                self.paint(ANSI_GREEN, &format!("✔ run {run_id} completed"))
+            }
+            Event::RunFailed { run_id, error } => {
+                self.paint(ANSI_RED, &format!("✘ run {run_id} failed: {error}"))
+            }
+            Event::RunAborted { run_id, reason } => {
+                self.paint(ANSI_RED, &format!("✘ run {run_id} aborted: {reason}"))
+            }
+            Event::TextFinal { .. } | Event::TextDelta { .. } | Event::ToolStatus { .. } => {
+                return None
+            }
+            Event::Attachment {
+                name, mime_type, ..
+            } => format!("attachment {name} ({mime_type})"),
+            Event::Error { message, .. } => self.paint(ANSI_RED, &format!("error: {message}")),
+            Event::ToolStart { tool, input, .. } => format!(
+                "{}{}",
+                self.paint(ANSI_CYAN, &format!("→ {tool}")),
+                self.summarize(input)
+            ),
+            Event::ToolUpdate { tool, update, .. } => match update {
+                ToolUpdate::OutputDelta { delta, .. } => {
+                    format!("  {} {}", self.paint(ANSI_DIM, tool), delta.trim_end())
+                }
+                ToolUpdate::Progress {
+                    current,
+                    total,
+                    message,
+                    ..
+                } => {
+                    let total = total.map(|total| format!("/{total}")).unwrap_or_default();
+                    let message = message.as_deref().unwrap_or("");
+                    format!(
+                        "  {} {current}{total} {message}",
+                        self.paint(ANSI_DIM, tool)
+                    )
+                    .trim_end()
+                    .to_string()
+                }
+                _ => return None,
+            },
+            Event::ToolResult { tool, output, .. } => format!(
+                "{}{}",
+                self.paint(ANSI_GREEN, &format!("← {tool}")),
+                self.summarize(&output.content)
+            ),
+            Event::ToolAttachment {
+                tool, attachment, ..
+            } => format!(
+                "  {} attachment {}",
+                self.paint(ANSI_DIM, tool),
+                attachment.name
+            ),
+            Event::ToolError { tool, error, .. } => {
+                self.paint(ANSI_RED, &format!("✘ {tool}: {error}"))
+            }
+            Event::StepStart { .. } => return None,
+            Event::StepFinish { tokens, cost, .. } => self.paint(
+                ANSI_DIM,
+                &format!(
+                    "· step: {} in / {} out, ${:.4}",
+                    tokens.input, tokens.output, cost
+                ),
+            ),
+            Event::PermissionAsked {
+                permission,
+                patterns,
+                ..
+            } => {
+                let mut line = self.paint(
+                    &format!("{ANSI_BOLD}{ANSI_YELLOW}"),
+                    &format!("? permission required: {permission}"),
+                );
+                for pattern in patterns {
+                    line.push_str(&format!("\n    {pattern}"));
+                }
+                line
+            }
+            Event::PermissionReplied { permission, reply } => {
+                let style = if matches!(reply, PermissionReply::Reject) {
+                    ANSI_RED
+                } else {
+                    ANSI_GREEN
+                };
+                self.paint(style, &format!("  permission {permission}: {reply:?}"))
+            }
+            Event::SessionCompacted {
+                truncated_before, ..
+            } => self.paint(
+                ANSI_DIM,
+                &format!("· compacted history before message {truncated_before}"),
+            ),
+            Event::SessionCompactionRequested { message_count, .. } => self.paint(
+                ANSI_DIM,
+                &format!("· compaction requested at {message_count} messages"),
+            ),
+            Event::SessionPhaseChanged { from, to, .. } => {
+                self.paint(ANSI_DIM, &format!("· {from:?} → {to:?}"))
+            }
+            Event::SessionPhaseTransitionRejected {
+                from, to, reason, ..
+            } => self.paint(
+                ANSI_YELLOW,
+                &format!("· rejected {from:?} → {to:?}: {reason}"),
+            ),
+            Event::ModelRetry {
+                model,
+                attempt,
+                delay_ms,
+                error,
+                ..
+            } => self.paint(
+                ANSI_YELLOW,
+                &format!("↻ {model} attempt {attempt} failed, retrying in {delay_ms}ms: {error}"),
+            ),
+        };
+        Some(line)
+    }
+}
+
+impl<W: Write + Send> EventSink for TerminalEventSink<W> {
+    fn emit(&self, event: Event) -> GraphResult<()> {
+        {
+            let mut state = self.state.lock().expect("poisoned writer");
+            match &event {
+                Event::TextDelta { delta, .. } => {
+                    state.streaming = true;
+                    return write_terminal(&mut state.writer, delta);
+                }
+                Event::TextFinal { text, .. } => {
+                    // Models that do not stream only report the final text.
+                    let out = if state.streaming {
+                        "\n".to_string()
+                    } else {
+                        format!("{text}\n")
+                    };
+                    state.streaming = false;
+                    return write_terminal(&mut state.writer, &out);
+                }
+                Event::StepStart { .. } => state.steps += 1,
+                Event::StepFinish { tokens, cost, .. } => {
+                    state.tokens.input += tokens.input;
+                    state.tokens.output += tokens.output;
+                    state.tokens.reasoning += tokens.reasoning;
+                    state.tokens.cache_read += tokens.cache_read;
+                    state.tokens.cache_write += tokens.cache_write;
+                    state.cost += cost;
+                }
+                Event::ToolStart { .. } => state.tool_calls += 1,
+                Event::ToolError { .. } => state.tool_errors += 1,
+                _ => {}
+            }
+            if let Some(line) = self.render(&event) {
+                let prefix = if state.streaming { "\n" } else { "" };
+                state.streaming = false;
+                write_terminal(&mut state.writer, &format!("{prefix}{line}\n"))?;
+            }
+        }
+        if matches!(
+            event,
+            Event::RunCompleted { .. } | Event::RunFailed { .. } | Event::RunAborted { .. }
+        ) {
+            self.finish()?;
+        }
+        Ok(())
+    }
+}
+
+fn indent(text: &str) -> String {
+    text.lines()
+        .map(|line| format!("    {line}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn write_terminal<W: Write>(writer: &mut W, text: &str) -> GraphResult<()> {
+    writer
+        .write_all(text.as_bytes())
+        .and_then(|_| writer.flush())
+        .map_err(|err| GraphError::ExecutionError {
+            node: "event_sink:terminal".to_string(),
+            message: format!("write event failed: {}", err),
+        })
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{JsonLineEventRecordSink, JsonLineEventSink, SseEventRecordSink, SseEventSink};
+    use super::{
+        JsonLineEventRecordSink, JsonLineEventSink, SseEventRecordSink, SseEventSink,
+        TerminalEventSink,
+    };
     use crate::runtime::error::GraphError;
-    use crate::runtime::event::{Event, EventMeta, EventRecord, EventRecordSink, EventSink};
+    use crate::runtime::event::{
+        Event, EventMeta, EventRecord, EventRecordSink, EventSink, PermissionReply, TokenUsage,
+    };
+    use crate::runtime::session_state::SessionPhase;
+    use crate::runtime::tool::ToolOutput;
     use std::io;
 
     #[test]
@@ -179,6 +534,7 @@ mod tests {
                 event_id: "e1".to_string(),
                 timestamp_ms: 1,
                 seq: 1,
+                ..Default::default()
             },
         );
         sink.emit_record(record).expect("emit record");
@@ -212,6 +568,7 @@ mod tests {
                 event_id: "e1".to_string(),
                 timestamp_ms: 1,
                 seq: 1,
+                ..Default::default()
             },
         );
         sink.emit_record(record).expect("emit record");
@@ -253,4 +610,97 @@ mod tests {
             other => panic!("expected execution error, got {:?}", other),
         }
     }
+
+    #[test]
+    fn terminal_event_sink_renders_plain_transcript() {
+        let sink = TerminalEventSink::new(Vec::new()).with_summary_width(12);
+        let events = vec![
+            Event::StepStart {
+                session_id: "s1".to_string(),
+            },
+            Event::TextDelta {
+                session_id: "s1".to_string(),
+                message_id: "m1".to_string(),
+                delta: "Hel".to_string(),
+            },
+            Event::TextDelta {
+                session_id: "s1".to_string(),
+                message_id: "m1".to_string(),
+                delta: "lo".to_string(),
+            },
+            Event::ToolStart {
+                tool: "grep".to_string(),
+                call_id: "c1".to_string(),
+                input: serde_json::json!({"pattern": "needle", "path": "src"}),
+            },
+            Event::ToolResult {
+                tool: "grep".to_string(),
+                call_id: "c1".to_string(),
+                output: ToolOutput::text("found\nit"),
+            },
+            Event::PermissionAsked {
+                permission: "bash".to_string(),
+                patterns: vec!["rm -rf target".to_string()],
+                metadata: Default::default(),
+                always: Vec::new(),
+            },
+            Event::PermissionReplied {
+                permission: "bash".to_string(),
+                reply: PermissionReply::Reject,
+            },
+            Event::SessionPhaseChanged {
+                session_id: "s1".to_string(),
+                message_id: "m1".to_string(),
+                from: SessionPhase::ModelThinking,
+                to: SessionPhase::ToolRunning,
+            },
+            Event::StepFinish {
+                session_id: "s1".to_string(),
+                tokens: TokenUsage {
+                    input: 10,
+                    output: 5,
+                    ..Default::default()
+                },
+                cost: 0.25,
+                model: None,
+            },
+        ];
+        for event in events {
+            sink.emit(event).expect("emit");
+        }
+        sink.finish().expect("finish");
+        sink.finish().expect("finish twice");
+
+        let output = String::from_utf8(sink.into_inner()).expect("utf8");
+        assert!(!output.contains('\x1b'));
+        assert!(output.starts_with("Hello\n"));
+        assert!(output.contains("→ grep {\"path\":\"src…"));
+        assert!(output.contains("← grep found it"));
+        assert!(output.contains("? permission required: bash\n    rm -rf target"));
+        assert!(output.contains("permission bash: Reject"));
+        assert!(output.contains("ModelThinking → ToolRunning"));
+        assert_eq!(output.matches("── summary:").count(), 1);
+        assert!(output.contains("1 steps, 1 tool calls (0 failed), tokens in 10 / out 5"));
+        assert!(output.ends_with("cost $0.2500\n"));
+    }
+
+    #[test]
+    fn terminal_event_sink_colors_and_summarizes_on_run_end() {
+        let sink = TerminalEventSink::new(Vec::new()).with_color(true);
+        sink.emit(Event::ToolError {
+            tool: "bash".to_string(),
+            call_id: "c1".to_string(),
+            error: "exit 1".to_string(),
+        })
+        .expect("emit");
+        sink.emit(Event::RunFailed {
+            run_id: "r1".to_string(),
+            error: "boom".to_string(),
+        })
+        .expect("emit");
+
+        let output = String::from_utf8(sink.into_inner()).expect("utf8");
+        assert!(output.contains("\x1b[31m✘ bash: exit 1\x1b[0m"));
+        assert!(output.contains("(1 failed)"));
+    }
 }
@@ -71,6 +71,13 @@ struct PermissionOverrides {
 
 impl PermissionOverrides {
     fn decide(&mut self, permission: &str) -> Option<PermissionDecision> {
+        let decision = self.peek(permission)?;
+        self.consume_once(permission);
+        Some(decision)
+    }
+
+    /// Decide without using up a matching "once" grant.
+    fn peek(&self, permission: &str) -> Option<PermissionDecision> {
         if self
             .reject
             .iter()
@@ -85,13 +92,28 @@ impl PermissionOverrides {
         {
             return Some(PermissionDecision::Allow);
         }
-        if let Some(pattern) = best_matching_pattern(&self.once, permission) {
-            self.once.remove(&pattern);
+        if best_matching_pattern(&self.once, permission).is_some() {
             return Some(PermissionDecision::Allow);
         }
         None
     }
 
+    /// Use up the most specific "once" grant for `permission`, unless a
+    /// reject or always override decides it first.
+    fn consume_once(&mut self, permission: &str) {
+        let overridden = self
+            .reject
+            .iter()
+            .chain(self.always.iter())
+            .any(|pattern| matches_pattern(pattern, permission));
+        if overridden {
+            return;
+        }
+        if let Some(pattern) = best_matching_pattern(&self.once, permission) {
+            self.once.remove(&pattern);
+        }
+    }
+
     fn apply_reply(&mut self, permission: &str, reply: crate::runtime::event::PermissionReply) {
         match reply {
             crate::runtime::event::PermissionReply::Once => {
@@ -137,6 +159,30 @@ impl PermissionSession {
         overrides.reject = snapshot.reject.into_iter().collect();
     }
 
+    /// Decide several permissions together, in order.
+    ///
+    /// "Once" grants are used up only when none of the permissions needs
+    /// asking, so a batch that is held back for approval leaves every reply
+    /// in place. The whole check runs under one lock, so replies applied
+    /// concurrently are never lost.
+    pub fn decide_batch(&self, permissions: &[String]) -> Vec<PermissionDecision> {
+        let mut overrides = self.overrides.lock().unwrap();
+        let decisions: Vec<PermissionDecision> = permissions
+            .iter()
+            .map(|permission| {
+                overrides
+                    .peek(permission)
+                    .unwrap_or_else(|| self.base.decide(permission))
+            })
+            .collect();
+        if !decisions.contains(&PermissionDecision::Ask) {
+            for permission in permissions {
+                overrides.consume_once(permission);
+            }
+        }
+        decisions
+    }
+
     pub fn apply_reply(&self, permission: &str, reply: crate::runtime::event::PermissionReply) {
         let mut overrides = self.overrides.lock().unwrap();
         overrides.apply_reply(permission, reply);
@@ -151,6 +197,38 @@ impl PermissionSession {
         self.apply_reply(permission, reply.clone());
         Some(reply)
     }
+
+    /// Apply a resume value to several permissions at once.
+    ///
+    /// The value is either one reply for all of them (`"once"`) or an
+    /// object mapping permissions to replies (`{"tool:rm": "reject", ...}`);
+    /// permissions without a valid reply are skipped.
+    pub fn apply_resume_all(
+        &self,
+        permissions: &[String],
+        command: &ResumeCommand,
+    ) -> Vec<(String, PermissionReply)> {
+        let per_permission = command.value.as_object().filter(|map| {
+            permissions
+                .iter()
+                .any(|permission| map.contains_key(permission))
+        });
+        let mut applied = Vec::new();
+        for permission in permissions {
+            if applied.iter().any(|(seen, _)| seen == permission) {
+                continue;
+            }
+            let reply = match per_permission {
+                Some(map) => map.get(permission).and_then(parse_permission_reply),
+                None => parse_permission_reply(&command.value),
+            };
+            if let Some(reply) = reply {
+                self.apply_reply(permission, reply.clone());
+                applied.push((permission.clone(), reply));
+            }
+        }
+        applied
+    }
 }
 
 impl PermissionGate for PermissionSession {
@@ -244,6 +322,26 @@ impl PermissionRequest {
     }
 }
 
+/// Several permission requests raised together, e.g. by a batch of tool
+/// calls; resume with [`PermissionSession::apply_resume_all`].
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct PermissionBatch {
+    pub requests: Vec<PermissionRequest>,
+}
+
+impl PermissionBatch {
+    /// Distinct permissions in request order.
+    pub fn permissions(&self) -> Vec<String> {
+        let mut permissions: Vec<String> = Vec::new();
+        for request in &self.requests {
+            if !permissions.contains(&request.permission) {
+                permissions.push(request.permission.clone());
+            }
+        }
+        permissions
+    }
+}
+
 /// Serializable snapshot of runtime permission replies.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct PermissionSnapshot {
@@ -391,6 +489,30 @@ mod tests {
         assert_eq!(session.decide("tool:write"), PermissionDecision::Allow);
     }
 
+    #[test]
+    fn permission_session_decide_batch_consumes_once_only_when_all_decided() {
+        let base = PermissionPolicy::new(vec![PermissionRule::new(
+            PermissionDecision::Ask,
+            vec!["tool:*".to_string()],
+        )]);
+        let session = PermissionSession::new(base);
+        session.apply_reply("tool:echo", PermissionReply::Once);
+        let batch = vec!["tool:echo".to_string(), "tool:rm".to_string()];
+
+        assert_eq!(
+            session.decide_batch(&batch),
+            vec![PermissionDecision::Allow, PermissionDecision::Ask]
+        );
+        assert_eq!(session.snapshot().once, vec!["tool:echo".to_string()]);
+
+        session.apply_reply("tool:rm", PermissionReply::Once);
+        assert_eq!(
+            session.decide_batch(&batch),
+            vec![PermissionDecision::Allow, PermissionDecision::Allow]
+        );
+        assert!(session.snapshot().once.is_empty());
+    }
+
     #[test]
     fn permission_session_once_pattern_consumes_after_first_match() {
         let base = PermissionPolicy::new(vec![PermissionRule::new(
@@ -474,6 +596,39 @@ mod tests {
         assert_eq!(session.decide("tool:echo"), PermissionDecision::Ask);
     }
 
+    #[test]
+    fn permission_session_applies_resume_to_several_permissions() {
+        let base = PermissionPolicy::new(vec![PermissionRule::new(
+            PermissionDecision::Ask,
+            vec!["tool:*".to_string()],
+        )]);
+        let session = PermissionSession::new(base);
+        let permissions = vec!["tool:read".to_string(), "tool:rm".to_string()];
+
+        let applied = session.apply_resume_all(
+            &permissions,
+            &ResumeCommand::new(serde_json::json!({"tool:read": "always", "tool:rm": "reject"})),
+        );
+        assert_eq!(
+            applied,
+            vec![
+                ("tool:read".to_string(), PermissionReply::Always),
+                ("tool:rm".to_string(), PermissionReply::Reject),
+            ]
+        );
+        assert_eq!(session.decide("tool:read"), PermissionDecision::Allow);
+        assert_eq!(session.decide("tool:rm"), PermissionDecision::Deny);
+
+        let session = PermissionSession::new(PermissionPolicy::new(vec![PermissionRule::new(
+            PermissionDecision::Ask,
+            vec!["tool:*".to_string()],
+        )]));
+        let applied = session.apply_resume_all(&permissions, &ResumeCommand::new("once"));
+        assert_eq!(applied.len(), 2);
+        assert_eq!(session.decide("tool:rm"), PermissionDecision::Allow);
+        assert_eq!(session.decide("tool:rm"), PermissionDecision::Ask);
+    }
+
     #[test]
     fn permission_request_roundtrip() {
         let mut metadata = serde_json::Map::new();
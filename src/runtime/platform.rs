@@ -7,7 +7,7 @@ use crate::runtime::error::GraphResult;
 use crate::runtime::event::{EventRecordSink, EventSink, NoopEventSink};
 use crate::runtime::executor::CompiledGraph;
 use crate::runtime::output::{
-    JsonLineEventRecordSink, JsonLineEventSink, SseEventRecordSink, SseEventSink,
+    JsonLineEventRecordSink, JsonLineEventSink, SseEventRecordSink, SseEventSink, TerminalEventSink,
 };
 use crate::runtime::state::GraphState;
 
@@ -137,6 +137,25 @@ where
     .await
 }
 
+/// Stream events as human-readable terminal output, ending with a token/cost
+/// summary. Pass [`TerminalEventSink::stdout`] for TTY-aware colour.
+pub async fn stream_terminal_events<S, W>(
+    graph: &CompiledGraph<S>,
+    state: S,
+    sink: TerminalEventSink<W>,
+) -> GraphResult<S>
+where
+    S: GraphState,
+    W: Write + Send + 'static,
+{
+    let sink = Arc::new(sink);
+    let result = graph
+        .stream_events(state, sink.clone() as Arc<dyn EventSink>)
+        .await;
+    sink.finish()?;
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -245,4 +264,17 @@ mod tests {
         assert!(output.starts_with("data: "));
         assert!(output.ends_with("\n\n"));
     }
+
+    #[test]
+    fn platform_streams_terminal_events() {
+        let graph = build_graph();
+        let buffer = SharedBuffer::default();
+        let sink = TerminalEventSink::new(buffer.writer());
+
+        let _ = block_on(stream_terminal_events(&graph, StreamState, sink)).expect("run");
+
+        let output = buffer.as_string();
+        assert!(output.starts_with("hello\n"));
+        assert!(output.contains("── summary:"));
+    }
 }
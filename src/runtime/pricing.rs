@@ -0,0 +1,350 @@
+//! Model pricing, cost accounting, and run budgets.
+//!
+//! Prices are expressed in USD per million tokens. A [`PricingRegistry`]
+//! turns [`TokenUsage`] into dollar cost, a [`CostLedger`] accumulates
+//! per-run and per-session totals, and a [`Budget`] bounds them.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::runtime::component::{ChatModel, ChatRequest, ChatResponse};
+use crate::runtime::error::{GraphError, GraphResult};
+use crate::runtime::event::{EventSink, TokenUsage};
+use crate::runtime::metrics::RunMetrics;
+use crate::runtime::node::BoxFuture;
+
+/// Response metadata key holding the computed cost in USD.
+pub const COST_METADATA_KEY: &str = "cost_usd";
+
+const TOKENS_PER_UNIT: f64 = 1_000_000.0;
+
+/// Per-model token prices in USD per million tokens.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    pub input: f64,
+    pub output: f64,
+    /// Price for cache reads; defaults to the input price.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read: Option<f64>,
+    /// Price for cache writes; defaults to the input price.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_write: Option<f64>,
+    /// Price for reasoning tokens; defaults to the output price.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<f64>,
+}
+
+impl ModelPrice {
+    pub fn new(input: f64, output: f64) -> Self {
+        Self {
+            input,
+            output,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_cache_read(mut self, price: f64) -> Self {
+        self.cache_read = Some(price);
+        self
+    }
+
+    pub fn with_cache_write(mut self, price: f64) -> Self {
+        self.cache_write = Some(price);
+        self
+    }
+
+    pub fn with_reasoning(mut self, price: f64) -> Self {
+        self.reasoning = Some(price);
+        self
+    }
+
+    /// Cost in USD for the given usage.
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        let total = usage.input as f64 * self.input
+            + usage.output as f64 * self.output
+            + usage.cache_read as f64 * self.cache_read.unwrap_or(self.input)
+            + usage.cache_write as f64 * self.cache_write.unwrap_or(self.input)
+            + usage.reasoning as f64 * self.reasoning.unwrap_or(self.output);
+        total / TOKENS_PER_UNIT
+    }
+}
+
+/// Registry of model prices keyed by model id.
+///
+/// Lookups try an exact match first, then the longest registered prefix, so
+/// `gpt-4o` also prices dated snapshots such as `gpt-4o-2024-08-06`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PricingRegistry {
+    prices: HashMap<String, ModelPrice>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    default_model: Option<String>,
+}
+
+impl PricingRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_price(mut self, model: impl Into<String>, price: ModelPrice) -> Self {
+        self.register(model, price);
+        self
+    }
+
+    /// Model used to price `StepFinish` events that do not name their model
+    /// (or name one without a price).
+    pub fn with_default_model(mut self, model: impl Into<String>) -> Self {
+        self.default_model = Some(model.into());
+        self
+    }
+
+    pub fn register(&mut self, model: impl Into<String>, price: ModelPrice) {
+        self.prices.insert(model.into(), price);
+    }
+
+    pub fn default_model(&self) -> Option<&str> {
+        self.default_model.as_deref()
+    }
+
+    pub fn price_for(&self, model: &str) -> Option<&ModelPrice> {
+        if let Some(price) = self.prices.get(model) {
+            return Some(price);
+        }
+        self.prices
+            .iter()
+            .filter(|(prefix, _)| model.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, price)| price)
+    }
+
+    pub fn cost(&self, model: &str, usage: &TokenUsage) -> Option<f64> {
+        self.price_for(model).map(|price| price.cost(usage))
+    }
+
+    /// Cost using the default model, if one is configured and priced.
+    pub fn default_cost(&self, usage: &TokenUsage) -> Option<f64> {
+        self.default_model
+            .as_deref()
+            .and_then(|model| self.cost(model, usage))
+    }
+
+    /// Cost of a chat response, using `response.model` or `fallback_model`.
+    pub fn response_cost(&self, response: &ChatResponse, fallback_model: &str) -> Option<f64> {
+        let usage = response.usage.as_ref()?;
+        let model = response.model.as_deref().unwrap_or(fallback_model);
+        self.cost(model, usage)
+    }
+
+    /// Compute the response cost and store it under [`COST_METADATA_KEY`].
+    pub fn apply_to_response(&self, response: &mut ChatResponse, fallback_model: &str) {
+        if let Some(cost) = self.response_cost(response, fallback_model) {
+            response
+                .metadata
+                .insert(COST_METADATA_KEY.to_string(), serde_json::json!(cost));
+        }
+    }
+}
+
+/// Wraps a chat model so every response carries its computed cost.
+pub struct PricedChatModel {
+    inner: Arc<dyn ChatModel>,
+    pricing: Arc<PricingRegistry>,
+}
+
+impl PricedChatModel {
+    pub fn new(inner: Arc<dyn ChatModel>, pricing: Arc<PricingRegistry>) -> Self {
+        Self { inner, pricing }
+    }
+}
+
+impl ChatModel for PricedChatModel {
+    fn model_id(&self) -> &str {
+        self.inner.model_id()
+    }
+
+    fn generate(&self, request: ChatRequest) -> BoxFuture<'_, GraphResult<ChatResponse>> {
+        Box::pin(async move {
+            let mut response = self.inner.generate(request).await?;
+            self.pricing
+                .apply_to_response(&mut response, self.inner.model_id());
+            Ok(response)
+        })
+    }
+
+    fn stream(
+        &self,
+        request: ChatRequest,
+        sink: Arc<dyn EventSink>,
+    ) -> BoxFuture<'_, GraphResult<ChatResponse>> {
+        Box::pin(async move {
+            let mut response = self.inner.stream(request, sink).await?;
+            self.pricing
+                .apply_to_response(&mut response, self.inner.model_id());
+            Ok(response)
+        })
+    }
+}
+
+/// Token and dollar limits for a run.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Budget {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_cost_usd: Option<f64>,
+}
+
+impl Budget {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_tokens(mut self, max_tokens: u64) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    pub fn with_max_cost_usd(mut self, max_cost_usd: f64) -> Self {
+        self.max_cost_usd = Some(max_cost_usd);
+        self
+    }
+
+    /// Returns `GraphError::Aborted` when the ledger exceeds a limit.
+    pub fn check(&self, ledger: &CostLedger) -> GraphResult<()> {
+        if let Some(max) = self.max_tokens {
+            if ledger.total_tokens > max {
+                return Err(GraphError::Aborted {
+                    reason: format!("token budget exceeded: {} > {}", ledger.total_tokens, max),
+                });
+            }
+        }
+        if let Some(max) = self.max_cost_usd {
+            if ledger.total_cost_usd > max {
+                return Err(GraphError::Aborted {
+                    reason: format!(
+                        "cost budget exceeded: ${:.6} > ${:.6}",
+                        ledger.total_cost_usd, max
+                    ),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Accumulated token and cost totals for a run, broken down by session.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CostLedger {
+    pub total_tokens: u64,
+    pub total_cost_usd: f64,
+    pub session_costs: HashMap<String, f64>,
+}
+
+impl CostLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, session_id: &str, tokens: &TokenUsage, cost: f64) {
+        self.total_tokens += tokens.input
+            + tokens.output
+            + tokens.reasoning
+            + tokens.cache_read
+            + tokens.cache_write;
+        self.total_cost_usd += cost;
+        *self
+            .session_costs
+            .entry(session_id.to_string())
+            .or_insert(0.0) += cost;
+    }
+
+    /// Set the cost totals of `metrics` to this ledger's. Applying the same
+    /// ledger again leaves them unchanged.
+    pub fn apply_to_metrics(&self, metrics: &mut RunMetrics) {
+        metrics.total_cost_usd = self.total_cost_usd;
+        metrics.session_costs = self.session_costs.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Budget, CostLedger, ModelPrice, PricedChatModel, PricingRegistry};
+    use crate::runtime::component::{ChatModel, ChatRequest, MockChatModel};
+    use crate::runtime::error::GraphError;
+    use crate::runtime::event::TokenUsage;
+    use crate::runtime::message::{Message, MessageRole};
+    use crate::runtime::metrics::RunMetrics;
+    use futures::executor::block_on;
+    use std::sync::Arc;
+
+    fn usage(input: u64, output: u64) -> TokenUsage {
+        TokenUsage {
+            input,
+            output,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn model_price_applies_cache_and_reasoning_rates() {
+        let price = ModelPrice::new(2.0, 10.0).with_cache_read(0.5);
+        let usage = TokenUsage {
+            input: 1_000_000,
+            output: 100_000,
+            reasoning: 100_000,
+            cache_read: 1_000_000,
+            cache_write: 0,
+        };
+        let cost = price.cost(&usage);
+        assert!((cost - (2.0 + 1.0 + 1.0 + 0.5)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn registry_matches_longest_prefix() {
+        let registry = PricingRegistry::new()
+            .with_price("gpt-4o", ModelPrice::new(2.5, 10.0))
+            .with_price("gpt-4o-mini", ModelPrice::new(0.15, 0.6));
+        let mini = registry.price_for("gpt-4o-mini-2024-07-18").expect("price");
+        assert_eq!(mini.input, 0.15);
+        let full = registry.price_for("gpt-4o-2024-08-06").expect("price");
+        assert_eq!(full.input, 2.5);
+        assert!(registry.price_for("claude").is_none());
+    }
+
+    #[test]
+    fn priced_chat_model_records_cost_metadata() {
+        let registry =
+            Arc::new(PricingRegistry::new().with_price("mock", ModelPrice::new(1_000.0, 2_000.0)));
+        let model = PricedChatModel::new(Arc::new(MockChatModel::new("mock", "ok")), registry);
+        let request = ChatRequest::new("s1", "m1", vec![Message::new(MessageRole::User)]);
+        let response = block_on(model.generate(request)).expect("generate");
+        let cost = response.metadata["cost_usd"].as_f64().expect("cost");
+        assert!((cost - 0.003).abs() < 1e-12);
+    }
+
+    #[test]
+    fn budget_aborts_when_limits_exceeded() {
+        let mut ledger = CostLedger::new();
+        ledger.record("s1", &usage(60, 40), 0.5);
+        ledger.record("s2", &usage(10, 0), 0.25);
+        assert_eq!(ledger.total_tokens, 110);
+        assert_eq!(ledger.session_costs["s1"], 0.5);
+
+        assert!(Budget::new().with_max_tokens(200).check(&ledger).is_ok());
+        assert!(matches!(
+            Budget::new().with_max_tokens(100).check(&ledger),
+            Err(GraphError::Aborted { .. })
+        ));
+        assert!(matches!(
+            Budget::new().with_max_cost_usd(0.5).check(&ledger),
+            Err(GraphError::Aborted { .. })
+        ));
+
+        let mut metrics = RunMetrics::new("run", "cfg");
+        ledger.apply_to_metrics(&mut metrics);
+        ledger.apply_to_metrics(&mut metrics);
+        assert!((metrics.total_cost_usd - 0.75).abs() < 1e-12);
+        assert_eq!(metrics.session_costs["s2"], 0.25);
+    }
+}
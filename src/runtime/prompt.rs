@@ -0,0 +1,870 @@
+//! Prompt templates that render to role-tagged message lists.
+//!
+//! Template text uses a small mustache-style syntax:
+//!
+//! - `{{name}}` / `{{user.name}}` insert a variable, escaped with the
+//!   template's [`Escape`] mode; `{{{name}}}` or `{{& name}}` insert it raw.
+//! - `{{#name}}…{{/name}}` renders when `name` is truthy (once per item for
+//!   arrays, with `{{.}}` as the item); `{{^name}}…{{/name}}` when it is not.
+//! - `{{> partial}}` includes a partial registered in a [`PromptLibrary`].
+//! - `{{! comment}}` is dropped and `\{{` is a literal `{{`.
+//!
+//! A missing variable is an error. Templates can be loaded from `.prompt`
+//! files (front matter plus `[role]` sections) or JSON, and
+//! [`PromptTemplate::render_request`] records the template id and version
+//! in `ChatRequest::metadata`.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::runtime::component::ChatRequest;
+use crate::runtime::error::{GraphError, GraphResult};
+use crate::runtime::message::{Message, MessageRole, Part};
+
+/// Metadata key holding the id of the template a request was rendered from.
+pub const PROMPT_ID_METADATA_KEY: &str = "prompt_id";
+/// Metadata key holding the template version.
+pub const PROMPT_VERSION_METADATA_KEY: &str = "prompt_version";
+
+const MAX_PARTIAL_DEPTH: usize = 16;
+
+/// How inserted variable values are escaped.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Escape {
+    /// Insert values as-is.
+    #[default]
+    None,
+    /// Escape `& < > " '` for XML-tagged prompts.
+    Xml,
+    /// Escape as the inside of a JSON string.
+    Json,
+}
+
+impl Escape {
+    fn apply(self, text: &str) -> String {
+        match self {
+            Escape::None => text.to_string(),
+            Escape::Xml => text
+                .replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;")
+                .replace('"', "&quot;")
+                .replace('\'', "&apos;"),
+            Escape::Json => {
+                let quoted = Value::String(text.to_string()).to_string();
+                quoted[1..quoted.len() - 1].to_string()
+            }
+        }
+    }
+}
+
+/// One message of a template.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MessageTemplate {
+    pub role: MessageRole,
+    pub template: String,
+}
+
+impl MessageTemplate {
+    pub fn new(role: MessageRole, template: impl Into<String>) -> Self {
+        Self {
+            role,
+            template: template.into(),
+        }
+    }
+}
+
+/// A versioned template rendering to a list of messages.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PromptTemplate {
+    pub id: String,
+    pub version: String,
+    #[serde(default)]
+    pub escape: Escape,
+    pub messages: Vec<MessageTemplate>,
+}
+
+impl PromptTemplate {
+    pub fn new(id: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            version: version.into(),
+            escape: Escape::None,
+            messages: Vec::new(),
+        }
+    }
+
+    pub fn with_escape(mut self, escape: Escape) -> Self {
+        self.escape = escape;
+        self
+    }
+
+    pub fn with_message(mut self, role: MessageRole, template: impl Into<String>) -> Self {
+        self.messages.push(MessageTemplate::new(role, template));
+        self
+    }
+
+    pub fn system(self, template: impl Into<String>) -> Self {
+        self.with_message(MessageRole::System, template)
+    }
+
+    pub fn user(self, template: impl Into<String>) -> Self {
+        self.with_message(MessageRole::User, template)
+    }
+
+    pub fn assistant(self, template: impl Into<String>) -> Self {
+        self.with_message(MessageRole::Assistant, template)
+    }
+
+    /// Load a `.json` template or a `.prompt` file:
+    ///
+    /// ```text
+    /// ---
+    /// id: triage
+    /// version: 2
+    /// escape: xml
+    /// ---
+    /// [system]
+    /// You triage tickets for {{product}}.
+    /// [user]
+    /// {{ticket}}
+    /// ```
+    pub fn from_file(path: impl AsRef<Path>) -> GraphResult<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|err| {
+            prompt_error(
+                &path.display().to_string(),
+                format!("read template failed: {}", err),
+            )
+        })?;
+        let template = if path.extension().is_some_and(|ext| ext == "json") {
+            serde_json::from_str(&text).map_err(|err| {
+                prompt_error(
+                    &path.display().to_string(),
+                    format!("decode template failed: {}", err),
+                )
+            })?
+        } else {
+            Self::parse(&text)?
+        };
+        template.validate()?;
+        Ok(template)
+    }
+
+    /// Parse the `.prompt` text format.
+    pub fn parse(text: &str) -> GraphResult<Self> {
+        let mut id = None;
+        let mut version = None;
+        let mut escape = Escape::None;
+        let mut body = text;
+        if let Some(rest) = text.strip_prefix("---\n") {
+            let end = rest
+                .find("\n---\n")
+                .ok_or_else(|| prompt_error("template", "unterminated front matter"))?;
+            for line in rest[..end].lines().filter(|line| !line.trim().is_empty()) {
+                let (key, value) = line.split_once(':').ok_or_else(|| {
+                    prompt_error("template", format!("invalid front matter line: {}", line))
+                })?;
+                let value = value.trim().to_string();
+                match key.trim() {
+                    "id" => id = Some(value),
+                    "version" => version = Some(value),
+                    "escape" => {
+                        escape =
+                            serde_json::from_value(Value::String(value.clone())).map_err(|_| {
+                                prompt_error("template", format!("unknown escape mode {}", value))
+                            })?
+                    }
+                    other => {
+                        return Err(prompt_error(
+                            "template",
+                            format!("unknown front matter key {}", other),
+                        ))
+                    }
+                }
+            }
+            body = &rest[end + 5..];
+        }
+        let id = id.ok_or_else(|| prompt_error("template", "missing `id` in front matter"))?;
+        let mut template = Self::new(id, version.unwrap_or_else(|| "1".to_string()));
+        template.escape = escape;
+
+        let mut current: Option<(MessageRole, Vec<&str>)> = None;
+        for line in body.lines() {
+            let header = line
+                .trim()
+                .strip_prefix('[')
+                .and_then(|rest| rest.strip_suffix(']'))
+                .and_then(MessageRole::parse);
+            match header {
+                Some(role) => {
+                    if let Some((role, lines)) = current.take() {
+                        template = template.with_message(role, lines.join("\n"));
+                    }
+                    current = Some((role, Vec::new()));
+                }
+                None => match current.as_mut() {
+                    Some((_, lines)) => lines.push(line),
+                    None if line.trim().is_empty() => {}
+                    None => {
+                        return Err(prompt_error(
+                            &template.id,
+                            "text before the first [role] header",
+                        ))
+                    }
+                },
+            }
+        }
+        if let Some((role, lines)) = current {
+            template = template.with_message(role, lines.join("\n"));
+        }
+        template.validate()?;
+        Ok(template)
+    }
+
+    /// Check that every message template parses.
+    pub fn validate(&self) -> GraphResult<()> {
+        for message in &self.messages {
+            parse_template(&message.template).map_err(|err| prompt_error(&self.id, err))?;
+        }
+        Ok(())
+    }
+
+    /// Render without partials; `vars` is usually a JSON object.
+    pub fn render(&self, vars: &Value) -> GraphResult<Vec<Message>> {
+        self.render_with(vars, &BTreeMap::new())
+    }
+
+    /// Render into a request tagged with this template's id and version.
+    pub fn render_request(
+        &self,
+        session_id: impl Into<String>,
+        message_id: impl Into<String>,
+        vars: &Value,
+    ) -> GraphResult<ChatRequest> {
+        let messages = self.render(vars)?;
+        Ok(self.tag_request(ChatRequest::new(session_id, message_id, messages)))
+    }
+
+    /// Record this template's id and version in `request.metadata`.
+    pub fn tag_request(&self, request: ChatRequest) -> ChatRequest {
+        request
+            .with_metadata(PROMPT_ID_METADATA_KEY, Value::String(self.id.clone()))
+            .with_metadata(
+                PROMPT_VERSION_METADATA_KEY,
+                Value::String(self.version.clone()),
+            )
+    }
+
+    fn render_with(
+        &self,
+        vars: &Value,
+        partials: &BTreeMap<String, String>,
+    ) -> GraphResult<Vec<Message>> {
+        let mut messages = Vec::new();
+        for message in &self.messages {
+            let nodes =
+                parse_template(&message.template).map_err(|err| prompt_error(&self.id, err))?;
+            let mut renderer = Renderer {
+                escape: self.escape,
+                partials,
+                depth: 0,
+                output: String::new(),
+            };
+            renderer
+                .render(&nodes, &mut vec![vars])
+                .map_err(|err| prompt_error(&self.id, err))?;
+            let text = renderer.output.trim();
+            if text.is_empty() {
+                continue;
+            }
+            let mut rendered = Message::new(message.role.clone());
+            rendered.parts.push(Part::TextFinal {
+                text: text.to_string(),
+            });
+            messages.push(rendered);
+        }
+        Ok(messages)
+    }
+}
+
+/// Templates by id and version, plus named partials.
+#[derive(Clone, Debug, Default)]
+pub struct PromptLibrary {
+    templates: BTreeMap<String, Vec<PromptTemplate>>,
+    partials: BTreeMap<String, String>,
+}
+
+impl PromptLibrary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load `*.prompt` and `*.json` templates and `*.partial` partials
+    /// (named by file stem) from a directory.
+    pub fn load_dir(path: impl AsRef<Path>) -> GraphResult<Self> {
+        let path = path.as_ref();
+        let mut entries = std::fs::read_dir(path)
+            .map_err(|err| {
+                prompt_error(
+                    &path.display().to_string(),
+                    format!("read directory failed: {}", err),
+                )
+            })?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .collect::<Vec<_>>();
+        entries.sort();
+        let mut library = Self::new();
+        for entry in entries {
+            match entry.extension().and_then(|ext| ext.to_str()) {
+                Some("prompt") | Some("json") => {
+                    library.register(PromptTemplate::from_file(&entry)?)?;
+                }
+                Some("partial") => {
+                    let name = entry
+                        .file_stem()
+                        .and_then(|stem| stem.to_str())
+                        .unwrap_or_default()
+                        .to_string();
+                    let text = std::fs::read_to_string(&entry).map_err(|err| {
+                        prompt_error(&name, format!("read partial failed: {}", err))
+                    })?;
+                    library.register_partial(name, text.trim_end_matches('\n'))?;
+                }
+                _ => {}
+            }
+        }
+        Ok(library)
+    }
+
+    /// Add a template; registering the same id and version twice is an error.
+    pub fn register(&mut self, template: PromptTemplate) -> GraphResult<()> {
+        template.validate()?;
+        let versions = self.templates.entry(template.id.clone()).or_default();
+        if versions
+            .iter()
+            .any(|existing| existing.version == template.version)
+        {
+            return Err(prompt_error(
+                &template.id,
+                format!("version {} is already registered", template.version),
+            ));
+        }
+        versions.push(template);
+        versions.sort_by(|a, b| compare_versions(&a.version, &b.version));
+        Ok(())
+    }
+
+    pub fn register_partial(
+        &mut self,
+        name: impl Into<String>,
+        template: impl Into<String>,
+    ) -> GraphResult<()> {
+        let name = name.into();
+        let template = template.into();
+        parse_template(&template).map_err(|err| prompt_error(&name, err))?;
+        self.partials.insert(name, template);
+        Ok(())
+    }
+
+    /// A specific version, or the highest one when `version` is `None`.
+    pub fn get(&self, id: &str, version: Option<&str>) -> Option<&PromptTemplate> {
+        let versions = self.templates.get(id)?;
+        match version {
+            Some(version) => versions.iter().find(|template| template.version == version),
+            None => versions.last(),
+        }
+    }
+
+    pub fn versions(&self, id: &str) -> Vec<&str> {
+        self.templates
+            .get(id)
+            .map(|versions| versions.iter().map(|t| t.version.as_str()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Render a template with this library's partials available.
+    pub fn render(
+        &self,
+        id: &str,
+        version: Option<&str>,
+        vars: &Value,
+    ) -> GraphResult<Vec<Message>> {
+        self.template(id, version)?
+            .render_with(vars, &self.partials)
+    }
+
+    /// Render into a request tagged with the template id and version.
+    pub fn render_request(
+        &self,
+        id: &str,
+        version: Option<&str>,
+        session_id: impl Into<String>,
+        message_id: impl Into<String>,
+        vars: &Value,
+    ) -> GraphResult<ChatRequest> {
+        let template = self.template(id, version)?;
+        let messages = template.render_with(vars, &self.partials)?;
+        Ok(template.tag_request(ChatRequest::new(session_id, message_id, messages)))
+    }
+
+    fn template(&self, id: &str, version: Option<&str>) -> GraphResult<&PromptTemplate> {
+        self.get(id, version).ok_or_else(|| {
+            prompt_error(
+                id,
+                match version {
+                    Some(version) => format!("version {} not found", version),
+                    None => "template not found".to_string(),
+                },
+            )
+        })
+    }
+}
+
+/// Order dotted numeric versions numerically, falling back to text order.
+fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    let numeric = |version: &str| {
+        version
+            .trim_start_matches('v')
+            .split('.')
+            .map(|segment| segment.parse::<u64>().ok())
+            .collect::<Option<Vec<_>>>()
+    };
+    match (numeric(a), numeric(b)) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        _ => a.cmp(b),
+    }
+}
+
+fn prompt_error(id: &str, message: impl Into<String>) -> GraphError {
+    GraphError::ExecutionError {
+        node: format!("prompt:{}", id),
+        message: message.into(),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Node {
+    Text(String),
+    Var {
+        name: String,
+        raw: bool,
+    },
+    Section {
+        name: String,
+        inverted: bool,
+        children: Vec<Node>,
+    },
+    Partial(String),
+}
+
+#[derive(Debug)]
+enum Token {
+    Text(String),
+    Var { name: String, raw: bool },
+    Open { name: String, inverted: bool },
+    Close(String),
+    Partial(String),
+    Comment,
+}
+
+impl Token {
+    /// Block tags on a line of their own take the whole line with them.
+    fn is_standalone_kind(&self) -> bool {
+        matches!(
+            self,
+            Token::Open { .. } | Token::Close(_) | Token::Partial(_) | Token::Comment
+        )
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut literal = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        if rest[..start].ends_with('\\') {
+            literal.push_str(&rest[..start - 1]);
+            literal.push_str("{{");
+            rest = &rest[start + 2..];
+            continue;
+        }
+        literal.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let (inner, consumed, triple) = if let Some(body) = after.strip_prefix('{') {
+            let end = body
+                .find("}}}")
+                .ok_or_else(|| "unterminated `{{{` tag".to_string())?;
+            (&body[..end], end + 4, true)
+        } else {
+            let end = after
+                .find("}}")
+                .ok_or_else(|| "unterminated `{{` tag".to_string())?;
+            (&after[..end], end + 2, false)
+        };
+        rest = &after[consumed..];
+        tokens.push(Token::Text(std::mem::take(&mut literal)));
+        let inner = inner.trim();
+        let name = |prefix: usize| {
+            let name = inner[prefix..].trim();
+            if name.is_empty() {
+                Err(format!("empty tag name in `{{{{{}}}}}`", inner))
+            } else {
+                Ok(name.to_string())
+            }
+        };
+        let token = if triple {
+            Token::Var {
+                name: name(0)?,
+                raw: true,
+            }
+        } else {
+            match inner.chars().next() {
+                Some('#') => Token::Open {
+                    name: name(1)?,
+                    inverted: false,
+                },
+                Some('^') => Token::Open {
+                    name: name(1)?,
+                    inverted: true,
+                },
+                Some('/') => Token::Close(name(1)?),
+                Some('>') => Token::Partial(name(1)?),
+                Some('&') => Token::Var {
+                    name: name(1)?,
+                    raw: true,
+                },
+                Some('!') => Token::Comment,
+                _ => Token::Var {
+                    name: name(0)?,
+                    raw: false,
+                },
+            }
+        };
+        tokens.push(token);
+    }
+    literal.push_str(rest);
+    tokens.push(Token::Text(literal));
+    strip_standalone_lines(&mut tokens);
+    Ok(tokens)
+}
+
+/// Remove the indentation and line break around block tags that sit alone
+/// on a line, so sections do not leave blank lines behind.
+fn strip_standalone_lines(tokens: &mut [Token]) {
+    // Tokens alternate Text, tag, Text, tag, ..., Text.
+    let len = tokens.len();
+    for index in (1..len).step_by(2) {
+        if !tokens[index].is_standalone_kind() {
+            continue;
+        }
+        let (before, after) = tokens.split_at_mut(index);
+        let (Token::Text(prev), Token::Text(next)) = (&mut before[index - 1], &mut after[1]) else {
+            continue;
+        };
+        let line_start = prev.rfind('\n').map(|pos| pos + 1).unwrap_or(0);
+        let at_line_start = prev[line_start..].chars().all(|c| c == ' ' || c == '\t')
+            && (line_start > 0 || index == 1);
+        let line_end = next.find('\n');
+        let at_line_end = match line_end {
+            Some(end) => next[..end]
+                .trim_end_matches('\r')
+                .chars()
+                .all(|c| c == ' ' || c == '\t'),
+            None => index + 2 == len && next.trim().is_empty(),
+        };
+        if at_line_start && at_line_end {
+            prev.truncate(line_start);
+            match line_end {
+                Some(end) => {
+                    next.drain(..=end);
+                }
+                None => next.clear(),
+            }
+        }
+    }
+}
+
+fn parse_template(text: &str) -> Result<Vec<Node>, String> {
+    // Each frame is the open section (name, inverted) and its children.
+    type Frame = (Option<(String, bool)>, Vec<Node>);
+    let mut stack: Vec<Frame> = vec![(None, Vec::new())];
+    for token in tokenize(text)? {
+        let children = &mut stack.last_mut().expect("root frame").1;
+        match token {
+            Token::Text(text) if text.is_empty() => {}
+            Token::Text(text) => children.push(Node::Text(text)),
+            Token::Var { name, raw } => children.push(Node::Var { name, raw }),
+            Token::Partial(name) => children.push(Node::Partial(name)),
+            Token::Comment => {}
+            Token::Open { name, inverted } => stack.push((Some((name, inverted)), Vec::new())),
+            Token::Close(name) => {
+                let (open, children) = stack.pop().expect("frame");
+                let Some((open_name, inverted)) = open else {
+                    return Err(format!("unexpected closing tag `{}`", name));
+                };
+                if open_name != name {
+                    return Err(format!("section `{}` closed by `{}`", open_name, name));
+                }
+                stack
+                    .last_mut()
+                    .expect("parent frame")
+                    .1
+                    .push(Node::Section {
+                        name,
+                        inverted,
+                        children,
+                    });
+            }
+        }
+    }
+    let (open, nodes) = stack.pop().expect("root frame");
+    match open {
+        None => Ok(nodes),
+        Some((name, _)) => Err(format!("section `{}` is not closed", name)),
+    }
+}
+
+struct Renderer<'a> {
+    escape: Escape,
+    partials: &'a BTreeMap<String, String>,
+    depth: usize,
+    output: String,
+}
+
+impl Renderer<'_> {
+    fn render(&mut self, nodes: &[Node], scopes: &mut Vec<&Value>) -> Result<(), String> {
+        for node in nodes {
+            match node {
+                Node::Text(text) => self.output.push_str(text),
+                Node::Var { name, raw } => {
+                    let value = lookup(scopes, name)
+                        .ok_or_else(|| format!("missing variable `{}`", name))?;
+                    let text = value_text(value);
+                    if *raw {
+                        self.output.push_str(&text);
+                    } else {
+                        self.output.push_str(&self.escape.apply(&text));
+                    }
+                }
+                Node::Section {
+                    name,
+                    inverted,
+                    children,
+                } => {
+                    let value = lookup(scopes, name);
+                    let truthy = value.is_some_and(is_truthy);
+                    if *inverted {
+                        if !truthy {
+                            self.render(children, scopes)?;
+                        }
+                        continue;
+                    }
+                    let Some(value) = value.filter(|value| is_truthy(value)) else {
+                        continue;
+                    };
+                    match value {
+                        Value::Array(items) => {
+                            for item in items {
+                                scopes.push(item);
+                                let result = self.render(children, scopes);
+                                scopes.pop();
+                                result?;
+                            }
+                        }
+                        other => {
+                            scopes.push(other);
+                            let result = self.render(children, scopes);
+                            scopes.pop();
+                            result?;
+                        }
+                    }
+                }
+                Node::Partial(name) => {
+                    let template = self
+                        .partials
+                        .get(name)
+                        .ok_or_else(|| format!("unknown partial `{}`", name))?;
+                    if self.depth >= MAX_PARTIAL_DEPTH {
+                        return Err(format!("partial `{}` nests too deeply", name));
+                    }
+                    let nodes = parse_template(template)?;
+                    self.depth += 1;
+                    let result = self.render(&nodes, scopes);
+                    self.depth -= 1;
+                    result?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Resolve `name` (`.` or a dotted path) from the innermost scope outwards.
+fn lookup<'v>(scopes: &[&'v Value], name: &str) -> Option<&'v Value> {
+    if name == "." {
+        return scopes.last().copied();
+    }
+    let mut segments = name.split('.');
+    let first = segments.next()?;
+    let mut value = scopes
+        .iter()
+        .rev()
+        .find_map(|scope| scope.as_object().and_then(|object| object.get(first)))?;
+    for segment in segments {
+        value = match value {
+            Value::Object(object) => object.get(segment)?,
+            Value::Array(items) => items.get(segment.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+    Some(value)
+}
+
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(flag) => *flag,
+        Value::String(text) => !text.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Number(_) | Value::Object(_) => true,
+    }
+}
+
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Escape, PromptLibrary, PromptTemplate, PROMPT_ID_METADATA_KEY, PROMPT_VERSION_METADATA_KEY,
+    };
+    use crate::runtime::error::GraphError;
+    use crate::runtime::message::{Message, MessageRole, Part};
+    use serde_json::json;
+
+    fn text(message: &Message) -> &str {
+        match &message.parts[0] {
+            Part::TextFinal { text } => text,
+            other => panic!("unexpected part {:?}", other),
+        }
+    }
+
+    #[test]
+    fn render_handles_sections_escaping_and_missing_variables() {
+        let template = PromptTemplate::new("review", "1")
+            .with_escape(Escape::Xml)
+            .system("You review code for {{team.name}}.")
+            .user(
+                "<diff>{{diff}}</diff>\n{{#files}}\n- {{.}}\n{{/files}}\n{{^files}}\nNo files.\n{{/files}}\n{{! note }}Raw: {{{diff}}} \\{{literal}}",
+            )
+            .assistant("{{#prefill}}{{prefill}}{{/prefill}}");
+
+        let messages = template
+            .render(&json!({
+                "team": {"name": "infra"},
+                "diff": "a < b && c",
+                "files": ["src/a.rs", "src/b.rs"],
+            }))
+            .expect("renders");
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].role, MessageRole::System);
+        assert_eq!(text(&messages[0]), "You review code for infra.");
+        assert_eq!(messages[1].role, MessageRole::User);
+        assert_eq!(
+            text(&messages[1]),
+            "<diff>a &lt; b &amp;&amp; c</diff>\n- src/a.rs\n- src/b.rs\nRaw: a < b && c {{literal}}"
+        );
+
+        let empty = template
+            .render(&json!({"team": {"name": "x"}, "diff": "d", "files": []}))
+            .expect("renders");
+        assert!(text(&empty[1]).contains("No files."));
+
+        match template.render(&json!({"diff": "d"})) {
+            Err(GraphError::ExecutionError { node, message }) => {
+                assert_eq!(node, "prompt:review");
+                assert!(message.contains("missing variable `team.name`"));
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert!(PromptTemplate::new("bad", "1")
+            .user("{{#open}}never closed")
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn json_escape_keeps_values_inside_string_literals() {
+        let messages = PromptTemplate::new("json", "1")
+            .with_escape(Escape::Json)
+            .user("{\"q\": \"{{q}}\"}")
+            .render(&json!({"q": "say \"hi\"\nnow"}))
+            .expect("renders");
+        let value: serde_json::Value = serde_json::from_str(text(&messages[0])).unwrap();
+        assert_eq!(value["q"], "say \"hi\"\nnow");
+    }
+
+    #[test]
+    fn library_loads_files_resolves_partials_and_tags_requests() {
+        let dir = std::env::temp_dir().join(format!("forge-prompts-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("triage-v1.prompt"),
+            "---\nid: triage\nversion: 1.9\n---\n[system]\nOld.\n[user]\n{{ticket}}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("triage-v2.prompt"),
+            "---\nid: triage\nversion: 1.10\n---\n[system]\n{{> rules}}\n[user]\n{{ticket}}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("rules.partial"),
+            "Be brief. Product: {{product}}.\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("greet.json"),
+            serde_json::to_string(&PromptTemplate::new("greet", "1").user("Hi {{name}}")).unwrap(),
+        )
+        .unwrap();
+
+        let library = PromptLibrary::load_dir(&dir).expect("loads");
+        std::fs::remove_dir_all(&dir).ok();
+        assert_eq!(library.versions("triage"), vec!["1.9", "1.10"]);
+
+        let vars = json!({"ticket": "Login broken", "product": "forge"});
+        let request = library
+            .render_request("triage", None, "s1", "m1", &vars)
+            .expect("renders");
+        assert_eq!(text(&request.messages[0]), "Be brief. Product: forge.");
+        assert_eq!(text(&request.messages[1]), "Login broken");
+        assert_eq!(request.metadata[PROMPT_ID_METADATA_KEY], "triage");
+        assert_eq!(request.metadata[PROMPT_VERSION_METADATA_KEY], "1.10");
+
+        let old = library
+            .render("triage", Some("1.9"), &vars)
+            .expect("renders");
+        assert_eq!(text(&old[0]), "Old.");
+        let greet = library
+            .render("greet", None, &json!({"name": "Ada"}))
+            .expect("renders");
+        assert_eq!(text(&greet[0]), "Hi Ada");
+
+        assert!(library.render("triage", Some("3"), &vars).is_err());
+        let mut library = library;
+        assert!(library
+            .register(PromptTemplate::new("greet", "1").user("again"))
+            .is_err());
+    }
+}
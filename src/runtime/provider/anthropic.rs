@@ -0,0 +1,983 @@
+//! Anthropic Messages API adapter for Forge component interfaces.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::runtime::cancel::{cancellable, CancellationToken};
+use crate::runtime::component::{ChatModel, ChatRequest, ChatResponse, ToolChoice};
+use crate::runtime::error::{GraphError, GraphResult};
+use crate::runtime::event::{Event, EventSink, TokenUsage};
+use crate::runtime::message::{MediaSource, Message, MessageRole, Part};
+use crate::runtime::node::BoxFuture;
+use crate::runtime::provider::transport::{
+    default_transport, HttpRequest, HttpResponse, HttpTransport,
+};
+use crate::runtime::provider::{prepare_media, send_request};
+use crate::runtime::session::AttachmentResolver;
+use crate::runtime::tool::ToolDefinition;
+
+const ANTHROPIC_DEFAULT_BASE_URL: &str = "https://api.anthropic.com/v1";
+const ANTHROPIC_DEFAULT_VERSION: &str = "2023-06-01";
+const ANTHROPIC_DEFAULT_MAX_TOKENS: u32 = 4096;
+
+/// Configuration for the Anthropic Messages API.
+#[derive(Clone, Debug)]
+pub struct AnthropicChatModelConfig {
+    pub model: String,
+    pub api_key: Option<String>,
+    pub base_url: String,
+    pub api_version: String,
+    pub beta: Option<String>,
+    /// Used when a request does not set `max_output_tokens` (the API requires it).
+    pub default_max_tokens: u32,
+    /// Deadline for the response head, and the longest wait between body
+    /// chunks; a stream that keeps sending may run longer.
+    pub timeout_ms: u64,
+    /// Resolves `attachment://` media sources before sending.
+    pub attachment_resolver: Option<Arc<AttachmentResolver>>,
+    /// Defaults to the shared `BlockingPoolTransport`.
+    pub transport: Option<Arc<dyn HttpTransport>>,
+}
+
+impl AnthropicChatModelConfig {
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            model: model.into(),
+            api_key: None,
+            base_url: ANTHROPIC_DEFAULT_BASE_URL.to_string(),
+            api_version: ANTHROPIC_DEFAULT_VERSION.to_string(),
+            beta: None,
+            default_max_tokens: ANTHROPIC_DEFAULT_MAX_TOKENS,
+            timeout_ms: 60_000,
+            attachment_resolver: None,
+            transport: None,
+        }
+    }
+
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    pub fn with_api_version(mut self, api_version: impl Into<String>) -> Self {
+        self.api_version = api_version.into();
+        self
+    }
+
+    /// Value for the `anthropic-beta` header.
+    pub fn with_beta(mut self, beta: impl Into<String>) -> Self {
+        self.beta = Some(beta.into());
+        self
+    }
+
+    pub fn with_default_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.default_max_tokens = max_tokens;
+        self
+    }
+
+    pub fn with_timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.timeout_ms = timeout_ms;
+        self
+    }
+
+    pub fn with_attachment_resolver(mut self, resolver: Arc<AttachmentResolver>) -> Self {
+        self.attachment_resolver = Some(resolver);
+        self
+    }
+
+    pub fn with_transport(mut self, transport: Arc<dyn HttpTransport>) -> Self {
+        self.transport = Some(transport);
+        self
+    }
+}
+
+/// Anthropic Messages adapter implementing the ChatModel interface.
+#[derive(Clone, Debug)]
+pub struct AnthropicChatModel {
+    model: String,
+    api_key: String,
+    base_url: String,
+    api_version: String,
+    beta: Option<String>,
+    default_max_tokens: u32,
+    timeout_ms: u64,
+    attachment_resolver: Option<Arc<AttachmentResolver>>,
+    transport: Arc<dyn HttpTransport>,
+}
+
+impl AnthropicChatModel {
+    pub fn new(config: AnthropicChatModelConfig) -> GraphResult<Self> {
+        let api_key = resolve_api_key(config.api_key)?;
+        if config.model.trim().is_empty() {
+            return Err(anthropic_error("model is required"));
+        }
+        Ok(Self {
+            model: config.model,
+            api_key,
+            base_url: config.base_url,
+            api_version: config.api_version,
+            beta: config.beta,
+            default_max_tokens: config.default_max_tokens.max(1),
+            timeout_ms: config.timeout_ms.max(1),
+            attachment_resolver: config.attachment_resolver,
+            transport: config.transport.unwrap_or_else(default_transport),
+        })
+    }
+
+    fn endpoint(&self) -> String {
+        format!("{}/messages", self.base_url.trim_end_matches('/'))
+    }
+
+    fn request_payload(&self, request: &ChatRequest) -> GraphResult<serde_json::Value> {
+        let request = prepare_media(
+            request,
+            self.attachment_resolver.as_deref(),
+            check_media_part,
+        )?;
+        Ok(build_request_payload(
+            &self.model,
+            self.default_max_tokens,
+            &request,
+        ))
+    }
+
+    /// POST a payload to the messages endpoint, mapping HTTP errors.
+    async fn send(
+        &self,
+        payload: serde_json::Value,
+        cancellation: Option<&CancellationToken>,
+    ) -> GraphResult<HttpResponse> {
+        let mut request = HttpRequest::new(self.endpoint(), payload)
+            .with_header("x-api-key", &self.api_key)
+            .with_header("anthropic-version", &self.api_version)
+            .with_header("Content-Type", "application/json")
+            .with_timeout_ms(self.timeout_ms)
+            .with_read_timeout_ms(self.timeout_ms);
+        if let Some(beta) = self.beta.as_deref() {
+            request = request.with_header("anthropic-beta", beta);
+        }
+        send_request(
+            self.transport.as_ref(),
+            "anthropic",
+            request,
+            cancellation,
+            parse_error_message,
+        )
+        .await
+    }
+}
+
+impl ChatModel for AnthropicChatModel {
+    fn model_id(&self) -> &str {
+        &self.model
+    }
+
+    fn generate(&self, request: ChatRequest) -> BoxFuture<'_, GraphResult<ChatResponse>> {
+        let payload = self.request_payload(&request);
+        Box::pin(async move {
+            request.check_cancelled()?;
+            let cancellation = request.cancellation.as_ref();
+            let response = self.send(payload?, cancellation).await?;
+            let body = cancellable(cancellation, response.text()).await?;
+            let response_json = serde_json::from_str(&body)
+                .map_err(|err| anthropic_error(format!("decode response failed: {}", err)))?;
+            parse_message_response(response_json)
+        })
+    }
+
+    /// Stream with `stream: true`, emitting `TextDelta` per `text_delta`.
+    ///
+    /// `tool_use` input is assembled from `input_json_delta` fragments and
+    /// usage is merged from `message_start` and `message_delta`.
+    /// Cancellation is checked between events.
+    fn stream(
+        &self,
+        request: ChatRequest,
+        sink: Arc<dyn EventSink>,
+    ) -> BoxFuture<'_, GraphResult<ChatResponse>> {
+        let payload = self.request_payload(&request).map(|mut payload| {
+            payload["stream"] = serde_json::json!(true);
+            payload
+        });
+        Box::pin(async move {
+            request.check_cancelled()?;
+            let cancellation = request.cancellation.as_ref();
+            let mut body = self.send(payload?, cancellation).await?.into_body();
+            let mut stream = StreamAssembler::default();
+            while let Some(line) = cancellable(cancellation, body.next_line()).await? {
+                let Some(data) = line.strip_prefix("data:").map(str::trim) else {
+                    continue;
+                };
+                let event: serde_json::Value = serde_json::from_str(data).map_err(|err| {
+                    anthropic_error(format!("decode stream event failed: {}", err))
+                })?;
+                match stream.push(&event)? {
+                    StreamStep::Text(delta) => sink.emit(Event::TextDelta {
+                        session_id: request.session_id.clone(),
+                        message_id: request.message_id.clone(),
+                        delta,
+                    })?,
+                    StreamStep::Continue => {}
+                    StreamStep::Stop => break,
+                }
+            }
+            parse_message_response(stream.into_response_json())
+        })
+    }
+}
+
+fn resolve_api_key(explicit: Option<String>) -> GraphResult<String> {
+    if let Some(key) = explicit {
+        if !key.trim().is_empty() {
+            return Ok(key);
+        }
+    }
+    if let Ok(key) = std::env::var("ANTHROPIC_API_KEY") {
+        if !key.trim().is_empty() {
+            return Ok(key);
+        }
+    }
+    Err(anthropic_error(
+        "missing ANTHROPIC_API_KEY (provide config.api_key or environment variable)",
+    ))
+}
+
+fn anthropic_error(message: impl Into<String>) -> GraphError {
+    GraphError::ExecutionError {
+        node: "provider:anthropic".to_string(),
+        message: message.into(),
+    }
+}
+
+fn build_request_payload(
+    model: &str,
+    default_max_tokens: u32,
+    request: &ChatRequest,
+) -> serde_json::Value {
+    // No native JSON-schema mode: the schema becomes a system instruction.
+    let system = request
+        .messages
+        .iter()
+        .filter(|message| message.role == MessageRole::System)
+        .map(message_text)
+        .chain(
+            request
+                .response_schema
+                .iter()
+                .map(|schema| schema.instruction()),
+        )
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    // The API requires alternating user/assistant turns, so consecutive
+    // messages with the same role (e.g. user text after tool results) merge.
+    let mut messages: Vec<serde_json::Value> = Vec::new();
+    for message in &request.messages {
+        let role = match message.role {
+            MessageRole::System => continue,
+            MessageRole::User | MessageRole::Tool => "user",
+            MessageRole::Assistant => "assistant",
+        };
+        let blocks = content_blocks(message);
+        if blocks.is_empty() {
+            continue;
+        }
+        match messages.last_mut() {
+            Some(last) if last["role"] == role => {
+                if let Some(content) = last["content"].as_array_mut() {
+                    content.extend(blocks);
+                }
+            }
+            _ => messages.push(serde_json::json!({"role": role, "content": blocks})),
+        }
+    }
+
+    let mut payload = serde_json::json!({
+        "model": model,
+        "max_tokens": request.max_output_tokens.unwrap_or(default_max_tokens),
+        "messages": messages,
+    });
+    if !system.is_empty() {
+        payload["system"] = serde_json::json!(system);
+    }
+    if let Some(temperature) = request.temperature {
+        payload["temperature"] = serde_json::json!(temperature);
+    }
+    if !request.tools.is_empty() {
+        payload["tools"] = request.tools.iter().map(render_tool).collect();
+    }
+    if let Some(tool_choice) = &request.tool_choice {
+        payload["tool_choice"] = render_tool_choice(tool_choice);
+    }
+    payload
+}
+
+fn render_tool(tool: &ToolDefinition) -> serde_json::Value {
+    let input_schema = tool
+        .input_schema
+        .clone()
+        .unwrap_or_else(|| serde_json::json!({"type": "object", "properties": {}}));
+    serde_json::json!({
+        "name": tool.name,
+        "description": tool.description,
+        "input_schema": input_schema,
+    })
+}
+
+fn render_tool_choice(tool_choice: &ToolChoice) -> serde_json::Value {
+    match tool_choice {
+        ToolChoice::Auto => serde_json::json!({"type": "auto"}),
+        ToolChoice::None => serde_json::json!({"type": "none"}),
+        ToolChoice::Required => serde_json::json!({"type": "any"}),
+        ToolChoice::Tool(name) => serde_json::json!({"type": "tool", "name": name}),
+    }
+}
+
+fn message_text(message: &Message) -> String {
+    message
+        .parts
+        .iter()
+        .filter_map(|part| match part {
+            Part::TextDelta { delta } => Some(delta.as_str()),
+            Part::TextFinal { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn content_blocks(message: &Message) -> Vec<serde_json::Value> {
+    let mut blocks = Vec::new();
+    let text = message_text(message);
+    if !text.is_empty() {
+        blocks.push(serde_json::json!({"type": "text", "text": text}));
+    }
+    for part in &message.parts {
+        match part {
+            Part::ToolCall {
+                tool,
+                call_id,
+                input,
+            } => blocks.push(serde_json::json!({
+                "type": "tool_use",
+                "id": call_id,
+                "name": tool,
+                "input": tool_input(input),
+            })),
+            Part::ToolResult {
+                call_id, output, ..
+            } => blocks.push(serde_json::json!({
+                "type": "tool_result",
+                "tool_use_id": call_id,
+                "content": render_tool_content(&output.content),
+            })),
+            Part::ToolError { call_id, error, .. } => blocks.push(serde_json::json!({
+                "type": "tool_result",
+                "tool_use_id": call_id,
+                "content": error,
+                "is_error": true,
+            })),
+            Part::Attachment { data, .. } => blocks.push(serde_json::json!({
+                "type": "text",
+                "text": data.to_string(),
+            })),
+            Part::Error { message } => blocks.push(serde_json::json!({
+                "type": "text",
+                "text": message,
+            })),
+            Part::Image {
+                source, mime_type, ..
+            } => blocks.push(serde_json::json!({
+                "type": "image",
+                "source": media_source(source, mime_type),
+            })),
+            Part::File {
+                source,
+                mime_type,
+                name,
+            } => {
+                let mut block = serde_json::json!({
+                    "type": "document",
+                    "source": media_source(source, mime_type),
+                });
+                if let Some(name) = name {
+                    block["title"] = serde_json::json!(name);
+                }
+                blocks.push(block);
+            }
+            _ => {}
+        }
+    }
+    blocks
+}
+
+fn media_source(source: &MediaSource, mime_type: &str) -> serde_json::Value {
+    match source {
+        MediaSource::Base64 { data } => serde_json::json!({
+            "type": "base64",
+            "media_type": mime_type,
+            "data": data,
+        }),
+        MediaSource::Url { url } => serde_json::json!({"type": "url", "url": url}),
+        MediaSource::Attachment { reference } => {
+            serde_json::json!({"type": "url", "url": reference})
+        }
+    }
+}
+
+/// The Messages API has no audio input.
+fn check_media_part(part: &Part) -> GraphResult<()> {
+    match part {
+        Part::Audio { .. } => Err(anthropic_error("audio parts are not supported")),
+        _ => Ok(()),
+    }
+}
+
+/// `tool_use.input` must be an object; raw argument strings are decoded
+/// when possible.
+fn tool_input(input: &serde_json::Value) -> serde_json::Value {
+    match input {
+        serde_json::Value::Object(_) => input.clone(),
+        serde_json::Value::String(raw) => match serde_json::from_str(raw) {
+            Ok(value @ serde_json::Value::Object(_)) => value,
+            _ => serde_json::json!({"input": raw}),
+        },
+        serde_json::Value::Null => serde_json::json!({}),
+        other => serde_json::json!({"input": other}),
+    }
+}
+
+fn render_tool_content(content: &serde_json::Value) -> String {
+    match content {
+        serde_json::Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn parse_message_response(value: serde_json::Value) -> GraphResult<ChatResponse> {
+    let blocks = value
+        .get("content")
+        .and_then(|content| content.as_array())
+        .ok_or_else(|| anthropic_error("missing content in response"))?;
+
+    let mut message = Message::new(MessageRole::Assistant);
+    let mut text = String::new();
+    let mut tool_calls = Vec::new();
+    for block in blocks {
+        match block.get("type").and_then(|kind| kind.as_str()) {
+            Some("text") => {
+                if let Some(chunk) = block.get("text").and_then(|v| v.as_str()) {
+                    text.push_str(chunk);
+                }
+            }
+            Some("tool_use") => tool_calls.push(Part::ToolCall {
+                tool: block
+                    .get("name")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+                call_id: block
+                    .get("id")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+                input: block
+                    .get("input")
+                    .cloned()
+                    .unwrap_or_else(|| serde_json::json!({})),
+            }),
+            _ => {}
+        }
+    }
+    if !text.is_empty() {
+        message.parts.push(Part::TextFinal { text });
+    }
+    message.parts.extend(tool_calls);
+    message.metadata = serde_json::json!({
+        "provider": "anthropic",
+        "raw": {"content": blocks},
+    });
+
+    let mut response = ChatResponse::new(message);
+    response.model = value
+        .get("model")
+        .and_then(|model| model.as_str())
+        .map(|model| model.to_string());
+    response.finish_reason = value
+        .get("stop_reason")
+        .and_then(|reason| reason.as_str())
+        .map(|reason| reason.to_string());
+    response.usage = value.get("usage").map(parse_usage);
+    if let Some(id) = value.get("id").cloned() {
+        response.metadata.insert("id".to_string(), id);
+    }
+    Ok(response)
+}
+
+fn parse_usage(usage: &serde_json::Value) -> TokenUsage {
+    let field = |key: &str| usage.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+    TokenUsage {
+        input: field("input_tokens"),
+        output: field("output_tokens"),
+        reasoning: 0,
+        cache_read: field("cache_read_input_tokens"),
+        cache_write: field("cache_creation_input_tokens"),
+    }
+}
+
+fn parse_error_message(body: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(body).ok()?;
+    let error = value.get("error")?;
+    let message = error.get("message")?.as_str()?;
+    Some(match error.get("type").and_then(|kind| kind.as_str()) {
+        Some(kind) => format!("{}: {}", kind, message),
+        None => message.to_string(),
+    })
+}
+
+enum StreamStep {
+    Text(String),
+    Continue,
+    Stop,
+}
+
+/// Accumulates Messages API stream events into a full response.
+#[derive(Debug, Default)]
+struct StreamAssembler {
+    message: serde_json::Map<String, serde_json::Value>,
+    blocks: BTreeMap<u64, StreamedBlock>,
+    usage: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug)]
+struct StreamedBlock {
+    block: serde_json::Value,
+    partial_json: String,
+}
+
+impl StreamAssembler {
+    fn push(&mut self, event: &serde_json::Value) -> GraphResult<StreamStep> {
+        let index = event.get("index").and_then(|v| v.as_u64()).unwrap_or(0);
+        match event.get("type").and_then(|kind| kind.as_str()) {
+            Some("message_start") => {
+                if let Some(message) = event.get("message").and_then(|m| m.as_object()) {
+                    self.message = message.clone();
+                    self.merge_usage(message.get("usage"));
+                }
+            }
+            Some("content_block_start") => {
+                let block = event
+                    .get("content_block")
+                    .cloned()
+                    .unwrap_or_else(|| serde_json::json!({}));
+                self.blocks.insert(
+                    index,
+                    StreamedBlock {
+                        block,
+                        partial_json: String::new(),
+                    },
+                );
+            }
+            Some("content_block_delta") => {
+                let delta = event.get("delta").cloned().unwrap_or_default();
+                let Some(entry) = self.blocks.get_mut(&index) else {
+                    return Ok(StreamStep::Continue);
+                };
+                match delta.get("type").and_then(|kind| kind.as_str()) {
+                    Some("text_delta") => {
+                        let text = delta.get("text").and_then(|v| v.as_str()).unwrap_or("");
+                        let current = entry.block["text"].as_str().unwrap_or("").to_string();
+                        entry.block["text"] = serde_json::json!(current + text);
+                        if !text.is_empty() {
+                            return Ok(StreamStep::Text(text.to_string()));
+                        }
+                    }
+                    Some("input_json_delta") => {
+                        if let Some(partial) = delta.get("partial_json").and_then(|v| v.as_str()) {
+                            entry.partial_json.push_str(partial);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Some("message_delta") => {
+                if let Some(delta) = event.get("delta").and_then(|d| d.as_object()) {
+                    for (key, value) in delta {
+                        self.message.insert(key.clone(), value.clone());
+                    }
+                }
+                self.merge_usage(event.get("usage"));
+            }
+            Some("message_stop") => return Ok(StreamStep::Stop),
+            Some("error") => {
+                let detail = event
+                    .get("error")
+                    .map(|error| {
+                        let message = error.get("message").and_then(|m| m.as_str());
+                        match (error.get("type").and_then(|t| t.as_str()), message) {
+                            (Some(kind), Some(message)) => format!("{}: {}", kind, message),
+                            (_, Some(message)) => message.to_string(),
+                            _ => error.to_string(),
+                        }
+                    })
+                    .unwrap_or_else(|| "unknown error".to_string());
+                return Err(anthropic_error(format!(
+                    "anthropic stream failed: {}",
+                    detail
+                )));
+            }
+            _ => {}
+        }
+        Ok(StreamStep::Continue)
+    }
+
+    fn merge_usage(&mut self, usage: Option<&serde_json::Value>) {
+        if let Some(usage) = usage.and_then(|u| u.as_object()) {
+            for (key, value) in usage {
+                if !value.is_null() {
+                    self.usage.insert(key.clone(), value.clone());
+                }
+            }
+        }
+    }
+
+    fn into_response_json(self) -> serde_json::Value {
+        let content = self
+            .blocks
+            .into_values()
+            .map(|entry| {
+                let mut block = entry.block;
+                if block["type"] == "tool_use" && !entry.partial_json.is_empty() {
+                    block["input"] = serde_json::from_str(&entry.partial_json)
+                        .unwrap_or(serde_json::Value::String(entry.partial_json));
+                }
+                block
+            })
+            .collect::<Vec<_>>();
+        let mut message = serde_json::Value::Object(self.message);
+        message["content"] = serde_json::Value::Array(content);
+        message["usage"] = serde_json::Value::Object(self.usage);
+        message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        build_request_payload, parse_error_message, parse_message_response, AnthropicChatModel,
+        AnthropicChatModelConfig,
+    };
+    use crate::runtime::component::{ChatModel, ChatRequest, ResponseSchema, ToolChoice};
+    use crate::runtime::error::GraphResult;
+    use crate::runtime::event::{Event, EventSink, TokenUsage};
+    use crate::runtime::message::{MediaSource, Message, MessageRole, Part};
+    use crate::runtime::provider::stub::{StubResponse, StubServer};
+    use crate::runtime::tool::{ToolDefinition, ToolOutput};
+    use futures::executor::block_on;
+    use std::sync::{Arc, Mutex};
+
+    fn text_message(role: MessageRole, text: &str) -> Message {
+        let mut message = Message::new(role);
+        message.parts.push(Part::TextFinal {
+            text: text.to_string(),
+        });
+        message
+    }
+
+    fn stub_model(url: &str) -> AnthropicChatModel {
+        AnthropicChatModel::new(
+            AnthropicChatModelConfig::new("claude-test")
+                .with_api_key("test-key")
+                .with_base_url(format!("{}/v1", url)),
+        )
+        .expect("construct")
+    }
+
+    #[test]
+    fn request_payload_extracts_system_and_maps_tool_blocks() {
+        let mut assistant = text_message(MessageRole::Assistant, "Let me look.");
+        assistant.parts.push(Part::ToolCall {
+            tool: "grep".to_string(),
+            call_id: "toolu_1".to_string(),
+            input: serde_json::json!({"pattern": "todo"}),
+        });
+        let mut results = Message::new(MessageRole::Tool);
+        results.parts.push(Part::ToolResult {
+            tool: "grep".to_string(),
+            call_id: "toolu_1".to_string(),
+            output: ToolOutput::text("src/lib.rs:1"),
+        });
+        let request = ChatRequest::new(
+            "s1",
+            "m1",
+            vec![
+                text_message(MessageRole::System, "Be terse."),
+                text_message(MessageRole::User, "Find todos"),
+                assistant,
+                results,
+                text_message(MessageRole::User, "Thanks"),
+            ],
+        )
+        .with_tool(ToolDefinition::new("grep", "Search files"))
+        .with_tool_choice(ToolChoice::Required);
+
+        let payload = build_request_payload("claude-test", 1024, &request);
+        assert_eq!(payload["system"], "Be terse.");
+        assert_eq!(payload["max_tokens"], 1024);
+        let messages = payload["messages"].as_array().expect("messages");
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[1]["role"], "assistant");
+        assert_eq!(messages[1]["content"][1]["type"], "tool_use");
+        assert_eq!(messages[1]["content"][1]["input"]["pattern"], "todo");
+        assert_eq!(messages[2]["role"], "user");
+        assert_eq!(messages[2]["content"][0]["type"], "tool_result");
+        assert_eq!(messages[2]["content"][0]["tool_use_id"], "toolu_1");
+        assert_eq!(messages[2]["content"][1]["text"], "Thanks");
+        assert_eq!(payload["tools"][0]["input_schema"]["type"], "object");
+        assert_eq!(payload["tool_choice"]["type"], "any");
+    }
+
+    #[test]
+    fn request_payload_appends_response_schema_to_system_prompt() {
+        let request = ChatRequest::new(
+            "s1",
+            "m1",
+            vec![text_message(MessageRole::System, "Be terse.")],
+        )
+        .with_response_schema(ResponseSchema::new(
+            "answer",
+            serde_json::json!({"type": "object"}),
+        ));
+        let payload = build_request_payload("claude-test", 1024, &request);
+        let system = payload["system"].as_str().expect("system");
+        assert!(system.starts_with("Be terse.\n\nRespond with only a JSON value"));
+        assert!(system.ends_with("{\"type\":\"object\"}"));
+    }
+
+    #[test]
+    fn request_payload_maps_image_and_document_blocks() {
+        let mut message = text_message(MessageRole::User, "Compare these");
+        message
+            .parts
+            .push(Part::image(MediaSource::bytes(b"\x89PNG"), "image/png"));
+        message.parts.push(Part::file(
+            MediaSource::url("https://example.com/spec.pdf"),
+            "application/pdf",
+            Some("spec".to_string()),
+        ));
+        let request = ChatRequest::new("s1", "m1", vec![message]);
+
+        let payload = build_request_payload("claude-test", 1024, &request);
+        assert_eq!(
+            payload["messages"][0]["content"],
+            serde_json::json!([
+                {"type": "text", "text": "Compare these"},
+                {"type": "image", "source": {"type": "base64", "media_type": "image/png", "data": "iVBORw=="}},
+                {"type": "document", "source": {"type": "url", "url": "https://example.com/spec.pdf"}, "title": "spec"}
+            ])
+        );
+
+        let mut audio = Message::new(MessageRole::User);
+        audio
+            .parts
+            .push(Part::audio(MediaSource::bytes(b"RIFF"), "audio/wav"));
+        let model = stub_model("http://127.0.0.1:9");
+        let err = block_on(model.generate(ChatRequest::new("s1", "m2", vec![audio])))
+            .expect_err("audio is unsupported");
+        assert!(err.to_string().contains("audio parts are not supported"));
+    }
+
+    #[test]
+    fn parse_message_response_maps_blocks_and_cache_usage() {
+        let response = serde_json::json!({
+            "id": "msg_1",
+            "model": "claude-test",
+            "stop_reason": "tool_use",
+            "content": [
+                {"type": "text", "text": "Checking."},
+                {"type": "tool_use", "id": "toolu_1", "name": "grep", "input": {"pattern": "x"}}
+            ],
+            "usage": {
+                "input_tokens": 20,
+                "output_tokens": 7,
+                "cache_read_input_tokens": 100,
+                "cache_creation_input_tokens": 30
+            }
+        });
+
+        let parsed = parse_message_response(response).expect("parse");
+        assert_eq!(parsed.text().as_deref(), Some("Checking."));
+        assert_eq!(parsed.finish_reason.as_deref(), Some("tool_use"));
+        assert_eq!(parsed.tool_calls()[0].call_id, "toolu_1");
+        assert_eq!(
+            parsed.usage,
+            Some(TokenUsage {
+                input: 20,
+                output: 7,
+                reasoning: 0,
+                cache_read: 100,
+                cache_write: 30,
+            })
+        );
+    }
+
+    #[test]
+    fn generate_sends_headers_and_maps_errors() {
+        let server = StubServer::start(vec![
+            StubResponse::json(
+                200,
+                serde_json::json!({
+                    "model": "claude-test",
+                    "stop_reason": "end_turn",
+                    "content": [{"type": "text", "text": "pong"}],
+                    "usage": {"input_tokens": 3, "output_tokens": 1}
+                }),
+            ),
+            StubResponse::json(
+                529,
+                serde_json::json!({
+                    "type": "error",
+                    "error": {"type": "overloaded_error", "message": "Overloaded"}
+                }),
+            ),
+        ]);
+        let model = stub_model(&server.url);
+        let request = ChatRequest::new("s1", "m1", vec![text_message(MessageRole::User, "ping")]);
+
+        let response = block_on(model.generate(request.clone())).expect("generate");
+        assert_eq!(response.text().as_deref(), Some("pong"));
+        let err = block_on(model.generate(request)).expect_err("overloaded");
+        assert!(err
+            .to_string()
+            .contains("status 529: overloaded_error: Overloaded"));
+
+        let requests = server.finish();
+        assert!(requests[0].request_line.starts_with("POST /v1/messages"));
+        assert_eq!(requests[0].header("x-api-key"), Some("test-key"));
+        assert_eq!(requests[0].header("anthropic-version"), Some("2023-06-01"));
+    }
+
+    struct CaptureSink {
+        events: Arc<Mutex<Vec<Event>>>,
+    }
+
+    impl EventSink for CaptureSink {
+        fn emit(&self, event: Event) -> GraphResult<()> {
+            self.events.lock().unwrap().push(event);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn stream_assembles_text_tool_use_and_usage() {
+        let events = [
+            (
+                "message_start",
+                serde_json::json!({"type": "message_start", "message": {
+                    "id": "msg_2", "model": "claude-test", "content": [],
+                    "usage": {"input_tokens": 15, "output_tokens": 1, "cache_read_input_tokens": 8}
+                }}),
+            ),
+            (
+                "content_block_start",
+                serde_json::json!({"type": "content_block_start", "index": 0,
+                "content_block": {"type": "text", "text": ""}}),
+            ),
+            ("ping", serde_json::json!({"type": "ping"})),
+            (
+                "content_block_delta",
+                serde_json::json!({"type": "content_block_delta", "index": 0,
+                "delta": {"type": "text_delta", "text": "Hel"}}),
+            ),
+            (
+                "content_block_delta",
+                serde_json::json!({"type": "content_block_delta", "index": 0,
+                "delta": {"type": "text_delta", "text": "lo"}}),
+            ),
+            (
+                "content_block_stop",
+                serde_json::json!({"type": "content_block_stop", "index": 0}),
+            ),
+            (
+                "content_block_start",
+                serde_json::json!({"type": "content_block_start", "index": 1,
+                "content_block": {"type": "tool_use", "id": "toolu_9", "name": "grep", "input": {}}}),
+            ),
+            (
+                "content_block_delta",
+                serde_json::json!({"type": "content_block_delta", "index": 1,
+                "delta": {"type": "input_json_delta", "partial_json": "{\"pattern\":"}}),
+            ),
+            (
+                "content_block_delta",
+                serde_json::json!({"type": "content_block_delta", "index": 1,
+                "delta": {"type": "input_json_delta", "partial_json": " \"todo\"}"}}),
+            ),
+            (
+                "content_block_stop",
+                serde_json::json!({"type": "content_block_stop", "index": 1}),
+            ),
+            (
+                "message_delta",
+                serde_json::json!({"type": "message_delta",
+                "delta": {"stop_reason": "tool_use"}, "usage": {"output_tokens": 12}}),
+            ),
+            ("message_stop", serde_json::json!({"type": "message_stop"})),
+        ];
+        let body = events
+            .iter()
+            .map(|(name, data)| format!("event: {}\ndata: {}\n\n", name, data))
+            .collect::<String>();
+        let server = StubServer::start(vec![StubResponse::raw_sse(body)]);
+        let model = stub_model(&server.url);
+        let captured = Arc::new(Mutex::new(Vec::new()));
+        let sink: Arc<dyn EventSink> = Arc::new(CaptureSink {
+            events: captured.clone(),
+        });
+        let request = ChatRequest::new("s1", "m1", vec![text_message(MessageRole::User, "hi")]);
+
+        let response = block_on(model.stream(request, sink)).expect("stream");
+        assert_eq!(captured.lock().unwrap().len(), 2);
+        assert_eq!(response.text().as_deref(), Some("Hello"));
+        assert_eq!(response.finish_reason.as_deref(), Some("tool_use"));
+        assert_eq!(
+            response.tool_calls()[0].input,
+            serde_json::json!({"pattern": "todo"})
+        );
+        let usage = response.usage.expect("usage");
+        assert_eq!((usage.input, usage.output, usage.cache_read), (15, 12, 8));
+        assert_eq!(server.finish()[0].json()["stream"], true);
+    }
+
+    #[test]
+    fn stream_surfaces_error_events() {
+        let body = format!(
+            "event: error\ndata: {}\n\n",
+            serde_json::json!({"type": "error", "error": {"type": "overloaded_error", "message": "busy"}})
+        );
+        let server = StubServer::start(vec![StubResponse::raw_sse(body)]);
+        let model = stub_model(&server.url);
+        let sink: Arc<dyn EventSink> = Arc::new(CaptureSink {
+            events: Arc::new(Mutex::new(Vec::new())),
+        });
+        let request = ChatRequest::new("s1", "m1", vec![text_message(MessageRole::User, "hi")]);
+
+        let err = block_on(model.stream(request, sink)).expect_err("error event");
+        assert!(err.to_string().contains("overloaded_error: busy"));
+        server.finish();
+    }
+
+    #[test]
+    fn parse_error_message_includes_error_type() {
+        let body = r#"{"type":"error","error":{"type":"invalid_request_error","message":"bad"}}"#;
+        assert_eq!(
+            parse_error_message(body).as_deref(),
+            Some("invalid_request_error: bad")
+        );
+    }
+}
@@ -0,0 +1,632 @@
+//! Record/replay cassettes for deterministic, offline model tests.
+//!
+//! In record mode [`CassetteChatModel`] forwards requests to a real model and
+//! appends each request/response pair (plus any streamed events) to a JSON
+//! fixture. In replay mode it serves those fixtures without touching the
+//! network, matching requests by normalized content and failing loudly when
+//! nothing matches.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::runtime::component::{ChatModel, ChatRequest, ChatResponse};
+use crate::runtime::error::{GraphError, GraphResult};
+use crate::runtime::event::{Event, EventSink};
+use crate::runtime::message::Part;
+use crate::runtime::node::BoxFuture;
+
+pub const CASSETTE_VERSION: u32 = 1;
+
+type Normalizer = Arc<dyn Fn(&mut serde_json::Value) + Send + Sync>;
+
+/// On-disk cassette contents.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Cassette {
+    pub version: u32,
+    pub model_id: String,
+    pub interactions: Vec<Interaction>,
+}
+
+/// One recorded model call.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    /// SHA-256 of the normalized request.
+    pub key: String,
+    /// Normalized request, kept for readable fixture diffs.
+    pub request: serde_json::Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response: Option<ChatResponse>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<RecordedError>,
+    /// Events emitted while streaming, in order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<Event>,
+}
+
+/// A recorded failure, replayed as the same kind of `GraphError`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecordedError {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_after_ms: Option<u64>,
+    pub message: String,
+}
+
+impl RecordedError {
+    fn from_error(error: &GraphError) -> Self {
+        match error {
+            GraphError::ProviderError {
+                provider,
+                status,
+                retry_after_ms,
+                message,
+            } => Self {
+                provider: Some(provider.clone()),
+                status: *status,
+                retry_after_ms: *retry_after_ms,
+                message: message.clone(),
+            },
+            other => Self {
+                provider: None,
+                status: None,
+                retry_after_ms: None,
+                message: other.to_string(),
+            },
+        }
+    }
+
+    fn to_error(&self) -> GraphError {
+        match &self.provider {
+            Some(provider) => GraphError::ProviderError {
+                provider: provider.clone(),
+                status: self.status,
+                retry_after_ms: self.retry_after_ms,
+                message: self.message.clone(),
+            },
+            None => cassette_error(self.message.clone()),
+        }
+    }
+}
+
+impl Cassette {
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let data = std::fs::read_to_string(path)?;
+        serde_json::from_str(&data)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+        let data = serde_json::to_string_pretty(self)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+        std::fs::write(path, data + "\n")
+    }
+}
+
+/// Whether a [`CassetteChatModel`] talks to its inner model or the fixture.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CassetteMode {
+    Record,
+    Replay,
+}
+
+/// Normalized form of a request used for matching.
+///
+/// Session/message ids, message ids and timestamps, request metadata and
+/// cancellation are dropped; text parts of a message are joined and trimmed.
+pub fn normalize_request(request: &ChatRequest) -> serde_json::Value {
+    let messages = request
+        .messages
+        .iter()
+        .map(|message| {
+            let mut text = String::new();
+            let mut parts = Vec::new();
+            for part in &message.parts {
+                match part {
+                    Part::TextDelta { delta } => text.push_str(delta),
+                    Part::TextFinal { text: chunk } => text.push_str(chunk),
+                    Part::TokenUsage { .. } => {}
+                    other => parts.push(serde_json::to_value(other).unwrap_or_default()),
+                }
+            }
+            serde_json::json!({
+                "role": message.role,
+                "text": text.trim(),
+                "parts": parts,
+            })
+        })
+        .collect::<Vec<_>>();
+    serde_json::json!({
+        "messages": messages,
+        "temperature": request.temperature,
+        "max_output_tokens": request.max_output_tokens,
+        "tools": request.tools,
+        "tool_choice": request.tool_choice,
+        "response_schema": request.response_schema,
+    })
+}
+
+fn request_key(normalized: &serde_json::Value) -> String {
+    let digest = Sha256::digest(normalized.to_string().as_bytes());
+    digest.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn cassette_error(message: impl Into<String>) -> GraphError {
+    GraphError::ExecutionError {
+        node: "provider:cassette".to_string(),
+        message: message.into(),
+    }
+}
+
+struct CassetteState {
+    cassette: Cassette,
+    used: Vec<bool>,
+}
+
+/// Records model calls to, or replays them from, a cassette file.
+pub struct CassetteChatModel {
+    mode: CassetteMode,
+    path: PathBuf,
+    inner: Option<Arc<dyn ChatModel>>,
+    model_id: String,
+    normalizer: Option<Normalizer>,
+    state: Mutex<CassetteState>,
+}
+
+impl CassetteChatModel {
+    /// Forward to `inner`, writing every interaction to `path`. An existing
+    /// cassette at `path` is replaced.
+    pub fn record(inner: Arc<dyn ChatModel>, path: impl Into<PathBuf>) -> Self {
+        let model_id = inner.model_id().to_string();
+        Self {
+            mode: CassetteMode::Record,
+            path: path.into(),
+            inner: Some(inner),
+            model_id: model_id.clone(),
+            normalizer: None,
+            state: Mutex::new(CassetteState {
+                cassette: Cassette {
+                    version: CASSETTE_VERSION,
+                    model_id,
+                    interactions: Vec::new(),
+                },
+                used: Vec::new(),
+            }),
+        }
+    }
+
+    /// Serve interactions from the cassette at `path`.
+    pub fn replay(path: impl Into<PathBuf>) -> GraphResult<Self> {
+        let path = path.into();
+        let cassette = Cassette::load(&path).map_err(|err| {
+            cassette_error(format!("load cassette {} failed: {}", path.display(), err))
+        })?;
+        if cassette.version > CASSETTE_VERSION {
+            return Err(cassette_error(format!(
+                "cassette {} has unsupported version {}",
+                path.display(),
+                cassette.version
+            )));
+        }
+        Ok(Self {
+            mode: CassetteMode::Replay,
+            path,
+            inner: None,
+            model_id: cassette.model_id.clone(),
+            normalizer: None,
+            state: Mutex::new(CassetteState {
+                used: vec![false; cassette.interactions.len()],
+                cassette,
+            }),
+        })
+    }
+
+    /// Replay when `path` exists, otherwise record through `inner`.
+    pub fn record_or_replay(
+        inner: Arc<dyn ChatModel>,
+        path: impl Into<PathBuf>,
+    ) -> GraphResult<Self> {
+        let path = path.into();
+        if path.exists() {
+            Self::replay(path)
+        } else {
+            Ok(Self::record(inner, path))
+        }
+    }
+
+    /// Further normalize requests before matching, e.g. to blank out
+    /// timestamps or temp paths that tools put into their results.
+    pub fn with_normalizer<F>(mut self, normalizer: F) -> Self
+    where
+        F: Fn(&mut serde_json::Value) + Send + Sync + 'static,
+    {
+        self.normalizer = Some(Arc::new(normalizer));
+        self
+    }
+
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Recorded interactions that have not been replayed yet.
+    pub fn unused_interactions(&self) -> Vec<Interaction> {
+        let state = self.state.lock().expect("cassette poisoned");
+        state
+            .cassette
+            .interactions
+            .iter()
+            .zip(&state.used)
+            .filter(|(_, used)| !**used)
+            .map(|(interaction, _)| interaction.clone())
+            .collect()
+    }
+
+    fn normalize(&self, request: &ChatRequest) -> serde_json::Value {
+        let mut normalized = normalize_request(request);
+        if let Some(normalizer) = &self.normalizer {
+            normalizer(&mut normalized);
+        }
+        normalized
+    }
+
+    async fn run(
+        &self,
+        request: ChatRequest,
+        sink: Option<Arc<dyn EventSink>>,
+    ) -> GraphResult<ChatResponse> {
+        request.check_cancelled()?;
+        let normalized = self.normalize(&request);
+        let key = request_key(&normalized);
+        match (&self.inner, self.mode) {
+            (Some(inner), CassetteMode::Record) => {
+                self.record_call(inner, request, sink, key, normalized)
+                    .await
+            }
+            _ => self.replay_call(&request, sink, &key, &normalized),
+        }
+    }
+
+    async fn record_call(
+        &self,
+        inner: &Arc<dyn ChatModel>,
+        request: ChatRequest,
+        sink: Option<Arc<dyn EventSink>>,
+        key: String,
+        normalized: serde_json::Value,
+    ) -> GraphResult<ChatResponse> {
+        let (result, events) = match sink {
+            Some(sink) => {
+                let recorder = Arc::new(RecordingSink {
+                    inner: sink,
+                    events: Mutex::new(Vec::new()),
+                });
+                let result = inner.stream(request, recorder.clone()).await;
+                let events = std::mem::take(&mut *recorder.events.lock().expect("poisoned"));
+                (result, events)
+            }
+            None => (inner.generate(request).await, Vec::new()),
+        };
+        if matches!(result, Err(GraphError::Aborted { .. })) {
+            return result;
+        }
+        let interaction = Interaction {
+            key,
+            request: normalized,
+            response: result.as_ref().ok().cloned(),
+            error: result.as_ref().err().map(RecordedError::from_error),
+            events,
+        };
+        let mut state = self.state.lock().expect("cassette poisoned");
+        state.cassette.interactions.push(interaction);
+        state.used.push(true);
+        state.cassette.save(&self.path).map_err(|err| {
+            cassette_error(format!(
+                "write cassette {} failed: {}",
+                self.path.display(),
+                err
+            ))
+        })?;
+        result
+    }
+
+    fn replay_call(
+        &self,
+        request: &ChatRequest,
+        sink: Option<Arc<dyn EventSink>>,
+        key: &str,
+        normalized: &serde_json::Value,
+    ) -> GraphResult<ChatResponse> {
+        let interaction = {
+            let mut state = self.state.lock().expect("cassette poisoned");
+            let CassetteState { cassette, used } = &mut *state;
+            let index = cassette
+                .interactions
+                .iter()
+                .enumerate()
+                .position(|(index, interaction)| interaction.key == key && !used[index])
+                .ok_or_else(|| {
+                    let recorded = cassette
+                        .interactions
+                        .iter()
+                        .filter(|interaction| interaction.key == key)
+                        .count();
+                    let reason = if recorded > 0 {
+                        format!("all {} matching interaction(s) already replayed", recorded)
+                    } else {
+                        "no recorded interaction matches".to_string()
+                    };
+                    cassette_error(format!(
+                        "cassette {}: {} (key {}); request: {}",
+                        self.path.display(),
+                        reason,
+                        key,
+                        normalized
+                    ))
+                })?;
+            used[index] = true;
+            cassette.interactions[index].clone()
+        };
+
+        if let Some(sink) = sink {
+            if interaction.events.is_empty() {
+                if let Some(text) = interaction.response.as_ref().and_then(|r| r.text()) {
+                    sink.emit(Event::TextFinal {
+                        session_id: request.session_id.clone(),
+                        message_id: request.message_id.clone(),
+                        text,
+                    })?;
+                }
+            }
+            for event in interaction.events {
+                sink.emit(retarget_event(event, request))?;
+            }
+        }
+        match (interaction.response, interaction.error) {
+            (Some(response), _) => Ok(response),
+            (None, Some(error)) => Err(error.to_error()),
+            (None, None) => Err(cassette_error(format!(
+                "cassette {}: interaction {} has neither response nor error",
+                self.path.display(),
+                key
+            ))),
+        }
+    }
+}
+
+/// Point a recorded event at the current request's session and message.
+fn retarget_event(event: Event, request: &ChatRequest) -> Event {
+    let Ok(mut value) = serde_json::to_value(&event) else {
+        return event;
+    };
+    if let Some(fields) = value
+        .as_object_mut()
+        .and_then(|variant| variant.values_mut().next())
+        .and_then(|fields| fields.as_object_mut())
+    {
+        for (key, id) in [
+            ("session_id", &request.session_id),
+            ("message_id", &request.message_id),
+        ] {
+            if fields.contains_key(key) {
+                fields.insert(key.to_string(), serde_json::json!(id));
+            }
+        }
+    }
+    serde_json::from_value(value).unwrap_or(event)
+}
+
+struct RecordingSink {
+    inner: Arc<dyn EventSink>,
+    events: Mutex<Vec<Event>>,
+}
+
+impl EventSink for RecordingSink {
+    fn emit(&self, event: Event) -> GraphResult<()> {
+        self.events
+            .lock()
+            .expect("recording sink poisoned")
+            .push(event.clone());
+        self.inner.emit(event)
+    }
+}
+
+impl ChatModel for CassetteChatModel {
+    fn model_id(&self) -> &str {
+        &self.model_id
+    }
+
+    fn generate(&self, request: ChatRequest) -> BoxFuture<'_, GraphResult<ChatResponse>> {
+        Box::pin(self.run(request, None))
+    }
+
+    fn stream(
+        &self,
+        request: ChatRequest,
+        sink: Arc<dyn EventSink>,
+    ) -> BoxFuture<'_, GraphResult<ChatResponse>> {
+        Box::pin(self.run(request, Some(sink)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CassetteChatModel, CassetteMode};
+    use crate::runtime::component::{ChatModel, ChatRequest, ChatResponse};
+    use crate::runtime::error::{GraphError, GraphResult};
+    use crate::runtime::event::{Event, EventSink};
+    use crate::runtime::message::{Message, MessageRole, Part};
+    use crate::runtime::node::BoxFuture;
+    use crate::runtime::provider::openai::{OpenAiChatModel, OpenAiChatModelConfig};
+    use crate::runtime::provider::stub::{StubResponse, StubServer};
+    use futures::executor::block_on;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct CaptureSink {
+        events: Mutex<Vec<Event>>,
+    }
+
+    impl EventSink for CaptureSink {
+        fn emit(&self, event: Event) -> GraphResult<()> {
+            self.events.lock().unwrap().push(event);
+            Ok(())
+        }
+    }
+
+    fn user_request(session: &str, text: &str) -> ChatRequest {
+        let mut message = Message::new(MessageRole::User);
+        message.parts.push(Part::TextFinal {
+            text: text.to_string(),
+        });
+        ChatRequest::new(session, "m1", vec![message])
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir()
+            .join(format!("forge-cassette-{}", uuid::Uuid::new_v4()))
+            .join(name)
+    }
+
+    #[test]
+    fn records_openai_calls_and_replays_them_offline() {
+        let server = StubServer::start(vec![
+            StubResponse::json(
+                200,
+                serde_json::json!({
+                    "model": "gpt-test",
+                    "choices": [{"message": {"role": "assistant", "content": "pong"}}]
+                }),
+            ),
+            StubResponse::sse(&[
+                serde_json::json!({"choices": [{"index": 0, "delta": {"content": "str"}}]}),
+                serde_json::json!({"choices": [{"index": 0, "delta": {"content": "eamed"},
+                    "finish_reason": "stop"}]}),
+            ]),
+            StubResponse::json(400, serde_json::json!({"error": {"message": "bad"}})),
+        ]);
+        let inner = OpenAiChatModel::new(
+            OpenAiChatModelConfig::new("gpt-test")
+                .with_api_key("test-key")
+                .with_base_url(format!("{}/v1", server.url)),
+        )
+        .expect("construct");
+        let path = temp_path("openai.json");
+        let recorder = CassetteChatModel::record(Arc::new(inner), &path);
+        let sink = Arc::new(CaptureSink::default());
+
+        block_on(recorder.generate(user_request("s1", "ping"))).expect("generate");
+        block_on(recorder.stream(user_request("s1", "stream please"), sink.clone()))
+            .expect("stream");
+        block_on(recorder.generate(user_request("s1", "fail"))).expect_err("400");
+        server.finish();
+        assert_eq!(sink.events.lock().unwrap().len(), 2);
+
+        let replay = CassetteChatModel::replay(&path).expect("load");
+        assert_eq!(replay.mode(), CassetteMode::Replay);
+        assert_eq!(replay.model_id(), "gpt-test");
+        let response = block_on(replay.generate(user_request("other", "  ping "))).unwrap();
+        assert_eq!(response.text().as_deref(), Some("pong"));
+
+        let replay_sink = Arc::new(CaptureSink::default());
+        let response =
+            block_on(replay.stream(user_request("s2", "stream please"), replay_sink.clone()))
+                .unwrap();
+        assert_eq!(response.text().as_deref(), Some("streamed"));
+        let events = replay_sink.events.lock().unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(matches!(
+            &events[0],
+            Event::TextDelta { session_id, delta, .. } if session_id == "s2" && delta == "str"
+        ));
+
+        let err = block_on(replay.generate(user_request("s1", "fail"))).expect_err("replayed");
+        assert!(matches!(
+            err,
+            GraphError::ProviderError {
+                status: Some(400),
+                ..
+            }
+        ));
+        assert!(replay.unused_interactions().is_empty());
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    struct EchoModel;
+
+    impl ChatModel for EchoModel {
+        fn model_id(&self) -> &str {
+            "echo"
+        }
+
+        fn generate(&self, request: ChatRequest) -> BoxFuture<'_, GraphResult<ChatResponse>> {
+            Box::pin(async move {
+                let mut message = Message::new(MessageRole::Assistant);
+                message.parts.push(Part::TextFinal {
+                    text: format!("{} messages", request.messages.len()),
+                });
+                Ok(ChatResponse::new(message))
+            })
+        }
+    }
+
+    fn mask_times(request: &mut serde_json::Value) {
+        let text = request["messages"][0]["text"].as_str().unwrap_or("");
+        let masked = regex::Regex::new(r"\d{2}:\d{2}")
+            .unwrap()
+            .replace_all(text, "<time>")
+            .to_string();
+        request["messages"][0]["text"] = serde_json::json!(masked);
+    }
+
+    #[test]
+    fn replay_fails_loudly_on_unmatched_or_exhausted_requests() {
+        let path = temp_path("echo.json");
+        let recorder = CassetteChatModel::record(Arc::new(EchoModel), &path);
+        block_on(recorder.generate(user_request("s1", "Run at 12:00"))).unwrap();
+
+        let replay = CassetteChatModel::replay(&path).unwrap();
+        let err =
+            block_on(replay.generate(user_request("s1", "something else"))).expect_err("unmatched");
+        let message = err.to_string();
+        assert!(message.contains("no recorded interaction matches"));
+        assert!(message.contains("something else"));
+        assert_eq!(replay.unused_interactions().len(), 1);
+
+        block_on(replay.generate(user_request("s1", "Run at 12:00"))).unwrap();
+        let err =
+            block_on(replay.generate(user_request("s1", "Run at 12:00"))).expect_err("exhausted");
+        assert!(err.to_string().contains("already replayed"));
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn custom_normalizer_masks_volatile_content() {
+        let path = temp_path("masked.json");
+        let recorder =
+            CassetteChatModel::record(Arc::new(EchoModel), &path).with_normalizer(mask_times);
+        block_on(recorder.generate(user_request("s1", "Run at 12:00"))).unwrap();
+
+        let replay = CassetteChatModel::replay(&path)
+            .unwrap()
+            .with_normalizer(mask_times);
+        let response = block_on(replay.generate(user_request("s1", "Run at 13:45"))).unwrap();
+        assert_eq!(response.text().as_deref(), Some("1 messages"));
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+}
@@ -1,3 +1,175 @@
 //! Provider adapters for external model APIs.
 
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::runtime::cancel::{cancellable, CancellationToken};
+use crate::runtime::component::ChatRequest;
+use crate::runtime::error::{GraphError, GraphResult};
+use crate::runtime::event::{Event, EventSink};
+use crate::runtime::message::Part;
+use crate::runtime::provider::transport::{HttpRequest, HttpResponse, HttpTransport};
+use crate::runtime::session::AttachmentResolver;
+
+pub mod anthropic;
+pub mod cassette;
 pub mod openai;
+pub mod openai_embeddings;
+pub mod retry;
+pub mod router;
+pub mod scripted;
+pub mod transport;
+
+#[cfg(test)]
+pub(crate) mod stub;
+
+/// Send `request` through `transport`, mapping failures to
+/// [`GraphError::ProviderError`] and aborting when `cancellation` fires.
+///
+/// Error statuses are read in full; `parse_detail` extracts the provider's
+/// message from a JSON body, falling back to the raw body.
+pub(crate) async fn send_request(
+    transport: &dyn HttpTransport,
+    provider: &str,
+    request: HttpRequest,
+    cancellation: Option<&CancellationToken>,
+    parse_detail: fn(&str) -> Option<String>,
+) -> GraphResult<HttpResponse> {
+    let response = cancellable(cancellation, transport.post(request))
+        .await
+        .map_err(|err| match err {
+            GraphError::ProviderError {
+                status: None,
+                message,
+                ..
+            } => GraphError::ProviderError {
+                provider: provider.to_string(),
+                status: None,
+                retry_after_ms: None,
+                message: format!("{} request failed: {}", provider, message),
+            },
+            other => other,
+        })?;
+    if response.status < 400 {
+        return Ok(response);
+    }
+    let status = response.status;
+    let retry_after_ms = retry_after_ms(&response);
+    let body = cancellable(cancellation, response.text())
+        .await
+        .unwrap_or_default();
+    let detail = parse_detail(&body).unwrap_or(body);
+    Err(GraphError::ProviderError {
+        provider: provider.to_string(),
+        status: Some(status),
+        retry_after_ms,
+        message: format!(
+            "{} request failed with status {}: {}",
+            provider, status, detail
+        ),
+    })
+}
+
+/// Resolve attachment-backed media parts and run `check` on every media
+/// part, so unsupported content fails before anything is sent.
+///
+/// Requests without media are borrowed unchanged.
+pub(crate) fn prepare_media<'a>(
+    request: &'a ChatRequest,
+    resolver: Option<&AttachmentResolver>,
+    check: fn(&Part) -> GraphResult<()>,
+) -> GraphResult<Cow<'a, ChatRequest>> {
+    let has_media = request
+        .messages
+        .iter()
+        .any(|message| message.parts.iter().any(Part::is_media));
+    if !has_media {
+        return Ok(Cow::Borrowed(request));
+    }
+    let mut request = request.clone();
+    for message in &mut request.messages {
+        for part in &mut message.parts {
+            if part.is_media() {
+                *part = part.resolve_media(resolver)?;
+                check(part)?;
+            }
+        }
+    }
+    Ok(Cow::Owned(request))
+}
+
+/// Server-requested delay from `retry-after-ms` or `Retry-After`
+/// (delta-seconds or an HTTP date).
+fn retry_after_ms(resp: &HttpResponse) -> Option<u64> {
+    if let Some(ms) = resp
+        .header("retry-after-ms")
+        .and_then(|value| value.trim().parse::<f64>().ok())
+    {
+        return Some(ms.max(0.0) as u64);
+    }
+    parse_retry_after(resp.header("retry-after")?, chrono::Utc::now())
+}
+
+fn parse_retry_after(value: &str, now: chrono::DateTime<chrono::Utc>) -> Option<u64> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<f64>() {
+        return Some((seconds.max(0.0) * 1000.0) as u64);
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (at.with_timezone(&chrono::Utc) - now)
+            .num_milliseconds()
+            .max(0) as u64,
+    )
+}
+
+/// Forwards events and remembers whether any were emitted, so wrappers
+/// know when a stream can no longer be retried or handed to another model.
+pub(crate) struct TrackingSink {
+    inner: Arc<dyn EventSink>,
+    emitted: AtomicBool,
+}
+
+impl TrackingSink {
+    pub(crate) fn new(inner: Arc<dyn EventSink>) -> Self {
+        Self {
+            inner,
+            emitted: AtomicBool::new(false),
+        }
+    }
+
+    pub(crate) fn emitted(&self) -> bool {
+        self.emitted.load(Ordering::SeqCst)
+    }
+}
+
+impl EventSink for TrackingSink {
+    fn emit(&self, event: Event) -> GraphResult<()> {
+        self.emitted.store(true, Ordering::SeqCst);
+        self.inner.emit(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_retry_after;
+
+    #[test]
+    fn parse_retry_after_accepts_seconds_and_http_dates() {
+        let now = chrono::DateTime::parse_from_rfc2822("Wed, 21 Oct 2026 07:28:00 GMT")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        assert_eq!(parse_retry_after("2", now), Some(2000));
+        assert_eq!(parse_retry_after("0.5", now), Some(500));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2026 07:28:30 GMT", now),
+            Some(30_000)
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2026 07:27:00 GMT", now),
+            Some(0)
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+}
@@ -1,11 +1,20 @@
 //! OpenAI chat model adapter for Forge component interfaces.
 
-use std::time::Duration;
+use std::collections::BTreeMap;
+use std::sync::Arc;
 
-use crate::runtime::component::{ChatModel, ChatRequest, ChatResponse};
+use crate::runtime::cancel::{cancellable, CancellationToken};
+use crate::runtime::component::{ChatModel, ChatRequest, ChatResponse, ToolChoice};
 use crate::runtime::error::{GraphError, GraphResult};
-use crate::runtime::message::{Message, MessageRole, Part};
+use crate::runtime::event::{Event, EventSink};
+use crate::runtime::message::{MediaSource, Message, MessageRole, Part};
 use crate::runtime::node::BoxFuture;
+use crate::runtime::provider::transport::{
+    default_transport, HttpRequest, HttpResponse, HttpTransport,
+};
+use crate::runtime::provider::{prepare_media, send_request};
+use crate::runtime::session::AttachmentResolver;
+use crate::runtime::tool::ToolDefinition;
 
 const OPENAI_DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
 
@@ -17,7 +26,13 @@ pub struct OpenAiChatModelConfig {
     pub base_url: String,
     pub organization: Option<String>,
     pub project: Option<String>,
+    /// Deadline for the response head, and the longest wait between body
+    /// chunks; a stream that keeps sending may run longer.
     pub timeout_ms: u64,
+    /// Resolves `attachment://` media sources before sending.
+    pub attachment_resolver: Option<Arc<AttachmentResolver>>,
+    /// Defaults to the shared `BlockingPoolTransport`.
+    pub transport: Option<Arc<dyn HttpTransport>>,
 }
 
 impl OpenAiChatModelConfig {
@@ -29,6 +44,8 @@ impl OpenAiChatModelConfig {
             organization: None,
             project: None,
             timeout_ms: 30_000,
+            attachment_resolver: None,
+            transport: None,
         }
     }
 
@@ -56,6 +73,16 @@ impl OpenAiChatModelConfig {
         self.timeout_ms = timeout_ms;
         self
     }
+
+    pub fn with_attachment_resolver(mut self, resolver: Arc<AttachmentResolver>) -> Self {
+        self.attachment_resolver = Some(resolver);
+        self
+    }
+
+    pub fn with_transport(mut self, transport: Arc<dyn HttpTransport>) -> Self {
+        self.transport = Some(transport);
+        self
+    }
 }
 
 /// OpenAI chat completions adapter implementing the ChatModel interface.
@@ -67,6 +94,8 @@ pub struct OpenAiChatModel {
     organization: Option<String>,
     project: Option<String>,
     timeout_ms: u64,
+    attachment_resolver: Option<Arc<AttachmentResolver>>,
+    transport: Arc<dyn HttpTransport>,
 }
 
 impl OpenAiChatModel {
@@ -82,6 +111,8 @@ impl OpenAiChatModel {
             organization: config.organization,
             project: config.project,
             timeout_ms: config.timeout_ms.max(1),
+            attachment_resolver: config.attachment_resolver,
+            transport: config.transport.unwrap_or_else(default_transport),
         })
     }
 
@@ -89,8 +120,13 @@ impl OpenAiChatModel {
         format!("{}/chat/completions", self.base_url.trim_end_matches('/'))
     }
 
-    fn request_payload(&self, request: &ChatRequest) -> serde_json::Value {
-        build_request_payload(&self.model, request)
+    fn request_payload(&self, request: &ChatRequest) -> GraphResult<serde_json::Value> {
+        let request = prepare_media(
+            request,
+            self.attachment_resolver.as_deref(),
+            check_media_part,
+        )?;
+        Ok(build_request_payload(&self.model, &request))
     }
 }
 
@@ -100,52 +136,212 @@ impl ChatModel for OpenAiChatModel {
     }
 
     fn generate(&self, request: ChatRequest) -> BoxFuture<'_, GraphResult<ChatResponse>> {
-        let endpoint = self.endpoint();
         let payload = self.request_payload(&request);
-        let api_key = self.api_key.clone();
-        let organization = self.organization.clone();
-        let project = self.project.clone();
-        let timeout_ms = self.timeout_ms;
         Box::pin(async move {
-            let agent = ureq::AgentBuilder::new()
-                .timeout(Duration::from_millis(timeout_ms))
-                .build();
-
-            let mut request_builder = agent
-                .post(&endpoint)
-                .set("Authorization", &format!("Bearer {}", api_key))
-                .set("Content-Type", "application/json");
-            if let Some(org) = organization.as_deref() {
-                request_builder = request_builder.set("OpenAI-Organization", org);
-            }
-            if let Some(proj) = project.as_deref() {
-                request_builder = request_builder.set("OpenAI-Project", proj);
-            }
+            request.check_cancelled()?;
+            let cancellation = request.cancellation.as_ref();
+            let response = self.send(payload?, cancellation).await?;
+            let body = cancellable(cancellation, response.text()).await?;
+            let response_json = serde_json::from_str(&body)
+                .map_err(|err| openai_error(format!("decode response failed: {}", err)))?;
+            parse_chat_response(response_json)
+        })
+    }
 
-            let response = request_builder.send_json(payload);
-            let response_json = match response {
-                Ok(resp) => resp
-                    .into_json::<serde_json::Value>()
-                    .map_err(|err| openai_error(format!("decode response failed: {}", err)))?,
-                Err(ureq::Error::Status(status, resp)) => {
-                    let body = resp.into_string().unwrap_or_default();
-                    let detail = parse_error_message(&body).unwrap_or(body);
-                    return Err(openai_error(format!(
-                        "openai request failed with status {}: {}",
-                        status, detail
-                    )));
+    /// Stream with `stream: true`, emitting `TextDelta` per content chunk.
+    ///
+    /// Tool-call argument fragments are assembled by index and the final
+    /// usage chunk is captured, so the returned response matches what
+    /// `generate` would produce. Cancellation is checked between chunks.
+    fn stream(
+        &self,
+        request: ChatRequest,
+        sink: Arc<dyn EventSink>,
+    ) -> BoxFuture<'_, GraphResult<ChatResponse>> {
+        let payload = self.request_payload(&request).map(|mut payload| {
+            payload["stream"] = serde_json::json!(true);
+            payload["stream_options"] = serde_json::json!({"include_usage": true});
+            payload
+        });
+        Box::pin(async move {
+            request.check_cancelled()?;
+            let cancellation = request.cancellation.as_ref();
+            let mut body = self.send(payload?, cancellation).await?.into_body();
+            let mut stream = StreamAssembler::default();
+            while let Some(line) = cancellable(cancellation, body.next_line()).await? {
+                let Some(data) = line.strip_prefix("data:").map(str::trim) else {
+                    continue;
+                };
+                if data == "[DONE]" {
+                    break;
                 }
-                Err(err) => {
-                    return Err(openai_error(format!("openai request failed: {}", err)));
+                let chunk: serde_json::Value = serde_json::from_str(data)
+                    .map_err(|err| openai_error(format!("decode stream chunk failed: {}", err)))?;
+                if let Some(message) = chunk
+                    .get("error")
+                    .and_then(|error| error.get("message"))
+                    .and_then(|message| message.as_str())
+                {
+                    return Err(openai_error(format!("openai stream failed: {}", message)));
                 }
-            };
-
-            parse_chat_response(response_json)
+                if let Some(delta) = stream.push(&chunk) {
+                    sink.emit(Event::TextDelta {
+                        session_id: request.session_id.clone(),
+                        message_id: request.message_id.clone(),
+                        delta,
+                    })?;
+                }
+            }
+            parse_chat_response(stream.into_response_json())
         })
     }
 }
 
-fn resolve_api_key(explicit: Option<String>) -> GraphResult<String> {
+impl OpenAiChatModel {
+    /// POST a payload to the chat completions endpoint, mapping HTTP errors.
+    async fn send(
+        &self,
+        payload: serde_json::Value,
+        cancellation: Option<&CancellationToken>,
+    ) -> GraphResult<HttpResponse> {
+        let mut request = HttpRequest::new(self.endpoint(), payload)
+            .with_header("Authorization", format!("Bearer {}", self.api_key))
+            .with_header("Content-Type", "application/json")
+            .with_timeout_ms(self.timeout_ms)
+            .with_read_timeout_ms(self.timeout_ms);
+        if let Some(org) = self.organization.as_deref() {
+            request = request.with_header("OpenAI-Organization", org);
+        }
+        if let Some(proj) = self.project.as_deref() {
+            request = request.with_header("OpenAI-Project", proj);
+        }
+        send_request(
+            self.transport.as_ref(),
+            "openai",
+            request,
+            cancellation,
+            parse_error_message,
+        )
+        .await
+    }
+}
+
+/// Accumulates streamed chat completion chunks into a full response.
+#[derive(Debug, Default)]
+struct StreamAssembler {
+    id: Option<serde_json::Value>,
+    model: Option<serde_json::Value>,
+    created: Option<serde_json::Value>,
+    content: String,
+    tool_calls: BTreeMap<u64, StreamedToolCall>,
+    finish_reason: Option<serde_json::Value>,
+    usage: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Default)]
+struct StreamedToolCall {
+    id: String,
+    name: String,
+    arguments: String,
+}
+
+impl StreamAssembler {
+    /// Fold one chunk in, returning any new text content.
+    fn push(&mut self, chunk: &serde_json::Value) -> Option<String> {
+        for (slot, key) in [
+            (&mut self.id, "id"),
+            (&mut self.model, "model"),
+            (&mut self.created, "created"),
+        ] {
+            if slot.is_none() {
+                *slot = chunk.get(key).cloned();
+            }
+        }
+        if let Some(usage) = chunk.get("usage").filter(|usage| !usage.is_null()) {
+            self.usage = Some(usage.clone());
+        }
+        let choice = chunk
+            .get("choices")
+            .and_then(|choices| choices.as_array())
+            .and_then(|choices| choices.first())?;
+        if let Some(reason) = choice.get("finish_reason").filter(|r| !r.is_null()) {
+            self.finish_reason = Some(reason.clone());
+        }
+        let delta = choice.get("delta")?;
+        for call in delta
+            .get("tool_calls")
+            .and_then(|calls| calls.as_array())
+            .into_iter()
+            .flatten()
+        {
+            let index = call.get("index").and_then(|v| v.as_u64()).unwrap_or(0);
+            let entry = self.tool_calls.entry(index).or_default();
+            if let Some(id) = call.get("id").and_then(|v| v.as_str()) {
+                entry.id = id.to_string();
+            }
+            let function = call.get("function");
+            if let Some(name) = function
+                .and_then(|f| f.get("name"))
+                .and_then(|v| v.as_str())
+            {
+                entry.name.push_str(name);
+            }
+            if let Some(arguments) = function
+                .and_then(|f| f.get("arguments"))
+                .and_then(|v| v.as_str())
+            {
+                entry.arguments.push_str(arguments);
+            }
+        }
+        let text = delta.get("content").and_then(|v| v.as_str())?;
+        if text.is_empty() {
+            return None;
+        }
+        self.content.push_str(text);
+        Some(text.to_string())
+    }
+
+    /// Shape the accumulated stream like a non-streaming completion.
+    fn into_response_json(self) -> serde_json::Value {
+        let tool_calls = self
+            .tool_calls
+            .into_values()
+            .map(|call| {
+                serde_json::json!({
+                    "id": call.id,
+                    "type": "function",
+                    "function": {"name": call.name, "arguments": call.arguments},
+                })
+            })
+            .collect::<Vec<_>>();
+        let mut message = serde_json::json!({
+            "role": "assistant",
+            "content": self.content,
+        });
+        if !tool_calls.is_empty() {
+            message["tool_calls"] = serde_json::Value::Array(tool_calls);
+        }
+        let mut response = serde_json::json!({
+            "choices": [{
+                "message": message,
+                "finish_reason": self.finish_reason,
+            }],
+        });
+        for (key, value) in [
+            ("id", self.id),
+            ("model", self.model),
+            ("created", self.created),
+            ("usage", self.usage),
+        ] {
+            if let Some(value) = value {
+                response[key] = value;
+            }
+        }
+        response
+    }
+}
+
+pub(super) fn resolve_api_key(explicit: Option<String>) -> GraphResult<String> {
     if let Some(key) = explicit {
         if !key.trim().is_empty() {
             return Ok(key);
@@ -161,7 +357,7 @@ fn resolve_api_key(explicit: Option<String>) -> GraphResult<String> {
     ))
 }
 
-fn openai_error(message: impl Into<String>) -> GraphError {
+pub(super) fn openai_error(message: impl Into<String>) -> GraphError {
     GraphError::ExecutionError {
         node: "provider:openai".to_string(),
         message: message.into(),
@@ -172,12 +368,7 @@ fn build_request_payload(model: &str, request: &ChatRequest) -> serde_json::Valu
     let messages = request
         .messages
         .iter()
-        .map(|message| {
-            serde_json::json!({
-                "role": openai_role(&message.role),
-                "content": render_message_content(message),
-            })
-        })
+        .flat_map(render_messages)
         .collect::<Vec<_>>();
 
     let mut payload = serde_json::json!({
@@ -190,9 +381,241 @@ fn build_request_payload(model: &str, request: &ChatRequest) -> serde_json::Valu
     if let Some(max_tokens) = request.max_output_tokens {
         payload["max_tokens"] = serde_json::json!(max_tokens);
     }
+    if !request.tools.is_empty() {
+        payload["tools"] = request.tools.iter().map(render_tool).collect();
+    }
+    if let Some(tool_choice) = &request.tool_choice {
+        payload["tool_choice"] = render_tool_choice(tool_choice);
+    }
+    if let Some(schema) = &request.response_schema {
+        payload["response_format"] = serde_json::json!({
+            "type": "json_schema",
+            "json_schema": {
+                "name": schema.name,
+                "schema": schema.schema,
+                "strict": schema.strict,
+            },
+        });
+    }
     payload
 }
 
+fn render_tool(tool: &ToolDefinition) -> serde_json::Value {
+    let parameters = tool
+        .input_schema
+        .clone()
+        .unwrap_or_else(|| serde_json::json!({"type": "object", "properties": {}}));
+    serde_json::json!({
+        "type": "function",
+        "function": {
+            "name": tool.name,
+            "description": tool.description,
+            "parameters": parameters,
+        }
+    })
+}
+
+fn render_tool_choice(tool_choice: &ToolChoice) -> serde_json::Value {
+    match tool_choice {
+        ToolChoice::Auto => serde_json::json!("auto"),
+        ToolChoice::None => serde_json::json!("none"),
+        ToolChoice::Required => serde_json::json!("required"),
+        ToolChoice::Tool(name) => serde_json::json!({
+            "type": "function",
+            "function": {"name": name},
+        }),
+    }
+}
+
+/// Map one Forge message to OpenAI messages.
+///
+/// Assistant tool calls become `tool_calls`; each tool result or error
+/// becomes its own `tool` message carrying the matching `tool_call_id`.
+fn render_messages(message: &Message) -> Vec<serde_json::Value> {
+    match message.role {
+        MessageRole::Assistant => {
+            let tool_calls = message
+                .parts
+                .iter()
+                .filter_map(|part| match part {
+                    Part::ToolCall {
+                        tool,
+                        call_id,
+                        input,
+                    } => Some(serde_json::json!({
+                        "id": call_id,
+                        "type": "function",
+                        "function": {
+                            "name": tool,
+                            "arguments": render_arguments(input),
+                        }
+                    })),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            if tool_calls.is_empty() {
+                return vec![render_plain_message(message)];
+            }
+            let text = message
+                .parts
+                .iter()
+                .filter_map(|part| match part {
+                    Part::TextDelta { delta } => Some(delta.as_str()),
+                    Part::TextFinal { text } => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect::<String>();
+            let content = if text.is_empty() {
+                serde_json::Value::Null
+            } else {
+                serde_json::Value::String(text)
+            };
+            vec![serde_json::json!({
+                "role": "assistant",
+                "content": content,
+                "tool_calls": tool_calls,
+            })]
+        }
+        MessageRole::Tool => {
+            let results = message
+                .parts
+                .iter()
+                .filter_map(|part| match part {
+                    Part::ToolResult {
+                        call_id, output, ..
+                    } => Some(tool_message(call_id, render_tool_content(&output.content))),
+                    Part::ToolError { call_id, error, .. } => {
+                        Some(tool_message(call_id, error.clone()))
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            if !results.is_empty() {
+                return results;
+            }
+            let mut rendered = render_plain_message(message);
+            if let Some(call_id) = message.metadata.get("tool_call_id") {
+                rendered["tool_call_id"] = call_id.clone();
+            }
+            vec![rendered]
+        }
+        MessageRole::System | MessageRole::User => vec![render_plain_message(message)],
+    }
+}
+
+fn render_plain_message(message: &Message) -> serde_json::Value {
+    let content = if message.parts.iter().any(Part::is_media) {
+        serde_json::Value::Array(render_content_parts(message))
+    } else {
+        serde_json::Value::String(render_message_content(message))
+    };
+    serde_json::json!({
+        "role": openai_role(&message.role),
+        "content": content,
+    })
+}
+
+/// Content array for messages carrying image, audio or file parts.
+fn render_content_parts(message: &Message) -> Vec<serde_json::Value> {
+    message
+        .parts
+        .iter()
+        .filter_map(|part| match part {
+            Part::Image {
+                source,
+                mime_type,
+                detail,
+            } => {
+                let mut image_url = serde_json::json!({"url": source.to_url(mime_type)});
+                if let Some(detail) = detail {
+                    image_url["detail"] = serde_json::json!(detail);
+                }
+                Some(serde_json::json!({"type": "image_url", "image_url": image_url}))
+            }
+            Part::Audio { source, mime_type } => Some(serde_json::json!({
+                "type": "input_audio",
+                "input_audio": {
+                    "data": media_data(source),
+                    "format": audio_format(mime_type).unwrap_or("wav"),
+                }
+            })),
+            Part::File {
+                source,
+                mime_type,
+                name,
+            } => Some(serde_json::json!({
+                "type": "file",
+                "file": {
+                    "filename": name.as_deref().unwrap_or("file"),
+                    "file_data": source.to_url(mime_type),
+                }
+            })),
+            other => part_text(other).map(|text| serde_json::json!({"type": "text", "text": text})),
+        })
+        .collect()
+}
+
+fn media_data(source: &MediaSource) -> &str {
+    match source {
+        MediaSource::Base64 { data } => data,
+        MediaSource::Url { url } => url,
+        MediaSource::Attachment { reference } => reference,
+    }
+}
+
+fn audio_format(mime_type: &str) -> Option<&'static str> {
+    match mime_type {
+        "audio/wav" | "audio/x-wav" | "audio/wave" => Some("wav"),
+        "audio/mpeg" | "audio/mp3" => Some("mp3"),
+        _ => None,
+    }
+}
+
+/// Chat completions accept audio and files only as inline data.
+fn check_media_part(part: &Part) -> GraphResult<()> {
+    match part {
+        Part::Audio { source, mime_type } => {
+            if audio_format(mime_type).is_none() {
+                return Err(openai_error(format!(
+                    "unsupported audio type {}, expected wav or mp3",
+                    mime_type
+                )));
+            }
+            if matches!(source, MediaSource::Url { .. }) {
+                return Err(openai_error("audio parts must be inline data, not URLs"));
+            }
+            Ok(())
+        }
+        Part::File {
+            source: MediaSource::Url { .. },
+            ..
+        } => Err(openai_error("file parts must be inline data, not URLs")),
+        _ => Ok(()),
+    }
+}
+
+fn tool_message(call_id: &str, content: String) -> serde_json::Value {
+    serde_json::json!({
+        "role": "tool",
+        "tool_call_id": call_id,
+        "content": content,
+    })
+}
+
+fn render_arguments(input: &serde_json::Value) -> String {
+    match input {
+        serde_json::Value::String(raw) => raw.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn render_tool_content(content: &serde_json::Value) -> String {
+    match content {
+        serde_json::Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
 fn openai_role(role: &MessageRole) -> &'static str {
     match role {
         MessageRole::System => "system",
@@ -202,19 +625,24 @@ fn openai_role(role: &MessageRole) -> &'static str {
     }
 }
 
-fn render_message_content(message: &Message) -> String {
-    let mut chunks = Vec::new();
-    for part in &message.parts {
-        match part {
-            Part::TextDelta { delta } => chunks.push(delta.clone()),
-            Part::TextFinal { text } => chunks.push(text.clone()),
-            Part::ToolResult { output, .. } => chunks.push(output.content.to_string()),
-            Part::ToolError { error, .. } => chunks.push(error.clone()),
-            Part::Attachment { data, .. } => chunks.push(data.to_string()),
-            Part::Error { message } => chunks.push(message.clone()),
-            _ => {}
-        }
+fn part_text(part: &Part) -> Option<String> {
+    match part {
+        Part::TextDelta { delta } => Some(delta.clone()),
+        Part::TextFinal { text } => Some(text.clone()),
+        Part::ToolResult { output, .. } => Some(output.content.to_string()),
+        Part::ToolError { error, .. } => Some(error.clone()),
+        Part::Attachment { data, .. } => Some(data.to_string()),
+        Part::Error { message } => Some(message.clone()),
+        _ => None,
     }
+}
+
+fn render_message_content(message: &Message) -> String {
+    let mut chunks = message
+        .parts
+        .iter()
+        .filter_map(part_text)
+        .collect::<Vec<_>>();
     if chunks.is_empty() && !message.metadata.is_null() {
         chunks.push(message.metadata.to_string());
     }
@@ -236,6 +664,7 @@ fn parse_chat_response(value: serde_json::Value) -> GraphResult<ChatResponse> {
     if !content.is_empty() {
         message.parts.push(Part::TextFinal { text: content });
     }
+    message.parts.extend(parse_tool_calls(raw_message));
     message.metadata = serde_json::json!({
         "provider": "openai",
         "raw": raw_message,
@@ -260,6 +689,43 @@ fn parse_chat_response(value: serde_json::Value) -> GraphResult<ChatResponse> {
     Ok(response)
 }
 
+fn parse_tool_calls(raw_message: &serde_json::Value) -> Vec<Part> {
+    let Some(calls) = raw_message.get("tool_calls").and_then(|v| v.as_array()) else {
+        return Vec::new();
+    };
+    calls
+        .iter()
+        .filter_map(|call| {
+            let function = call.get("function")?;
+            let tool = function.get("name")?.as_str()?.to_string();
+            let call_id = call
+                .get("id")
+                .and_then(|id| id.as_str())
+                .unwrap_or_default()
+                .to_string();
+            let arguments = function
+                .get("arguments")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default();
+            Some(Part::ToolCall {
+                tool,
+                call_id,
+                input: parse_arguments(arguments),
+            })
+        })
+        .collect()
+}
+
+/// Decode tool-call arguments, keeping malformed JSON as a raw string so
+/// the caller can surface the error to the model.
+fn parse_arguments(arguments: &str) -> serde_json::Value {
+    if arguments.trim().is_empty() {
+        return serde_json::json!({});
+    }
+    serde_json::from_str(arguments)
+        .unwrap_or_else(|_| serde_json::Value::String(arguments.to_string()))
+}
+
 fn parse_usage(value: &serde_json::Value) -> Option<crate::runtime::event::TokenUsage> {
     let usage = value.get("usage")?;
     Some(crate::runtime::event::TokenUsage {
@@ -309,7 +775,7 @@ fn extract_message_content(raw_message: &serde_json::Value) -> String {
     String::new()
 }
 
-fn parse_error_message(body: &str) -> Option<String> {
+pub(super) fn parse_error_message(body: &str) -> Option<String> {
     let value: serde_json::Value = serde_json::from_str(body).ok()?;
     value
         .get("error")
@@ -324,10 +790,16 @@ mod tests {
         build_request_payload, extract_message_content, parse_chat_response, parse_error_message,
         OpenAiChatModel, OpenAiChatModelConfig,
     };
-    use crate::runtime::component::{ChatModel, ChatRequest};
-    use crate::runtime::event::TokenUsage;
-    use crate::runtime::message::{Message, MessageRole, Part};
+    use crate::runtime::cancel::CancellationToken;
+    use crate::runtime::component::{ChatModel, ChatRequest, ResponseSchema, ToolChoice};
+    use crate::runtime::error::{GraphError, GraphResult};
+    use crate::runtime::event::{Event, EventSink, NoopEventSink, TokenUsage};
+    use crate::runtime::message::{MediaSource, Message, MessageRole, Part};
+    use crate::runtime::provider::stub::{StubResponse, StubServer};
+    use crate::runtime::session::{AttachmentResolver, FileAttachmentStore};
+    use crate::runtime::tool::{AttachmentStore, ToolAttachment, ToolDefinition, ToolOutput};
     use futures::executor::block_on;
+    use std::sync::{Arc, Mutex};
 
     #[test]
     fn request_payload_maps_messages_and_generation_options() {
@@ -350,6 +822,132 @@ mod tests {
         assert_eq!(payload["max_tokens"], 32);
     }
 
+    #[test]
+    fn request_payload_maps_tools_and_tool_choice() {
+        let request = ChatRequest::new("s1", "m1", Vec::new())
+            .with_tool(
+                ToolDefinition::new("grep", "Search files").with_input_schema(serde_json::json!({
+                    "type": "object",
+                    "properties": {"pattern": {"type": "string"}}
+                })),
+            )
+            .with_tool(ToolDefinition::new("now", "Current time"))
+            .with_tool_choice(ToolChoice::Tool("grep".to_string()));
+
+        let payload = build_request_payload("gpt-4o-mini", &request);
+        assert_eq!(payload["tools"][0]["type"], "function");
+        assert_eq!(payload["tools"][0]["function"]["name"], "grep");
+        assert_eq!(
+            payload["tools"][0]["function"]["parameters"]["properties"]["pattern"]["type"],
+            "string"
+        );
+        assert_eq!(
+            payload["tools"][1]["function"]["parameters"]["type"],
+            "object"
+        );
+        assert_eq!(payload["tool_choice"]["function"]["name"], "grep");
+
+        let auto = build_request_payload(
+            "gpt-4o-mini",
+            &ChatRequest::new("s1", "m1", Vec::new()).with_tool_choice(ToolChoice::Auto),
+        );
+        assert_eq!(auto["tool_choice"], "auto");
+        assert!(auto.get("tools").is_none());
+    }
+
+    #[test]
+    fn request_payload_maps_response_schema_to_json_schema_mode() {
+        let request = ChatRequest::new("s1", "m1", Vec::new()).with_response_schema(
+            ResponseSchema::new("answer", serde_json::json!({"type": "object"})).with_strict(true),
+        );
+        let payload = build_request_payload("gpt-4o-mini", &request);
+        assert_eq!(payload["response_format"]["type"], "json_schema");
+        assert_eq!(payload["response_format"]["json_schema"]["name"], "answer");
+        assert_eq!(payload["response_format"]["json_schema"]["strict"], true);
+        assert_eq!(
+            payload["response_format"]["json_schema"]["schema"]["type"],
+            "object"
+        );
+    }
+
+    #[test]
+    fn request_payload_round_trips_tool_calls_and_results() {
+        let mut assistant = Message::new(MessageRole::Assistant);
+        assistant.parts.push(Part::ToolCall {
+            tool: "grep".to_string(),
+            call_id: "call_1".to_string(),
+            input: serde_json::json!({"pattern": "todo"}),
+        });
+        assistant.parts.push(Part::ToolCall {
+            tool: "now".to_string(),
+            call_id: "call_2".to_string(),
+            input: serde_json::json!({}),
+        });
+        let mut results = Message::new(MessageRole::Tool);
+        results.parts.push(Part::ToolResult {
+            tool: "grep".to_string(),
+            call_id: "call_1".to_string(),
+            output: ToolOutput::text("src/lib.rs:1"),
+        });
+        results.parts.push(Part::ToolError {
+            tool: "now".to_string(),
+            call_id: "call_2".to_string(),
+            error: "clock unavailable".to_string(),
+        });
+        let request = ChatRequest::new("s1", "m1", vec![assistant, results]);
+
+        let payload = build_request_payload("gpt-4o-mini", &request);
+        let messages = payload["messages"].as_array().expect("messages");
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0]["role"], "assistant");
+        assert!(messages[0]["content"].is_null());
+        assert_eq!(messages[0]["tool_calls"][0]["id"], "call_1");
+        assert_eq!(
+            messages[0]["tool_calls"][0]["function"]["arguments"],
+            r#"{"pattern":"todo"}"#
+        );
+        assert_eq!(messages[1]["role"], "tool");
+        assert_eq!(messages[1]["tool_call_id"], "call_1");
+        assert_eq!(messages[1]["content"], "src/lib.rs:1");
+        assert_eq!(messages[2]["tool_call_id"], "call_2");
+        assert_eq!(messages[2]["content"], "clock unavailable");
+    }
+
+    #[test]
+    fn parse_chat_response_extracts_tool_calls() {
+        let response = serde_json::json!({
+            "choices": [{
+                "message": {
+                    "role": "assistant",
+                    "content": null,
+                    "tool_calls": [
+                        {
+                            "id": "call_1",
+                            "type": "function",
+                            "function": {"name": "grep", "arguments": "{\"pattern\":\"todo\"}"}
+                        },
+                        {
+                            "id": "call_2",
+                            "type": "function",
+                            "function": {"name": "broken", "arguments": "{not json"}
+                        }
+                    ]
+                },
+                "finish_reason": "tool_calls"
+            }]
+        });
+
+        let parsed = parse_chat_response(response).expect("parse");
+        assert_eq!(parsed.finish_reason.as_deref(), Some("tool_calls"));
+        assert!(parsed.text().is_none());
+        let calls = parsed.tool_calls();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].tool, "grep");
+        assert_eq!(calls[0].call_id, "call_1");
+        assert_eq!(calls[0].input, serde_json::json!({"pattern": "todo"}));
+        assert_eq!(calls[1].input, serde_json::json!("{not json"));
+    }
+
     #[test]
     fn parse_chat_response_supports_string_content() {
         let response = serde_json::json!({
@@ -410,6 +1008,266 @@ mod tests {
         assert_eq!(model.model_id(), "gpt-4o-mini");
     }
 
+    struct CaptureSink {
+        events: Arc<Mutex<Vec<Event>>>,
+        cancel_on_delta: Option<CancellationToken>,
+    }
+
+    impl EventSink for CaptureSink {
+        fn emit(&self, event: Event) -> GraphResult<()> {
+            if let Some(token) = &self.cancel_on_delta {
+                token.cancel("user pressed stop");
+            }
+            self.events.lock().unwrap().push(event);
+            Ok(())
+        }
+    }
+
+    fn stub_model(url: &str) -> OpenAiChatModel {
+        OpenAiChatModel::new(
+            OpenAiChatModelConfig::new("gpt-4o-mini")
+                .with_api_key("test-key")
+                .with_base_url(format!("{}/v1", url)),
+        )
+        .expect("construct")
+    }
+
+    fn user_request(text: &str) -> ChatRequest {
+        let mut message = Message::new(MessageRole::User);
+        message.parts.push(Part::TextFinal {
+            text: text.to_string(),
+        });
+        ChatRequest::new("s1", "m1", vec![message])
+    }
+
+    fn stream_chunks() -> Vec<serde_json::Value> {
+        let chunk = |delta: serde_json::Value, finish: serde_json::Value| {
+            serde_json::json!({
+                "id": "chatcmpl-9",
+                "model": "gpt-4o-mini",
+                "created": 7,
+                "choices": [{"index": 0, "delta": delta, "finish_reason": finish}]
+            })
+        };
+        vec![
+            chunk(
+                serde_json::json!({"role": "assistant", "content": ""}),
+                serde_json::Value::Null,
+            ),
+            chunk(
+                serde_json::json!({"content": "Hel"}),
+                serde_json::Value::Null,
+            ),
+            chunk(
+                serde_json::json!({"content": "lo"}),
+                serde_json::Value::Null,
+            ),
+            chunk(
+                serde_json::json!({"tool_calls": [{
+                    "index": 0,
+                    "id": "call_1",
+                    "type": "function",
+                    "function": {"name": "grep", "arguments": "{\"pat"}
+                }]}),
+                serde_json::Value::Null,
+            ),
+            chunk(
+                serde_json::json!({"tool_calls": [{
+                    "index": 0,
+                    "function": {"arguments": "tern\":\"todo\"}"}
+                }]}),
+                serde_json::Value::Null,
+            ),
+            chunk(serde_json::json!({}), serde_json::json!("tool_calls")),
+            serde_json::json!({
+                "id": "chatcmpl-9",
+                "choices": [],
+                "usage": {"prompt_tokens": 12, "completion_tokens": 4}
+            }),
+        ]
+    }
+
+    #[test]
+    fn generate_posts_to_chat_completions() {
+        let server = StubServer::start(vec![StubResponse::json(
+            200,
+            serde_json::json!({
+                "model": "gpt-4o-mini",
+                "choices": [{"message": {"role": "assistant", "content": "pong"}, "finish_reason": "stop"}]
+            }),
+        )]);
+        let model = stub_model(&server.url);
+
+        let response = block_on(model.generate(user_request("ping"))).expect("generate");
+        assert_eq!(response.text().as_deref(), Some("pong"));
+
+        let requests = server.finish();
+        assert!(requests[0]
+            .request_line
+            .starts_with("POST /v1/chat/completions"));
+        assert_eq!(requests[0].header("authorization"), Some("Bearer test-key"));
+        assert!(requests[0].json().get("stream").is_none());
+    }
+
+    #[test]
+    fn request_payload_renders_media_parts_as_content_array() {
+        let mut message = Message::new(MessageRole::User);
+        message.parts.push(Part::TextFinal {
+            text: "What is this?".to_string(),
+        });
+        message.parts.push(Part::Image {
+            source: MediaSource::url("https://example.com/cat.png"),
+            mime_type: "image/png".to_string(),
+            detail: Some("low".to_string()),
+        });
+        message
+            .parts
+            .push(Part::audio(MediaSource::bytes(b"RIFF"), "audio/wav"));
+        message.parts.push(Part::file(
+            MediaSource::bytes(b"%PDF"),
+            "application/pdf",
+            Some("report.pdf".to_string()),
+        ));
+        let request = ChatRequest::new("s1", "m1", vec![message]);
+
+        let payload = build_request_payload("gpt-4o", &request);
+        assert_eq!(
+            payload["messages"][0]["content"],
+            serde_json::json!([
+                {"type": "text", "text": "What is this?"},
+                {"type": "image_url", "image_url": {"url": "https://example.com/cat.png", "detail": "low"}},
+                {"type": "input_audio", "input_audio": {"data": "UklGRg==", "format": "wav"}},
+                {"type": "file", "file": {"filename": "report.pdf", "file_data": "data:application/pdf;base64,JVBERg=="}}
+            ])
+        );
+    }
+
+    #[test]
+    fn generate_resolves_attachment_media_and_rejects_unsupported_audio() {
+        let temp =
+            std::env::temp_dir().join(format!("forge-openai-media-{}", uuid::Uuid::new_v4()));
+        let reference = FileAttachmentStore::new(temp.clone())
+            .store(&ToolAttachment::inline(
+                "chart.png",
+                "image/png",
+                serde_json::json!("iVBORw0KGgo="),
+            ))
+            .expect("store");
+        let server = StubServer::start(vec![StubResponse::json(
+            200,
+            serde_json::json!({
+                "choices": [{"message": {"role": "assistant", "content": "a chart"}}]
+            }),
+        )]);
+        let model = OpenAiChatModel::new(
+            OpenAiChatModelConfig::new("gpt-4o")
+                .with_api_key("test-key")
+                .with_base_url(format!("{}/v1", server.url))
+                .with_attachment_resolver(Arc::new(AttachmentResolver::new(temp.clone()))),
+        )
+        .expect("model");
+
+        let mut message = Message::new(MessageRole::User);
+        message
+            .parts
+            .push(Part::image(MediaSource::attachment(&reference), ""));
+        let request = ChatRequest::new("s1", "m1", vec![message]);
+        block_on(model.generate(request)).expect("generate");
+        let sent = server.finish()[0].json();
+        assert_eq!(
+            sent["messages"][0]["content"][0]["image_url"]["url"],
+            "data:image/png;base64,iVBORw0KGgo="
+        );
+
+        let mut audio = Message::new(MessageRole::User);
+        audio.parts.push(Part::audio(
+            MediaSource::url("https://example.com/a.wav"),
+            "audio/wav",
+        ));
+        let err = block_on(model.generate(ChatRequest::new("s1", "m2", vec![audio])))
+            .expect_err("audio urls are rejected");
+        assert!(err.to_string().contains("audio parts must be inline data"));
+        std::fs::remove_dir_all(temp).ok();
+    }
+
+    #[test]
+    fn generate_maps_error_status() {
+        let server = StubServer::start(vec![StubResponse::json(
+            429,
+            serde_json::json!({"error": {"message": "quota exceeded"}}),
+        )]);
+        let model = stub_model(&server.url);
+
+        let err = block_on(model.generate(user_request("ping"))).expect_err("429");
+        assert!(err.to_string().contains("status 429: quota exceeded"));
+        server.finish();
+    }
+
+    #[test]
+    fn stream_emits_deltas_and_assembles_response() {
+        let server = StubServer::start(vec![StubResponse::sse(&stream_chunks())]);
+        let model = stub_model(&server.url);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink: Arc<dyn EventSink> = Arc::new(CaptureSink {
+            events: events.clone(),
+            cancel_on_delta: None,
+        });
+
+        let response = block_on(model.stream(user_request("hi"), sink)).expect("stream");
+
+        let deltas: Vec<String> = events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|event| match event {
+                Event::TextDelta { delta, .. } => Some(delta.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(deltas, vec!["Hel".to_string(), "lo".to_string()]);
+        assert_eq!(response.text().as_deref(), Some("Hello"));
+        assert_eq!(response.model.as_deref(), Some("gpt-4o-mini"));
+        assert_eq!(response.finish_reason.as_deref(), Some("tool_calls"));
+        let calls = response.tool_calls();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].call_id, "call_1");
+        assert_eq!(calls[0].input, serde_json::json!({"pattern": "todo"}));
+        let usage = response.usage.expect("usage");
+        assert_eq!((usage.input, usage.output), (12, 4));
+
+        let body = server.finish()[0].json();
+        assert_eq!(body["stream"], true);
+        assert_eq!(body["stream_options"]["include_usage"], true);
+    }
+
+    #[test]
+    fn stream_honours_cancellation_token() {
+        let server = StubServer::start(vec![StubResponse::sse(&stream_chunks())]);
+        let model = stub_model(&server.url);
+        let token = CancellationToken::new();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink: Arc<dyn EventSink> = Arc::new(CaptureSink {
+            events: events.clone(),
+            cancel_on_delta: Some(token.clone()),
+        });
+
+        let request = user_request("hi").with_cancellation_token(token.clone());
+        let result = block_on(model.stream(request, sink));
+        match result {
+            Err(GraphError::Aborted { reason }) => assert_eq!(reason, "user pressed stop"),
+            other => panic!("expected abort, got {:?}", other),
+        }
+        assert_eq!(events.lock().unwrap().len(), 1);
+        server.finish();
+
+        let request = user_request("hi").with_cancellation_token(token);
+        let sink: Arc<dyn EventSink> = Arc::new(NoopEventSink);
+        assert!(matches!(
+            block_on(stub_model("http://127.0.0.1:9").stream(request, sink)),
+            Err(GraphError::Aborted { .. })
+        ));
+    }
+
     #[test]
     #[ignore = "requires OPENAI_API_KEY and external network"]
     fn openai_generate_smoke_test() {
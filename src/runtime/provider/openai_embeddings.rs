@@ -0,0 +1,416 @@
+//! OpenAI-compatible embeddings adapter for Forge component interfaces.
+
+use std::sync::Arc;
+
+use crate::runtime::component::EmbeddingModel;
+use crate::runtime::error::GraphResult;
+use crate::runtime::event::TokenUsage;
+use crate::runtime::node::BoxFuture;
+use crate::runtime::provider::openai::{openai_error, parse_error_message, resolve_api_key};
+use crate::runtime::provider::send_request;
+use crate::runtime::provider::transport::{default_transport, HttpRequest, HttpTransport};
+
+const OPENAI_DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
+/// OpenAI rejects requests with more than 2048 inputs.
+const DEFAULT_MAX_BATCH_INPUTS: usize = 2048;
+/// OpenAI rejects requests with more than 300k input tokens.
+const DEFAULT_MAX_BATCH_TOKENS: u64 = 300_000;
+
+/// Configuration for OpenAI-compatible `/embeddings` endpoints.
+#[derive(Clone, Debug)]
+pub struct OpenAiEmbeddingModelConfig {
+    pub model: String,
+    pub api_key: Option<String>,
+    pub base_url: String,
+    pub organization: Option<String>,
+    pub project: Option<String>,
+    /// Deadline for the response head, and the longest wait between body reads.
+    pub timeout_ms: u64,
+    /// Requested output size; only supported by some models.
+    pub dimensions: Option<u32>,
+    pub max_batch_inputs: usize,
+    /// Estimated (chars / 4) token budget per request.
+    pub max_batch_tokens: u64,
+    /// Defaults to the shared `BlockingPoolTransport`.
+    pub transport: Option<Arc<dyn HttpTransport>>,
+}
+
+impl OpenAiEmbeddingModelConfig {
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            model: model.into(),
+            api_key: None,
+            base_url: OPENAI_DEFAULT_BASE_URL.to_string(),
+            organization: None,
+            project: None,
+            timeout_ms: 30_000,
+            dimensions: None,
+            max_batch_inputs: DEFAULT_MAX_BATCH_INPUTS,
+            max_batch_tokens: DEFAULT_MAX_BATCH_TOKENS,
+            transport: None,
+        }
+    }
+
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    pub fn with_organization(mut self, organization: impl Into<String>) -> Self {
+        self.organization = Some(organization.into());
+        self
+    }
+
+    pub fn with_project(mut self, project: impl Into<String>) -> Self {
+        self.project = Some(project.into());
+        self
+    }
+
+    pub fn with_timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.timeout_ms = timeout_ms;
+        self
+    }
+
+    pub fn with_dimensions(mut self, dimensions: u32) -> Self {
+        self.dimensions = Some(dimensions);
+        self
+    }
+
+    pub fn with_max_batch_inputs(mut self, max_batch_inputs: usize) -> Self {
+        self.max_batch_inputs = max_batch_inputs;
+        self
+    }
+
+    pub fn with_max_batch_tokens(mut self, max_batch_tokens: u64) -> Self {
+        self.max_batch_tokens = max_batch_tokens;
+        self
+    }
+
+    pub fn with_transport(mut self, transport: Arc<dyn HttpTransport>) -> Self {
+        self.transport = Some(transport);
+        self
+    }
+}
+
+/// Embeddings for a full input list, in input order.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EmbeddingResponse {
+    pub embeddings: Vec<Vec<f32>>,
+    /// Summed over every batch; only `input` is populated.
+    pub usage: TokenUsage,
+    /// Model name reported by the provider, if any.
+    pub model: Option<String>,
+}
+
+/// OpenAI-compatible embeddings adapter implementing the EmbeddingModel interface.
+#[derive(Clone, Debug)]
+pub struct OpenAiEmbeddingModel {
+    model: String,
+    api_key: String,
+    base_url: String,
+    organization: Option<String>,
+    project: Option<String>,
+    timeout_ms: u64,
+    dimensions: Option<u32>,
+    max_batch_inputs: usize,
+    max_batch_tokens: u64,
+    transport: Arc<dyn HttpTransport>,
+}
+
+impl OpenAiEmbeddingModel {
+    pub fn new(config: OpenAiEmbeddingModelConfig) -> GraphResult<Self> {
+        let api_key = resolve_api_key(config.api_key)?;
+        if config.model.trim().is_empty() {
+            return Err(openai_error("model is required"));
+        }
+        Ok(Self {
+            model: config.model,
+            api_key,
+            base_url: config.base_url,
+            organization: config.organization,
+            project: config.project,
+            timeout_ms: config.timeout_ms.max(1),
+            dimensions: config.dimensions,
+            max_batch_inputs: config.max_batch_inputs.max(1),
+            max_batch_tokens: config.max_batch_tokens.max(1),
+            transport: config.transport.unwrap_or_else(default_transport),
+        })
+    }
+
+    fn endpoint(&self) -> String {
+        format!("{}/embeddings", self.base_url.trim_end_matches('/'))
+    }
+
+    /// Embed `inputs`, splitting them into batches under the configured
+    /// limits, and return the vectors with summed token usage.
+    pub async fn embed_with_usage(&self, inputs: Vec<String>) -> GraphResult<EmbeddingResponse> {
+        let mut response = EmbeddingResponse::default();
+        for batch in batch_ranges(&inputs, self.max_batch_inputs, self.max_batch_tokens) {
+            let body = self
+                .send(self.request_payload(&inputs[batch.clone()]))
+                .await?;
+            let (mut vectors, usage, model) = parse_embedding_response(&body, batch.len())?;
+            response.embeddings.append(&mut vectors);
+            response.usage.input += usage;
+            if response.model.is_none() {
+                response.model = model;
+            }
+        }
+        Ok(response)
+    }
+
+    fn request_payload(&self, inputs: &[String]) -> serde_json::Value {
+        let mut payload = serde_json::json!({
+            "model": self.model,
+            "input": inputs,
+            "encoding_format": "float",
+        });
+        if let Some(dimensions) = self.dimensions {
+            payload["dimensions"] = serde_json::json!(dimensions);
+        }
+        payload
+    }
+
+    /// POST a payload to the embeddings endpoint, mapping HTTP errors.
+    async fn send(&self, payload: serde_json::Value) -> GraphResult<serde_json::Value> {
+        let mut request = HttpRequest::new(self.endpoint(), payload)
+            .with_header("Authorization", format!("Bearer {}", self.api_key))
+            .with_header("Content-Type", "application/json")
+            .with_timeout_ms(self.timeout_ms)
+            .with_read_timeout_ms(self.timeout_ms);
+        if let Some(org) = self.organization.as_deref() {
+            request = request.with_header("OpenAI-Organization", org);
+        }
+        if let Some(proj) = self.project.as_deref() {
+            request = request.with_header("OpenAI-Project", proj);
+        }
+        let response = send_request(
+            self.transport.as_ref(),
+            "openai",
+            request,
+            None,
+            parse_error_message,
+        )
+        .await?;
+        let body = response.text().await?;
+        serde_json::from_str(&body)
+            .map_err(|err| openai_error(format!("decode response failed: {}", err)))
+    }
+}
+
+impl EmbeddingModel for OpenAiEmbeddingModel {
+    fn model_id(&self) -> &str {
+        &self.model
+    }
+
+    fn embed(&self, inputs: Vec<String>) -> BoxFuture<'_, GraphResult<Vec<Vec<f32>>>> {
+        Box::pin(async move { Ok(self.embed_with_usage(inputs).await?.embeddings) })
+    }
+}
+
+/// Split inputs into contiguous batches with at most `max_inputs` items and
+/// roughly `max_tokens` estimated tokens; an oversized input gets its own
+/// batch and is left for the provider to reject.
+fn batch_ranges(
+    inputs: &[String],
+    max_inputs: usize,
+    max_tokens: u64,
+) -> Vec<std::ops::Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    let mut tokens = 0;
+    for (index, input) in inputs.iter().enumerate() {
+        let estimate = (input.chars().count() as u64).div_ceil(4).max(1);
+        if index > start && (index - start >= max_inputs || tokens + estimate > max_tokens) {
+            ranges.push(start..index);
+            start = index;
+            tokens = 0;
+        }
+        tokens += estimate;
+    }
+    if start < inputs.len() {
+        ranges.push(start..inputs.len());
+    }
+    ranges
+}
+
+/// Order `data` by its `index` field and check one vector per input.
+fn parse_embedding_response(
+    body: &serde_json::Value,
+    expected: usize,
+) -> GraphResult<(Vec<Vec<f32>>, u64, Option<String>)> {
+    let data = body
+        .get("data")
+        .and_then(|data| data.as_array())
+        .ok_or_else(|| openai_error("missing data in embeddings response"))?;
+    let mut vectors: Vec<Option<Vec<f32>>> = vec![None; expected];
+    for (position, item) in data.iter().enumerate() {
+        let index = item
+            .get("index")
+            .and_then(|index| index.as_u64())
+            .map(|index| index as usize)
+            .unwrap_or(position);
+        let vector = item
+            .get("embedding")
+            .and_then(|embedding| embedding.as_array())
+            .ok_or_else(|| openai_error(format!("embedding {} is not a float array", index)))?
+            .iter()
+            .map(|value| value.as_f64().map(|value| value as f32))
+            .collect::<Option<Vec<f32>>>()
+            .ok_or_else(|| openai_error(format!("embedding {} is not a float array", index)))?;
+        let slot = vectors
+            .get_mut(index)
+            .ok_or_else(|| openai_error(format!("embedding index {} out of range", index)))?;
+        *slot = Some(vector);
+    }
+    let vectors = vectors
+        .into_iter()
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| {
+            openai_error(format!(
+                "embeddings response returned {} of {} vectors",
+                data.len(),
+                expected
+            ))
+        })?;
+    let usage = body
+        .get("usage")
+        .and_then(|usage| usage.get("prompt_tokens"))
+        .and_then(|tokens| tokens.as_u64())
+        .unwrap_or(0);
+    let model = body
+        .get("model")
+        .and_then(|model| model.as_str())
+        .map(str::to_string);
+    Ok((vectors, usage, model))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{batch_ranges, OpenAiEmbeddingModel, OpenAiEmbeddingModelConfig};
+    use crate::runtime::component::EmbeddingModel;
+    use crate::runtime::error::GraphError;
+    use crate::runtime::provider::stub::{StubResponse, StubServer};
+    use futures::executor::block_on;
+
+    fn stub_model(url: &str) -> OpenAiEmbeddingModelConfig {
+        OpenAiEmbeddingModelConfig::new("text-embedding-3-small")
+            .with_api_key("test-key")
+            .with_base_url(format!("{}/v1", url))
+    }
+
+    fn batch_response(indexes: &[u64], tokens: u64) -> StubResponse {
+        let data = indexes
+            .iter()
+            .map(|index| {
+                serde_json::json!({
+                    "object": "embedding",
+                    "index": index,
+                    "embedding": [*index as f64, 0.5]
+                })
+            })
+            .collect::<Vec<_>>();
+        StubResponse::json(
+            200,
+            serde_json::json!({
+                "object": "list",
+                "model": "text-embedding-3-small",
+                "data": data,
+                "usage": {"prompt_tokens": tokens, "total_tokens": tokens}
+            }),
+        )
+    }
+
+    #[test]
+    fn embed_batches_inputs_and_preserves_order() {
+        let server = StubServer::start(vec![
+            batch_response(&[1, 0], 4),
+            batch_response(&[1, 0], 5),
+            batch_response(&[0], 2),
+        ]);
+        let model = OpenAiEmbeddingModel::new(
+            stub_model(&server.url)
+                .with_organization("org-1")
+                .with_project("proj-1")
+                .with_dimensions(2)
+                .with_max_batch_inputs(2),
+        )
+        .expect("model");
+
+        let inputs = ["a", "b", "c", "d", "e"]
+            .iter()
+            .map(|text| text.to_string())
+            .collect::<Vec<_>>();
+        let response = block_on(model.embed_with_usage(inputs)).expect("embed");
+        assert_eq!(
+            response.embeddings,
+            vec![
+                vec![0.0, 0.5],
+                vec![1.0, 0.5],
+                vec![0.0, 0.5],
+                vec![1.0, 0.5],
+                vec![0.0, 0.5],
+            ]
+        );
+        assert_eq!(response.usage.input, 11);
+        assert_eq!(response.model.as_deref(), Some("text-embedding-3-small"));
+
+        let requests = server.finish();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[0].request_line.starts_with("POST /v1/embeddings"));
+        assert_eq!(requests[0].header("authorization"), Some("Bearer test-key"));
+        assert_eq!(requests[0].header("openai-organization"), Some("org-1"));
+        assert_eq!(requests[0].header("openai-project"), Some("proj-1"));
+        let first = requests[0].json();
+        assert_eq!(first["input"], serde_json::json!(["a", "b"]));
+        assert_eq!(first["dimensions"], 2);
+        assert_eq!(requests[2].json()["input"], serde_json::json!(["e"]));
+    }
+
+    #[test]
+    fn embed_maps_errors_and_short_responses() {
+        let server = StubServer::start(vec![
+            StubResponse::json(
+                400,
+                serde_json::json!({"error": {"message": "dimensions not supported"}}),
+            ),
+            batch_response(&[0], 1),
+        ]);
+        let model = OpenAiEmbeddingModel::new(stub_model(&server.url)).expect("model");
+
+        let err = block_on(model.embed(vec!["a".to_string()])).expect_err("status");
+        match err {
+            GraphError::ProviderError {
+                status, message, ..
+            } => {
+                assert_eq!(status, Some(400));
+                assert!(message.contains("dimensions not supported"));
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+        let err = block_on(model.embed(vec!["a".to_string(), "b".to_string()]))
+            .expect_err("missing vector");
+        assert!(err.to_string().contains("returned 1 of 2 vectors"));
+        server.finish();
+
+        assert!(block_on(model.embed(Vec::new())).expect("empty").is_empty());
+    }
+
+    #[test]
+    fn batch_ranges_respect_input_and_token_limits() {
+        let inputs = vec![
+            "x".repeat(8),
+            "x".repeat(8),
+            "x".repeat(40),
+            "x".to_string(),
+        ];
+        assert_eq!(batch_ranges(&inputs, 10, 5), vec![0..2, 2..3, 3..4]);
+        assert_eq!(batch_ranges(&inputs, 3, 1_000), vec![0..3, 3..4]);
+        assert!(batch_ranges(&[], 3, 10).is_empty());
+    }
+}
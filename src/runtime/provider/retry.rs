@@ -0,0 +1,739 @@
+//! Retry and client-side rate limiting wrappers for model interfaces.
+//!
+//! [`RetryingChatModel`] and [`RetryingEmbeddingModel`] wrap any model,
+//! retrying [`GraphError::ProviderError`]s whose status the [`RetryPolicy`]
+//! classifies as transient. A shared [`RateLimiter`] throttles requests
+//! before they leave the process.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::runtime::cancel::{sleep, CancellationToken};
+use crate::runtime::component::{ChatModel, ChatRequest, ChatResponse, EmbeddingModel};
+use crate::runtime::error::{GraphError, GraphResult};
+use crate::runtime::event::{Event, EventSink};
+use crate::runtime::node::BoxFuture;
+use crate::runtime::provider::TrackingSink;
+
+/// Statuses retried by default: timeouts, conflicts, rate limits, overload
+/// and gateway failures.
+pub const DEFAULT_RETRYABLE_STATUSES: &[u16] = &[408, 409, 425, 429, 500, 502, 503, 504, 529];
+
+/// Exponential backoff policy with status classification.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryPolicy {
+    /// Total attempts including the first request.
+    pub max_attempts: u32,
+    pub initial_delay_ms: u64,
+    /// Upper bound for computed delays and server `Retry-After` values.
+    pub max_delay_ms: u64,
+    pub multiplier: f64,
+    /// Randomise each delay within `[delay / 2, delay]`.
+    pub jitter: bool,
+    pub retryable_statuses: Vec<u16>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            initial_delay_ms: 500,
+            max_delay_ms: 30_000,
+            multiplier: 2.0,
+            jitter: true,
+            retryable_statuses: DEFAULT_RETRYABLE_STATUSES.to_vec(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    pub fn with_initial_delay_ms(mut self, delay_ms: u64) -> Self {
+        self.initial_delay_ms = delay_ms;
+        self
+    }
+
+    pub fn with_max_delay_ms(mut self, delay_ms: u64) -> Self {
+        self.max_delay_ms = delay_ms;
+        self
+    }
+
+    pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    pub fn with_jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    pub fn with_retryable_statuses(mut self, statuses: impl IntoIterator<Item = u16>) -> Self {
+        self.retryable_statuses = statuses.into_iter().collect();
+        self
+    }
+
+    /// Whether `error` is transient. Transport failures without a status
+    /// (timeouts, resets) are retried; everything that is not a provider
+    /// error is fatal.
+    pub fn is_retryable(&self, error: &GraphError) -> bool {
+        match error {
+            GraphError::ProviderError { status: None, .. } => true,
+            GraphError::ProviderError {
+                status: Some(status),
+                ..
+            } => self.retryable_statuses.contains(status),
+            _ => false,
+        }
+    }
+
+    /// Backoff before retrying after failed attempt `attempt` (1-based),
+    /// without jitter.
+    pub fn backoff_ms(&self, attempt: u32) -> u64 {
+        let exponent = attempt.saturating_sub(1).min(63) as i32;
+        let delay = self.initial_delay_ms as f64 * self.multiplier.powi(exponent);
+        delay.min(self.max_delay_ms as f64) as u64
+    }
+
+    /// Delay before the next attempt: the server's `Retry-After` when present,
+    /// otherwise jittered exponential backoff. Both are capped at
+    /// `max_delay_ms`.
+    pub fn delay_ms(&self, attempt: u32, error: &GraphError) -> u64 {
+        if let GraphError::ProviderError {
+            retry_after_ms: Some(retry_after),
+            ..
+        } = error
+        {
+            return (*retry_after).min(self.max_delay_ms);
+        }
+        let delay = self.backoff_ms(attempt);
+        if self.jitter && delay > 1 {
+            let half = delay / 2;
+            half + random_u64() % (delay - half + 1)
+        } else {
+            delay
+        }
+    }
+}
+
+fn random_u64() -> u64 {
+    let bytes = uuid::Uuid::new_v4().into_bytes();
+    u64::from_le_bytes(bytes[..8].try_into().expect("8 bytes"))
+}
+
+/// Token-bucket rate limiter shared across concurrent runs via `Arc`.
+///
+/// Each request takes one token; tokens refill continuously up to
+/// `capacity`.
+#[derive(Debug)]
+pub struct RateLimiter {
+    capacity: f64,
+    refill_per_second: f64,
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimiter {
+    /// Bucket of `capacity` tokens (the burst size), refilled at
+    /// `refill_per_second`. Starts full.
+    pub fn new(capacity: u32, refill_per_second: f64) -> Self {
+        let capacity = f64::from(capacity.max(1));
+        Self {
+            capacity,
+            refill_per_second: refill_per_second.max(f64::MIN_POSITIVE),
+            bucket: Mutex::new(Bucket {
+                tokens: capacity,
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// `requests` per minute, allowing bursts of the same size.
+    pub fn per_minute(requests: u32) -> Self {
+        Self::new(requests, f64::from(requests.max(1)) / 60.0)
+    }
+
+    /// Take a token, or return how long until one is available.
+    pub fn try_acquire(&self) -> Result<(), Duration> {
+        let mut bucket = self.bucket.lock().expect("rate limiter poisoned");
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.refill_per_second).min(self.capacity);
+        bucket.refilled_at = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            let missing = 1.0 - bucket.tokens;
+            Err(Duration::from_secs_f64(missing / self.refill_per_second))
+        }
+    }
+
+    /// Wait until a token is available. Returns the time spent waiting.
+    ///
+    /// The wait is timer-driven and never blocks the polling thread.
+    pub async fn acquire(&self, cancellation: Option<&CancellationToken>) -> GraphResult<Duration> {
+        let started = Instant::now();
+        loop {
+            match self.try_acquire() {
+                Ok(()) => return Ok(started.elapsed()),
+                Err(wait) => sleep(wait, cancellation).await?,
+            }
+        }
+    }
+}
+
+/// Shared retry loop. `emit` receives one `ModelRetry` event per retry.
+async fn retry_loop<'a, T>(
+    policy: &RetryPolicy,
+    limiter: Option<&RateLimiter>,
+    model: &str,
+    cancellation: Option<&CancellationToken>,
+    emit: impl Fn(Event) -> GraphResult<()>,
+    mut call: impl FnMut() -> BoxFuture<'a, GraphResult<T>>,
+    can_retry: impl Fn() -> bool,
+) -> GraphResult<T> {
+    let mut attempt = 1;
+    loop {
+        if let Some(limiter) = limiter {
+            limiter.acquire(cancellation).await?;
+        }
+        let error = match call().await {
+            Ok(value) => return Ok(value),
+            Err(error) => error,
+        };
+        if attempt >= policy.max_attempts || !policy.is_retryable(&error) || !can_retry() {
+            return Err(error);
+        }
+        let delay_ms = policy.delay_ms(attempt, &error);
+        let status = match &error {
+            GraphError::ProviderError { status, .. } => *status,
+            _ => None,
+        };
+        emit(Event::ModelRetry {
+            model: model.to_string(),
+            attempt,
+            delay_ms,
+            status,
+            error: error.to_string(),
+        })?;
+        sleep(Duration::from_millis(delay_ms), cancellation).await?;
+        attempt += 1;
+    }
+}
+
+/// Retrying, optionally rate-limited wrapper around any [`ChatModel`].
+///
+/// `generate` reports retries to the sink set with [`with_event_sink`];
+/// `stream` reports them to the stream's sink. A stream is only retried
+/// if the failed attempt had not emitted any events yet, so callers never
+/// see duplicated deltas.
+///
+/// [`with_event_sink`]: RetryingChatModel::with_event_sink
+pub struct RetryingChatModel {
+    inner: Arc<dyn ChatModel>,
+    policy: RetryPolicy,
+    limiter: Option<Arc<RateLimiter>>,
+    sink: Option<Arc<dyn EventSink>>,
+}
+
+impl RetryingChatModel {
+    pub fn new(inner: Arc<dyn ChatModel>) -> Self {
+        Self {
+            inner,
+            policy: RetryPolicy::default(),
+            limiter: None,
+            sink: None,
+        }
+    }
+
+    pub fn with_policy(mut self, policy: RetryPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn with_rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.limiter = Some(limiter);
+        self
+    }
+
+    pub fn with_event_sink(mut self, sink: Arc<dyn EventSink>) -> Self {
+        self.sink = Some(sink);
+        self
+    }
+
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+}
+
+impl ChatModel for RetryingChatModel {
+    fn model_id(&self) -> &str {
+        self.inner.model_id()
+    }
+
+    fn generate(&self, request: ChatRequest) -> BoxFuture<'_, GraphResult<ChatResponse>> {
+        Box::pin(async move {
+            let sink = self.sink.clone();
+            retry_loop(
+                &self.policy,
+                self.limiter.as_deref(),
+                self.inner.model_id(),
+                request.cancellation.as_ref(),
+                |event| sink.as_ref().map_or(Ok(()), |sink| sink.emit(event)),
+                || {
+                    let request = request.clone();
+                    Box::pin(async move {
+                        request.check_cancelled()?;
+                        self.inner.generate(request).await
+                    })
+                },
+                || true,
+            )
+            .await
+        })
+    }
+
+    fn stream(
+        &self,
+        request: ChatRequest,
+        sink: Arc<dyn EventSink>,
+    ) -> BoxFuture<'_, GraphResult<ChatResponse>> {
+        Box::pin(async move {
+            let tracked = Arc::new(TrackingSink::new(sink.clone()));
+            retry_loop(
+                &self.policy,
+                self.limiter.as_deref(),
+                self.inner.model_id(),
+                request.cancellation.as_ref(),
+                |event| sink.emit(event),
+                || {
+                    let request = request.clone();
+                    let tracked: Arc<dyn EventSink> = tracked.clone();
+                    Box::pin(async move {
+                        request.check_cancelled()?;
+                        self.inner.stream(request, tracked).await
+                    })
+                },
+                || !tracked.emitted(),
+            )
+            .await
+        })
+    }
+}
+
+/// Retrying, optionally rate-limited wrapper around any [`EmbeddingModel`].
+pub struct RetryingEmbeddingModel {
+    inner: Arc<dyn EmbeddingModel>,
+    policy: RetryPolicy,
+    limiter: Option<Arc<RateLimiter>>,
+    sink: Option<Arc<dyn EventSink>>,
+    cancel: Option<CancellationToken>,
+}
+
+impl RetryingEmbeddingModel {
+    pub fn new(inner: Arc<dyn EmbeddingModel>) -> Self {
+        Self {
+            inner,
+            policy: RetryPolicy::default(),
+            limiter: None,
+            sink: None,
+            cancel: None,
+        }
+    }
+
+    pub fn with_policy(mut self, policy: RetryPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn with_rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.limiter = Some(limiter);
+        self
+    }
+
+    pub fn with_event_sink(mut self, sink: Arc<dyn EventSink>) -> Self {
+        self.sink = Some(sink);
+        self
+    }
+
+    /// Abort backoff and rate-limit waits (and further attempts) once
+    /// `token` is cancelled.
+    pub fn with_cancel_token(mut self, token: CancellationToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+}
+
+impl EmbeddingModel for RetryingEmbeddingModel {
+    fn model_id(&self) -> &str {
+        self.inner.model_id()
+    }
+
+    fn embed(&self, inputs: Vec<String>) -> BoxFuture<'_, GraphResult<Vec<Vec<f32>>>> {
+        Box::pin(async move {
+            let sink = self.sink.clone();
+            retry_loop(
+                &self.policy,
+                self.limiter.as_deref(),
+                self.inner.model_id(),
+                self.cancel.as_ref(),
+                |event| sink.as_ref().map_or(Ok(()), |sink| sink.emit(event)),
+                || {
+                    let inputs = inputs.clone();
+                    Box::pin(async move {
+                        if let Some(token) =
+                            self.cancel.as_ref().filter(|token| token.is_cancelled())
+                        {
+                            return Err(GraphError::Aborted {
+                                reason: token.abort_reason(),
+                            });
+                        }
+                        self.inner.embed(inputs).await
+                    })
+                },
+                || true,
+            )
+            .await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RateLimiter, RetryPolicy, RetryingChatModel, RetryingEmbeddingModel};
+    use crate::runtime::cancel::CancellationToken;
+    use crate::runtime::component::{ChatModel, ChatRequest, ChatResponse, EmbeddingModel};
+    use crate::runtime::error::{GraphError, GraphResult};
+    use crate::runtime::event::{Event, EventSink};
+    use crate::runtime::message::{Message, MessageRole, Part};
+    use crate::runtime::node::BoxFuture;
+    use crate::runtime::provider::openai::{OpenAiChatModel, OpenAiChatModelConfig};
+    use crate::runtime::provider::stub::{StubResponse, StubServer};
+    use futures::executor::block_on;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct CaptureSink {
+        events: Mutex<Vec<Event>>,
+    }
+
+    impl EventSink for CaptureSink {
+        fn emit(&self, event: Event) -> GraphResult<()> {
+            self.events.lock().unwrap().push(event);
+            Ok(())
+        }
+    }
+
+    fn provider_error(status: Option<u16>, retry_after_ms: Option<u64>) -> GraphError {
+        GraphError::ProviderError {
+            provider: "test".to_string(),
+            status,
+            retry_after_ms,
+            message: "boom".to_string(),
+        }
+    }
+
+    fn fast_policy() -> RetryPolicy {
+        RetryPolicy::new()
+            .with_initial_delay_ms(1)
+            .with_jitter(false)
+    }
+
+    fn user_request(text: &str) -> ChatRequest {
+        let mut message = Message::new(MessageRole::User);
+        message.parts.push(Part::TextFinal {
+            text: text.to_string(),
+        });
+        ChatRequest::new("s1", "m1", vec![message])
+    }
+
+    /// Fails with queued errors, then answers "ok".
+    struct FlakyModel {
+        failures: Mutex<Vec<GraphError>>,
+        calls: Mutex<u32>,
+        emit_before_failing: bool,
+    }
+
+    impl FlakyModel {
+        fn new(failures: Vec<GraphError>) -> Self {
+            Self {
+                failures: Mutex::new(failures),
+                calls: Mutex::new(0),
+                emit_before_failing: false,
+            }
+        }
+
+        fn next(&self) -> GraphResult<()> {
+            *self.calls.lock().unwrap() += 1;
+            let mut failures = self.failures.lock().unwrap();
+            if failures.is_empty() {
+                Ok(())
+            } else {
+                Err(failures.remove(0))
+            }
+        }
+    }
+
+    impl ChatModel for FlakyModel {
+        fn model_id(&self) -> &str {
+            "flaky"
+        }
+
+        fn generate(&self, _request: ChatRequest) -> BoxFuture<'_, GraphResult<ChatResponse>> {
+            Box::pin(async move {
+                self.next()?;
+                let mut message = Message::new(MessageRole::Assistant);
+                message.parts.push(Part::TextFinal {
+                    text: "ok".to_string(),
+                });
+                Ok(ChatResponse::new(message))
+            })
+        }
+
+        fn stream(
+            &self,
+            request: ChatRequest,
+            sink: Arc<dyn EventSink>,
+        ) -> BoxFuture<'_, GraphResult<ChatResponse>> {
+            Box::pin(async move {
+                if self.emit_before_failing {
+                    sink.emit(Event::TextDelta {
+                        session_id: request.session_id.clone(),
+                        message_id: request.message_id.clone(),
+                        delta: "partial".to_string(),
+                    })?;
+                }
+                self.generate(request).await
+            })
+        }
+    }
+
+    impl EmbeddingModel for FlakyModel {
+        fn model_id(&self) -> &str {
+            "flaky"
+        }
+
+        fn embed(&self, inputs: Vec<String>) -> BoxFuture<'_, GraphResult<Vec<Vec<f32>>>> {
+            Box::pin(async move {
+                self.next()?;
+                Ok(inputs.iter().map(|_| vec![1.0]).collect())
+            })
+        }
+    }
+
+    #[test]
+    fn policy_classifies_statuses_and_honours_retry_after() {
+        let policy = RetryPolicy::new()
+            .with_jitter(false)
+            .with_max_delay_ms(5_000);
+        assert!(policy.is_retryable(&provider_error(Some(429), None)));
+        assert!(policy.is_retryable(&provider_error(Some(503), None)));
+        assert!(policy.is_retryable(&provider_error(None, None)));
+        assert!(!policy.is_retryable(&provider_error(Some(400), None)));
+        assert!(!policy.is_retryable(&provider_error(Some(401), None)));
+        assert!(!policy.is_retryable(&GraphError::Aborted {
+            reason: "stop".to_string()
+        }));
+
+        assert_eq!(policy.backoff_ms(1), 500);
+        assert_eq!(policy.backoff_ms(3), 2000);
+        assert_eq!(policy.backoff_ms(10), 5_000);
+        assert_eq!(
+            policy.delay_ms(1, &provider_error(Some(429), Some(1_200))),
+            1_200
+        );
+        assert_eq!(
+            policy.delay_ms(1, &provider_error(Some(429), Some(60_000))),
+            5_000
+        );
+
+        let jittered = RetryPolicy::new().delay_ms(2, &provider_error(Some(500), None));
+        assert!((500..=1000).contains(&jittered));
+    }
+
+    #[test]
+    fn generate_retries_transient_errors_and_emits_events() {
+        let inner = Arc::new(FlakyModel::new(vec![
+            provider_error(Some(503), None),
+            provider_error(None, None),
+        ]));
+        let sink = Arc::new(CaptureSink::default());
+        let model = RetryingChatModel::new(inner.clone())
+            .with_policy(fast_policy())
+            .with_event_sink(sink.clone());
+
+        let response = block_on(model.generate(user_request("hi"))).expect("retried");
+        assert_eq!(response.text().as_deref(), Some("ok"));
+        assert_eq!(*inner.calls.lock().unwrap(), 3);
+        let events = sink.events.lock().unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(matches!(
+            &events[0],
+            Event::ModelRetry { model, attempt: 1, status: Some(503), .. } if model == "flaky"
+        ));
+        assert!(matches!(
+            &events[1],
+            Event::ModelRetry {
+                attempt: 2,
+                status: None,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn fatal_errors_and_exhausted_attempts_are_returned() {
+        let inner = Arc::new(FlakyModel::new(vec![provider_error(Some(400), None)]));
+        let model = RetryingChatModel::new(inner.clone()).with_policy(fast_policy());
+        let err = block_on(model.generate(user_request("hi"))).expect_err("fatal");
+        assert!(matches!(
+            err,
+            GraphError::ProviderError {
+                status: Some(400),
+                ..
+            }
+        ));
+        assert_eq!(*inner.calls.lock().unwrap(), 1);
+
+        let inner = Arc::new(FlakyModel::new(vec![provider_error(Some(429), None); 3]));
+        let embedder = RetryingEmbeddingModel::new(inner.clone())
+            .with_policy(fast_policy().with_max_attempts(2));
+        assert!(block_on(embedder.embed(vec!["a".to_string()])).is_err());
+        assert_eq!(*inner.calls.lock().unwrap(), 2);
+    }
+
+    #[test]
+    fn stream_is_not_retried_after_partial_output() {
+        let mut flaky = FlakyModel::new(vec![provider_error(Some(503), None)]);
+        flaky.emit_before_failing = true;
+        let inner = Arc::new(flaky);
+        let sink = Arc::new(CaptureSink::default());
+        let model = RetryingChatModel::new(inner.clone()).with_policy(fast_policy());
+
+        assert!(block_on(model.stream(user_request("hi"), sink.clone())).is_err());
+        assert_eq!(*inner.calls.lock().unwrap(), 1);
+        assert_eq!(sink.events.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn cancellation_stops_backoff() {
+        let inner = Arc::new(FlakyModel::new(vec![provider_error(Some(503), None)]));
+        let model = RetryingChatModel::new(inner).with_policy(
+            fast_policy()
+                .with_initial_delay_ms(60_000)
+                .with_max_delay_ms(60_000),
+        );
+        let token = CancellationToken::new();
+        let request = user_request("hi").with_cancellation_token(token.clone());
+        let canceller = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            token.cancel("user stop");
+        });
+
+        // The backoff wait is pending on a timer, not sleeping on this thread.
+        let mut pending = model.generate(request);
+        let started = std::time::Instant::now();
+        let waker = futures::task::noop_waker();
+        let poll = pending
+            .as_mut()
+            .poll(&mut std::task::Context::from_waker(&waker));
+        assert!(poll.is_pending());
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+
+        let err = block_on(pending).expect_err("cancelled");
+        canceller.join().unwrap();
+        assert!(matches!(err, GraphError::Aborted { .. }));
+
+        let inner = Arc::new(FlakyModel::new(vec![provider_error(Some(503), None)]));
+        let token = CancellationToken::new();
+        let embedder = RetryingEmbeddingModel::new(inner)
+            .with_policy(fast_policy().with_initial_delay_ms(60_000))
+            .with_cancel_token(token.clone());
+        let canceller = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            token.cancel("user stop");
+        });
+        let err = block_on(embedder.embed(vec!["a".to_string()])).expect_err("cancelled");
+        canceller.join().unwrap();
+        assert!(matches!(err, GraphError::Aborted { .. }));
+    }
+
+    #[test]
+    fn rate_limiter_is_shared_token_bucket() {
+        let limiter = Arc::new(RateLimiter::new(2, 1000.0));
+        assert!(limiter.try_acquire().is_ok());
+        assert!(limiter.try_acquire().is_ok());
+        let wait = limiter.try_acquire().expect_err("bucket drained");
+        assert!(wait <= std::time::Duration::from_millis(1));
+        block_on(limiter.acquire(None)).expect("refills");
+
+        let slow = RateLimiter::per_minute(1);
+        assert!(slow.try_acquire().is_ok());
+        let wait = slow.try_acquire().expect_err("one per minute");
+        assert!(wait > std::time::Duration::from_secs(59));
+
+        let shared = Arc::new(RateLimiter::new(3, 0.001));
+        let handles = (0..6)
+            .map(|_| {
+                let shared = shared.clone();
+                std::thread::spawn(move || shared.try_acquire().is_ok())
+            })
+            .collect::<Vec<_>>();
+        let granted = handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .filter(|granted| *granted)
+            .count();
+        assert_eq!(granted, 3);
+    }
+
+    #[test]
+    fn openai_retry_after_header_drives_delay() {
+        let server = StubServer::start(vec![
+            StubResponse::json(429, serde_json::json!({"error": {"message": "slow down"}}))
+                .with_header("retry-after-ms", "5"),
+            StubResponse::json(
+                200,
+                serde_json::json!({
+                    "choices": [{"message": {"role": "assistant", "content": "pong"}}]
+                }),
+            ),
+        ]);
+        let inner = OpenAiChatModel::new(
+            OpenAiChatModelConfig::new("gpt-test")
+                .with_api_key("test-key")
+                .with_base_url(format!("{}/v1", server.url)),
+        )
+        .expect("construct");
+        let sink = Arc::new(CaptureSink::default());
+        let model = RetryingChatModel::new(Arc::new(inner))
+            .with_policy(RetryPolicy::new().with_initial_delay_ms(10_000))
+            .with_event_sink(sink.clone());
+
+        let response = block_on(model.generate(user_request("ping"))).expect("retried");
+        assert_eq!(response.text().as_deref(), Some("pong"));
+        assert_eq!(server.finish().len(), 2);
+        assert!(matches!(
+            sink.events.lock().unwrap()[0],
+            Event::ModelRetry {
+                delay_ms: 5,
+                status: Some(429),
+                ..
+            }
+        ));
+    }
+}
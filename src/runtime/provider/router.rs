@@ -0,0 +1,639 @@
+//! Combinators that compose several [`ChatModel`]s into one.
+//!
+//! Every combinator records the model that produced a response under
+//! [`SERVED_BY_METADATA_KEY`] in `ChatResponse::metadata`. When combinators
+//! are nested, the innermost entry (the model that actually answered) wins.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::runtime::cancel::{self, cancellable, CancellationToken};
+use crate::runtime::component::{ChatModel, ChatRequest, ChatResponse};
+use crate::runtime::error::{GraphError, GraphResult};
+use crate::runtime::event::EventSink;
+use crate::runtime::node::BoxFuture;
+use crate::runtime::provider::TrackingSink;
+use crate::runtime::tokens::{HeuristicEstimator, TokenEstimator};
+
+/// Metadata key holding the `model_id` of the model that served a request.
+pub const SERVED_BY_METADATA_KEY: &str = "served_by";
+
+type ErrorPredicate = Arc<dyn Fn(&GraphError) -> bool + Send + Sync>;
+type RequestPredicate = Arc<dyn Fn(&ChatRequest) -> bool + Send + Sync>;
+
+fn combined_id(kind: &str, models: &[Arc<dyn ChatModel>]) -> String {
+    let ids = models
+        .iter()
+        .map(|model| model.model_id())
+        .collect::<Vec<_>>();
+    format!("{}({})", kind, ids.join(","))
+}
+
+fn record_served_by(
+    response: &mut ChatResponse,
+    model: &dyn ChatModel,
+    key: &str,
+    detail: serde_json::Value,
+) {
+    response
+        .metadata
+        .entry(SERVED_BY_METADATA_KEY)
+        .or_insert_with(|| serde_json::json!(model.model_id()));
+    response.metadata.entry(key).or_insert(detail);
+}
+
+/// Tries models in order, moving to the next on error or timeout.
+///
+/// A stream only falls back if the failed model had not emitted any events,
+/// so callers never see output from two models interleaved. Fallbacks are
+/// listed under `"fallback_errors"` in the response metadata.
+pub struct FallbackChatModel {
+    id: String,
+    models: Vec<Arc<dyn ChatModel>>,
+    attempt_timeout: Option<Duration>,
+    should_fall_back: ErrorPredicate,
+}
+
+impl FallbackChatModel {
+    pub fn new(primary: Arc<dyn ChatModel>) -> Self {
+        let models = vec![primary];
+        Self {
+            id: combined_id("fallback", &models),
+            models,
+            attempt_timeout: None,
+            should_fall_back: Arc::new(|error| {
+                !matches!(
+                    error,
+                    GraphError::Aborted { .. }
+                        | GraphError::Interrupted(_)
+                        | GraphError::PermissionDenied { .. }
+                )
+            }),
+        }
+    }
+
+    pub fn with_fallback(mut self, model: Arc<dyn ChatModel>) -> Self {
+        self.models.push(model);
+        self.id = combined_id("fallback", &self.models);
+        self
+    }
+
+    /// Give up on an attempt after `timeout_ms` and try the next model.
+    ///
+    /// The attempt races a timer on the calling task and is dropped when the
+    /// timer wins; its request's cancellation token is cancelled too, so
+    /// work it handed off elsewhere can stop early.
+    pub fn with_attempt_timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.attempt_timeout = Some(Duration::from_millis(timeout_ms.max(1)));
+        self
+    }
+
+    /// Decide which errors trigger a fallback. By default everything except
+    /// aborts, interrupts and permission denials does.
+    pub fn with_fallback_on<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&GraphError) -> bool + Send + Sync + 'static,
+    {
+        self.should_fall_back = Arc::new(predicate);
+        self
+    }
+
+    async fn run(
+        &self,
+        request: ChatRequest,
+        sink: Option<Arc<dyn EventSink>>,
+    ) -> GraphResult<ChatResponse> {
+        let mut failures = Vec::new();
+        for (index, model) in self.models.iter().enumerate() {
+            request.check_cancelled()?;
+            let tracked = sink.clone().map(|sink| Arc::new(TrackingSink::new(sink)));
+            let attempt_sink = tracked.clone().map(|sink| sink as Arc<dyn EventSink>);
+            let result = match self.attempt_timeout {
+                Some(timeout) => {
+                    run_with_timeout(model, request.clone(), attempt_sink, timeout).await
+                }
+                None => match attempt_sink {
+                    Some(sink) => model.stream(request.clone(), sink).await,
+                    None => model.generate(request.clone()).await,
+                },
+            };
+            let error = match result {
+                Ok(mut response) => {
+                    record_served_by(
+                        &mut response,
+                        model.as_ref(),
+                        "fallback_errors",
+                        serde_json::Value::Array(failures),
+                    );
+                    return Ok(response);
+                }
+                Err(error) => error,
+            };
+            let is_last = index + 1 == self.models.len();
+            let emitted = tracked.is_some_and(|sink| sink.emitted());
+            if is_last || emitted || !(self.should_fall_back)(&error) {
+                return Err(error);
+            }
+            failures.push(serde_json::json!({
+                "model": model.model_id(),
+                "error": error.to_string(),
+            }));
+        }
+        unreachable!("FallbackChatModel always holds a primary model")
+    }
+}
+
+/// Run one model call bounded by `timeout`, without blocking the caller.
+///
+/// The call gets its own cancellation token, which is cancelled when the
+/// caller's token is or when the timeout expires.
+async fn run_with_timeout(
+    model: &Arc<dyn ChatModel>,
+    mut request: ChatRequest,
+    sink: Option<Arc<dyn EventSink>>,
+    timeout: Duration,
+) -> GraphResult<ChatResponse> {
+    let parent = request.cancellation.take();
+    let token = CancellationToken::new();
+    request.cancellation = Some(token.clone());
+
+    let attempt = async {
+        match sink {
+            Some(sink) => model.stream(request, sink).await,
+            None => model.generate(request).await,
+        }
+    };
+    let outcome = cancellable(parent.as_ref(), async {
+        Ok(cancel::timeout(timeout, attempt).await)
+    })
+    .await;
+    match outcome {
+        Ok(Some(result)) => result,
+        Ok(None) => {
+            let message = format!(
+                "{} timed out after {}ms",
+                model.model_id(),
+                timeout.as_millis()
+            );
+            token.cancel(message.clone());
+            Err(GraphError::ProviderError {
+                provider: model.model_id().to_string(),
+                status: None,
+                retry_after_ms: None,
+                message,
+            })
+        }
+        Err(error) => {
+            token.cancel(error.to_string());
+            Err(error)
+        }
+    }
+}
+
+impl ChatModel for FallbackChatModel {
+    fn model_id(&self) -> &str {
+        &self.id
+    }
+
+    fn generate(&self, request: ChatRequest) -> BoxFuture<'_, GraphResult<ChatResponse>> {
+        Box::pin(self.run(request, None))
+    }
+
+    fn stream(
+        &self,
+        request: ChatRequest,
+        sink: Arc<dyn EventSink>,
+    ) -> BoxFuture<'_, GraphResult<ChatResponse>> {
+        Box::pin(self.run(request, Some(sink)))
+    }
+}
+
+struct Route {
+    name: String,
+    predicate: RequestPredicate,
+    model: Arc<dyn ChatModel>,
+}
+
+/// Sends each request to the first route whose predicate matches, or to the
+/// default model. The chosen route name is recorded under `"route"`.
+pub struct RouterChatModel {
+    id: String,
+    routes: Vec<Route>,
+    default: Arc<dyn ChatModel>,
+    estimator: Arc<dyn TokenEstimator>,
+}
+
+impl RouterChatModel {
+    pub fn new(default: Arc<dyn ChatModel>) -> Self {
+        Self {
+            id: combined_id("router", std::slice::from_ref(&default)),
+            routes: Vec::new(),
+            default,
+            estimator: Arc::new(HeuristicEstimator::default()),
+        }
+    }
+
+    /// Estimator used by routes added later with
+    /// [`with_prompt_size_route`](Self::with_prompt_size_route).
+    pub fn with_token_estimator(mut self, estimator: Arc<dyn TokenEstimator>) -> Self {
+        self.estimator = estimator;
+        self
+    }
+
+    pub fn with_route<F>(
+        mut self,
+        name: impl Into<String>,
+        predicate: F,
+        model: Arc<dyn ChatModel>,
+    ) -> Self
+    where
+        F: Fn(&ChatRequest) -> bool + Send + Sync + 'static,
+    {
+        self.routes.push(Route {
+            name: name.into(),
+            predicate: Arc::new(predicate),
+            model,
+        });
+        let mut models = self
+            .routes
+            .iter()
+            .map(|route| route.model.clone())
+            .collect::<Vec<_>>();
+        models.push(self.default.clone());
+        self.id = combined_id("router", &models);
+        self
+    }
+
+    /// Route requests whose `metadata[key]` equals `value`.
+    pub fn with_metadata_route(
+        self,
+        key: impl Into<String>,
+        value: serde_json::Value,
+        model: Arc<dyn ChatModel>,
+    ) -> Self {
+        let key = key.into();
+        let name = format!("{}={}", key, value);
+        self.with_route(
+            name,
+            move |request| request.metadata.get(&key) == Some(&value),
+            model,
+        )
+    }
+
+    /// Route requests whose estimated prompt size is at least
+    /// `min_tokens`, e.g. to a larger-context model.
+    pub fn with_prompt_size_route(self, min_tokens: u64, model: Arc<dyn ChatModel>) -> Self {
+        let estimator = self.estimator.clone();
+        self.with_route(
+            format!("prompt_tokens>={}", min_tokens),
+            move |request| estimator.count_request(request) >= min_tokens,
+            model,
+        )
+    }
+
+    /// The route name and model that would serve `request`.
+    pub fn select(&self, request: &ChatRequest) -> (&str, &Arc<dyn ChatModel>) {
+        self.routes
+            .iter()
+            .find(|route| (route.predicate)(request))
+            .map(|route| (route.name.as_str(), &route.model))
+            .unwrap_or(("default", &self.default))
+    }
+}
+
+impl ChatModel for RouterChatModel {
+    fn model_id(&self) -> &str {
+        &self.id
+    }
+
+    fn generate(&self, request: ChatRequest) -> BoxFuture<'_, GraphResult<ChatResponse>> {
+        Box::pin(async move {
+            let (route, model) = self.select(&request);
+            let mut response = model.generate(request).await?;
+            record_served_by(
+                &mut response,
+                model.as_ref(),
+                "route",
+                serde_json::json!(route),
+            );
+            Ok(response)
+        })
+    }
+
+    fn stream(
+        &self,
+        request: ChatRequest,
+        sink: Arc<dyn EventSink>,
+    ) -> BoxFuture<'_, GraphResult<ChatResponse>> {
+        Box::pin(async move {
+            let (route, model) = self.select(&request);
+            let mut response = model.stream(request, sink).await?;
+            record_served_by(
+                &mut response,
+                model.as_ref(),
+                "route",
+                serde_json::json!(route),
+            );
+            Ok(response)
+        })
+    }
+}
+
+/// How [`LoadBalancedChatModel`] picks a deployment.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LoadBalanceStrategy {
+    #[default]
+    RoundRobin,
+    /// The deployment with the fewest requests in flight (ties go to the
+    /// earliest).
+    LeastInFlight,
+    Random,
+}
+
+/// Spreads requests across equivalent deployments. The chosen deployment's
+/// index is recorded under `"deployment"`.
+pub struct LoadBalancedChatModel {
+    id: String,
+    deployments: Vec<Arc<dyn ChatModel>>,
+    in_flight: Vec<Arc<AtomicUsize>>,
+    strategy: LoadBalanceStrategy,
+    next: AtomicUsize,
+}
+
+impl LoadBalancedChatModel {
+    pub fn new(first: Arc<dyn ChatModel>) -> Self {
+        let deployments = vec![first];
+        Self {
+            id: combined_id("balanced", &deployments),
+            deployments,
+            in_flight: vec![Arc::new(AtomicUsize::new(0))],
+            strategy: LoadBalanceStrategy::default(),
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn with_deployment(mut self, model: Arc<dyn ChatModel>) -> Self {
+        self.deployments.push(model);
+        self.in_flight.push(Arc::new(AtomicUsize::new(0)));
+        self.id = combined_id("balanced", &self.deployments);
+        self
+    }
+
+    pub fn with_strategy(mut self, strategy: LoadBalanceStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Requests currently in flight per deployment.
+    pub fn in_flight(&self) -> Vec<usize> {
+        self.in_flight
+            .iter()
+            .map(|count| count.load(Ordering::SeqCst))
+            .collect()
+    }
+
+    fn pick(&self) -> usize {
+        let count = self.deployments.len();
+        match self.strategy {
+            LoadBalanceStrategy::RoundRobin => self.next.fetch_add(1, Ordering::SeqCst) % count,
+            LoadBalanceStrategy::LeastInFlight => (0..count)
+                .min_by_key(|index| self.in_flight[*index].load(Ordering::SeqCst))
+                .unwrap_or(0),
+            LoadBalanceStrategy::Random => {
+                let bytes = uuid::Uuid::new_v4().into_bytes();
+                u64::from_le_bytes(bytes[..8].try_into().expect("8 bytes")) as usize % count
+            }
+        }
+    }
+
+    async fn run(
+        &self,
+        request: ChatRequest,
+        sink: Option<Arc<dyn EventSink>>,
+    ) -> GraphResult<ChatResponse> {
+        let index = self.pick();
+        let model = &self.deployments[index];
+        let _guard = InFlight::enter(self.in_flight[index].clone());
+        let mut response = match sink {
+            Some(sink) => model.stream(request, sink).await?,
+            None => model.generate(request).await?,
+        };
+        record_served_by(
+            &mut response,
+            model.as_ref(),
+            "deployment",
+            serde_json::json!(index),
+        );
+        Ok(response)
+    }
+}
+
+struct InFlight(Arc<AtomicUsize>);
+
+impl InFlight {
+    fn enter(counter: Arc<AtomicUsize>) -> Self {
+        counter.fetch_add(1, Ordering::SeqCst);
+        Self(counter)
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl ChatModel for LoadBalancedChatModel {
+    fn model_id(&self) -> &str {
+        &self.id
+    }
+
+    fn generate(&self, request: ChatRequest) -> BoxFuture<'_, GraphResult<ChatResponse>> {
+        Box::pin(self.run(request, None))
+    }
+
+    fn stream(
+        &self,
+        request: ChatRequest,
+        sink: Arc<dyn EventSink>,
+    ) -> BoxFuture<'_, GraphResult<ChatResponse>> {
+        Box::pin(self.run(request, Some(sink)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        FallbackChatModel, LoadBalanceStrategy, LoadBalancedChatModel, RouterChatModel,
+        SERVED_BY_METADATA_KEY,
+    };
+    use crate::runtime::cancel::CancellationToken;
+    use crate::runtime::component::{ChatModel, ChatRequest, ChatResponse, MockChatModel};
+    use crate::runtime::error::{GraphError, GraphResult};
+    use crate::runtime::message::{Message, MessageRole, Part};
+    use crate::runtime::node::BoxFuture;
+    use crate::runtime::tokens::{HeuristicEstimator, TokenEstimator};
+    use futures::executor::block_on;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn user_request(text: &str) -> ChatRequest {
+        let mut message = Message::new(MessageRole::User);
+        message.parts.push(Part::TextFinal {
+            text: text.to_string(),
+        });
+        ChatRequest::new("s1", "m1", vec![message])
+    }
+
+    struct FailingModel {
+        error: GraphError,
+    }
+
+    impl ChatModel for FailingModel {
+        fn model_id(&self) -> &str {
+            "failing"
+        }
+
+        fn generate(&self, _request: ChatRequest) -> BoxFuture<'_, GraphResult<ChatResponse>> {
+            Box::pin(async move { Err(self.error.clone()) })
+        }
+    }
+
+    /// Waits on a timer until its deadline or until the request is cancelled.
+    struct SlowModel;
+
+    impl ChatModel for SlowModel {
+        fn model_id(&self) -> &str {
+            "slow"
+        }
+
+        fn generate(&self, request: ChatRequest) -> BoxFuture<'_, GraphResult<ChatResponse>> {
+            Box::pin(async move {
+                crate::runtime::cancel::sleep(
+                    Duration::from_secs(5),
+                    request.cancellation.as_ref(),
+                )
+                .await?;
+                Ok(ChatResponse::new(Message::new(MessageRole::Assistant)))
+            })
+        }
+    }
+
+    fn served_by(response: &ChatResponse) -> &str {
+        response.metadata[SERVED_BY_METADATA_KEY]
+            .as_str()
+            .expect("served_by")
+    }
+
+    #[test]
+    fn fallback_uses_secondary_on_error_and_records_it() {
+        let model = FallbackChatModel::new(Arc::new(FailingModel {
+            error: GraphError::ProviderError {
+                provider: "failing".to_string(),
+                status: Some(503),
+                retry_after_ms: None,
+                message: "unavailable".to_string(),
+            },
+        }))
+        .with_fallback(Arc::new(MockChatModel::new("backup", "from backup")));
+        assert_eq!(model.model_id(), "fallback(failing,backup)");
+
+        let response = block_on(model.generate(user_request("hi"))).expect("fallback");
+        assert_eq!(response.text().as_deref(), Some("from backup"));
+        assert_eq!(served_by(&response), "backup");
+        assert_eq!(response.metadata["fallback_errors"][0]["model"], "failing");
+
+        let strict = FallbackChatModel::new(Arc::new(FailingModel {
+            error: GraphError::Aborted {
+                reason: "stop".to_string(),
+            },
+        }))
+        .with_fallback(Arc::new(MockChatModel::new("backup", "from backup")));
+        assert!(matches!(
+            block_on(strict.generate(user_request("hi"))),
+            Err(GraphError::Aborted { .. })
+        ));
+    }
+
+    #[test]
+    fn fallback_times_out_slow_primary() {
+        let model = FallbackChatModel::new(Arc::new(SlowModel))
+            .with_fallback(Arc::new(MockChatModel::new("backup", "fast")))
+            .with_attempt_timeout_ms(30);
+
+        // The timed attempt waits on the calling task without blocking it.
+        let mut pending = model.generate(user_request("hi"));
+        let waker = futures::task::noop_waker();
+        let started = std::time::Instant::now();
+        assert!(pending
+            .as_mut()
+            .poll(&mut std::task::Context::from_waker(&waker))
+            .is_pending());
+        assert!(started.elapsed() < Duration::from_millis(30));
+
+        let response = block_on(pending).expect("fallback");
+        assert_eq!(served_by(&response), "backup");
+        let error = response.metadata["fallback_errors"][0]["error"]
+            .as_str()
+            .unwrap();
+        assert!(error.contains("timed out after 30ms"));
+
+        let token = CancellationToken::new();
+        token.cancel("user stop");
+        let request = user_request("hi").with_cancellation_token(token);
+        assert!(matches!(
+            block_on(model.generate(request)),
+            Err(GraphError::Aborted { .. })
+        ));
+    }
+
+    #[test]
+    fn router_selects_by_metadata_and_prompt_size() {
+        let model = RouterChatModel::new(Arc::new(MockChatModel::new("small", "s")))
+            .with_metadata_route(
+                "tier",
+                serde_json::json!("premium"),
+                Arc::new(MockChatModel::new("premium", "p")),
+            )
+            .with_prompt_size_route(100, Arc::new(MockChatModel::new("long", "l")));
+
+        let response = block_on(model.generate(user_request("hi"))).unwrap();
+        assert_eq!(served_by(&response), "small");
+        assert_eq!(response.metadata["route"], "default");
+
+        let premium = user_request("hi").with_metadata("tier", serde_json::json!("premium"));
+        let response = block_on(model.generate(premium)).unwrap();
+        assert_eq!(served_by(&response), "premium");
+        assert_eq!(response.metadata["route"], "tier=\"premium\"");
+
+        let long = user_request(&"word ".repeat(100));
+        assert!(HeuristicEstimator::default().count_request(&long) >= 100);
+        let response = block_on(model.generate(long)).unwrap();
+        assert_eq!(served_by(&response), "long");
+    }
+
+    #[test]
+    fn load_balancer_spreads_requests_and_nested_served_by_wins() {
+        let balancer = LoadBalancedChatModel::new(Arc::new(MockChatModel::new("east", "e")))
+            .with_deployment(Arc::new(MockChatModel::new("west", "w")));
+        let served = (0..4)
+            .map(|_| {
+                let response = block_on(balancer.generate(user_request("hi"))).unwrap();
+                served_by(&response).to_string()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(served, ["east", "west", "east", "west"]);
+        assert_eq!(balancer.in_flight(), [0, 0]);
+
+        let least = LoadBalancedChatModel::new(Arc::new(MockChatModel::new("a", "a")))
+            .with_deployment(Arc::new(MockChatModel::new("b", "b")))
+            .with_strategy(LoadBalanceStrategy::LeastInFlight);
+        let response = block_on(least.generate(user_request("hi"))).unwrap();
+        assert_eq!(response.metadata["deployment"], 0);
+
+        let nested = FallbackChatModel::new(Arc::new(balancer));
+        let response = block_on(nested.generate(user_request("hi"))).unwrap();
+        assert_eq!(served_by(&response), "east");
+    }
+}
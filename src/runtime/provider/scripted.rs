@@ -0,0 +1,504 @@
+//! Scripted chat model for multi-turn agent tests.
+//!
+//! [`ScriptedChatModel`] answers requests from a queue of [`ScriptedTurn`]s:
+//! text, tool calls, usage and finish reasons, or an error. Turns can be
+//! gated on request content, delayed, and streamed as text deltas, and every
+//! request the model receives is kept for assertions.
+//!
+//! ```ignore
+//! let model = ScriptedChatModel::new("agent")
+//!     .with_turn(ScriptedTurn::tool_call("search", "call-1", json!({"q": "rust"})))
+//!     .with_turn(ScriptedTurn::streamed(["Found ", "it."]).with_usage(12, 3));
+//! // ... run the agent ...
+//! assert_eq!(model.requests().len(), 2);
+//! assert_eq!(model.remaining(), 0);
+//! ```
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::runtime::cancel::sleep;
+use crate::runtime::component::{ChatModel, ChatRequest, ChatResponse};
+use crate::runtime::error::{GraphError, GraphResult};
+use crate::runtime::event::{Event, EventSink, TokenUsage};
+use crate::runtime::message::{Message, MessageRole, Part};
+use crate::runtime::node::BoxFuture;
+
+type Matcher = Arc<dyn Fn(&ChatRequest) -> bool + Send + Sync>;
+
+/// One scripted model reply.
+#[derive(Clone)]
+pub struct ScriptedTurn {
+    outcome: Result<ChatResponse, GraphError>,
+    deltas: Vec<String>,
+    latency: Option<Duration>,
+    matcher: Option<Matcher>,
+    description: Option<String>,
+    repeat: bool,
+}
+
+impl std::fmt::Debug for ScriptedTurn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScriptedTurn")
+            .field("outcome", &self.outcome)
+            .field("deltas", &self.deltas)
+            .field("latency", &self.latency)
+            .field("matcher", &self.description)
+            .field("repeat", &self.repeat)
+            .finish()
+    }
+}
+
+impl ScriptedTurn {
+    /// Reply with `response`; the message id is set to the request's and a
+    /// missing model id or finish reason is filled in when it is served.
+    pub fn response(response: ChatResponse) -> Self {
+        Self {
+            outcome: Ok(response),
+            deltas: Vec::new(),
+            latency: None,
+            matcher: None,
+            description: None,
+            repeat: false,
+        }
+    }
+
+    /// Reply with an assistant message holding `text`.
+    pub fn text(text: impl Into<String>) -> Self {
+        let mut message = Message::new(MessageRole::Assistant);
+        message.parts.push(Part::TextFinal { text: text.into() });
+        Self::response(ChatResponse::new(message))
+    }
+
+    /// Reply with text assembled from `deltas`, streamed one delta at a time.
+    pub fn streamed<I, S>(deltas: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let deltas = deltas.into_iter().map(Into::into).collect::<Vec<String>>();
+        let mut turn = Self::text(deltas.concat());
+        turn.deltas = deltas;
+        turn
+    }
+
+    /// Reply with a single tool call and no text.
+    pub fn tool_call(
+        tool: impl Into<String>,
+        call_id: impl Into<String>,
+        input: serde_json::Value,
+    ) -> Self {
+        Self::response(ChatResponse::new(Message::new(MessageRole::Assistant)))
+            .with_tool_call(tool, call_id, input)
+    }
+
+    /// Fail with `error`; any deltas are streamed before the failure.
+    pub fn error(error: GraphError) -> Self {
+        Self {
+            outcome: Err(error),
+            ..Self::response(ChatResponse::new(Message::new(MessageRole::Assistant)))
+        }
+    }
+
+    /// Append a tool call to the reply message.
+    pub fn with_tool_call(
+        mut self,
+        tool: impl Into<String>,
+        call_id: impl Into<String>,
+        input: serde_json::Value,
+    ) -> Self {
+        if let Ok(response) = &mut self.outcome {
+            response.message.parts.push(Part::ToolCall {
+                tool: tool.into(),
+                call_id: call_id.into(),
+                input,
+            });
+        }
+        self
+    }
+
+    pub fn with_usage(self, input: u64, output: u64) -> Self {
+        self.with_token_usage(TokenUsage {
+            input,
+            output,
+            ..TokenUsage::default()
+        })
+    }
+
+    pub fn with_token_usage(mut self, usage: TokenUsage) -> Self {
+        if let Ok(response) = &mut self.outcome {
+            response.usage = Some(usage);
+        }
+        self
+    }
+
+    pub fn with_finish_reason(mut self, finish_reason: impl Into<String>) -> Self {
+        if let Ok(response) = &mut self.outcome {
+            response.finish_reason = Some(finish_reason.into());
+        }
+        self
+    }
+
+    /// Stream these deltas instead of a single `TextFinal` event.
+    pub fn with_deltas<I, S>(mut self, deltas: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.deltas = deltas.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Wait this long before replying; cancellation still interrupts it.
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = Some(latency);
+        self
+    }
+
+    /// Only serve this turn for requests accepted by `matcher`.
+    pub fn when(
+        mut self,
+        description: impl Into<String>,
+        matcher: impl Fn(&ChatRequest) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.description = Some(description.into());
+        self.matcher = Some(Arc::new(matcher));
+        self
+    }
+
+    /// Only serve this turn when the last message's text contains `needle`.
+    pub fn when_last_message_contains(self, needle: impl Into<String>) -> Self {
+        let needle = needle.into();
+        self.when(
+            format!("last message contains {:?}", needle),
+            move |request| {
+                request
+                    .messages
+                    .last()
+                    .is_some_and(|message| message_text(message).contains(&needle))
+            },
+        )
+    }
+
+    /// Keep this turn in the queue after serving it.
+    pub fn repeating(mut self) -> Self {
+        self.repeat = true;
+        self
+    }
+
+    fn matches(&self, request: &ChatRequest) -> bool {
+        self.matcher
+            .as_ref()
+            .map_or(true, |matcher| matcher(request))
+    }
+}
+
+/// Chat model that replays a script of turns and records its requests.
+///
+/// Each request is answered by the first queued turn whose matcher accepts
+/// it; served turns are removed unless marked [`repeating`]. A request no
+/// turn matches fails with an error naming the request.
+///
+/// [`repeating`]: ScriptedTurn::repeating
+#[derive(Clone, Debug)]
+pub struct ScriptedChatModel {
+    model_id: String,
+    turns: Arc<Mutex<VecDeque<ScriptedTurn>>>,
+    requests: Arc<Mutex<Vec<ChatRequest>>>,
+}
+
+impl ScriptedChatModel {
+    pub fn new(model_id: impl Into<String>) -> Self {
+        Self {
+            model_id: model_id.into(),
+            turns: Arc::new(Mutex::new(VecDeque::new())),
+            requests: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub fn with_turn(self, turn: ScriptedTurn) -> Self {
+        self.push(turn);
+        self
+    }
+
+    pub fn with_turns(self, turns: impl IntoIterator<Item = ScriptedTurn>) -> Self {
+        for turn in turns {
+            self.push(turn);
+        }
+        self
+    }
+
+    /// Queue another turn; clones of this model share the queue.
+    pub fn push(&self, turn: ScriptedTurn) {
+        self.turns.lock().expect("script poisoned").push_back(turn);
+    }
+
+    /// Every request received so far, in order.
+    pub fn requests(&self) -> Vec<ChatRequest> {
+        self.requests.lock().expect("script poisoned").clone()
+    }
+
+    pub fn last_request(&self) -> Option<ChatRequest> {
+        self.requests
+            .lock()
+            .expect("script poisoned")
+            .last()
+            .cloned()
+    }
+
+    pub fn call_count(&self) -> usize {
+        self.requests.lock().expect("script poisoned").len()
+    }
+
+    /// Turns still queued, including repeating ones.
+    pub fn remaining(&self) -> usize {
+        self.turns.lock().expect("script poisoned").len()
+    }
+
+    fn next_turn(&self, request: &ChatRequest) -> GraphResult<ScriptedTurn> {
+        let index = {
+            let mut requests = self.requests.lock().expect("script poisoned");
+            requests.push(request.clone());
+            requests.len() - 1
+        };
+        let mut turns = self.turns.lock().expect("script poisoned");
+        let position = turns
+            .iter()
+            .position(|turn| turn.matches(request))
+            .ok_or_else(|| {
+                let last = request
+                    .messages
+                    .last()
+                    .map(message_text)
+                    .unwrap_or_default();
+                GraphError::ExecutionError {
+                    node: "provider:scripted".to_string(),
+                    message: format!(
+                        "no scripted turn matches request {} ({} queued); last message: {:?}",
+                        index,
+                        turns.len(),
+                        last
+                    ),
+                }
+            })?;
+        if turns[position].repeat {
+            Ok(turns[position].clone())
+        } else {
+            Ok(turns.remove(position).expect("position is in range"))
+        }
+    }
+
+    async fn run(
+        &self,
+        request: ChatRequest,
+        sink: Option<Arc<dyn EventSink>>,
+    ) -> GraphResult<ChatResponse> {
+        request.check_cancelled()?;
+        let turn = self.next_turn(&request)?;
+        if let Some(latency) = turn.latency {
+            sleep(latency, request.cancellation.as_ref()).await?;
+        }
+        if let Some(sink) = &sink {
+            for delta in &turn.deltas {
+                request.check_cancelled()?;
+                sink.emit(Event::TextDelta {
+                    session_id: request.session_id.clone(),
+                    message_id: request.message_id.clone(),
+                    delta: delta.clone(),
+                })?;
+            }
+        }
+        let mut response = turn.outcome?;
+        response.message.id = request.message_id.clone();
+        if response.model.is_none() {
+            response.model = Some(self.model_id.clone());
+        }
+        if response.finish_reason.is_none() {
+            let reason = if response.tool_calls().is_empty() {
+                "stop"
+            } else {
+                "tool_calls"
+            };
+            response.finish_reason = Some(reason.to_string());
+        }
+        if let Some(sink) = &sink {
+            if turn.deltas.is_empty() {
+                if let Some(text) = response.text() {
+                    sink.emit(Event::TextFinal {
+                        session_id: request.session_id.clone(),
+                        message_id: request.message_id.clone(),
+                        text,
+                    })?;
+                }
+            }
+        }
+        Ok(response)
+    }
+}
+
+impl ChatModel for ScriptedChatModel {
+    fn model_id(&self) -> &str {
+        &self.model_id
+    }
+
+    fn generate(&self, request: ChatRequest) -> BoxFuture<'_, GraphResult<ChatResponse>> {
+        Box::pin(self.run(request, None))
+    }
+
+    fn stream(
+        &self,
+        request: ChatRequest,
+        sink: Arc<dyn EventSink>,
+    ) -> BoxFuture<'_, GraphResult<ChatResponse>> {
+        Box::pin(self.run(request, Some(sink)))
+    }
+}
+
+fn message_text(message: &Message) -> String {
+    message
+        .parts
+        .iter()
+        .filter_map(|part| match part {
+            Part::TextDelta { delta } => Some(delta.as_str()),
+            Part::TextFinal { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ScriptedChatModel, ScriptedTurn};
+    use crate::runtime::cancel::CancellationToken;
+    use crate::runtime::component::{ChatModel, ChatRequest};
+    use crate::runtime::error::{GraphError, GraphResult};
+    use crate::runtime::event::{Event, EventSink};
+    use crate::runtime::message::{Message, MessageRole, Part};
+    use futures::executor::block_on;
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    #[derive(Default)]
+    struct CaptureSink {
+        events: Mutex<Vec<Event>>,
+    }
+
+    impl EventSink for CaptureSink {
+        fn emit(&self, event: Event) -> GraphResult<()> {
+            self.events.lock().unwrap().push(event);
+            Ok(())
+        }
+    }
+
+    fn request(text: &str) -> ChatRequest {
+        let mut message = Message::new(MessageRole::User);
+        message.parts.push(Part::TextFinal {
+            text: text.to_string(),
+        });
+        ChatRequest::new("s1", "m1", vec![message])
+    }
+
+    #[test]
+    fn serves_queued_turns_in_order_and_records_requests() {
+        let model = ScriptedChatModel::new("scripted")
+            .with_turn(
+                ScriptedTurn::tool_call("search", "call-1", serde_json::json!({"q": "rust"}))
+                    .with_usage(10, 2),
+            )
+            .with_turn(ScriptedTurn::text("done").with_finish_reason("length"));
+
+        let first = block_on(model.generate(request("find rust"))).expect("first turn");
+        assert_eq!(first.tool_calls()[0].tool, "search");
+        assert_eq!(first.finish_reason.as_deref(), Some("tool_calls"));
+        assert_eq!(first.usage.as_ref().map(|usage| usage.input), Some(10));
+        assert_eq!(first.model.as_deref(), Some("scripted"));
+        assert_eq!(first.message.id, "m1");
+
+        let second = block_on(model.generate(request("continue"))).expect("second turn");
+        assert_eq!(second.text().as_deref(), Some("done"));
+        assert_eq!(second.finish_reason.as_deref(), Some("length"));
+
+        let err = block_on(model.generate(request("again"))).expect_err("script exhausted");
+        assert!(err
+            .to_string()
+            .contains("no scripted turn matches request 2"));
+        let requests = model.requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(super::message_text(&requests[1].messages[0]), "continue");
+        assert_eq!(requests[2].session_id, "s1");
+        assert_eq!(model.remaining(), 0);
+    }
+
+    #[test]
+    fn matchers_pick_turns_and_repeating_turns_stay_queued() {
+        let model = ScriptedChatModel::new("scripted")
+            .with_turn(ScriptedTurn::text("weather").when_last_message_contains("forecast"))
+            .with_turn(
+                ScriptedTurn::error(GraphError::ProviderError {
+                    provider: "scripted".to_string(),
+                    status: Some(429),
+                    retry_after_ms: Some(5),
+                    message: "slow down".to_string(),
+                })
+                .when("has tools", |request| !request.tools.is_empty()),
+            )
+            .with_turn(ScriptedTurn::text("fallback").repeating());
+
+        let reply = |text| block_on(model.generate(request(text))).map(|r| r.text());
+        assert_eq!(reply("hello").unwrap().as_deref(), Some("fallback"));
+        assert_eq!(reply("the forecast?").unwrap().as_deref(), Some("weather"));
+        assert_eq!(
+            reply("forecast again").unwrap().as_deref(),
+            Some("fallback")
+        );
+
+        let with_tools =
+            request("x").with_tool(crate::runtime::tool::ToolDefinition::new("t", "tool"));
+        match block_on(model.generate(with_tools)) {
+            Err(GraphError::ProviderError { status, .. }) => assert_eq!(status, Some(429)),
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert_eq!(model.remaining(), 1);
+        assert_eq!(model.call_count(), 4);
+    }
+
+    #[test]
+    fn stream_emits_deltas_after_latency_and_honours_cancellation() {
+        let model = ScriptedChatModel::new("scripted")
+            .with_turn(
+                ScriptedTurn::streamed(["Hel", "lo"]).with_latency(Duration::from_millis(20)),
+            )
+            .with_turn(ScriptedTurn::text("never").with_latency(Duration::from_secs(30)));
+        let sink = Arc::new(CaptureSink::default());
+
+        let started = Instant::now();
+        let response =
+            block_on(model.stream(request("hi"), sink.clone() as Arc<dyn EventSink>)).unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(20));
+        assert_eq!(response.text().as_deref(), Some("Hello"));
+        let deltas = sink
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|event| match event {
+                Event::TextDelta { delta, .. } => Some(delta.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(deltas, vec!["Hel", "lo"]);
+
+        let token = CancellationToken::new();
+        let canceller = token.clone();
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            canceller.cancel("test over");
+        });
+        let started = Instant::now();
+        let result = block_on(model.generate(request("slow").with_cancellation_token(token)));
+        handle.join().unwrap();
+        assert!(matches!(result, Err(GraphError::Aborted { .. })));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
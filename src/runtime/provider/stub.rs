@@ -0,0 +1,149 @@
+//! Minimal HTTP stub server for provider adapter tests.
+
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::thread::JoinHandle;
+
+/// A request captured by [`StubServer`].
+#[derive(Clone, Debug)]
+pub(crate) struct StubRequest {
+    pub request_line: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl StubRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn json(&self) -> serde_json::Value {
+        serde_json::from_str(&self.body).expect("request body is JSON")
+    }
+}
+
+/// Canned response served by [`StubServer`].
+#[derive(Clone, Debug)]
+pub(crate) struct StubResponse {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl StubResponse {
+    pub fn json(status: u16, body: serde_json::Value) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            headers: Vec::new(),
+            body: body.to_string(),
+        }
+    }
+
+    /// Server-sent events, one `data:` frame per chunk followed by `[DONE]`.
+    pub fn sse(chunks: &[serde_json::Value]) -> Self {
+        let mut body = String::new();
+        for chunk in chunks {
+            body.push_str(&format!("data: {}\n\n", chunk));
+        }
+        body.push_str("data: [DONE]\n\n");
+        Self::raw_sse(body)
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn raw_sse(body: impl Into<String>) -> Self {
+        Self {
+            status: 200,
+            content_type: "text/event-stream",
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+}
+
+/// Serves one canned response per connection, in order, then exits.
+pub(crate) struct StubServer {
+    pub url: String,
+    handle: JoinHandle<Vec<StubRequest>>,
+}
+
+impl StubServer {
+    pub fn start(responses: Vec<StubResponse>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let url = format!("http://{}", listener.local_addr().expect("addr"));
+        let handle = std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            for response in responses {
+                let (mut stream, _) = listener.accept().expect("accept");
+                requests.push(read_request(&mut stream));
+                let mut head = format!(
+                    "HTTP/1.1 {} Stub\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+                    response.status,
+                    response.content_type,
+                    response.body.len()
+                );
+                for (name, value) in &response.headers {
+                    head.push_str(&format!("{}: {}\r\n", name, value));
+                }
+                head.push_str("\r\n");
+                let _ = stream.write_all(head.as_bytes());
+                let _ = stream.write_all(response.body.as_bytes());
+            }
+            requests
+        });
+        Self { url, handle }
+    }
+
+    /// Wait for every response to be served and return the captured requests.
+    pub fn finish(self) -> Vec<StubRequest> {
+        self.handle.join().expect("stub server thread")
+    }
+}
+
+fn read_request(stream: &mut std::net::TcpStream) -> StubRequest {
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut raw = Vec::new();
+    loop {
+        let n = stream.read(&mut buf).expect("read");
+        raw.extend_from_slice(&buf[..n]);
+        let text = String::from_utf8_lossy(&raw).to_string();
+        if let Some(header_end) = text.find("\r\n\r\n") {
+            let length = text[..header_end]
+                .lines()
+                .find_map(|line| {
+                    line.to_ascii_lowercase()
+                        .strip_prefix("content-length:")
+                        .map(|value| value.trim().parse::<usize>().unwrap_or(0))
+                })
+                .unwrap_or(0);
+            if raw.len() >= header_end + 4 + length || n == 0 {
+                let mut lines = text[..header_end].lines();
+                let request_line = lines.next().unwrap_or_default().to_string();
+                let headers = lines
+                    .filter_map(|line| line.split_once(':'))
+                    .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+                    .collect();
+                return StubRequest {
+                    request_line,
+                    headers,
+                    body: text[header_end + 4..].to_string(),
+                };
+            }
+        }
+        if n == 0 {
+            return StubRequest {
+                request_line: String::new(),
+                headers: Vec::new(),
+                body: String::new(),
+            };
+        }
+    }
+}
@@ -0,0 +1,912 @@
+//! Pluggable HTTP transport for provider adapters.
+//!
+//! Adapters describe a call as an [`HttpRequest`] and await an
+//! [`HttpTransport`]. The default [`BlockingPoolTransport`] runs `ureq` on a
+//! dedicated worker pool, so the executor thread polling a model future is
+//! never blocked and many calls can be in flight at once. Response bodies are
+//! handed back line by line through [`HttpBody`]; dropping the response
+//! future or the body (for example when a call is cancelled) releases the
+//! worker's pool slot at once. A blocked `ureq` call cannot be interrupted,
+//! so the abandoned thread exits on its own once its current connect or
+//! read times out.
+//!
+//! [`HttpRequest::timeout_ms`] bounds the wait for the response head only;
+//! the body is read under a per-read idle timeout
+//! ([`HttpRequest::read_timeout_ms`]), so a long stream that keeps sending
+//! is never cut off.
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::io::{BufRead, Read};
+use std::pin::Pin;
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+use crate::runtime::cancel::timeout;
+use crate::runtime::error::{GraphError, GraphResult};
+use crate::runtime::node::BoxFuture;
+
+/// Worker limit for [`BlockingPoolTransport::shared`].
+pub const DEFAULT_MAX_WORKERS: usize = 64;
+/// Body size limit for [`BlockingPoolTransport`] unless configured.
+pub const DEFAULT_MAX_BODY_BYTES: u64 = 32 * 1024 * 1024;
+/// Idle pool workers exit after this long without work.
+const WORKER_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A JSON POST request.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HttpRequest {
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: serde_json::Value,
+    /// Deadline for connecting, sending, and receiving the response head.
+    pub timeout_ms: u64,
+    /// Longest wait for each read of the response, between body chunks included.
+    pub read_timeout_ms: u64,
+}
+
+impl HttpRequest {
+    pub fn new(url: impl Into<String>, body: serde_json::Value) -> Self {
+        Self {
+            url: url.into(),
+            headers: Vec::new(),
+            body,
+            timeout_ms: 30_000,
+            read_timeout_ms: 30_000,
+        }
+    }
+
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn with_timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.timeout_ms = timeout_ms;
+        self
+    }
+
+    pub fn with_read_timeout_ms(mut self, read_timeout_ms: u64) -> Self {
+        self.read_timeout_ms = read_timeout_ms;
+        self
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+}
+
+/// Status, headers and a streaming body. Error statuses are responses too.
+#[derive(Debug)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    body: HttpBody,
+}
+
+impl HttpResponse {
+    pub fn new(status: u16, headers: Vec<(String, String)>, body: HttpBody) -> Self {
+        Self {
+            status,
+            headers,
+            body,
+        }
+    }
+
+    /// Case-insensitive header lookup.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        find_header(&self.headers, name)
+    }
+
+    pub fn into_body(self) -> HttpBody {
+        self.body
+    }
+
+    /// Read the whole body, joining lines with `\n`.
+    pub async fn text(self) -> GraphResult<String> {
+        let mut body = self.body;
+        let mut lines = Vec::new();
+        while let Some(line) = body.next_line().await? {
+            lines.push(line);
+        }
+        Ok(lines.join("\n"))
+    }
+}
+
+fn find_header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+#[derive(Debug, Default)]
+struct BodyState {
+    lines: VecDeque<String>,
+    finished: bool,
+    error: Option<GraphError>,
+    closed: bool,
+    waker: Option<Waker>,
+}
+
+/// Receiving half of a response body, read line by line.
+#[derive(Debug)]
+pub struct HttpBody {
+    state: Arc<Mutex<BodyState>>,
+    /// Pool job streaming the body, released if the body is dropped early.
+    job: Option<Arc<JobHandle>>,
+}
+
+/// Sending half of a response body; dropping it ends the body.
+#[derive(Debug)]
+pub struct BodySender {
+    state: Arc<Mutex<BodyState>>,
+}
+
+impl HttpBody {
+    pub fn channel() -> (BodySender, HttpBody) {
+        let state = Arc::new(Mutex::new(BodyState::default()));
+        (
+            BodySender {
+                state: state.clone(),
+            },
+            HttpBody { state, job: None },
+        )
+    }
+
+    /// A body that is already complete.
+    pub fn from_text(text: &str) -> Self {
+        let (sender, body) = Self::channel();
+        for line in text.lines() {
+            sender.send_line(line);
+        }
+        body
+    }
+
+    /// Next line without its terminator, or `None` at the end of the body.
+    pub fn next_line(&mut self) -> NextLine<'_> {
+        NextLine { body: self }
+    }
+}
+
+impl Drop for HttpBody {
+    fn drop(&mut self) {
+        let finished = {
+            let mut state = self.state.lock().unwrap();
+            state.closed = true;
+            state.finished
+        };
+        if let (Some(job), false) = (&self.job, finished) {
+            job.abandon();
+        }
+    }
+}
+
+/// Future returned by [`HttpBody::next_line`].
+pub struct NextLine<'a> {
+    body: &'a mut HttpBody,
+}
+
+impl Future for NextLine<'_> {
+    type Output = GraphResult<Option<String>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.body.state.lock().unwrap();
+        if let Some(line) = state.lines.pop_front() {
+            return Poll::Ready(Ok(Some(line)));
+        }
+        if let Some(error) = state.error.take() {
+            return Poll::Ready(Err(error));
+        }
+        if state.finished {
+            return Poll::Ready(Ok(None));
+        }
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl BodySender {
+    /// Queue a line; returns `false` once the reader has gone away.
+    pub fn send_line(&self, line: impl Into<String>) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if state.closed {
+            return false;
+        }
+        state.lines.push_back(line.into());
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+        true
+    }
+
+    /// End the body with an error, reported after any queued lines.
+    pub fn fail(self, error: GraphError) {
+        self.state.lock().unwrap().error = Some(error);
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.state.lock().unwrap().closed
+    }
+}
+
+impl Drop for BodySender {
+    fn drop(&mut self) {
+        let mut state = self.state.lock().unwrap();
+        state.finished = true;
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Sends provider HTTP requests without blocking the calling executor.
+pub trait HttpTransport: Send + Sync + std::fmt::Debug {
+    /// POST `request.body` as JSON.
+    ///
+    /// Resolves once the status and headers are available; transport
+    /// failures are `GraphError::ProviderError` with no status.
+    fn post(&self, request: HttpRequest) -> BoxFuture<'static, GraphResult<HttpResponse>>;
+}
+
+/// The process-wide [`BlockingPoolTransport`].
+pub fn default_transport() -> Arc<dyn HttpTransport> {
+    Arc::new(BlockingPoolTransport::shared())
+}
+
+/// A queued call; returns `false` if the worker must exit afterwards.
+type Job = Box<dyn FnOnce() -> bool + Send>;
+
+#[derive(Default)]
+struct PoolState {
+    queue: VecDeque<Job>,
+    workers: usize,
+    idle: usize,
+}
+
+struct WorkerPool {
+    max_workers: usize,
+    state: Mutex<PoolState>,
+    available: Condvar,
+}
+
+impl WorkerPool {
+    /// Start a worker if queued jobs outnumber idle workers and there is
+    /// room; otherwise wake an idle one.
+    fn grow(self: &Arc<Self>, state: &mut PoolState) -> std::io::Result<()> {
+        if state.queue.len() > state.idle && state.workers < self.max_workers {
+            let pool = self.clone();
+            std::thread::Builder::new()
+                .name("forge-http".to_string())
+                .spawn(move || run_worker(pool))?;
+            state.workers += 1;
+        } else {
+            self.available.notify_one();
+        }
+        Ok(())
+    }
+
+    /// Give up the slot of a worker stuck on an abandoned job.
+    fn release(self: &Arc<Self>) {
+        let mut state = self.state.lock().unwrap();
+        state.workers -= 1;
+        // Queued jobs keep waiting for the remaining workers if this fails.
+        let _ = self.grow(&mut state);
+    }
+}
+
+/// One request's claim on a pool worker, so a caller that gives up can
+/// hand the slot to queued requests while the worker is still blocked.
+struct JobHandle {
+    pool: Arc<WorkerPool>,
+    state: Mutex<JobState>,
+}
+
+#[derive(Default)]
+struct JobState {
+    running: bool,
+    finished: bool,
+    abandoned: bool,
+}
+
+impl std::fmt::Debug for JobHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.state.lock().unwrap();
+        f.debug_struct("JobHandle")
+            .field("running", &state.running)
+            .field("finished", &state.finished)
+            .field("abandoned", &state.abandoned)
+            .finish()
+    }
+}
+
+impl JobHandle {
+    /// Called by the worker before running; `false` if already abandoned.
+    fn start(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        state.running = !state.abandoned;
+        state.running
+    }
+
+    /// Called by the worker afterwards; `true` if its slot was released
+    /// meanwhile, in which case the thread must exit.
+    fn finish(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        state.finished = true;
+        state.abandoned && state.running
+    }
+
+    fn abandon(&self) {
+        let release = {
+            let mut state = self.state.lock().unwrap();
+            if state.finished || state.abandoned {
+                return;
+            }
+            state.abandoned = true;
+            state.running
+        };
+        if release {
+            self.pool.release();
+        }
+    }
+}
+
+/// Runs blocking `ureq` calls on a pool of worker threads.
+///
+/// Workers are started on demand up to `max_workers`; further requests
+/// queue until a worker frees up. Streaming responses hold their worker
+/// until the body is read to the end or dropped.
+#[derive(Clone)]
+pub struct BlockingPoolTransport {
+    pool: Arc<WorkerPool>,
+    max_body_bytes: u64,
+}
+
+impl std::fmt::Debug for BlockingPoolTransport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.pool.state.lock().unwrap();
+        f.debug_struct("BlockingPoolTransport")
+            .field("max_workers", &self.pool.max_workers)
+            .field("workers", &state.workers)
+            .field("queued", &state.queue.len())
+            .field("max_body_bytes", &self.max_body_bytes)
+            .finish()
+    }
+}
+
+impl BlockingPoolTransport {
+    pub fn new(max_workers: usize) -> Self {
+        Self {
+            pool: Arc::new(WorkerPool {
+                max_workers: max_workers.max(1),
+                state: Mutex::new(PoolState::default()),
+                available: Condvar::new(),
+            }),
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+        }
+    }
+
+    /// Fail responses whose body exceeds `max_bytes`. The error carries the
+    /// response status, since retrying would hit the same limit.
+    pub fn with_max_body_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_body_bytes = max_bytes;
+        self
+    }
+
+    /// A pool shared by every adapter that is not given its own transport.
+    pub fn shared() -> Self {
+        static SHARED: OnceLock<BlockingPoolTransport> = OnceLock::new();
+        SHARED
+            .get_or_init(|| BlockingPoolTransport::new(DEFAULT_MAX_WORKERS))
+            .clone()
+    }
+
+    pub fn max_workers(&self) -> usize {
+        self.pool.max_workers
+    }
+
+    /// Worker threads currently alive.
+    pub fn workers(&self) -> usize {
+        self.pool.state.lock().unwrap().workers
+    }
+
+    fn submit(&self, job: Job) -> GraphResult<()> {
+        let mut state = self.pool.state.lock().unwrap();
+        state.queue.push_back(job);
+        match self.pool.grow(&mut state) {
+            Ok(()) => Ok(()),
+            // Other workers will pick the job up eventually.
+            Err(_) if state.workers > 0 => Ok(()),
+            Err(err) => {
+                state.queue.pop_back();
+                Err(transport_error(format!(
+                    "spawn http worker failed: {}",
+                    err
+                )))
+            }
+        }
+    }
+}
+
+fn run_worker(pool: Arc<WorkerPool>) {
+    loop {
+        let job = {
+            let mut state = pool.state.lock().unwrap();
+            loop {
+                if let Some(job) = state.queue.pop_front() {
+                    break job;
+                }
+                state.idle += 1;
+                let (next, timeout) = pool
+                    .available
+                    .wait_timeout(state, WORKER_IDLE_TIMEOUT)
+                    .unwrap();
+                state = next;
+                state.idle -= 1;
+                if timeout.timed_out() && state.queue.is_empty() {
+                    state.workers -= 1;
+                    return;
+                }
+            }
+        };
+        if !job() {
+            // The job was abandoned and this worker's slot given away.
+            return;
+        }
+    }
+}
+
+impl HttpTransport for BlockingPoolTransport {
+    fn post(&self, request: HttpRequest) -> BoxFuture<'static, GraphResult<HttpResponse>> {
+        let (sender, receiver) = oneshot();
+        let job = Arc::new(JobHandle {
+            pool: self.pool.clone(),
+            state: Mutex::new(JobState::default()),
+        });
+        let worker_job = job.clone();
+        let max_body_bytes = self.max_body_bytes;
+        let timeout_ms = request.timeout_ms.max(1);
+        let submitted = self.submit(Box::new(move || {
+            if worker_job.start() {
+                execute(request, max_body_bytes, sender);
+            }
+            !worker_job.finish()
+        }));
+        if let Err(err) = submitted {
+            return Box::pin(async move { Err(err) });
+        }
+        let pending = PendingResponse {
+            receiver,
+            job: Some(job),
+        };
+        Box::pin(async move {
+            // Dropping the pending response on timeout abandons the request.
+            timeout(Duration::from_millis(timeout_ms), pending)
+                .await
+                .unwrap_or_else(|| {
+                    Err(transport_error(format!(
+                        "no response within {} ms",
+                        timeout_ms
+                    )))
+                })
+        })
+    }
+}
+
+/// Response future of [`BlockingPoolTransport`]; dropping it before the
+/// response head arrives abandons the request.
+struct PendingResponse {
+    receiver: OneshotReceiver<GraphResult<HttpResponse>>,
+    job: Option<Arc<JobHandle>>,
+}
+
+impl Future for PendingResponse {
+    type Output = GraphResult<HttpResponse>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let result = match Pin::new(&mut self.receiver).poll(cx) {
+            Poll::Ready(result) => result,
+            Poll::Pending => return Poll::Pending,
+        };
+        let job = self.job.take();
+        Poll::Ready(result.map(|mut response| {
+            response.body.job = job;
+            response
+        }))
+    }
+}
+
+impl Drop for PendingResponse {
+    fn drop(&mut self) {
+        if let Some(job) = self.job.take() {
+            job.abandon();
+        }
+    }
+}
+
+/// Perform the request, hand back the response head, then stream the body.
+fn execute(
+    request: HttpRequest,
+    max_body_bytes: u64,
+    respond: OneshotSender<GraphResult<HttpResponse>>,
+) {
+    // No overall ureq deadline: it would include the body. The response
+    // head deadline is enforced by the caller's future.
+    let head_timeout = Duration::from_millis(request.timeout_ms.max(1));
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(head_timeout)
+        .timeout_write(head_timeout)
+        .timeout_read(Duration::from_millis(request.read_timeout_ms.max(1)))
+        .build();
+    let mut builder = agent.post(&request.url);
+    for (name, value) in &request.headers {
+        builder = builder.set(name, value);
+    }
+    let response = match builder.send_json(request.body) {
+        Ok(response) | Err(ureq::Error::Status(_, response)) => response,
+        Err(err) => {
+            respond.send(Err(transport_error(err.to_string())));
+            return;
+        }
+    };
+    let headers = response
+        .headers_names()
+        .into_iter()
+        .filter_map(|name| {
+            let value = response.header(&name)?.to_string();
+            Some((name, value))
+        })
+        .collect();
+    let status = response.status();
+    // One byte past the limit is enough to tell that it was exceeded.
+    let mut reader = std::io::BufReader::new(
+        response
+            .into_reader()
+            .take(max_body_bytes.saturating_add(1)),
+    );
+    let (sender, body) = HttpBody::channel();
+    if !respond.send(Ok(HttpResponse::new(status, headers, body))) {
+        return;
+    }
+    let mut read = 0u64;
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        let line = match reader.read_until(b'\n', &mut buf) {
+            Ok(0) => return,
+            Ok(n) => {
+                read += n as u64;
+                if read > max_body_bytes {
+                    sender.fail(GraphError::ProviderError {
+                        provider: "http".to_string(),
+                        status: Some(status),
+                        retry_after_ms: None,
+                        message: format!("response body exceeds {} bytes", max_body_bytes),
+                    });
+                    return;
+                }
+                let end =
+                    buf.len() - buf.ends_with(b"\n") as usize - buf.ends_with(b"\r\n") as usize;
+                String::from_utf8(buf[..end].to_vec())
+                    .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+            }
+            Err(err) => Err(err),
+        };
+        match line {
+            Ok(line) => {
+                if !sender.send_line(line) {
+                    return;
+                }
+            }
+            Err(err) => {
+                sender.fail(transport_error(format!("read response failed: {}", err)));
+                return;
+            }
+        }
+    }
+}
+
+fn transport_error(message: String) -> GraphError {
+    GraphError::ProviderError {
+        provider: "http".to_string(),
+        status: None,
+        retry_after_ms: None,
+        message,
+    }
+}
+
+struct OneshotState<T> {
+    value: Option<T>,
+    sender_dropped: bool,
+    receiver_dropped: bool,
+    waker: Option<Waker>,
+}
+
+struct OneshotSender<T> {
+    state: Arc<Mutex<OneshotState<T>>>,
+}
+
+struct OneshotReceiver<T> {
+    state: Arc<Mutex<OneshotState<T>>>,
+}
+
+fn oneshot<T>() -> (OneshotSender<T>, OneshotReceiver<T>) {
+    let state = Arc::new(Mutex::new(OneshotState {
+        value: None,
+        sender_dropped: false,
+        receiver_dropped: false,
+        waker: None,
+    }));
+    (
+        OneshotSender {
+            state: state.clone(),
+        },
+        OneshotReceiver { state },
+    )
+}
+
+impl<T> OneshotSender<T> {
+    /// Returns `false` when the receiver was already dropped.
+    fn send(self, value: T) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if state.receiver_dropped {
+            return false;
+        }
+        state.value = Some(value);
+        true
+    }
+}
+
+impl<T> Drop for OneshotSender<T> {
+    fn drop(&mut self) {
+        let mut state = self.state.lock().unwrap();
+        state.sender_dropped = true;
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Drop for OneshotReceiver<T> {
+    fn drop(&mut self) {
+        self.state.lock().unwrap().receiver_dropped = true;
+    }
+}
+
+impl Future for OneshotReceiver<GraphResult<HttpResponse>> {
+    type Output = GraphResult<HttpResponse>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(value) = state.value.take() {
+            return Poll::Ready(value);
+        }
+        if state.sender_dropped {
+            return Poll::Ready(Err(transport_error(
+                "http worker exited without a response".to_string(),
+            )));
+        }
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BlockingPoolTransport, HttpRequest, HttpTransport};
+    use crate::runtime::cancel::{cancellable, CancellationToken};
+    use crate::runtime::error::GraphError;
+    use crate::runtime::provider::retry::RetryPolicy;
+    use futures::executor::block_on;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::time::{Duration, Instant};
+
+    fn read_head(stream: &mut std::net::TcpStream) {
+        let mut buf = [0u8; 4096];
+        let mut raw = Vec::new();
+        while !String::from_utf8_lossy(&raw).contains("\r\n\r\n") {
+            let n = stream.read(&mut buf).expect("read");
+            if n == 0 {
+                return;
+            }
+            raw.extend_from_slice(&buf[..n]);
+        }
+    }
+
+    /// Serves one request with `body`, returning the URL and server thread.
+    fn respond_once(body: &str) -> (String, std::thread::JoinHandle<()>) {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let body = body.to_string();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("accept");
+            read_head(&mut stream);
+            let _ = write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+        });
+        (url, server)
+    }
+
+    #[test]
+    fn requests_run_concurrently_on_the_pool() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        // Answers only once both requests are connected, so a transport that
+        // serialized calls would time out.
+        let server = std::thread::spawn(move || {
+            let mut streams = Vec::new();
+            for _ in 0..2 {
+                let (mut stream, _) = listener.accept().expect("accept");
+                read_head(&mut stream);
+                streams.push(stream);
+            }
+            for (index, mut stream) in streams.into_iter().enumerate() {
+                let body = format!("{{\"n\":{}}}\nsecond line", index);
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nX-Trace: t{}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    index,
+                    body.len(),
+                    body
+                )
+                .unwrap();
+            }
+        });
+
+        let transport = BlockingPoolTransport::new(4);
+        let request = |n: u64| {
+            HttpRequest::new(format!("{}/v1", url), serde_json::json!({"n": n}))
+                .with_header("Authorization", "Bearer k")
+                .with_timeout_ms(5_000)
+        };
+        let (a, b) = block_on(futures::future::join(
+            transport.post(request(1)),
+            transport.post(request(2)),
+        ));
+        server.join().unwrap();
+        let a = a.expect("first");
+        let b = b.expect("second");
+        assert_eq!(a.status, 200);
+        assert!(a.header("x-trace").is_some());
+        let text = block_on(b.text()).expect("body");
+        assert!(text.ends_with("\nsecond line"));
+        assert_eq!(transport.workers(), 2);
+    }
+
+    #[test]
+    fn cancellation_interrupts_a_request_in_flight() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("accept");
+            read_head(&mut stream);
+            // Never respond; hold the connection well past the second call.
+            std::thread::sleep(Duration::from_millis(1_500));
+        });
+
+        let transport = BlockingPoolTransport::new(1);
+        let token = CancellationToken::new();
+        let canceller = token.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            canceller.cancel("user stop");
+        });
+        let started = Instant::now();
+        let result = block_on(cancellable(
+            Some(&token),
+            transport.post(HttpRequest::new(url, serde_json::json!({})).with_timeout_ms(10_000)),
+        ));
+        assert!(matches!(result, Err(GraphError::Aborted { .. })));
+        assert!(started.elapsed() < Duration::from_millis(250));
+
+        // The blocked worker's slot was handed back, so a one-worker pool
+        // still serves the next call.
+        let (url, responder) = respond_once("ok");
+        let response = block_on(
+            transport.post(HttpRequest::new(url, serde_json::json!({})).with_timeout_ms(5_000)),
+        )
+        .expect("served while the first worker is blocked");
+        assert_eq!(block_on(response.text()).expect("body"), "ok");
+        assert!(started.elapsed() < Duration::from_millis(1_000));
+        responder.join().unwrap();
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn streamed_bodies_may_outlast_the_head_deadline() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("accept");
+            read_head(&mut stream);
+            write!(stream, "HTTP/1.1 200 OK\r\nConnection: close\r\n\r\n").unwrap();
+            for index in 0..4 {
+                std::thread::sleep(Duration::from_millis(100));
+                writeln!(stream, "chunk {}", index).unwrap();
+            }
+        });
+
+        let transport = BlockingPoolTransport::new(1);
+        let started = Instant::now();
+        let response = block_on(
+            transport.post(
+                HttpRequest::new(url, serde_json::json!({}))
+                    .with_timeout_ms(150)
+                    .with_read_timeout_ms(2_000),
+            ),
+        )
+        .expect("head");
+        let text = block_on(response.text()).expect("whole stream");
+        assert_eq!(text, "chunk 0\nchunk 1\nchunk 2\nchunk 3");
+        assert!(started.elapsed() >= Duration::from_millis(400));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn a_missing_response_head_times_out() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("accept");
+            read_head(&mut stream);
+            std::thread::sleep(Duration::from_millis(500));
+        });
+
+        let started = Instant::now();
+        let err = block_on(
+            BlockingPoolTransport::new(1).post(
+                HttpRequest::new(url, serde_json::json!({}))
+                    .with_timeout_ms(100)
+                    .with_read_timeout_ms(5_000),
+            ),
+        )
+        .expect_err("no head");
+        assert!(
+            err.to_string().contains("no response within 100 ms"),
+            "{}",
+            err
+        );
+        assert!(started.elapsed() < Duration::from_millis(400));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn oversized_bodies_fail() {
+        let (url, responder) = respond_once(&"x".repeat(100));
+        let transport = BlockingPoolTransport::new(1).with_max_body_bytes(10);
+        let response =
+            block_on(transport.post(HttpRequest::new(url, serde_json::json!({})))).expect("head");
+        let err = block_on(response.text()).expect_err("too large");
+        assert!(err.to_string().contains("exceeds 10 bytes"), "{}", err);
+        assert!(matches!(
+            err,
+            GraphError::ProviderError {
+                status: Some(200),
+                ..
+            }
+        ));
+        assert!(!RetryPolicy::new().is_retryable(&err));
+        responder.join().unwrap();
+    }
+
+    #[test]
+    fn connection_failures_are_provider_errors() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        let err = block_on(
+            BlockingPoolTransport::new(1).post(HttpRequest::new(url, serde_json::json!({}))),
+        )
+        .expect_err("refused");
+        assert!(matches!(
+            err,
+            GraphError::ProviderError { status: None, .. }
+        ));
+    }
+}
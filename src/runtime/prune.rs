@@ -130,6 +130,7 @@ mod tests {
                 session_id: "s1".to_string(),
                 tokens: TokenUsage::default(),
                 cost: 0.0,
+                model: None,
             }),
         ];
 
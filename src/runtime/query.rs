@@ -0,0 +1,459 @@
+//! Query layer over persisted run logs.
+//!
+//! [`EventQuery`] filters records in a [`RunLogStore`] by run, event
+//! variant, tool, call id, time range, and seq range; [`QuerySummary`]
+//! aggregates counts and tool durations over the matches.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::runtime::error::{GraphError, GraphResult};
+use crate::runtime::event::{Event, EventRecord};
+use crate::runtime::session::RunLogStore;
+
+/// Filter over persisted event records. Empty filters match everything.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct EventQuery {
+    pub run_ids: Vec<String>,
+    pub variants: Vec<String>,
+    pub tool: Option<String>,
+    pub call_id: Option<String>,
+    pub since_ms: Option<u64>,
+    pub until_ms: Option<u64>,
+    pub from_seq: Option<u64>,
+    pub to_seq: Option<u64>,
+    pub limit: Option<usize>,
+}
+
+/// A record matched by a query, tagged with its run.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct QueryMatch {
+    pub run_id: String,
+    pub record: EventRecord,
+}
+
+impl EventQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_run(mut self, run_id: impl Into<String>) -> Self {
+        self.run_ids.push(run_id.into());
+        self
+    }
+
+    /// Match an event variant by its serialized name (e.g. `"ToolError"`).
+    pub fn with_variant(mut self, variant: impl Into<String>) -> Self {
+        self.variants.push(variant.into());
+        self
+    }
+
+    pub fn with_tool(mut self, tool: impl Into<String>) -> Self {
+        self.tool = Some(tool.into());
+        self
+    }
+
+    pub fn with_call_id(mut self, call_id: impl Into<String>) -> Self {
+        self.call_id = Some(call_id.into());
+        self
+    }
+
+    /// Match records with `since_ms <= timestamp_ms < until_ms`.
+    pub fn with_time_range(mut self, since_ms: Option<u64>, until_ms: Option<u64>) -> Self {
+        self.since_ms = since_ms;
+        self.until_ms = until_ms;
+        self
+    }
+
+    /// Match records with `from_seq <= seq <= to_seq`.
+    pub fn with_seq_range(mut self, from_seq: Option<u64>, to_seq: Option<u64>) -> Self {
+        self.from_seq = from_seq;
+        self.to_seq = to_seq;
+        self
+    }
+
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn matches(&self, record: &EventRecord) -> bool {
+        let meta = &record.meta;
+        if !self.variants.is_empty()
+            && !self
+                .variants
+                .iter()
+                .any(|variant| variant == record.event.variant_name())
+        {
+            return false;
+        }
+        if self.tool.is_some() || self.call_id.is_some() {
+            let Some((tool, call_id)) = record.event.tool_call() else {
+                return false;
+            };
+            if self
+                .tool
+                .as_deref()
+                .is_some_and(|expected| expected != tool)
+                || self
+                    .call_id
+                    .as_deref()
+                    .is_some_and(|expected| expected != call_id)
+            {
+                return false;
+            }
+        }
+        if self.since_ms.is_some_and(|since| meta.timestamp_ms < since)
+            || self
+                .until_ms
+                .is_some_and(|until| meta.timestamp_ms >= until)
+            || self.from_seq.is_some_and(|from| meta.seq < from)
+            || self.to_seq.is_some_and(|to| meta.seq > to)
+        {
+            return false;
+        }
+        true
+    }
+
+    /// Run the query against every selected run in the store.
+    pub fn execute(&self, store: &RunLogStore) -> std::io::Result<Vec<QueryMatch>> {
+        let runs = if self.run_ids.is_empty() {
+            store.list_runs()?
+        } else {
+            self.run_ids.clone()
+        };
+        let mut matches = Vec::new();
+        for run_id in runs {
+            let records = match (self.from_seq, self.to_seq) {
+                (None, None) => store.load(&run_id)?,
+                (from, Some(to)) => store.read_range(&run_id, from.unwrap_or(0)..=to)?,
+                (Some(from), None) => store.tail_from(&run_id, from)?,
+            };
+            for record in records {
+                if !self.matches(&record) {
+                    continue;
+                }
+                matches.push(QueryMatch {
+                    run_id: run_id.clone(),
+                    record,
+                });
+                if self.limit.is_some_and(|limit| matches.len() >= limit) {
+                    return Ok(matches);
+                }
+            }
+        }
+        Ok(matches)
+    }
+
+    /// Build a query from CLI-style flags.
+    ///
+    /// Supported flags: `--run`, `--variant` (repeatable), `--tool`,
+    /// `--call-id`, `--since-ms`, `--until-ms`, `--from-seq`, `--to-seq`,
+    /// `--limit`. Unrecognized arguments are returned for the caller.
+    pub fn parse_args(args: &[String]) -> GraphResult<(Self, Vec<String>)> {
+        let mut query = Self::new();
+        let mut rest = Vec::new();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let mut value = || {
+                iter.next()
+                    .cloned()
+                    .ok_or_else(|| query_error(format!("missing value for {}", arg)))
+            };
+            match arg.as_str() {
+                "--run" => query.run_ids.push(value()?),
+                "--variant" => query.variants.push(value()?),
+                "--tool" => query.tool = Some(value()?),
+                "--call-id" => query.call_id = Some(value()?),
+                "--since-ms" => query.since_ms = Some(parse_number(arg, &value()?)?),
+                "--until-ms" => query.until_ms = Some(parse_number(arg, &value()?)?),
+                "--from-seq" => query.from_seq = Some(parse_number(arg, &value()?)?),
+                "--to-seq" => query.to_seq = Some(parse_number(arg, &value()?)?),
+                "--limit" => query.limit = Some(parse_number(arg, &value()?)? as usize),
+                _ => rest.push(arg.clone()),
+            }
+        }
+        Ok((query, rest))
+    }
+}
+
+/// Count/total/max of tool call durations, in milliseconds.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DurationStats {
+    pub count: usize,
+    pub total_ms: u64,
+    pub max_ms: u64,
+}
+
+impl DurationStats {
+    fn record(&mut self, duration_ms: u64) {
+        self.count += 1;
+        self.total_ms += duration_ms;
+        self.max_ms = self.max_ms.max(duration_ms);
+    }
+
+    pub fn avg_ms(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.total_ms as f64 / self.count as f64
+        }
+    }
+}
+
+/// Aggregates over a set of query matches.
+///
+/// Tool durations pair `ToolStart` with `ToolResult`/`ToolError` by run and
+/// call id, so they require the query to include those variants.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct QuerySummary {
+    pub total: usize,
+    pub by_run: BTreeMap<String, usize>,
+    pub by_variant: BTreeMap<String, usize>,
+    pub by_tool: BTreeMap<String, usize>,
+    pub tool_failures: BTreeMap<String, usize>,
+    pub tool_durations: BTreeMap<String, DurationStats>,
+    pub first_timestamp_ms: Option<u64>,
+    pub last_timestamp_ms: Option<u64>,
+}
+
+impl QuerySummary {
+    pub fn from_matches(matches: &[QueryMatch]) -> Self {
+        let mut summary = Self::default();
+        let mut started: HashMap<(&str, &str), u64> = HashMap::new();
+        for item in matches {
+            let record = &item.record;
+            let timestamp = record.meta.timestamp_ms;
+            summary.total += 1;
+            *summary.by_run.entry(item.run_id.clone()).or_default() += 1;
+            *summary
+                .by_variant
+                .entry(record.event.variant_name().to_string())
+                .or_default() += 1;
+            summary.first_timestamp_ms = Some(
+                summary
+                    .first_timestamp_ms
+                    .map_or(timestamp, |first| first.min(timestamp)),
+            );
+            summary.last_timestamp_ms = Some(
+                summary
+                    .last_timestamp_ms
+                    .map_or(timestamp, |last| last.max(timestamp)),
+            );
+
+            let Some((tool, call_id)) = record.event.tool_call() else {
+                continue;
+            };
+            match &record.event {
+                Event::ToolStart { .. } => {
+                    *summary.by_tool.entry(tool.to_string()).or_default() += 1;
+                    started.insert((item.run_id.as_str(), call_id), timestamp);
+                }
+                Event::ToolResult { .. } | Event::ToolError { .. } => {
+                    if matches!(record.event, Event::ToolError { .. }) {
+                        *summary.tool_failures.entry(tool.to_string()).or_default() += 1;
+                    }
+                    if let Some(start) = started.remove(&(item.run_id.as_str(), call_id)) {
+                        summary
+                            .tool_durations
+                            .entry(tool.to_string())
+                            .or_default()
+                            .record(timestamp.saturating_sub(start));
+                    }
+                }
+                _ => {}
+            }
+        }
+        summary
+    }
+}
+
+fn parse_number(flag: &str, value: &str) -> GraphResult<u64> {
+    value
+        .parse()
+        .map_err(|_| query_error(format!("{} expects a number, got {:?}", flag, value)))
+}
+
+fn query_error(message: impl Into<String>) -> GraphError {
+    GraphError::ExecutionError {
+        node: "query".to_string(),
+        message: message.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EventQuery, QuerySummary};
+    use crate::runtime::event::{Event, EventMeta, EventRecord};
+    use crate::runtime::session::RunLogStore;
+    use crate::runtime::tool::ToolOutput;
+
+    fn record(seq: u64, timestamp_ms: u64, event: Event) -> EventRecord {
+        EventRecord::with_meta(
+            event,
+            EventMeta {
+                event_id: format!("e{}", seq),
+                timestamp_ms,
+                seq,
+                ..Default::default()
+            },
+        )
+    }
+
+    fn tool_start(call_id: &str, tool: &str) -> Event {
+        Event::ToolStart {
+            tool: tool.to_string(),
+            call_id: call_id.to_string(),
+            input: serde_json::json!({}),
+        }
+    }
+
+    fn seeded_store() -> (RunLogStore, std::path::PathBuf) {
+        let temp = std::env::temp_dir().join(format!("forge-query-{}", uuid::Uuid::new_v4()));
+        let store = RunLogStore::new(&temp);
+        let run_a = [
+            record(1, 100, tool_start("c1", "grep")),
+            record(
+                2,
+                130,
+                Event::ToolResult {
+                    tool: "grep".to_string(),
+                    call_id: "c1".to_string(),
+                    output: ToolOutput::text("ok"),
+                },
+            ),
+            record(3, 140, tool_start("c2", "bash")),
+            record(
+                4,
+                190,
+                Event::ToolError {
+                    tool: "bash".to_string(),
+                    call_id: "c2".to_string(),
+                    error: "exit 1".to_string(),
+                },
+            ),
+        ];
+        for item in &run_a {
+            store.append("run-a", item).expect("append");
+        }
+        let run_b = [
+            record(1, 500, tool_start("c9", "bash")),
+            record(
+                2,
+                510,
+                Event::PermissionAsked {
+                    permission: "bash".to_string(),
+                    patterns: Vec::new(),
+                    metadata: Default::default(),
+                    always: Vec::new(),
+                },
+            ),
+        ];
+        for item in &run_b {
+            store.append("run-b", item).expect("append");
+        }
+        (store, temp)
+    }
+
+    #[test]
+    fn query_filters_by_variant_tool_and_run() {
+        let (store, temp) = seeded_store();
+
+        let failed = EventQuery::new()
+            .with_variant("ToolError")
+            .execute(&store)
+            .expect("query");
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].run_id, "run-a");
+
+        let bash = EventQuery::new()
+            .with_tool("bash")
+            .execute(&store)
+            .expect("query");
+        assert_eq!(bash.len(), 3);
+
+        let run_b = EventQuery::new()
+            .with_run("run-b")
+            .with_variant("PermissionAsked")
+            .execute(&store)
+            .expect("query");
+        assert_eq!(run_b.len(), 1);
+        let _ = std::fs::remove_dir_all(temp);
+    }
+
+    #[test]
+    fn query_filters_by_time_and_seq_range() {
+        let (store, temp) = seeded_store();
+
+        let window = EventQuery::new()
+            .with_time_range(Some(120), Some(500))
+            .execute(&store)
+            .expect("query");
+        assert_eq!(
+            window
+                .iter()
+                .map(|item| item.record.meta.seq)
+                .collect::<Vec<_>>(),
+            vec![2, 3, 4]
+        );
+
+        let seqs = EventQuery::new()
+            .with_run("run-a")
+            .with_seq_range(Some(2), Some(3))
+            .execute(&store)
+            .expect("query");
+        assert_eq!(seqs.len(), 2);
+
+        let limited = EventQuery::new()
+            .with_limit(1)
+            .execute(&store)
+            .expect("query");
+        assert_eq!(limited.len(), 1);
+        let _ = std::fs::remove_dir_all(temp);
+    }
+
+    #[test]
+    fn summary_aggregates_counts_and_durations() {
+        let (store, temp) = seeded_store();
+        let matches = EventQuery::new().execute(&store).expect("query");
+        let summary = QuerySummary::from_matches(&matches);
+
+        assert_eq!(summary.total, 6);
+        assert_eq!(summary.by_run["run-a"], 4);
+        assert_eq!(summary.by_variant["ToolStart"], 3);
+        assert_eq!(summary.by_tool["bash"], 2);
+        assert_eq!(summary.tool_failures["bash"], 1);
+        assert_eq!(summary.tool_durations["grep"].total_ms, 30);
+        assert_eq!(summary.tool_durations["bash"].count, 1);
+        assert_eq!(summary.tool_durations["bash"].max_ms, 50);
+        assert_eq!(summary.first_timestamp_ms, Some(100));
+        assert_eq!(summary.last_timestamp_ms, Some(510));
+        let _ = std::fs::remove_dir_all(temp);
+    }
+
+    #[test]
+    fn parse_args_builds_query() {
+        let args: Vec<String> = [
+            "logs",
+            "--run",
+            "r1",
+            "--variant",
+            "ToolError",
+            "--since-ms",
+            "10",
+            "--summary",
+        ]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+        let (query, rest) = EventQuery::parse_args(&args).expect("parse");
+        assert_eq!(query.run_ids, vec!["r1".to_string()]);
+        assert_eq!(query.variants, vec!["ToolError".to_string()]);
+        assert_eq!(query.since_ms, Some(10));
+        assert_eq!(rest, vec!["logs".to_string(), "--summary".to_string()]);
+
+        let bad = vec!["--limit".to_string(), "x".to_string()];
+        assert!(EventQuery::parse_args(&bad).is_err());
+    }
+}
@@ -0,0 +1,745 @@
+//! Sensitive-data redaction for persisted and exported events.
+//!
+//! A [`RedactionPolicy`] masks the payloads of sensitive tools, values at
+//! configured JSON paths, and substrings matching regex patterns. It is
+//! applied on the way out (record sinks, `RunLogStore`, `SessionStore`);
+//! the live in-process state keeps the real values.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use regex::Regex;
+
+use crate::runtime::error::{GraphError, GraphResult};
+use crate::runtime::event::{Event, EventRecord, EventRecordSink, EventSink, ToolUpdate};
+use crate::runtime::session::SessionSnapshot;
+use crate::runtime::tool::{AttachmentPayload, ToolAttachment, ToolRegistry};
+use crate::runtime::trace::{SpanKind, TraceEvent, TraceSpan};
+
+/// Default replacement text for redacted values.
+pub const REDACTED: &str = "[REDACTED]";
+
+const DEFAULT_PATTERNS: &[&str] = &[
+    // Provider API keys (OpenAI/Anthropic style).
+    r"\bsk-[A-Za-z0-9_-]{16,}",
+    // AWS access key ids.
+    r"\bAKIA[0-9A-Z]{16}\b",
+    // GitHub tokens.
+    r"\bgh[pousr]_[A-Za-z0-9]{36,}",
+    // JSON web tokens.
+    r"\beyJ[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+",
+    // Bearer credentials.
+    r"(?i)\bbearer\s+[A-Za-z0-9._~+/-]+=*",
+    // Email addresses.
+    r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}",
+];
+
+/// Redaction rules for events, records, and session snapshots.
+///
+/// JSON paths are dot-separated and address the serialized event, whose
+/// top-level key is the variant name (e.g. `ToolStart.input.password`);
+/// `*` matches any single key or array index.
+#[derive(Clone, Debug)]
+pub struct RedactionPolicy {
+    sensitive_tools: HashSet<String>,
+    json_paths: Vec<Vec<String>>,
+    patterns: Vec<Regex>,
+    replacement: String,
+}
+
+impl Default for RedactionPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RedactionPolicy {
+    pub fn new() -> Self {
+        Self {
+            sensitive_tools: HashSet::new(),
+            json_paths: Vec::new(),
+            patterns: Vec::new(),
+            replacement: REDACTED.to_string(),
+        }
+    }
+
+    /// Mask inputs and outputs of the named tool.
+    pub fn with_sensitive_tool(mut self, tool: impl Into<String>) -> Self {
+        self.sensitive_tools.insert(tool.into());
+        self
+    }
+
+    /// Mask every tool registered with `ToolDefinition::mark_sensitive`.
+    pub fn with_registry(mut self, registry: &ToolRegistry) -> Self {
+        for definition in registry.definitions() {
+            if definition.sensitive {
+                self.sensitive_tools.insert(definition.name);
+            }
+        }
+        self
+    }
+
+    pub fn with_json_path(mut self, path: impl AsRef<str>) -> Self {
+        let segments = path
+            .as_ref()
+            .split('.')
+            .filter(|segment| !segment.is_empty())
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        if !segments.is_empty() {
+            self.json_paths.push(segments);
+        }
+        self
+    }
+
+    pub fn with_pattern(mut self, pattern: &str) -> GraphResult<Self> {
+        let regex = Regex::new(pattern).map_err(|err| GraphError::ExecutionError {
+            node: "redaction".to_string(),
+            message: format!("invalid redaction pattern {:?}: {}", pattern, err),
+        })?;
+        self.patterns.push(regex);
+        Ok(self)
+    }
+
+    /// Add built-in patterns for API keys, access tokens, JWTs, and emails.
+    pub fn with_default_patterns(mut self) -> Self {
+        for pattern in DEFAULT_PATTERNS {
+            self.patterns
+                .push(Regex::new(pattern).expect("valid default pattern"));
+        }
+        self
+    }
+
+    pub fn with_replacement(mut self, replacement: impl Into<String>) -> Self {
+        self.replacement = replacement.into();
+        self
+    }
+
+    pub fn is_sensitive_tool(&self, tool: &str) -> bool {
+        self.sensitive_tools.contains(tool)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sensitive_tools.is_empty() && self.json_paths.is_empty() && self.patterns.is_empty()
+    }
+
+    /// Replace pattern matches in a string.
+    pub fn redact_text(&self, text: &str) -> String {
+        let mut output = std::borrow::Cow::Borrowed(text);
+        for pattern in &self.patterns {
+            if let std::borrow::Cow::Owned(replaced) =
+                pattern.replace_all(&output, self.replacement.as_str())
+            {
+                output = std::borrow::Cow::Owned(replaced);
+            }
+        }
+        output.into_owned()
+    }
+
+    /// Apply patterns to every string leaf of a JSON value.
+    pub fn redact_value(&self, value: &mut serde_json::Value) {
+        if self.patterns.is_empty() {
+            return;
+        }
+        match value {
+            serde_json::Value::String(text) => *text = self.redact_text(text),
+            serde_json::Value::Array(items) => {
+                items.iter_mut().for_each(|item| self.redact_value(item))
+            }
+            serde_json::Value::Object(map) => {
+                map.values_mut().for_each(|item| self.redact_value(item))
+            }
+            _ => {}
+        }
+    }
+
+    /// Redact an event. Fails closed: if masking leaves a payload that no
+    /// longer decodes (e.g. a path hit a typed field such as
+    /// `StepFinish.tokens.input`), the whole event is replaced by an `Error`
+    /// marker rather than passed through.
+    pub fn redact_event(&self, event: &Event) -> Event {
+        if self.is_empty() {
+            return event.clone();
+        }
+        let event = self.mask_sensitive_tool(event.clone());
+        if self.json_paths.is_empty() && self.patterns.is_empty() {
+            return event;
+        }
+        serde_json::to_value(&event)
+            .ok()
+            .and_then(|mut value| {
+                self.redact_json(&mut value);
+                serde_json::from_value(value).ok()
+            })
+            .unwrap_or_else(|| Event::Error {
+                session_id: String::new(),
+                message_id: String::new(),
+                message: format!(
+                    "{} {} event withheld by redaction",
+                    self.replacement,
+                    event.variant_name()
+                ),
+            })
+    }
+
+    /// Apply JSON paths and patterns to an arbitrary JSON value.
+    pub fn redact_json(&self, value: &mut serde_json::Value) {
+        for path in &self.json_paths {
+            self.mask_path(value, path);
+        }
+        self.redact_value(value);
+    }
+
+    /// Redact persisted JSON that is not an event, such as checkpoint state,
+    /// interrupt payloads, and resume values: any object naming a sensitive
+    /// `tool` (tool calls, results, permission request metadata) has its
+    /// `input`, `output`, `error`, and `attachment` masked, then paths and
+    /// patterns apply.
+    pub fn redact_stored(&self, value: &mut serde_json::Value) {
+        if self.is_empty() {
+            return;
+        }
+        if !self.sensitive_tools.is_empty() {
+            self.mask_sensitive_objects(value);
+        }
+        self.redact_json(value);
+    }
+
+    /// Redact a stored attachment. Attachments produced by a sensitive
+    /// tool lose their inline payload; others get patterns applied to their
+    /// name and inline data.
+    pub fn redact_attachment(
+        &self,
+        attachment: &ToolAttachment,
+        tool: Option<&str>,
+    ) -> ToolAttachment {
+        let mut attachment = attachment.clone();
+        if tool.is_some_and(|tool| self.is_sensitive_tool(tool)) {
+            self.mask_attachment(&mut attachment);
+            return attachment;
+        }
+        attachment.name = self.redact_text(&attachment.name);
+        if let AttachmentPayload::Inline { data } = &mut attachment.payload {
+            self.redact_value(data);
+        }
+        attachment
+    }
+
+    pub fn redact_record(&self, record: &EventRecord) -> EventRecord {
+        EventRecord {
+            meta: record.meta.clone(),
+            event: self.redact_event(&record.event),
+        }
+    }
+
+    /// Redact message content, compaction summaries, trace events, and span
+    /// attributes.
+    ///
+    /// Trace events are addressed like events (`Compacted.summary`); span
+    /// attributes under their span kind (`Tool.error`). Spans of sensitive
+    /// tools have every attribute but `call_id` masked.
+    pub fn redact_snapshot(&self, snapshot: &SessionSnapshot) -> SessionSnapshot {
+        let mut snapshot = snapshot.clone();
+        if self.is_empty() {
+            return snapshot;
+        }
+        for message in &mut snapshot.messages {
+            message.content = self.redact_text(&message.content);
+        }
+        for compaction in &mut snapshot.compactions {
+            compaction.summary = self.redact_text(&compaction.summary);
+        }
+        for event in &mut snapshot.trace.events {
+            *event = self.redact_trace_event(event);
+        }
+        for span in &mut snapshot.trace.spans {
+            self.redact_span(span);
+        }
+        snapshot
+    }
+
+    fn redact_trace_event(&self, event: &TraceEvent) -> TraceEvent {
+        serde_json::to_value(event)
+            .ok()
+            .and_then(|mut value| {
+                self.redact_json(&mut value);
+                serde_json::from_value(value).ok()
+            })
+            .unwrap_or_else(|| match event {
+                TraceEvent::NodeStart { .. } => TraceEvent::NodeStart {
+                    node: self.replacement.clone(),
+                },
+                TraceEvent::NodeFinish { .. } => TraceEvent::NodeFinish {
+                    node: self.replacement.clone(),
+                },
+                TraceEvent::Compacted {
+                    truncated_before, ..
+                } => TraceEvent::Compacted {
+                    summary: self.replacement.clone(),
+                    truncated_before: *truncated_before,
+                },
+            })
+    }
+
+    fn redact_span(&self, span: &mut TraceSpan) {
+        if span.kind == SpanKind::Tool && self.is_sensitive_tool(&span.name) {
+            for (key, value) in span.attributes.iter_mut() {
+                if key != "call_id" {
+                    *value = serde_json::Value::String(self.replacement.clone());
+                }
+            }
+        }
+        let kind = format!("{:?}", span.kind);
+        let mut value = serde_json::json!({
+            kind.as_str(): serde_json::Value::Object(std::mem::take(&mut span.attributes)),
+        });
+        self.redact_json(&mut value);
+        if let Some(serde_json::Value::Object(attributes)) =
+            value.as_object_mut().and_then(|map| map.remove(&kind))
+        {
+            span.attributes = attributes;
+        }
+    }
+
+    fn mask_sensitive_tool(&self, event: Event) -> Event {
+        let replacement = || serde_json::Value::String(self.replacement.clone());
+        match event {
+            Event::ToolStart { tool, call_id, .. } if self.is_sensitive_tool(&tool) => {
+                Event::ToolStart {
+                    tool,
+                    call_id,
+                    input: replacement(),
+                }
+            }
+            Event::ToolUpdate {
+                tool,
+                call_id,
+                update,
+            } if self.is_sensitive_tool(&tool) => {
+                let update = match update {
+                    ToolUpdate::OutputDelta { stream, .. } => ToolUpdate::OutputDelta {
+                        delta: self.replacement.clone(),
+                        stream,
+                    },
+                    ToolUpdate::OutputPreview { truncated, .. } => ToolUpdate::OutputPreview {
+                        preview: replacement(),
+                        truncated,
+                    },
+                    other => other,
+                };
+                Event::ToolUpdate {
+                    tool,
+                    call_id,
+                    update,
+                }
+            }
+            Event::ToolResult {
+                tool,
+                call_id,
+                mut output,
+            } if self.is_sensitive_tool(&tool) => {
+                output.content = replacement();
+                output
+                    .attachments
+                    .iter_mut()
+                    .for_each(|attachment| self.mask_attachment(attachment));
+                Event::ToolResult {
+                    tool,
+                    call_id,
+                    output,
+                }
+            }
+            Event::ToolAttachment {
+                tool,
+                call_id,
+                mut attachment,
+            } if self.is_sensitive_tool(&tool) => {
+                self.mask_attachment(&mut attachment);
+                Event::ToolAttachment {
+                    tool,
+                    call_id,
+                    attachment,
+                }
+            }
+            Event::ToolError { tool, call_id, .. } if self.is_sensitive_tool(&tool) => {
+                Event::ToolError {
+                    tool,
+                    call_id,
+                    error: self.replacement.clone(),
+                }
+            }
+            // Tool permission requests carry the call's input.
+            Event::PermissionAsked {
+                permission,
+                patterns,
+                mut metadata,
+                always,
+            } => {
+                let sensitive = metadata
+                    .get("tool")
+                    .and_then(serde_json::Value::as_str)
+                    .is_some_and(|tool| self.is_sensitive_tool(tool));
+                if sensitive {
+                    if let Some(input) = metadata.get_mut("input") {
+                        *input = replacement();
+                    }
+                }
+                Event::PermissionAsked {
+                    permission,
+                    patterns,
+                    metadata,
+                    always,
+                }
+            }
+            other => other,
+        }
+    }
+
+    fn mask_attachment(&self, attachment: &mut ToolAttachment) {
+        if let AttachmentPayload::Inline { data } = &mut attachment.payload {
+            *data = serde_json::Value::String(self.replacement.clone());
+        }
+    }
+
+    fn mask_path(&self, value: &mut serde_json::Value, path: &[String]) {
+        let Some((head, rest)) = path.split_first() else {
+            self.mask_leaves(value);
+            return;
+        };
+        match value {
+            serde_json::Value::Object(map) => {
+                for (key, child) in map.iter_mut() {
+                    if head == "*" || head == key {
+                        self.mask_path(child, rest);
+                    }
+                }
+            }
+            serde_json::Value::Array(items) => {
+                for (index, child) in items.iter_mut().enumerate() {
+                    if head == "*" || *head == index.to_string() {
+                        self.mask_path(child, rest);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn mask_sensitive_objects(&self, value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::Object(map) => {
+                let sensitive = map
+                    .get("tool")
+                    .and_then(serde_json::Value::as_str)
+                    .is_some_and(|tool| self.is_sensitive_tool(tool));
+                for (key, child) in map.iter_mut() {
+                    if sensitive
+                        && matches!(key.as_str(), "input" | "output" | "error" | "attachment")
+                    {
+                        *child = serde_json::Value::String(self.replacement.clone());
+                    } else {
+                        self.mask_sensitive_objects(child);
+                    }
+                }
+            }
+            serde_json::Value::Array(items) => items
+                .iter_mut()
+                .for_each(|item| self.mask_sensitive_objects(item)),
+            _ => {}
+        }
+    }
+
+    fn mask_leaves(&self, value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::Array(items) => {
+                items.iter_mut().for_each(|item| self.mask_leaves(item))
+            }
+            serde_json::Value::Object(map) => {
+                map.values_mut().for_each(|item| self.mask_leaves(item))
+            }
+            serde_json::Value::Null => {}
+            other => *other = serde_json::Value::String(self.replacement.clone()),
+        }
+    }
+}
+
+/// Event sink that redacts events before forwarding them.
+pub struct RedactingEventSink {
+    inner: Arc<dyn EventSink>,
+    policy: Arc<RedactionPolicy>,
+}
+
+impl RedactingEventSink {
+    pub fn new(inner: Arc<dyn EventSink>, policy: Arc<RedactionPolicy>) -> Self {
+        Self { inner, policy }
+    }
+}
+
+impl EventSink for RedactingEventSink {
+    fn emit(&self, event: Event) -> GraphResult<()> {
+        self.inner.emit(self.policy.redact_event(&event))
+    }
+}
+
+/// Record sink that redacts records before forwarding them.
+#[derive(Debug)]
+pub struct RedactingEventRecordSink {
+    inner: Arc<dyn EventRecordSink>,
+    policy: Arc<RedactionPolicy>,
+}
+
+impl RedactingEventRecordSink {
+    pub fn new(inner: Arc<dyn EventRecordSink>, policy: Arc<RedactionPolicy>) -> Self {
+        Self { inner, policy }
+    }
+}
+
+impl EventRecordSink for RedactingEventRecordSink {
+    fn emit_record(&self, record: EventRecord) -> GraphResult<()> {
+        self.inner.emit_record(self.policy.redact_record(&record))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RedactingEventSink, RedactionPolicy, REDACTED};
+    use crate::runtime::cancel::CancellationToken;
+    use crate::runtime::error::GraphError;
+    use crate::runtime::event::TokenUsage;
+    use crate::runtime::event::{Event, EventSink};
+    use crate::runtime::executor::ToolExecutor;
+    use crate::runtime::permission::{PermissionDecision, PermissionPolicy, PermissionRule};
+    use crate::runtime::session::{SessionMessage, SessionSnapshot};
+    use crate::runtime::tool::ToolOutput;
+    use crate::runtime::tool::{AttachmentPolicy, ToolCall, ToolDefinition, ToolRegistry};
+    use crate::runtime::trace::{SpanKind, TraceEvent, TraceSpan};
+    use futures::executor::block_on;
+    use std::sync::{Arc, Mutex};
+
+    struct CaptureSink {
+        events: Arc<Mutex<Vec<Event>>>,
+    }
+
+    impl EventSink for CaptureSink {
+        fn emit(&self, event: Event) -> crate::runtime::error::GraphResult<()> {
+            self.events.lock().unwrap().push(event);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn sensitive_tool_payloads_are_masked() {
+        let policy = RedactionPolicy::new().with_sensitive_tool("write");
+        let start = policy.redact_event(&Event::ToolStart {
+            tool: "write".to_string(),
+            call_id: "c1".to_string(),
+            input: serde_json::json!({"path": "a", "content": "secret"}),
+        });
+        assert!(matches!(
+            start,
+            Event::ToolStart { input, .. } if input == serde_json::json!(REDACTED)
+        ));
+
+        let result = policy.redact_event(&Event::ToolResult {
+            tool: "write".to_string(),
+            call_id: "c1".to_string(),
+            output: ToolOutput::text("secret"),
+        });
+        assert!(matches!(
+            result,
+            Event::ToolResult { output, .. } if output.content == serde_json::json!(REDACTED)
+        ));
+
+        let other = Event::ToolStart {
+            tool: "read".to_string(),
+            call_id: "c2".to_string(),
+            input: serde_json::json!({"path": "a"}),
+        };
+        assert_eq!(policy.redact_event(&other), other);
+    }
+
+    #[test]
+    fn sensitive_tool_input_is_masked_in_permission_requests() {
+        let mut registry = ToolRegistry::new();
+        registry.register_with_definition(
+            ToolDefinition::new("vault", "Read a secret").mark_sensitive(),
+            Arc::new(|_call, _ctx| Box::pin(async { Ok(ToolOutput::text("unreachable")) })),
+        );
+        let registry = Arc::new(registry);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::new(RedactingEventSink::new(
+            Arc::new(CaptureSink {
+                events: Arc::clone(&events),
+            }),
+            Arc::new(RedactionPolicy::new().with_registry(&registry)),
+        ));
+        let gate = Arc::new(PermissionPolicy::new(vec![PermissionRule::new(
+            PermissionDecision::Ask,
+            vec!["tool:vault".to_string()],
+        )]));
+        let executor = ToolExecutor::new(
+            registry,
+            gate,
+            sink,
+            AttachmentPolicy::default(),
+            None,
+            CancellationToken::new(),
+        );
+
+        let result = block_on(executor.run(ToolCall::new(
+            "vault",
+            "c1",
+            serde_json::json!({"key": "hunter2"}),
+        )));
+
+        let Err(GraphError::Interrupted(interrupts)) = result else {
+            panic!("expected permission interrupt");
+        };
+        assert_eq!(interrupts[0].value["metadata"]["input"]["key"], "hunter2");
+        let events = events.lock().unwrap();
+        let Event::PermissionAsked { metadata, .. } = &events[0] else {
+            panic!("expected permission request, got {:?}", events[0]);
+        };
+        assert_eq!(metadata["tool"], "vault");
+        assert_eq!(metadata["call_id"], "c1");
+        assert_eq!(metadata["input"], REDACTED);
+    }
+
+    #[test]
+    fn json_paths_mask_nested_values() {
+        let policy = RedactionPolicy::new().with_json_path("*.input.credentials");
+        let event = policy.redact_event(&Event::ToolStart {
+            tool: "http".to_string(),
+            call_id: "c1".to_string(),
+            input: serde_json::json!({
+                "url": "https://example.com",
+                "credentials": {"user": "bob", "password": "hunter2", "ttl": 30},
+            }),
+        });
+        let Event::ToolStart { input, .. } = event else {
+            panic!("expected tool start");
+        };
+        assert_eq!(input["url"], "https://example.com");
+        assert_eq!(input["credentials"]["password"], REDACTED);
+        assert_eq!(input["credentials"]["ttl"], REDACTED);
+    }
+
+    #[test]
+    fn paths_on_typed_fields_fail_closed() {
+        let policy = RedactionPolicy::new()
+            .with_json_path("StepFinish.tokens.input")
+            .with_pattern("secret-session")
+            .expect("pattern");
+        let event = policy.redact_event(&Event::StepFinish {
+            session_id: "secret-session".to_string(),
+            tokens: TokenUsage {
+                input: 42,
+                ..TokenUsage::default()
+            },
+            cost: 0.0,
+            model: None,
+        });
+        let Event::Error { message, .. } = &event else {
+            panic!("expected redaction marker, got {:?}", event);
+        };
+        assert!(message.starts_with(REDACTED));
+        assert!(message.contains("StepFinish"));
+        assert!(!format!("{:?}", event).contains("secret-session"));
+    }
+
+    #[test]
+    fn default_patterns_mask_keys_and_emails() {
+        let policy = RedactionPolicy::new().with_default_patterns();
+        let text = policy.redact_text(
+            "key sk-abcdefghijklmnopqrstuvwx sent to ops@example.com with Bearer abc.def",
+        );
+        assert_eq!(
+            text,
+            format!("key {REDACTED} sent to {REDACTED} with {REDACTED}")
+        );
+        assert!(RedactionPolicy::new().with_pattern("(").is_err());
+    }
+
+    #[test]
+    fn redacting_sink_leaves_original_event_untouched() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let policy = Arc::new(
+            RedactionPolicy::new()
+                .with_pattern(r"token-\d+")
+                .expect("pattern"),
+        );
+        let sink = RedactingEventSink::new(
+            Arc::new(CaptureSink {
+                events: Arc::clone(&events),
+            }),
+            policy,
+        );
+        let event = Event::TextFinal {
+            session_id: "s".to_string(),
+            message_id: "m".to_string(),
+            text: "use token-1234".to_string(),
+        };
+        sink.emit(event.clone()).expect("emit");
+
+        assert!(matches!(
+            &events.lock().unwrap()[0],
+            Event::TextFinal { text, .. } if text == &format!("use {REDACTED}")
+        ));
+        assert!(matches!(event, Event::TextFinal { text, .. } if text == "use token-1234"));
+    }
+
+    #[test]
+    fn snapshot_messages_are_redacted() {
+        let policy = RedactionPolicy::new().with_default_patterns();
+        let mut snapshot = SessionSnapshot::new("s1");
+        snapshot.messages.push(SessionMessage {
+            role: "user".to_string(),
+            content: "mail me at a@b.io".to_string(),
+        });
+        let redacted = policy.redact_snapshot(&snapshot);
+        assert_eq!(
+            redacted.messages[0].content,
+            format!("mail me at {REDACTED}")
+        );
+        assert_eq!(snapshot.messages[0].content, "mail me at a@b.io");
+    }
+
+    #[test]
+    fn snapshot_trace_applies_full_policy() {
+        let policy = RedactionPolicy::new()
+            .with_sensitive_tool("vault")
+            .with_json_path("Model.session_id")
+            .with_pattern("hunter2")
+            .expect("pattern");
+        let mut snapshot = SessionSnapshot::new("s1");
+        snapshot.trace.record_event(TraceEvent::Compacted {
+            summary: "password is hunter2".to_string(),
+            truncated_before: 3,
+        });
+        snapshot.trace.record_span(
+            TraceSpan::new("agent", 0, 1)
+                .with_kind(SpanKind::Tool)
+                .with_name("vault")
+                .with_attribute("call_id", serde_json::json!("c1"))
+                .with_attribute("error", serde_json::json!("bad key k-1")),
+        );
+        snapshot.trace.record_span(
+            TraceSpan::new("agent", 0, 1)
+                .with_kind(SpanKind::Model)
+                .with_attribute("session_id", serde_json::json!("s1")),
+        );
+
+        let redacted = policy.redact_snapshot(&snapshot);
+        assert_eq!(
+            redacted.trace.events[0],
+            TraceEvent::Compacted {
+                summary: format!("password is {REDACTED}"),
+                truncated_before: 3,
+            }
+        );
+        let tool = &redacted.trace.spans[0].attributes;
+        assert_eq!(tool["call_id"], "c1");
+        assert_eq!(tool["error"], REDACTED);
+        assert_eq!(redacted.trace.spans[1].attributes["session_id"], REDACTED);
+    }
+}
@@ -0,0 +1,451 @@
+//! Minimal JSON Schema validation for tool inputs and structured output.
+//!
+//! Supports the subset models are asked to produce: `type`, `enum`,
+//! `const`, `properties`, `required`, `additionalProperties`, `items`,
+//! `prefixItems`, string/number/array bounds, `pattern`, `allOf`/`anyOf`/
+//! `oneOf`/`not`, and local `$ref`s (`#/$defs/...`, `#/definitions/...`).
+//! Unknown keywords are ignored. A `$ref` that leads back to itself without
+//! descending into the instance is reported as a violation.
+
+use std::cell::RefCell;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A single validation failure at a JSON pointer path.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaViolation {
+    /// JSON pointer into the instance; empty for the root.
+    pub path: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let path = if self.path.is_empty() {
+            "/"
+        } else {
+            &self.path
+        };
+        write!(f, "{}: {}", path, self.message)
+    }
+}
+
+/// Validate `instance` against `schema`, returning every violation found.
+pub fn validate(schema: &Value, instance: &Value) -> Vec<SchemaViolation> {
+    let mut violations = Vec::new();
+    Validator {
+        root: schema,
+        active_refs: RefCell::new(Vec::new()),
+    }
+    .check(schema, instance, "", &mut violations);
+    violations
+}
+
+struct Validator<'a> {
+    root: &'a Value,
+    /// `$ref`s being followed, with the instance path each was entered at.
+    active_refs: RefCell<Vec<(String, String)>>,
+}
+
+fn violation(path: &str, message: impl Into<String>) -> SchemaViolation {
+    SchemaViolation {
+        path: path.to_string(),
+        message: message.into(),
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(number) if number.is_i64() || number.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn matches_type(expected: &str, value: &Value) -> bool {
+    match expected {
+        "number" => value.is_number(),
+        "integer" => {
+            type_name(value) == "integer"
+                || value.as_f64().is_some_and(|number| number.fract() == 0.0)
+        }
+        other => type_name(value) == other,
+    }
+}
+
+fn escape_pointer(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
+
+impl<'a> Validator<'a> {
+    fn resolve(&self, reference: &str) -> Option<&'a Value> {
+        let pointer = reference.strip_prefix('#')?;
+        self.root.pointer(pointer)
+    }
+
+    fn is_valid(&self, schema: &Value, instance: &Value, path: &str) -> bool {
+        let mut violations = Vec::new();
+        self.check(schema, instance, path, &mut violations);
+        violations.is_empty()
+    }
+
+    fn check_ref(
+        &self,
+        reference: &str,
+        instance: &Value,
+        path: &str,
+        out: &mut Vec<SchemaViolation>,
+    ) {
+        let Some(target) = self.resolve(reference) else {
+            out.push(violation(path, format!("unresolved $ref {}", reference)));
+            return;
+        };
+        let key = (reference.to_string(), path.to_string());
+        if self.active_refs.borrow().contains(&key) {
+            out.push(violation(path, format!("circular $ref {}", reference)));
+            return;
+        }
+        self.active_refs.borrow_mut().push(key);
+        self.check(target, instance, path, out);
+        self.active_refs.borrow_mut().pop();
+    }
+
+    fn check(&self, schema: &Value, instance: &Value, path: &str, out: &mut Vec<SchemaViolation>) {
+        let schema = match schema {
+            Value::Bool(true) => return,
+            Value::Bool(false) => {
+                out.push(violation(path, "no value is allowed here"));
+                return;
+            }
+            Value::Object(schema) => schema,
+            _ => return,
+        };
+
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            self.check_ref(reference, instance, path, out);
+        }
+
+        if let Some(expected) = schema.get("type") {
+            let allowed: Vec<&str> = match expected {
+                Value::String(name) => vec![name.as_str()],
+                Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+                _ => Vec::new(),
+            };
+            if !allowed.is_empty() && !allowed.iter().any(|name| matches_type(name, instance)) {
+                out.push(violation(
+                    path,
+                    format!(
+                        "expected {}, got {}",
+                        allowed.join(" or "),
+                        type_name(instance)
+                    ),
+                ));
+                return;
+            }
+        }
+
+        if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+            if !options.contains(instance) {
+                out.push(violation(
+                    path,
+                    format!("must be one of {}", Value::Array(options.clone())),
+                ));
+            }
+        }
+        if let Some(expected) = schema.get("const") {
+            if expected != instance {
+                out.push(violation(path, format!("must equal {}", expected)));
+            }
+        }
+
+        match instance {
+            Value::String(text) => self.check_string(schema, text, path, out),
+            Value::Number(_) => self.check_number(schema, instance, path, out),
+            Value::Array(items) => self.check_array(schema, items, path, out),
+            Value::Object(fields) => self.check_object(schema, fields, path, out),
+            _ => {}
+        }
+
+        if let Some(all) = schema.get("allOf").and_then(Value::as_array) {
+            for sub in all {
+                self.check(sub, instance, path, out);
+            }
+        }
+        if let Some(any) = schema.get("anyOf").and_then(Value::as_array) {
+            if !any.iter().any(|sub| self.is_valid(sub, instance, path)) {
+                out.push(violation(path, "does not match any allowed schema (anyOf)"));
+            }
+        }
+        if let Some(one) = schema.get("oneOf").and_then(Value::as_array) {
+            let matched = one
+                .iter()
+                .filter(|sub| self.is_valid(sub, instance, path))
+                .count();
+            if matched != 1 {
+                out.push(violation(
+                    path,
+                    format!("must match exactly one schema (oneOf), matched {}", matched),
+                ));
+            }
+        }
+        if let Some(not) = schema.get("not") {
+            if self.is_valid(not, instance, path) {
+                out.push(violation(path, "must not match the `not` schema"));
+            }
+        }
+    }
+
+    fn check_string(
+        &self,
+        schema: &serde_json::Map<String, Value>,
+        text: &str,
+        path: &str,
+        out: &mut Vec<SchemaViolation>,
+    ) {
+        let length = text.chars().count() as u64;
+        if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+            if length < min {
+                out.push(violation(
+                    path,
+                    format!("must be at least {} characters", min),
+                ));
+            }
+        }
+        if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+            if length > max {
+                out.push(violation(
+                    path,
+                    format!("must be at most {} characters", max),
+                ));
+            }
+        }
+        if let Some(pattern) = schema.get("pattern").and_then(Value::as_str) {
+            match regex::Regex::new(pattern) {
+                Ok(regex) if !regex.is_match(text) => {
+                    out.push(violation(path, format!("must match pattern {}", pattern)))
+                }
+                Ok(_) => {}
+                Err(_) => out.push(violation(path, format!("invalid pattern {}", pattern))),
+            }
+        }
+    }
+
+    fn check_number(
+        &self,
+        schema: &serde_json::Map<String, Value>,
+        instance: &Value,
+        path: &str,
+        out: &mut Vec<SchemaViolation>,
+    ) {
+        let Some(number) = instance.as_f64() else {
+            return;
+        };
+        let bound = |key: &str| schema.get(key).and_then(Value::as_f64);
+        if let Some(min) = bound("minimum").filter(|min| number < *min) {
+            out.push(violation(path, format!("must be >= {}", min)));
+        }
+        if let Some(max) = bound("maximum").filter(|max| number > *max) {
+            out.push(violation(path, format!("must be <= {}", max)));
+        }
+        if let Some(min) = bound("exclusiveMinimum").filter(|min| number <= *min) {
+            out.push(violation(path, format!("must be > {}", min)));
+        }
+        if let Some(max) = bound("exclusiveMaximum").filter(|max| number >= *max) {
+            out.push(violation(path, format!("must be < {}", max)));
+        }
+    }
+
+    fn check_array(
+        &self,
+        schema: &serde_json::Map<String, Value>,
+        items: &[Value],
+        path: &str,
+        out: &mut Vec<SchemaViolation>,
+    ) {
+        let count = items.len() as u64;
+        if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+            if count < min {
+                out.push(violation(path, format!("must have at least {} items", min)));
+            }
+        }
+        if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+            if count > max {
+                out.push(violation(path, format!("must have at most {} items", max)));
+            }
+        }
+        if schema.get("uniqueItems") == Some(&Value::Bool(true)) {
+            let duplicate = items
+                .iter()
+                .enumerate()
+                .any(|(index, item)| items[..index].contains(item));
+            if duplicate {
+                out.push(violation(path, "items must be unique"));
+            }
+        }
+        let prefix = schema
+            .get("prefixItems")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or(&[]);
+        for (index, item) in items.iter().enumerate() {
+            let item_path = format!("{}/{}", path, index);
+            match prefix.get(index) {
+                Some(sub) => self.check(sub, item, &item_path, out),
+                None => {
+                    if let Some(sub) = schema.get("items") {
+                        self.check(sub, item, &item_path, out);
+                    }
+                }
+            }
+        }
+    }
+
+    fn check_object(
+        &self,
+        schema: &serde_json::Map<String, Value>,
+        fields: &serde_json::Map<String, Value>,
+        path: &str,
+        out: &mut Vec<SchemaViolation>,
+    ) {
+        if let Some(required) = schema.get("required").and_then(Value::as_array) {
+            for name in required.iter().filter_map(Value::as_str) {
+                if !fields.contains_key(name) {
+                    out.push(violation(
+                        path,
+                        format!("missing required property `{}`", name),
+                    ));
+                }
+            }
+        }
+        let properties = schema.get("properties").and_then(Value::as_object);
+        for (name, value) in fields {
+            let field_path = format!("{}/{}", path, escape_pointer(name));
+            match properties.and_then(|properties| properties.get(name)) {
+                Some(sub) => self.check(sub, value, &field_path, out),
+                None => match schema.get("additionalProperties") {
+                    Some(Value::Bool(false)) => {
+                        out.push(violation(path, format!("unexpected property `{}`", name)))
+                    }
+                    Some(sub @ Value::Object(_)) => self.check(sub, value, &field_path, out),
+                    _ => {}
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::validate;
+    use serde_json::json;
+
+    #[test]
+    fn validate_reports_paths_for_nested_violations() {
+        let schema = json!({
+            "type": "object",
+            "required": ["name", "tags", "score"],
+            "additionalProperties": false,
+            "properties": {
+                "name": {"type": "string", "minLength": 1},
+                "score": {"type": "integer", "minimum": 0, "maximum": 10},
+                "tags": {"type": "array", "items": {"$ref": "#/$defs/tag"}},
+                "kind": {"enum": ["a", "b"]}
+            },
+            "$defs": {"tag": {"type": "string", "pattern": "^[a-z]+$"}}
+        });
+
+        assert!(validate(&schema, &json!({"name": "x", "tags": ["ok"], "score": 3})).is_empty());
+
+        let violations = validate(
+            &schema,
+            &json!({"name": "", "tags": ["ok", "Bad"], "score": 11.5, "kind": "c", "extra": 1}),
+        );
+        let rendered = violations
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rendered,
+            [
+                "/: unexpected property `extra`",
+                "/kind: must be one of [\"a\",\"b\"]",
+                "/name: must be at least 1 characters",
+                "/score: expected integer, got number",
+                "/tags/1: must match pattern ^[a-z]+$",
+            ]
+        );
+        assert_eq!(
+            validate(&schema, &json!({}))[0].message,
+            "missing required property `name`"
+        );
+    }
+
+    #[test]
+    fn validate_supports_combinators_and_nullable_types() {
+        let schema = json!({
+            "anyOf": [{"type": "string"}, {"type": "array", "maxItems": 1}],
+            "not": {"const": "forbidden"}
+        });
+        assert!(validate(&schema, &json!("fine")).is_empty());
+        assert!(validate(&schema, &json!([1])).is_empty());
+        assert_eq!(validate(&schema, &json!([1, 2])).len(), 1);
+        assert_eq!(validate(&schema, &json!("forbidden")).len(), 1);
+
+        let nullable = json!({"type": ["integer", "null"]});
+        assert!(validate(&nullable, &json!(null)).is_empty());
+        assert!(validate(&nullable, &json!(2.0)).is_empty());
+        assert!(!validate(&nullable, &json!("2")).is_empty());
+    }
+
+    #[test]
+    fn validate_reports_circular_refs_instead_of_recursing_forever() {
+        let violations = validate(&json!({"$ref": "#"}), &json!(1));
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].to_string(), "/: circular $ref #");
+
+        let schema = json!({
+            "$defs": {"a": {"$ref": "#/$defs/b"}, "b": {"anyOf": [{"$ref": "#/$defs/a"}]}},
+            "properties": {"x": {"$ref": "#/$defs/a"}}
+        });
+        let violations: Vec<String> = validate(&schema, &json!({"x": 1}))
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            violations,
+            ["/x: does not match any allowed schema (anyOf)"]
+        );
+
+        let self_ref = json!({"$defs": {"a": {"$ref": "#/$defs/a"}}, "$ref": "#/$defs/a"});
+        assert_eq!(
+            validate(&self_ref, &json!({}))[0].message,
+            "circular $ref #/$defs/a"
+        );
+    }
+
+    #[test]
+    fn validate_follows_recursive_refs_that_descend_into_the_instance() {
+        let tree = json!({
+            "$defs": {"node": {
+                "type": "object",
+                "required": ["value"],
+                "properties": {
+                    "value": {"type": "integer"},
+                    "children": {"type": "array", "items": {"$ref": "#/$defs/node"}}
+                }
+            }},
+            "anyOf": [{"$ref": "#/$defs/node"}]
+        });
+        let instance = json!({"value": 1, "children": [{"value": 2, "children": [{"value": 3}]}]});
+        assert!(validate(&tree, &instance).is_empty());
+
+        let violations = validate(&tree, &json!({"value": 1, "children": [{"value": "x"}]}));
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].message.contains("anyOf"));
+    }
+}
@@ -761,6 +761,7 @@ mod tests {
                 cache_write: 5,
             },
             cost: 0.01,
+            model: None,
         };
 
         assert!(state.apply_event(&event));
@@ -994,6 +995,7 @@ mod tests {
                 cache_write: 5,
             },
             cost: 0.01,
+            model: None,
         };
 
         assert!(state.apply_event(&event));
@@ -0,0 +1,295 @@
+//! Structured output: schema-constrained generation with validation and
+//! repair.
+//!
+//! [`StructuredOutput`] sends a request carrying a [`ResponseSchema`],
+//! extracts JSON from the reply, validates it with [`schema::validate`] and
+//! deserializes it into the caller's type. Invalid replies are fed back to
+//! the model with the validation errors, up to `max_repairs` times.
+//!
+//! [`schema::validate`]: crate::runtime::schema::validate
+
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use serde::de::DeserializeOwned;
+
+use crate::runtime::component::{ChatModel, ChatRequest, ChatResponse, ResponseSchema};
+use crate::runtime::error::{GraphError, GraphResult};
+use crate::runtime::message::{Message, MessageRole, Part};
+use crate::runtime::schema::{validate, SchemaViolation};
+
+const DEFAULT_MAX_REPAIRS: u32 = 2;
+
+/// A validated, typed response.
+#[derive(Clone, Debug)]
+pub struct StructuredResponse<T> {
+    pub value: T,
+    /// The validated JSON the value was deserialized from.
+    pub json: serde_json::Value,
+    /// The final model response.
+    pub response: ChatResponse,
+    /// Model calls made, including repairs.
+    pub attempts: u32,
+}
+
+/// Generates values of type `T` that conform to a JSON Schema.
+pub struct StructuredOutput<T> {
+    model: Arc<dyn ChatModel>,
+    schema: ResponseSchema,
+    max_repairs: u32,
+    _output: PhantomData<fn() -> T>,
+}
+
+impl<T: DeserializeOwned> StructuredOutput<T> {
+    pub fn new(model: Arc<dyn ChatModel>, schema: ResponseSchema) -> Self {
+        Self {
+            model,
+            schema,
+            max_repairs: DEFAULT_MAX_REPAIRS,
+            _output: PhantomData,
+        }
+    }
+
+    /// Re-prompt at most `max_repairs` times after an invalid reply.
+    pub fn with_max_repairs(mut self, max_repairs: u32) -> Self {
+        self.max_repairs = max_repairs;
+        self
+    }
+
+    pub fn schema(&self) -> &ResponseSchema {
+        &self.schema
+    }
+
+    /// Send `request` with this schema attached and return the parsed value.
+    pub async fn generate(&self, request: ChatRequest) -> GraphResult<StructuredResponse<T>> {
+        let mut request = request.with_response_schema(self.schema.clone());
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let response = self.model.generate(request.clone()).await?;
+            let text = response.text().unwrap_or_default();
+            let problems = match self.parse(&text) {
+                Ok((value, json)) => {
+                    return Ok(StructuredResponse {
+                        value,
+                        json,
+                        response,
+                        attempts,
+                    })
+                }
+                Err(problems) => problems,
+            };
+            if attempts > self.max_repairs {
+                return Err(GraphError::ExecutionError {
+                    node: "structured_output".to_string(),
+                    message: format!(
+                        "response did not match schema `{}` after {} attempt(s): {}",
+                        self.schema.name,
+                        attempts,
+                        problems.join("; ")
+                    ),
+                });
+            }
+            request.messages.push(response.message);
+            request.messages.push(repair_message(&problems));
+        }
+    }
+
+    /// Extract, validate and deserialize a reply; returns readable problems
+    /// on failure.
+    pub fn parse(&self, text: &str) -> Result<(T, serde_json::Value), Vec<String>> {
+        let json = extract_json(text).map_err(|err| vec![err])?;
+        let violations = validate(&self.schema.schema, &json);
+        if !violations.is_empty() {
+            return Err(violations.iter().map(SchemaViolation::to_string).collect());
+        }
+        let value = serde_json::from_value(json.clone())
+            .map_err(|err| vec![format!("could not decode value: {}", err)])?;
+        Ok((value, json))
+    }
+}
+
+fn repair_message(problems: &[String]) -> Message {
+    let mut text = String::from("Your previous response did not match the required JSON schema:\n");
+    for problem in problems {
+        text.push_str(&format!("- {}\n", problem));
+    }
+    text.push_str("Reply with only the corrected JSON.");
+    let mut message = Message::new(MessageRole::User);
+    message.parts.push(Part::TextFinal { text });
+    message
+}
+
+/// Parse JSON from model output, tolerating code fences and surrounding
+/// prose.
+pub fn extract_json(text: &str) -> Result<serde_json::Value, String> {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return Err("response was empty, expected JSON".to_string());
+    }
+    let parse_error = match serde_json::from_str(trimmed) {
+        Ok(value) => return Ok(value),
+        Err(err) => err,
+    };
+    if let Some(fenced) = fenced_block(trimmed) {
+        if let Ok(value) = serde_json::from_str(fenced) {
+            return Ok(value);
+        }
+    }
+    let start = trimmed.find(['{', '[']);
+    let end = trimmed.rfind(['}', ']']);
+    if let (Some(start), Some(end)) = (start, end) {
+        if start < end {
+            if let Ok(value) = serde_json::from_str(&trimmed[start..=end]) {
+                return Ok(value);
+            }
+        }
+    }
+    Err(format!("response is not valid JSON: {}", parse_error))
+}
+
+fn fenced_block(text: &str) -> Option<&str> {
+    let start = text.find("```")?;
+    let after = &text[start + 3..];
+    let body_start = after.find('\n')? + 1;
+    let body = &after[body_start..];
+    let end = body.find("```")?;
+    Some(body[..end].trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{extract_json, StructuredOutput};
+    use crate::runtime::component::{ChatModel, ChatRequest, ChatResponse, ResponseSchema};
+    use crate::runtime::error::GraphResult;
+    use crate::runtime::message::{Message, MessageRole, Part};
+    use crate::runtime::node::BoxFuture;
+    use futures::executor::block_on;
+    use serde::Deserialize;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Verdict {
+        label: String,
+        confidence: f64,
+    }
+
+    fn verdict_schema() -> ResponseSchema {
+        ResponseSchema::new(
+            "verdict",
+            serde_json::json!({
+                "type": "object",
+                "required": ["label", "confidence"],
+                "properties": {
+                    "label": {"enum": ["spam", "ham"]},
+                    "confidence": {"type": "number", "minimum": 0, "maximum": 1}
+                }
+            }),
+        )
+    }
+
+    /// Replies with queued texts and records the requests it saw.
+    struct ScriptedModel {
+        replies: Mutex<Vec<&'static str>>,
+        requests: Mutex<Vec<ChatRequest>>,
+    }
+
+    impl ScriptedModel {
+        fn new(replies: Vec<&'static str>) -> Self {
+            Self {
+                replies: Mutex::new(replies),
+                requests: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    impl ChatModel for ScriptedModel {
+        fn model_id(&self) -> &str {
+            "scripted"
+        }
+
+        fn generate(&self, request: ChatRequest) -> BoxFuture<'_, GraphResult<ChatResponse>> {
+            Box::pin(async move {
+                self.requests.lock().unwrap().push(request);
+                let text = self.replies.lock().unwrap().remove(0);
+                let mut message = Message::new(MessageRole::Assistant);
+                message.parts.push(Part::TextFinal {
+                    text: text.to_string(),
+                });
+                Ok(ChatResponse::new(message))
+            })
+        }
+    }
+
+    fn user_request() -> ChatRequest {
+        let mut message = Message::new(MessageRole::User);
+        message.parts.push(Part::TextFinal {
+            text: "Classify: WIN A PRIZE".to_string(),
+        });
+        ChatRequest::new("s1", "m1", vec![message])
+    }
+
+    #[test]
+    fn generate_repairs_invalid_replies_and_returns_typed_value() {
+        let model = Arc::new(ScriptedModel::new(vec![
+            "Sure! {\"label\": \"junk\", \"confidence\": 0.9}",
+            "```json\n{\"label\": \"spam\", \"confidence\": 0.97}\n```",
+        ]));
+        let structured = StructuredOutput::<Verdict>::new(model.clone(), verdict_schema());
+
+        let result = block_on(structured.generate(user_request())).expect("repaired");
+        assert_eq!(
+            result.value,
+            Verdict {
+                label: "spam".to_string(),
+                confidence: 0.97
+            }
+        );
+        assert_eq!(result.attempts, 2);
+
+        let requests = model.requests.lock().unwrap();
+        assert_eq!(
+            requests[0]
+                .response_schema
+                .as_ref()
+                .map(|s| s.name.as_str()),
+            Some("verdict")
+        );
+        let repair = requests[1].messages.last().unwrap();
+        assert_eq!(repair.role, MessageRole::User);
+        let repair_text = match &repair.parts[0] {
+            Part::TextFinal { text } => text.clone(),
+            other => panic!("unexpected part {:?}", other),
+        };
+        assert!(repair_text.contains("/label: must be one of [\"spam\",\"ham\"]"));
+        assert_eq!(requests[1].messages.len(), 3);
+    }
+
+    #[test]
+    fn generate_gives_up_after_max_repairs() {
+        let model = Arc::new(ScriptedModel::new(vec!["not json", "{\"label\": \"ham\"}"]));
+        let structured =
+            StructuredOutput::<Verdict>::new(model.clone(), verdict_schema()).with_max_repairs(1);
+
+        let err = block_on(structured.generate(user_request())).expect_err("invalid");
+        let message = err.to_string();
+        assert!(message.contains("after 2 attempt(s)"));
+        assert!(message.contains("missing required property `confidence`"));
+        assert_eq!(model.requests.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn extract_json_handles_fences_and_prose() {
+        assert_eq!(extract_json(" [1, 2] ").unwrap(), serde_json::json!([1, 2]));
+        assert_eq!(
+            extract_json("Here you go:\n```\n{\"a\": 1}\n```\nThanks").unwrap(),
+            serde_json::json!({"a": 1})
+        );
+        assert_eq!(
+            extract_json("Result: {\"a\": {\"b\": 2}} done").unwrap(),
+            serde_json::json!({"a": {"b": 2}})
+        );
+        assert!(extract_json("").is_err());
+        assert!(extract_json("no json here").is_err());
+    }
+}
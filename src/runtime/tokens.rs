@@ -0,0 +1,414 @@
+//! Local token counting for requests that have not been sent yet.
+//!
+//! Provider-reported [`TokenUsage`](crate::runtime::event::TokenUsage) only
+//! arrives after a call. A [`TokenEstimator`] predicts the prompt size of a
+//! [`ChatRequest`] up front so compaction, truncation and routing can act
+//! before an oversized request goes out. [`HeuristicEstimator`] needs no
+//! data; [`BpeEstimator`] counts exactly with a byte-pair-encoding
+//! vocabulary loaded from a local `.tiktoken` file.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::runtime::component::ChatRequest;
+use crate::runtime::error::{GraphError, GraphResult};
+use crate::runtime::message::{Message, MessageRole, Part};
+
+/// Flat charge for an image, audio or file part.
+pub const MEDIA_PART_TOKENS: u64 = 1024;
+
+/// Pre-tokenizer used by `cl100k`-style vocabularies (without the
+/// look-ahead whitespace rule, which the `regex` crate does not support).
+pub const DEFAULT_BPE_PATTERN: &str = r"(?i:'s|'t|'re|'ve|'m|'ll|'d)|[^\r\n\p{L}\p{N}]?\p{L}+|\p{N}{1,3}| ?[^\s\p{L}\p{N}]+[\r\n]*|\s*[\r\n]+|\s+";
+
+/// Predicts how many tokens text, messages and requests will use.
+pub trait TokenEstimator: Send + Sync + std::fmt::Debug {
+    fn count_text(&self, text: &str) -> u64;
+
+    /// Fixed cost per message for role markers and separators.
+    fn message_overhead(&self) -> u64 {
+        4
+    }
+
+    fn count_message(&self, message: &Message) -> u64 {
+        let mut total = self.message_overhead();
+        for part in &message.parts {
+            total += match part {
+                Part::TextDelta { delta } => self.count_text(delta),
+                Part::TextFinal { text } => self.count_text(text),
+                Part::ToolCall { tool, input, .. } => {
+                    self.count_text(tool) + self.count_text(&input.to_string())
+                }
+                Part::ToolResult { output, .. } => self.count_text(&output.content.to_string()),
+                Part::ToolError { error, .. } => self.count_text(error),
+                Part::Attachment { data, .. } => self.count_text(&data.to_string()),
+                Part::Image { .. } | Part::Audio { .. } | Part::File { .. } => MEDIA_PART_TOKENS,
+                _ => 0,
+            };
+        }
+        total
+    }
+
+    /// Prompt size of `request`: messages, tool definitions and any
+    /// response schema.
+    fn count_request(&self, request: &ChatRequest) -> u64 {
+        let mut total = request
+            .messages
+            .iter()
+            .map(|message| self.count_message(message))
+            .sum::<u64>();
+        for tool in &request.tools {
+            total += self.count_text(&tool.name) + self.count_text(&tool.description);
+            if let Some(schema) = &tool.input_schema {
+                total += self.count_text(&schema.to_string());
+            }
+        }
+        if let Some(schema) = &request.response_schema {
+            total += self.count_text(&schema.schema.to_string());
+        }
+        total
+    }
+}
+
+/// Character-ratio estimate; about four characters per token for English.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HeuristicEstimator {
+    pub chars_per_token: f64,
+    pub message_overhead: u64,
+}
+
+impl Default for HeuristicEstimator {
+    fn default() -> Self {
+        Self {
+            chars_per_token: 4.0,
+            message_overhead: 4,
+        }
+    }
+}
+
+impl HeuristicEstimator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use a lower ratio for code or non-Latin text.
+    pub fn with_chars_per_token(mut self, chars_per_token: f64) -> Self {
+        self.chars_per_token = chars_per_token.max(0.1);
+        self
+    }
+
+    pub fn with_message_overhead(mut self, message_overhead: u64) -> Self {
+        self.message_overhead = message_overhead;
+        self
+    }
+}
+
+impl TokenEstimator for HeuristicEstimator {
+    fn count_text(&self, text: &str) -> u64 {
+        (text.len() as f64 / self.chars_per_token).ceil() as u64
+    }
+
+    fn message_overhead(&self) -> u64 {
+        self.message_overhead
+    }
+}
+
+/// Byte-pair-encoding token counter over a ranked vocabulary.
+#[derive(Clone, Debug)]
+pub struct BpeEstimator {
+    ranks: HashMap<Vec<u8>, u32>,
+    pattern: regex::Regex,
+    message_overhead: u64,
+}
+
+impl BpeEstimator {
+    pub fn new(ranks: HashMap<Vec<u8>, u32>) -> Self {
+        Self {
+            ranks,
+            pattern: regex::Regex::new(DEFAULT_BPE_PATTERN).expect("default BPE pattern"),
+            message_overhead: 4,
+        }
+    }
+
+    /// Load a `.tiktoken` vocabulary: one `<base64 token> <rank>` per line.
+    pub fn from_file(path: impl AsRef<Path>) -> GraphResult<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|err| {
+            tokenizer_error(format!(
+                "read vocabulary {} failed: {}",
+                path.display(),
+                err
+            ))
+        })?;
+        Self::from_tiktoken(&text)
+    }
+
+    /// Parse `.tiktoken` vocabulary text.
+    pub fn from_tiktoken(text: &str) -> GraphResult<Self> {
+        let mut ranks = HashMap::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let parsed = line.split_once(' ').and_then(|(token, rank)| {
+                Some((decode_base64(token)?, rank.trim().parse::<u32>().ok()?))
+            });
+            let (token, rank) = parsed.ok_or_else(|| {
+                tokenizer_error(format!("invalid vocabulary line {}: {}", index + 1, line))
+            })?;
+            ranks.insert(token, rank);
+        }
+        if ranks.is_empty() {
+            return Err(tokenizer_error("vocabulary is empty"));
+        }
+        Ok(Self::new(ranks))
+    }
+
+    /// Replace the pre-tokenizer regex.
+    pub fn with_pattern(mut self, pattern: &str) -> GraphResult<Self> {
+        self.pattern = regex::Regex::new(pattern)
+            .map_err(|err| tokenizer_error(format!("invalid pattern: {}", err)))?;
+        Ok(self)
+    }
+
+    pub fn with_message_overhead(mut self, message_overhead: u64) -> Self {
+        self.message_overhead = message_overhead;
+        self
+    }
+
+    pub fn vocab_size(&self) -> usize {
+        self.ranks.len()
+    }
+
+    /// Tokens for one pre-tokenized piece, merging the lowest-ranked
+    /// adjacent pair until none is in the vocabulary.
+    fn count_piece(&self, piece: &[u8]) -> u64 {
+        if piece.len() <= 1 || self.ranks.contains_key(piece) {
+            return 1;
+        }
+        let mut bounds = (0..=piece.len()).collect::<Vec<_>>();
+        loop {
+            let best = (0..bounds.len().saturating_sub(2))
+                .filter_map(|index| {
+                    self.ranks
+                        .get(&piece[bounds[index]..bounds[index + 2]])
+                        .map(|rank| (*rank, index))
+                })
+                .min();
+            match best {
+                Some((_, index)) => {
+                    bounds.remove(index + 1);
+                }
+                None => break,
+            }
+        }
+        (bounds.len() - 1) as u64
+    }
+}
+
+impl TokenEstimator for BpeEstimator {
+    fn count_text(&self, text: &str) -> u64 {
+        self.pattern
+            .find_iter(text)
+            .map(|piece| self.count_piece(piece.as_str().as_bytes()))
+            .sum()
+    }
+
+    fn message_overhead(&self) -> u64 {
+        self.message_overhead
+    }
+}
+
+/// A request trimmed to fit a token budget.
+#[derive(Clone, Debug)]
+pub struct FittedRequest {
+    pub request: ChatRequest,
+    /// Messages removed from the front of the conversation.
+    pub dropped: usize,
+    /// Estimated prompt size after trimming.
+    pub tokens: u64,
+}
+
+/// Drop the oldest non-system messages until `request` fits in `budget`.
+///
+/// System messages and the latest message are always kept, and tool
+/// results are never left without the assistant message that called them.
+/// The result may still exceed `budget` when nothing more can be removed.
+pub fn fit_to_budget(
+    request: &ChatRequest,
+    estimator: &dyn TokenEstimator,
+    budget: u64,
+) -> FittedRequest {
+    let mut request = request.clone();
+    let mut tokens = estimator.count_request(&request);
+    let mut dropped = 0;
+    while tokens > budget {
+        let droppable = request
+            .messages
+            .iter()
+            .position(|message| message.role != MessageRole::System)
+            .filter(|index| *index + 1 < request.messages.len());
+        let Some(index) = droppable else {
+            break;
+        };
+        tokens = tokens.saturating_sub(estimator.count_message(&request.messages.remove(index)));
+        dropped += 1;
+        while request.messages.len() > index + 1
+            && request.messages[index].role == MessageRole::Tool
+        {
+            tokens =
+                tokens.saturating_sub(estimator.count_message(&request.messages.remove(index)));
+            dropped += 1;
+        }
+    }
+    FittedRequest {
+        request,
+        dropped,
+        tokens,
+    }
+}
+
+fn tokenizer_error(message: impl Into<String>) -> GraphError {
+    GraphError::ExecutionError {
+        node: "tokenizer".to_string(),
+        message: message.into(),
+    }
+}
+
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for byte in text.bytes().filter(|byte| *byte != b'=') {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        fit_to_budget, BpeEstimator, HeuristicEstimator, TokenEstimator, MEDIA_PART_TOKENS,
+    };
+    use crate::runtime::component::ChatRequest;
+    use crate::runtime::message::{MediaSource, Message, MessageRole, Part};
+    use crate::runtime::tool::ToolOutput;
+
+    fn text(role: MessageRole, text: &str) -> Message {
+        let mut message = Message::new(role);
+        message.parts.push(Part::TextFinal {
+            text: text.to_string(),
+        });
+        message
+    }
+
+    #[test]
+    fn heuristic_counts_text_messages_and_media() {
+        let estimator = HeuristicEstimator::default();
+        assert_eq!(estimator.count_text(""), 0);
+        assert_eq!(estimator.count_text("abcdefgh"), 2);
+        assert_eq!(estimator.count_text("abcdefghi"), 3);
+
+        let mut message = text(MessageRole::User, "abcdefgh");
+        message.parts.push(Part::image(
+            MediaSource::url("https://example.com/a.png"),
+            "image/png",
+        ));
+        assert_eq!(estimator.count_message(&message), 4 + 2 + MEDIA_PART_TOKENS);
+
+        let request = ChatRequest::new("s", "m", vec![text(MessageRole::User, "abcd")]);
+        assert_eq!(estimator.count_request(&request), 5);
+        let dense = HeuristicEstimator::new()
+            .with_chars_per_token(2.0)
+            .with_message_overhead(0);
+        assert_eq!(dense.count_request(&request), 2);
+    }
+
+    #[test]
+    fn bpe_merges_by_rank_from_tiktoken_vocabulary() {
+        // Ranks: "a"=0 "b"=1 "c"=2 " "=3 "ab"=4 "abc"=5 " ab"=6 " c"=7
+        let vocab = "YQ== 0\nYg== 1\nYw== 2\nIA== 3\nYWI= 4\nYWJj 5\nIGFi 6\nIGM= 7\n";
+        let path =
+            std::env::temp_dir().join(format!("forge-vocab-{}.tiktoken", uuid::Uuid::new_v4()));
+        std::fs::write(&path, vocab).unwrap();
+        let estimator = BpeEstimator::from_file(&path).expect("load");
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(estimator.vocab_size(), 8);
+        assert_eq!(estimator.count_text("abc"), 1);
+        // "abc" + " abc" -> [abc] + [ ][abc]
+        assert_eq!(estimator.count_text("abc abc"), 3);
+        // Unknown bytes fall back to one token each.
+        assert_eq!(estimator.count_text("zz"), 2);
+
+        assert!(BpeEstimator::from_tiktoken("not-base64! x").is_err());
+        assert!(BpeEstimator::from_tiktoken("").is_err());
+    }
+
+    #[test]
+    fn fit_to_budget_drops_oldest_turns_and_orphaned_tool_results() {
+        let mut call = Message::new(MessageRole::Assistant);
+        call.parts.push(Part::ToolCall {
+            tool: "read".to_string(),
+            call_id: "c1".to_string(),
+            input: serde_json::json!({"path": "a.txt"}),
+        });
+        let mut result = Message::new(MessageRole::Tool);
+        result.parts.push(Part::ToolResult {
+            tool: "read".to_string(),
+            call_id: "c1".to_string(),
+            output: ToolOutput::text("x".repeat(400)),
+        });
+        let request = ChatRequest::new(
+            "s",
+            "m",
+            vec![
+                text(MessageRole::System, "be brief"),
+                call,
+                result,
+                text(MessageRole::Assistant, "done"),
+                text(MessageRole::User, "next question"),
+            ],
+        );
+        let estimator = HeuristicEstimator::default();
+        let before = estimator.count_request(&request);
+
+        let fitted = fit_to_budget(&request, &estimator, 30);
+        let roles = fitted
+            .request
+            .messages
+            .iter()
+            .map(|message| message.role.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            roles,
+            vec![
+                MessageRole::System,
+                MessageRole::Assistant,
+                MessageRole::User
+            ]
+        );
+        assert_eq!(fitted.dropped, 2);
+        assert!(fitted.tokens <= 30 && fitted.tokens < before);
+        assert_eq!(fitted.tokens, estimator.count_request(&fitted.request));
+
+        let tiny = fit_to_budget(&request, &estimator, 1);
+        assert_eq!(tiny.request.messages.len(), 2);
+        assert_eq!(fit_to_budget(&request, &estimator, before).dropped, 0);
+    }
+}
@@ -19,7 +19,7 @@ pub enum ToolState {
 }
 
 /// Tool invocation metadata.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct ToolCall {
     pub tool: String,
     pub call_id: String,
@@ -1,6 +1,12 @@
 //! Trace data structures for runtime replay.
 
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::runtime::audit::{verify_chain, AuditSeal, ChainReport, HashChain};
 use crate::runtime::error::GraphResult;
+use crate::runtime::event::Event;
 use serde::{Deserialize, Serialize};
 
 /// A trace event capturing high-level execution activity.
@@ -18,12 +24,101 @@ pub enum TraceEvent {
     },
 }
 
-/// Span covering a node execution window.
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// Kind of activity covered by a trace span.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub enum SpanKind {
+    #[default]
+    Node,
+    Tool,
+    Model,
+    Permission,
+    Compaction,
+}
+
+impl SpanKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SpanKind::Node => "node",
+            SpanKind::Tool => "tool",
+            SpanKind::Model => "model",
+            SpanKind::Permission => "permission",
+            SpanKind::Compaction => "compaction",
+        }
+    }
+
+    fn is_node(&self) -> bool {
+        *self == SpanKind::Node
+    }
+}
+
+/// Span covering a node, tool, model, permission, or compaction window.
+///
+/// `node` is the graph node the span ran under; `name` identifies the
+/// activity (tool name, permission, etc.) for non-node spans.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct TraceSpan {
     pub node: String,
     pub start_ms: u64,
     pub duration_ms: u64,
+    #[serde(default, skip_serializing_if = "SpanKind::is_node")]
+    pub kind: SpanKind,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_span_id: Option<String>,
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub attributes: serde_json::Map<String, serde_json::Value>,
+}
+
+impl TraceSpan {
+    pub fn new(node: impl Into<String>, start_ms: u64, duration_ms: u64) -> Self {
+        Self {
+            node: node.into(),
+            start_ms,
+            duration_ms,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_kind(mut self, kind: SpanKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    pub fn with_span_id(mut self, span_id: impl Into<String>) -> Self {
+        self.span_id = Some(span_id.into());
+        self
+    }
+
+    pub fn with_parent_span_id(mut self, parent_span_id: impl Into<String>) -> Self {
+        self.parent_span_id = Some(parent_span_id.into());
+        self
+    }
+
+    pub fn with_attribute(mut self, key: impl Into<String>, value: serde_json::Value) -> Self {
+        self.attributes.insert(key.into(), value);
+        self
+    }
+
+    /// Display label: the activity name, falling back to the node.
+    pub fn label(&self) -> &str {
+        if self.name.is_empty() {
+            &self.node
+        } else {
+            &self.name
+        }
+    }
+
+    pub fn end_ms(&self) -> u64 {
+        self.start_ms.saturating_add(self.duration_ms)
+    }
 }
 
 /// Execution trace container.
@@ -47,6 +142,302 @@ impl ExecutionTrace {
     }
 }
 
+/// Records spans for an execution, pairing start/finish events by key.
+///
+/// Node and compaction spans are opened explicitly by the executor; tool,
+/// model (`StepStart`/`StepFinish`), and permission (`PermissionAsked`/
+/// `PermissionReplied`) spans are derived from observed runtime events.
+#[derive(Debug)]
+pub struct SpanRecorder {
+    trace: Arc<Mutex<ExecutionTrace>>,
+    root_parent_span_id: Option<String>,
+    current_node: Option<(String, String)>,
+    open: HashMap<String, TraceSpan>,
+    /// Open span keys per model session or permission, oldest first, so
+    /// nested steps and concurrent asks each get their own span.
+    waiting: HashMap<String, Vec<String>>,
+    next_key: u64,
+}
+
+impl SpanRecorder {
+    pub fn new(trace: Arc<Mutex<ExecutionTrace>>) -> Self {
+        Self {
+            trace,
+            root_parent_span_id: None,
+            current_node: None,
+            open: HashMap::new(),
+            waiting: HashMap::new(),
+            next_key: 0,
+        }
+    }
+
+    /// Parent span for node spans (e.g. the enclosing graph's node span).
+    pub fn with_parent_span_id(mut self, parent_span_id: Option<String>) -> Self {
+        self.root_parent_span_id = parent_span_id;
+        self
+    }
+
+    /// Span id of the node currently (or most recently) executing, if any.
+    pub fn current_span_id(&self) -> Option<&str> {
+        self.current_node.as_ref().map(|(_, id)| id.as_str())
+    }
+
+    pub fn enter_node(&mut self, node: impl Into<String>) -> String {
+        let node = node.into();
+        let span_id = new_span_id();
+        let mut span = TraceSpan::new(node.clone(), now_ms(), 0).with_span_id(span_id.clone());
+        span.parent_span_id = self.root_parent_span_id.clone();
+        self.open.insert(node_key(&node), span);
+        self.current_node = Some((node, span_id.clone()));
+        span_id
+    }
+
+    pub fn exit_node(&mut self, error: Option<&str>) {
+        let Some((node, _)) = self.current_node.clone() else {
+            return;
+        };
+        let key = node_key(&node);
+        if let Some(error) = error {
+            if let Some(span) = self.open.get_mut(&key) {
+                span.attributes
+                    .insert("error".to_string(), serde_json::json!(error));
+            }
+        }
+        self.close(&key);
+    }
+
+    pub fn open(&mut self, key: impl Into<String>, kind: SpanKind, name: impl Into<String>) {
+        let mut span = TraceSpan::new(self.node_name(), now_ms(), 0)
+            .with_kind(kind)
+            .with_name(name)
+            .with_span_id(new_span_id());
+        span.parent_span_id = self
+            .current_span_id()
+            .map(str::to_string)
+            .or_else(|| self.root_parent_span_id.clone());
+        self.open.insert(key.into(), span);
+    }
+
+    pub fn annotate(&mut self, key: &str, attribute: impl Into<String>, value: serde_json::Value) {
+        if let Some(span) = self.open.get_mut(key) {
+            span.attributes.insert(attribute.into(), value);
+        }
+    }
+
+    pub fn close(&mut self, key: &str) -> Option<TraceSpan> {
+        let mut span = self.open.remove(key)?;
+        span.duration_ms = now_ms().saturating_sub(span.start_ms);
+        self.trace.lock().unwrap().record_span(span.clone());
+        Some(span)
+    }
+
+    /// Derive tool/model/permission spans from a runtime event.
+    pub fn observe(&mut self, event: &Event) {
+        match event {
+            Event::ToolStart { tool, call_id, .. } => {
+                self.open(tool_key(call_id), SpanKind::Tool, tool.clone());
+                self.annotate(&tool_key(call_id), "call_id", serde_json::json!(call_id));
+            }
+            Event::ToolResult { call_id, .. } => {
+                self.close(&tool_key(call_id));
+            }
+            Event::ToolError { call_id, error, .. } => {
+                let key = tool_key(call_id);
+                self.annotate(&key, "error", serde_json::json!(error));
+                self.close(&key);
+            }
+            Event::StepStart { session_id } => {
+                let key = self.push_key(model_key(session_id), None);
+                self.open(key.clone(), SpanKind::Model, "model");
+                self.annotate(&key, "session_id", serde_json::json!(session_id));
+            }
+            Event::StepFinish {
+                session_id,
+                tokens,
+                cost,
+                model,
+            } => {
+                // Steps nest, so a finish closes the innermost open step.
+                let Some(key) = self.pop_key(&model_key(session_id)) else {
+                    return;
+                };
+                self.annotate(&key, "tokens", serde_json::json!(tokens));
+                self.annotate(&key, "cost", serde_json::json!(cost));
+                if let Some(model) = model {
+                    self.annotate(&key, "model", serde_json::json!(model));
+                }
+                self.close(&key);
+            }
+            Event::PermissionAsked {
+                permission,
+                metadata,
+                ..
+            } => {
+                let call_id = metadata.get("call_id").and_then(|value| value.as_str());
+                let key = self.push_key(permission_key(permission), call_id);
+                self.open(key.clone(), SpanKind::Permission, permission.clone());
+                if let Some(call_id) = call_id {
+                    self.annotate(&key, "call_id", serde_json::json!(call_id));
+                }
+            }
+            Event::PermissionReplied { permission, reply } => {
+                // One reply answers every pending ask for the permission.
+                let keys = self
+                    .waiting
+                    .remove(&permission_key(permission))
+                    .unwrap_or_default();
+                for key in keys {
+                    self.annotate(&key, "reply", serde_json::json!(reply));
+                    self.close(&key);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Close any spans still open (e.g. permission waits left pending by an interrupt).
+    pub fn finish(&mut self) {
+        let mut keys: Vec<String> = self.open.keys().cloned().collect();
+        keys.sort();
+        for key in keys {
+            self.annotate(&key, "incomplete", serde_json::json!(true));
+            self.close(&key);
+        }
+        self.waiting.clear();
+        self.current_node = None;
+    }
+
+    /// Register a new open span under `group`, keyed by `id` when the event
+    /// carries one and by a recorder-wide counter otherwise.
+    fn push_key(&mut self, group: String, id: Option<&str>) -> String {
+        let key = match id {
+            Some(id) => format!("{}#{}", group, id),
+            None => {
+                self.next_key += 1;
+                format!("{}#{}", group, self.next_key)
+            }
+        };
+        self.waiting.entry(group).or_default().push(key.clone());
+        key
+    }
+
+    fn pop_key(&mut self, group: &str) -> Option<String> {
+        let keys = self.waiting.get_mut(group)?;
+        let key = keys.pop();
+        if keys.is_empty() {
+            self.waiting.remove(group);
+        }
+        key
+    }
+
+    fn node_name(&self) -> String {
+        self.current_node
+            .as_ref()
+            .map(|(node, _)| node.clone())
+            .unwrap_or_default()
+    }
+}
+
+fn node_key(node: &str) -> String {
+    format!("node:{}", node)
+}
+
+fn tool_key(call_id: &str) -> String {
+    format!("tool:{}", call_id)
+}
+
+fn model_key(session_id: &str) -> String {
+    format!("model:{}", session_id)
+}
+
+fn permission_key(permission: &str) -> String {
+    format!("permission:{}", permission)
+}
+
+fn new_span_id() -> String {
+    uuid::Uuid::new_v4().simple().to_string()[..16].to_string()
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Export trace spans to the Chrome trace-event JSON format.
+///
+/// The output opens in `chrome://tracing`, Perfetto, or Speedscope. Each span
+/// kind is rendered on its own thread lane.
+#[derive(Clone, Debug, Default)]
+pub struct ChromeTraceExport;
+
+impl ChromeTraceExport {
+    const PID: u64 = 1;
+
+    pub fn to_json(trace: &ExecutionTrace) -> serde_json::Value {
+        let mut events = Vec::new();
+        let mut lanes: Vec<SpanKind> = trace.spans.iter().map(|span| span.kind).collect();
+        lanes.sort_by_key(|kind| Self::lane(*kind));
+        lanes.dedup();
+        for kind in lanes {
+            events.push(serde_json::json!({
+                "name": "thread_name",
+                "ph": "M",
+                "pid": Self::PID,
+                "tid": Self::lane(kind),
+                "args": { "name": kind.as_str() },
+            }));
+        }
+        for span in &trace.spans {
+            let mut args = span.attributes.clone();
+            if !span.node.is_empty() {
+                args.insert("node".to_string(), serde_json::json!(span.node));
+            }
+            if let Some(span_id) = &span.span_id {
+                args.insert("span_id".to_string(), serde_json::json!(span_id));
+            }
+            if let Some(parent) = &span.parent_span_id {
+                args.insert("parent_span_id".to_string(), serde_json::json!(parent));
+            }
+            events.push(serde_json::json!({
+                "name": span.label(),
+                "cat": span.kind.as_str(),
+                "ph": "X",
+                "ts": span.start_ms.saturating_mul(1000),
+                "dur": span.duration_ms.saturating_mul(1000),
+                "pid": Self::PID,
+                "tid": Self::lane(span.kind),
+                "args": args,
+            }));
+        }
+        serde_json::json!({
+            "traceEvents": events,
+            "displayTimeUnit": "ms",
+        })
+    }
+
+    pub fn write(trace: &ExecutionTrace, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        let data = serde_json::to_string(&Self::to_json(trace))
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+        if let Some(parent) = path.as_ref().parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, data)?;
+        Ok(())
+    }
+
+    fn lane(kind: SpanKind) -> u64 {
+        match kind {
+            SpanKind::Node => 1,
+            SpanKind::Model => 2,
+            SpanKind::Tool => 3,
+            SpanKind::Permission => 4,
+            SpanKind::Compaction => 5,
+        }
+    }
+}
+
 /// Replay trace events in order.
 #[derive(Clone, Debug, Default)]
 pub struct TraceReplay;
@@ -160,9 +551,18 @@ impl TraceReplay {
         trace: &ExecutionTrace,
         path: impl AsRef<std::path::Path>,
     ) -> std::io::Result<()> {
+        let sequencer = crate::runtime::event::EventSequencer::new();
+        let records = HashChain::new().link_all(
+            trace
+                .events
+                .iter()
+                .map(|event| sequencer.record(map_trace_event(event)))
+                .collect(),
+        );
         let json = serde_json::json!({
             "version": Self::RECORD_LOG_VERSION,
-            "records": Self::replay_to_record_json(trace),
+            "records": records,
+            "seal": AuditSeal::for_records(&records),
         });
         let data = serde_json::to_string_pretty(&json)
             .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
@@ -173,6 +573,29 @@ impl TraceReplay {
         Ok(())
     }
 
+    /// Verify the hash chain and seal of an audit log written by
+    /// [`TraceReplay::write_audit_log_records`], in file order.
+    pub fn verify_audit_log(path: impl AsRef<std::path::Path>) -> std::io::Result<ChainReport> {
+        let contents = std::fs::read_to_string(path)?;
+        let mut value: serde_json::Value = serde_json::from_str(&contents)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+        let seal: Option<AuditSeal> = match value.get_mut("seal").map(serde_json::Value::take) {
+            Some(seal) => Some(
+                serde_json::from_value(seal)
+                    .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?,
+            ),
+            None => None,
+        };
+        let records_value = match value {
+            serde_json::Value::Object(mut obj) => obj.remove("records").unwrap_or_default(),
+            other => other,
+        };
+        let records: Vec<crate::runtime::event::EventRecord> =
+            serde_json::from_value(records_value)
+                .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+        Ok(verify_chain(&records, seal.as_ref()))
+    }
+
     pub fn read_audit_log_records(
         path: impl AsRef<std::path::Path>,
     ) -> std::io::Result<Vec<crate::runtime::event::EventRecord>> {
@@ -232,6 +655,7 @@ fn map_trace_event(event: &TraceEvent) -> crate::runtime::event::Event {
             session_id: node.clone(),
             tokens: crate::runtime::event::TokenUsage::default(),
             cost: 0.0,
+            model: None,
         },
         TraceEvent::Compacted {
             summary,
@@ -246,8 +670,11 @@ fn map_trace_event(event: &TraceEvent) -> crate::runtime::event::Event {
 
 #[cfg(test)]
 mod tests {
-    use super::{ExecutionTrace, TraceEvent, TraceReplay, TraceSpan};
-    use crate::runtime::event::{Event, EventRecord, EventRecordSink, EventSink};
+    use super::{
+        ChromeTraceExport, ExecutionTrace, SpanKind, SpanRecorder, TraceEvent, TraceReplay,
+        TraceSpan,
+    };
+    use crate::runtime::event::{Event, EventRecord, EventRecordSink, EventSink, PermissionReply};
     use std::sync::{Arc, Mutex};
 
     #[test]
@@ -256,16 +683,179 @@ mod tests {
         trace.record_event(TraceEvent::NodeStart {
             node: "n1".to_string(),
         });
-        trace.record_span(TraceSpan {
-            node: "n1".to_string(),
-            start_ms: 10,
-            duration_ms: 42,
-        });
+        trace.record_span(TraceSpan::new("n1", 10, 42));
 
         assert_eq!(trace.events.len(), 1);
         assert_eq!(trace.spans.len(), 1);
     }
 
+    #[test]
+    fn trace_span_omits_default_fields() {
+        let span = TraceSpan::new("planner", 1000, 25);
+        let json = serde_json::to_value(&span).expect("serialize");
+        assert_eq!(
+            json,
+            serde_json::json!({"node": "planner", "start_ms": 1000, "duration_ms": 25})
+        );
+    }
+
+    #[test]
+    fn span_recorder_pairs_tool_model_and_permission_events() {
+        let trace = Arc::new(Mutex::new(ExecutionTrace::new()));
+        let mut recorder = SpanRecorder::new(Arc::clone(&trace));
+        let node_span = recorder.enter_node("agent");
+        recorder.observe(&Event::StepStart {
+            session_id: "s1".to_string(),
+        });
+        recorder.observe(&Event::ToolStart {
+            tool: "grep".to_string(),
+            call_id: "c1".to_string(),
+            input: serde_json::json!({}),
+        });
+        recorder.observe(&Event::ToolResult {
+            tool: "grep".to_string(),
+            call_id: "c1".to_string(),
+            output: crate::runtime::tool::ToolOutput::text("ok"),
+        });
+        recorder.observe(&Event::StepFinish {
+            session_id: "s1".to_string(),
+            tokens: Default::default(),
+            cost: 0.0,
+            model: None,
+        });
+        recorder.observe(&Event::PermissionAsked {
+            permission: "bash".to_string(),
+            patterns: Vec::new(),
+            metadata: Default::default(),
+            always: Vec::new(),
+        });
+        recorder.exit_node(None);
+        recorder.finish();
+
+        let trace = trace.lock().unwrap();
+        let kinds: Vec<SpanKind> = trace.spans.iter().map(|span| span.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                SpanKind::Tool,
+                SpanKind::Model,
+                SpanKind::Node,
+                SpanKind::Permission
+            ]
+        );
+        let tool = &trace.spans[0];
+        assert_eq!(tool.name, "grep");
+        assert_eq!(tool.node, "agent");
+        assert_eq!(tool.parent_span_id.as_deref(), Some(node_span.as_str()));
+        let permission = &trace.spans[3];
+        assert_eq!(
+            permission.attributes.get("incomplete"),
+            Some(&serde_json::json!(true))
+        );
+    }
+
+    #[test]
+    fn span_recorder_records_permission_reply() {
+        let trace = Arc::new(Mutex::new(ExecutionTrace::new()));
+        let mut recorder = SpanRecorder::new(Arc::clone(&trace));
+        recorder.observe(&Event::PermissionAsked {
+            permission: "bash".to_string(),
+            patterns: Vec::new(),
+            metadata: Default::default(),
+            always: Vec::new(),
+        });
+        recorder.observe(&Event::PermissionReplied {
+            permission: "bash".to_string(),
+            reply: PermissionReply::Once,
+        });
+
+        let trace = trace.lock().unwrap();
+        assert_eq!(trace.spans.len(), 1);
+        assert_eq!(trace.spans[0].kind, SpanKind::Permission);
+        assert!(trace.spans[0].attributes.contains_key("reply"));
+    }
+
+    #[test]
+    fn span_recorder_keeps_concurrent_asks_and_nested_steps_apart() {
+        let trace = Arc::new(Mutex::new(ExecutionTrace::new()));
+        let mut recorder = SpanRecorder::new(Arc::clone(&trace));
+        for call_id in ["c1", "c2"] {
+            let mut metadata = serde_json::Map::new();
+            metadata.insert("call_id".to_string(), serde_json::json!(call_id));
+            recorder.observe(&Event::PermissionAsked {
+                permission: "tool:bash".to_string(),
+                patterns: Vec::new(),
+                metadata,
+                always: Vec::new(),
+            });
+        }
+        for _ in 0..2 {
+            recorder.observe(&Event::StepStart {
+                session_id: "s1".to_string(),
+            });
+        }
+        for tokens in [1, 2] {
+            recorder.observe(&Event::StepFinish {
+                session_id: "s1".to_string(),
+                tokens: crate::runtime::event::TokenUsage {
+                    input: tokens,
+                    ..Default::default()
+                },
+                cost: 0.0,
+                model: None,
+            });
+        }
+        recorder.observe(&Event::PermissionReplied {
+            permission: "tool:bash".to_string(),
+            reply: PermissionReply::Once,
+        });
+
+        let trace = trace.lock().unwrap();
+        assert_eq!(trace.spans.len(), 4);
+        let models: Vec<&TraceSpan> = trace
+            .spans
+            .iter()
+            .filter(|span| span.kind == SpanKind::Model)
+            .collect();
+        assert_eq!(models.len(), 2);
+        assert_eq!(models[0].attributes["tokens"]["input"], 1);
+        assert!(models[0].start_ms >= models[1].start_ms);
+        let asks: Vec<&serde_json::Value> = trace
+            .spans
+            .iter()
+            .filter(|span| span.kind == SpanKind::Permission)
+            .map(|span| &span.attributes["call_id"])
+            .collect();
+        assert_eq!(asks, vec!["c1", "c2"]);
+        assert!(trace
+            .spans
+            .iter()
+            .all(|span| !span.attributes.contains_key("incomplete")));
+    }
+
+    #[test]
+    fn chrome_trace_export_emits_complete_events() {
+        let mut trace = ExecutionTrace::new();
+        trace.record_span(TraceSpan::new("planner", 1, 5));
+        trace.record_span(
+            TraceSpan::new("planner", 2, 3)
+                .with_kind(SpanKind::Tool)
+                .with_name("grep"),
+        );
+
+        let json = ChromeTraceExport::to_json(&trace);
+        let events = json["traceEvents"].as_array().expect("events");
+        let complete: Vec<_> = events.iter().filter(|event| event["ph"] == "X").collect();
+        assert_eq!(complete.len(), 2);
+        assert_eq!(complete[0]["name"], "planner");
+        assert_eq!(complete[0]["ts"], 1000);
+        assert_eq!(complete[0]["dur"], 5000);
+        assert_eq!(complete[1]["name"], "grep");
+        assert_eq!(complete[1]["cat"], "tool");
+        assert_ne!(complete[0]["tid"], complete[1]["tid"]);
+        assert_eq!(events.iter().filter(|event| event["ph"] == "M").count(), 2);
+    }
+
     #[test]
     fn trace_roundtrip() {
         let mut trace = ExecutionTrace::new();
@@ -404,6 +994,7 @@ mod tests {
                     event_id: "e1".to_string(),
                     timestamp_ms: 1,
                     seq: 7,
+                    ..Default::default()
                 },
             ),
             EventRecord::with_meta(
@@ -414,6 +1005,7 @@ mod tests {
                     event_id: "e2".to_string(),
                     timestamp_ms: 2,
                     seq: 9,
+                    ..Default::default()
                 },
             ),
         ];
@@ -489,6 +1081,30 @@ mod tests {
         assert!(contents.contains("\"event_id\""));
     }
 
+    #[test]
+    fn trace_replay_audit_log_records_are_chained_and_sealed() {
+        let mut trace = ExecutionTrace::new();
+        for node in ["a", "b"] {
+            trace.record_event(TraceEvent::NodeStart {
+                node: node.to_string(),
+            });
+        }
+        let path = std::env::temp_dir().join(format!(
+            "forge-audit-records-chain-{}.json",
+            uuid::Uuid::new_v4()
+        ));
+        TraceReplay::write_audit_log_records(&trace, &path).expect("write");
+        let report = TraceReplay::verify_audit_log(&path).expect("verify");
+        assert!(report.is_valid());
+        assert!(report.sealed);
+        assert_eq!(report.record_count, 2);
+
+        let contents = std::fs::read_to_string(&path).expect("read");
+        std::fs::write(&path, contents.replacen("\"b\"", "\"c\"", 1)).expect("write");
+        let report = TraceReplay::verify_audit_log(&path).expect("verify");
+        assert!(!report.is_valid());
+    }
+
     #[test]
     fn trace_replay_read_audit_log_records_round_trip() {
         let mut trace = ExecutionTrace::new();
@@ -590,6 +1206,7 @@ mod tests {
                     event_id: "b".to_string(),
                     timestamp_ms: 2,
                     seq: 2,
+                    ..Default::default()
                 },
             ),
             EventRecord::with_meta(
@@ -600,6 +1217,7 @@ mod tests {
                     event_id: "a".to_string(),
                     timestamp_ms: 1,
                     seq: 1,
+                    ..Default::default()
                 },
             ),
         ];
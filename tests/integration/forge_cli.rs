@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use forge::runtime::event::{Event, EventMeta, EventRecord};
+use forge::runtime::session::{CheckpointRecord, CheckpointStore, RunLogStore};
+
+fn record(seq: u64, event: Event) -> EventRecord {
+    EventRecord::with_meta(
+        event,
+        EventMeta {
+            event_id: format!("e{}", seq),
+            timestamp_ms: seq * 10,
+            seq,
+            ..Default::default()
+        },
+    )
+}
+
+fn checkpoint(id: &str, state: serde_json::Value, at: &str) -> CheckpointRecord {
+    let mut record =
+        CheckpointRecord::new("run-1", id, state, "next", 1, Vec::new(), HashMap::new());
+    record.created_at = at.to_string();
+    record
+}
+
+/// A store root holding one hash-chained run with two events and two checkpoints.
+fn seeded_root() -> PathBuf {
+    let root = std::env::temp_dir().join(format!("forge-cli-{}", uuid::Uuid::new_v4()));
+    let logs = RunLogStore::new(&root).with_hash_chain();
+    for (seq, text) in [(1, "first"), (2, "second")] {
+        logs.append(
+            "run-1",
+            &record(
+                seq,
+                Event::TextFinal {
+                    session_id: "s1".to_string(),
+                    message_id: format!("m{}", seq),
+                    text: text.to_string(),
+                },
+            ),
+        )
+        .expect("append");
+    }
+    let checkpoints = CheckpointStore::new(&root);
+    checkpoints
+        .save(&checkpoint(
+            "cp-a",
+            serde_json::json!({"count": 1, "config": {"api_key": "hunter2"}}),
+            "2026-01-01T00:00:00Z",
+        ))
+        .expect("save");
+    checkpoints
+        .save(&checkpoint(
+            "cp-b",
+            serde_json::json!({"count": 2, "config": {"api_key": "hunter2"}}),
+            "2026-01-01T00:00:01Z",
+        ))
+        .expect("save");
+    root
+}
+
+fn forge(root: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_forge"))
+        .arg("--root")
+        .arg(root)
+        .args(args)
+        .env_remove("FORGE_ROOT")
+        .env("NO_COLOR", "1")
+        .output()
+        .expect("run forge")
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn forge_rejects_missing_commands_unknown_commands_and_flags() {
+    let root = seeded_root();
+
+    let output = forge(&root, &[]);
+    assert!(!output.status.success());
+    assert!(
+        stderr(&output).contains("usage: forge"),
+        "{}",
+        stderr(&output)
+    );
+
+    let output = forge(&root, &["frobnicate"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("unknown command frobnicate"));
+
+    let output = forge(&root, &["--verbose", "runs"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("unknown flag --verbose"));
+
+    let output = forge(&root, &["checkpoints"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("checkpoints requires <run>"));
+
+    let output = forge(&root, &["log", "run-1", "--from-seq", "two"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("--from-seq expects a number"));
+    let _ = std::fs::remove_dir_all(root);
+}
+
+#[test]
+fn forge_lists_runs_and_checkpoints() {
+    let root = seeded_root();
+
+    let output = forge(&root, &["--json", "runs"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let runs: serde_json::Value = serde_json::from_str(&stdout(&output)).expect("json");
+    assert_eq!(runs[0]["run_id"], "run-1");
+    assert_eq!(runs[0]["event_count"], 2);
+    assert_eq!(runs[0]["checkpoint_count"], 2);
+
+    let output = forge(&root, &["checkpoints", "run-1"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let ids: Vec<String> = stdout(&output)
+        .lines()
+        .map(|line| line.split('\t').next().unwrap_or_default().to_string())
+        .collect();
+    assert_eq!(ids, ["cp-a", "cp-b"]);
+    let _ = std::fs::remove_dir_all(root);
+}
+
+#[test]
+fn forge_log_prints_raw_records_from_a_seq() {
+    let root = seeded_root();
+
+    let output = forge(&root, &["log", "run-1", "--raw", "--from-seq", "2"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let records: Vec<EventRecord> = stdout(&output)
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("record"))
+        .collect();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].meta.seq, 2);
+    let _ = std::fs::remove_dir_all(root);
+}
+
+#[test]
+fn forge_diffs_and_verifies_a_run() {
+    let root = seeded_root();
+
+    let output = forge(&root, &["diff", "run-1", "cp-a", "cp-b"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(
+        stdout(&output).contains("~ /count 1 -> 2"),
+        "{}",
+        stdout(&output)
+    );
+
+    let output = forge(&root, &["verify", "run-1"]);
+    assert!(output.status.success(), "{}", stdout(&output));
+    assert!(stdout(&output).contains("2 records, checked hash chain"));
+    assert!(stdout(&output).ends_with("OK\n"));
+
+    let segment = root.join("run-1").join("events.jsonl");
+    let log = std::fs::read_to_string(&segment).expect("read segment");
+    std::fs::write(&segment, log.replace("first", "forged")).expect("tamper");
+    let output = forge(&root, &["verify", "run-1"]);
+    assert!(!output.status.success());
+    assert!(
+        stdout(&output).contains("broken at seq 2"),
+        "{}",
+        stdout(&output)
+    );
+    assert!(stdout(&output).ends_with("FAILED\n"));
+    let _ = std::fs::remove_dir_all(root);
+}
+
+#[test]
+fn forge_export_applies_redaction_flags() {
+    let root = seeded_root();
+    let out = root.join("bundle.json");
+
+    let output = forge(
+        &root,
+        &[
+            "export",
+            "run-1",
+            out.to_str().expect("utf-8 path"),
+            "--redact-path",
+            "config.api_key",
+        ],
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stderr(&output).contains("2 events, 2 checkpoints"));
+    let bundle = std::fs::read_to_string(&out).expect("bundle");
+    assert!(!bundle.contains("hunter2"));
+
+    let output = forge(&root, &["export", "run-1", "--redact-pattern", "("]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("export requires <out.json>"));
+
+    let output = forge(
+        &root,
+        &[
+            "export",
+            "run-1",
+            out.to_str().expect("utf-8 path"),
+            "--redact-pattern",
+            "(",
+        ],
+    );
+    assert!(!output.status.success());
+    let _ = std::fs::remove_dir_all(root);
+}
+
+#[test]
+fn forge_query_summarizes_matching_records() {
+    let root = seeded_root();
+
+    let output = forge(&root, &["query", "--run", "run-1", "--summary"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let summary: serde_json::Value = serde_json::from_str(&stdout(&output)).expect("json");
+    assert_eq!(summary["total"], 2, "{summary}");
+
+    let output = forge(&root, &["query", "--run", "run-1", "extra"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("unexpected argument extra"));
+    let _ = std::fs::remove_dir_all(root);
+}
@@ -3,7 +3,7 @@ use std::sync::{Arc, Mutex};
 
 use forge::runtime::constants::{END, START};
 use forge::runtime::error::{interrupt, interrupt_all, GraphError, Interrupt, ResumeCommand};
-use forge::runtime::event::{Event, EventSink};
+use forge::runtime::event::{Event, EventRecord, EventRecordSink, EventSink};
 use forge::runtime::executor::{CheckpointDurability, ExecutionConfig, ExecutionResult};
 use forge::runtime::graph::StateGraph;
 use forge::runtime::session::CheckpointStore;
@@ -57,6 +57,18 @@ impl EventSink for CaptureSink {
     }
 }
 
+#[derive(Debug)]
+struct CaptureRecordSink {
+    records: Arc<Mutex<Vec<EventRecord>>>,
+}
+
+impl EventRecordSink for CaptureRecordSink {
+    fn emit_record(&self, record: EventRecord) -> Result<(), GraphError> {
+        self.records.lock().unwrap().push(record);
+        Ok(())
+    }
+}
+
 #[derive(Clone, Default, Serialize, Deserialize)]
 struct MultiPauseState {
     steps: usize,
@@ -508,3 +520,69 @@ fn pause_resume_can_continue_from_latest_persisted_checkpoint() {
     };
     assert_eq!(final_state.steps, 1);
 }
+
+#[test]
+fn pause_resume_records_run_events_under_the_configured_run_id() {
+    let records = Arc::new(Mutex::new(Vec::new()));
+    let record_sink: Arc<dyn EventRecordSink> = Arc::new(CaptureRecordSink {
+        records: records.clone(),
+    });
+
+    let mut graph = StateGraph::<PauseState>::new();
+    graph.add_node("pause", pause_node);
+    graph.add_stream_node("finish", |mut state: PauseState, sink| async move {
+        sink.emit(Event::TextDelta {
+            session_id: "s1".to_string(),
+            message_id: "m1".to_string(),
+            delta: "done".to_string(),
+        })?;
+        state.steps += 1;
+        Ok(state)
+    });
+    graph.add_edge(START, "pause");
+    graph.add_edge("pause", "finish");
+    graph.add_edge("finish", END);
+
+    let compiled = graph.compile().expect("compile").with_config(
+        ExecutionConfig::new()
+            .with_run_id("run-42")
+            .with_event_record_sink(record_sink)
+            .with_event_namespace("jobs"),
+    );
+
+    let result = block_on(compiled.invoke_resumable(PauseState::default())).expect("run");
+    let checkpoint = match result {
+        ExecutionResult::Interrupted { checkpoint, .. } => checkpoint,
+        _ => panic!("expected interrupt"),
+    };
+    assert_eq!(checkpoint.run_id, "run-42");
+    assert_eq!(checkpoint.event_seq, 2);
+
+    let resumed =
+        block_on(compiled.resume(checkpoint, ResumeCommand::new("continue"))).expect("resume");
+    assert!(matches!(resumed, ExecutionResult::Complete(_)));
+
+    let records = records.lock().unwrap();
+    let variants: Vec<&str> = records
+        .iter()
+        .map(|record| record.event.variant_name())
+        .collect();
+    assert_eq!(
+        variants,
+        vec![
+            "RunStarted",
+            "RunPaused",
+            "RunResumed",
+            "TextDelta",
+            "RunCompleted"
+        ]
+    );
+    let seqs: Vec<u64> = records.iter().map(|record| record.meta.seq).collect();
+    assert_eq!(seqs, vec![1, 2, 3, 4, 5]);
+    assert!(records.iter().all(|record| {
+        record.meta.run_id.as_deref() == Some("run-42")
+            && record.meta.namespace.as_deref() == Some("jobs")
+    }));
+    assert_eq!(records[3].meta.node.as_deref(), Some("finish"));
+    assert_eq!(records[0].meta.node, None);
+}
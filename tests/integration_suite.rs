@@ -5,6 +5,8 @@ mod helpers;
 
 #[path = "integration/agent_handoff.rs"]
 mod agent_handoff;
+#[path = "integration/forge_cli.rs"]
+mod forge_cli;
 #[path = "integration/graph_routing.rs"]
 mod graph_routing;
 #[path = "integration/pause_resume.rs"]